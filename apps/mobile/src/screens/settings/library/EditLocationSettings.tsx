@@ -54,6 +54,8 @@ const EditLocationSettingsScreen = ({
 			sync_preview_media: data.syncPreviewMedia,
 			generate_preview_media: data.generatePreviewMedia,
 			hidden: data.hidden,
+			follow_symlinks: null,
+			io_budget: null,
 			indexer_rules_ids: []
 		})
 	);
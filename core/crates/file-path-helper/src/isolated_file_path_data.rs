@@ -2,7 +2,7 @@ use sd_core_prisma_helpers::{
 	file_path_for_file_identifier, file_path_for_media_processor, file_path_for_object_validator,
 	file_path_to_full_path, file_path_to_handle_custom_uri, file_path_to_handle_p2p_serve_file,
 	file_path_to_isolate, file_path_to_isolate_with_id, file_path_to_isolate_with_pub_id,
-	file_path_walker, file_path_watcher_remove, file_path_with_object,
+	file_path_to_resolve_alias, file_path_walker, file_path_watcher_remove, file_path_with_object,
 };
 
 use sd_prisma::prisma::{file_path, location};
@@ -507,7 +507,8 @@ impl_from_db!(
 	file_path_walker,
 	file_path_to_isolate_with_id,
 	file_path_with_object,
-	file_path_watcher_remove
+	file_path_watcher_remove,
+	file_path_to_resolve_alias
 );
 
 impl_from_db_without_location_id!(
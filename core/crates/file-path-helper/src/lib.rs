@@ -402,6 +402,27 @@ pub fn get_inode(metadata: &Metadata) -> u64 {
 	}
 }
 
+/// How many hard links point to the same inode as the file with this metadata, files that were
+/// never hard linked have 1.
+///
+/// It isn't available on Windows yet, where every file is taken as having only one link
+#[must_use]
+pub fn hard_links_count(metadata: &Metadata) -> u64 {
+	#[cfg(target_family = "unix")]
+	{
+		use std::os::unix::fs::MetadataExt;
+
+		metadata.nlink()
+	}
+
+	#[cfg(target_family = "windows")]
+	{
+		// TODO use `number_of_links` when it's stable
+		let _ = metadata; // just to avoid warnings on Windows
+		1
+	}
+}
+
 pub async fn get_inode_from_path(path: impl AsRef<Path> + Send) -> Result<u64, FilePathError> {
 	#[cfg(target_family = "unix")]
 	{
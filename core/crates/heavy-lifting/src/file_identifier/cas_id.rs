@@ -1,6 +1,6 @@
 use sd_core_prisma_helpers::CasId;
 
-use std::path::Path;
+use std::{io::Read, path::Path};
 
 use blake3::Hasher;
 use static_assertions::const_assert;
@@ -23,6 +23,16 @@ const_assert!((HEADER_OR_FOOTER_SIZE * 2 + SAMPLE_COUNT * SAMPLE_SIZE) < MINIMUM
 // Asserting that the sample size is larger than header/footer size, as the same buffer is used for both
 const_assert!(SAMPLE_SIZE > HEADER_OR_FOOTER_SIZE);
 
+/// How many bytes of a file with this size are read by [`generate_cas_id`]
+#[must_use]
+pub const fn hashed_bytes_count(size: u64) -> u64 {
+	if size <= MINIMUM_FILE_SIZE {
+		size
+	} else {
+		HEADER_OR_FOOTER_SIZE * 2 + SAMPLE_COUNT * SAMPLE_SIZE
+	}
+}
+
 #[instrument(
 	skip(path),
 	ret(level = Level::TRACE),
@@ -78,3 +88,60 @@ pub async fn generate_cas_id(
 
 	Ok(hasher.finalize().to_hex()[..16].to_string().into())
 }
+
+/// The `cas_id` of files moved out of an object by a sampled `cas_id` collision, derived from
+/// their `integrity_checksum` so copies found later are linked to the same object
+#[must_use]
+pub fn cas_id_from_integrity_checksum(integrity_checksum: &str) -> CasId<'static> {
+	let mut hasher = Hasher::new();
+	hasher.update(b"integrity_checksum");
+	hasher.update(integrity_checksum.as_bytes());
+
+	hasher.finalize().to_hex()[..16].to_string().into()
+}
+
+/// Same as [`generate_cas_id`], but for contents that can only be read sequentially, like files
+/// inside archives. The skipped bytes between samples are read and discarded.
+// SAFETY: Casts here are safe, they're hardcoded values we have some const assertions above to make sure they're correct
+#[allow(clippy::cast_possible_truncation)]
+pub fn generate_cas_id_from_reader(
+	mut reader: impl Read,
+	size: u64,
+) -> Result<CasId<'static>, io::Error> {
+	let mut hasher = Hasher::new();
+	hasher.update(&size.to_le_bytes());
+
+	if size <= MINIMUM_FILE_SIZE {
+		let mut buf = Vec::with_capacity(size as usize);
+		reader.take(size).read_to_end(&mut buf)?;
+		if buf.len() as u64 != size {
+			return Err(io::ErrorKind::UnexpectedEof.into());
+		}
+		hasher.update(&buf);
+	} else {
+		let mut buf = vec![0; SAMPLE_SIZE as usize].into_boxed_slice();
+
+		// The same ranges sampled by `generate_cas_id`, in order and without overlapping
+		let seek_jump = (size - HEADER_OR_FOOTER_SIZE * 2) / SAMPLE_COUNT;
+		let ranges = [(0, HEADER_OR_FOOTER_SIZE)]
+			.into_iter()
+			.chain(
+				(0..SAMPLE_COUNT)
+					.map(|sample| (HEADER_OR_FOOTER_SIZE + seek_jump * sample, SAMPLE_SIZE)),
+			)
+			.chain([(size - HEADER_OR_FOOTER_SIZE, HEADER_OR_FOOTER_SIZE)]);
+
+		let mut current_pos = 0;
+		for (start, len) in ranges {
+			std::io::copy(
+				&mut (&mut reader).take(start - current_pos),
+				&mut std::io::sink(),
+			)?;
+			reader.read_exact(&mut buf[..len as usize])?;
+			hasher.update(&buf[..len as usize]);
+			current_pos = start + len;
+		}
+	}
+
+	Ok(hasher.finalize().to_hex()[..16].to_string().into())
+}
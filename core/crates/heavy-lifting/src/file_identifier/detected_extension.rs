@@ -0,0 +1,66 @@
+use sd_file_ext::{
+	extensions::{Extension, TextExtension},
+	magic::MAGIC_BYTES_HEADER_LEN,
+	text::is_text,
+};
+
+use std::path::Path;
+
+use static_assertions::const_assert;
+use tokio::{
+	fs::File,
+	io::{self, AsyncReadExt},
+};
+use tracing::{instrument, Level};
+
+// Plain text has no magic bytes, so we need a decent chunk of the file to tell it from binary data
+const HEADER_SIZE: u64 = 1024 * 8;
+
+const_assert!(HEADER_SIZE >= MAGIC_BYTES_HEADER_LEN as u64);
+
+/// How many bytes of a file with this size are read by [`read_header`]
+#[must_use]
+pub const fn sniffed_bytes_count(size: u64) -> u64 {
+	if size < HEADER_SIZE {
+		size
+	} else {
+		HEADER_SIZE
+	}
+}
+
+/// Reads the first bytes of a file, enough to check its magic bytes and to tell plain text from
+/// binary data
+#[instrument(
+	skip(path),
+	ret(level = Level::TRACE),
+	err,
+	fields(path = %path.as_ref().display()
+))]
+pub async fn read_header(path: impl AsRef<Path> + Send) -> Result<Vec<u8>, io::Error> {
+	let mut header = vec![];
+	File::open(path)
+		.await?
+		.take(HEADER_SIZE)
+		.read_to_end(&mut header)
+		.await?;
+
+	Ok(header)
+}
+
+/// Tells the extension of a file from its [header](read_header), for files without an extension,
+/// with one we don't know or misnamed ones. Many formats share their magic bytes, like zip based
+/// documents and archives, so the detected extension is only reliable to tell the kind of the file.
+#[must_use]
+pub fn detect_extension(header: &[u8], size: u64) -> Option<Extension> {
+	Extension::from_magic_bytes(header).or_else(|| {
+		is_text(header, (header.len() as u64) < size).map(|_| Extension::Text(TextExtension::Txt))
+	})
+}
+
+/// Tells if the file [header](read_header) doesn't have the magic bytes its extension calls for,
+/// meaning it was misnamed. Extensions without known magic bytes, like plain text or source code,
+/// never mismatch.
+#[must_use]
+pub fn has_mismatched_magic_bytes(header: &[u8], extension: &Extension) -> bool {
+	extension.check_header_magic_bytes(header) == Some(false)
+}
@@ -0,0 +1,43 @@
+use crate::IoThrottle;
+
+use std::path::Path;
+
+use blake3::Hasher;
+use tokio::{
+	fs::File,
+	io::{self, AsyncReadExt},
+};
+use tracing::{instrument, Level};
+
+const BLOCK_LEN: usize = 1024 * 1024;
+
+/// Hashes the whole file, unlike [`generate_cas_id`](super::generate_cas_id) which only samples
+/// big files. The checksum is the same computed by the old object validator job.
+#[instrument(
+	skip(path, io_throttle),
+	ret(level = Level::TRACE),
+	err,
+	fields(path = %path.as_ref().display()
+))]
+pub async fn generate_integrity_checksum(
+	path: impl AsRef<Path> + Send,
+	io_throttle: &IoThrottle,
+) -> Result<String, io::Error> {
+	let mut file = File::open(path).await?;
+	let mut hasher = Hasher::new();
+	let mut buf = vec![0; BLOCK_LEN].into_boxed_slice();
+
+	loop {
+		let read_count = file.read(&mut buf).await?;
+		if read_count == 0 {
+			break;
+		}
+
+		hasher.update(&buf[..read_count]);
+
+		// Charged after reading, so small files only use as much of the budget as their size
+		io_throttle.hash_bytes(read_count as u64).await;
+	}
+
+	Ok(hasher.finalize().to_hex().to_string())
+}
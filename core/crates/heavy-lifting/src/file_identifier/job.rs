@@ -15,7 +15,7 @@ use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::{file_path_for_file_identifier, CasId};
 
 use sd_prisma::{
-	prisma::{device, file_path, location, SortOrder},
+	prisma::{device, file_path, location, object, SortOrder},
 	prisma_sync,
 };
 use sd_sync::{sync_db_not_null_entry, OperationFactory};
@@ -43,9 +43,9 @@ use tokio::time::Instant;
 use tracing::{debug, instrument, trace, warn, Level};
 
 use super::{
-	accumulate_file_paths_by_cas_id, dispatch_object_processor_tasks, orphan_path_filters_deep,
-	orphan_path_filters_shallow,
-	tasks::{self, identifier, object_processor, FilePathToCreateOrLinkObject},
+	accumulate_file_paths_by_cas_id, dispatch_object_processor_tasks, integrity_checksum_filters,
+	orphan_path_filters_deep, orphan_path_filters_shallow,
+	tasks::{self, checksummer, identifier, object_processor, FilePathToCreateOrLinkObject},
 	CHUNK_SIZE,
 };
 
@@ -55,6 +55,7 @@ enum Phase {
 	SearchingOrphans,
 	IdentifyingFiles,
 	ProcessingObjects,
+	VerifyingIntegrity,
 }
 
 impl Default for Phase {
@@ -71,6 +72,7 @@ impl fmt::Display for Phase {
 			}
 			Self::IdentifyingFiles => write!(f, "identifying_files"),
 			Self::ProcessingObjects => write!(f, "processing_objects"),
+			Self::VerifyingIntegrity => write!(f, "verifying_integrity"),
 		}
 	}
 }
@@ -87,11 +89,14 @@ pub struct FileIdentifier {
 	location: Arc<location::Data>,
 	location_path: Arc<PathBuf>,
 	sub_path: Option<PathBuf>,
+	verify_integrity: bool,
 
 	// Inner state
 	file_paths_accumulator: HashMap<CasId<'static>, Vec<FilePathToCreateOrLinkObject>>,
 	file_paths_ids_with_priority: HashSet<file_path::id::Type>,
 	last_orphan_file_path_id: Option<i32>,
+	last_checksummed_file_path_id: Option<file_path::id::Type>,
+	objects_to_verify: HashSet<object::id::Type>,
 
 	// Job control
 	phase: Phase,
@@ -111,6 +116,7 @@ impl Hash for FileIdentifier {
 		if let Some(ref sub_path) = self.sub_path {
 			sub_path.hash(state);
 		}
+		self.verify_integrity.hash(state);
 	}
 }
 
@@ -132,7 +138,11 @@ impl Job for FileIdentifier {
 						match task_kind {
 							TaskKind::Identifier => tasks::Identifier::deserialize(
 								&task_bytes,
-								(Arc::clone(ctx.db()), ctx.sync().clone()),
+								(
+									Arc::clone(ctx.db()),
+									ctx.sync().clone(),
+									ctx.io_throttle().clone(),
+								),
 							)
 							.await
 							.map(IntoTask::into_task),
@@ -143,6 +153,17 @@ impl Job for FileIdentifier {
 							)
 							.await
 							.map(IntoTask::into_task),
+
+							TaskKind::Checksummer => tasks::Checksummer::deserialize(
+								&task_bytes,
+								(
+									Arc::clone(ctx.db()),
+									ctx.sync().clone(),
+									ctx.io_throttle().clone(),
+								),
+							)
+							.await
+							.map(IntoTask::into_task),
 						}
 					})
 					.collect::<Vec<_>>()
@@ -170,6 +191,7 @@ impl Job for FileIdentifier {
 		ret(level = Level::TRACE),
 		err,
 	)]
+	#[allow(clippy::too_many_lines)]
 	async fn run<OuterCtx: OuterContext>(
 		mut self,
 		dispatcher: JobTaskDispatcher,
@@ -214,53 +236,91 @@ impl Job for FileIdentifier {
 			}
 		}
 
-		while let Some(task) = pending_running_tasks.next().await {
-			match task {
-				Ok(TaskStatus::Done((task_id, TaskOutput::Out(out)))) => {
-					match self
-						.process_task_output(task_id, out, &ctx, device_id, &dispatcher)
-						.await
-					{
-						Ok(tasks) => pending_running_tasks.extend(tasks),
-						Err(DispatcherError::JobCanceled(_)) => {
-							return Ok(self.cancel_job(&mut pending_running_tasks).await);
-						}
-						Err(DispatcherError::Shutdown(tasks)) => {
-							self.tasks_for_shutdown.extend(tasks);
+		loop {
+			while let Some(task) = pending_running_tasks.next().await {
+				match task {
+					Ok(TaskStatus::Done((task_id, TaskOutput::Out(out)))) => {
+						match self
+							.process_task_output(task_id, out, &ctx, device_id, &dispatcher)
+							.await
+						{
+							Ok(tasks) => pending_running_tasks.extend(tasks),
+							Err(DispatcherError::JobCanceled(_)) => {
+								return Ok(self.cancel_job(&mut pending_running_tasks).await);
+							}
+							Err(DispatcherError::Shutdown(tasks)) => {
+								self.tasks_for_shutdown.extend(tasks);
+							}
 						}
 					}
-				}
 
-				Ok(TaskStatus::Done((task_id, TaskOutput::Empty))) => {
-					warn!(%task_id, "Task returned an empty output");
-				}
+					Ok(TaskStatus::Done((task_id, TaskOutput::Empty))) => {
+						warn!(%task_id, "Task returned an empty output");
+					}
 
-				Ok(TaskStatus::Shutdown(task)) => {
-					self.tasks_for_shutdown.push(task);
-				}
+					Ok(TaskStatus::Shutdown(task)) => {
+						self.tasks_for_shutdown.push(task);
+					}
 
-				Ok(TaskStatus::Error(e)) => {
-					cancel_pending_tasks(&mut pending_running_tasks).await;
+					Ok(TaskStatus::Error(e)) => {
+						cancel_pending_tasks(&mut pending_running_tasks).await;
 
-					return Err(e);
-				}
+						return Err(e);
+					}
 
-				Ok(TaskStatus::Canceled | TaskStatus::ForcedAbortion) => {
-					return Ok(self.cancel_job(&mut pending_running_tasks).await);
+					Ok(TaskStatus::Canceled | TaskStatus::ForcedAbortion) => {
+						return Ok(self.cancel_job(&mut pending_running_tasks).await);
+					}
+
+					Err(e) => {
+						cancel_pending_tasks(&mut pending_running_tasks).await;
+
+						return Err(e.into());
+					}
 				}
+			}
+
+			if !self.tasks_for_shutdown.is_empty() {
+				return Ok(ReturnStatus::Shutdown(
+					SerializableJob::<OuterCtx>::serialize(self).await,
+				));
+			}
 
-				Err(e) => {
+			// Checksums are only computed once every file is identified and linked to its object
+			if !self.verify_integrity || matches!(self.phase, Phase::VerifyingIntegrity) {
+				break;
+			}
+
+			self.phase = Phase::VerifyingIntegrity;
+
+			ctx.progress(vec![
+				ProgressUpdate::TaskCount(0),
+				ProgressUpdate::CompletedTaskCount(0),
+				ProgressUpdate::phase(self.phase),
+			])
+			.await;
+
+			match self
+				.dispatch_checksummer_tasks(&ctx, &dispatcher, &pending_running_tasks)
+				.await
+			{
+				Ok(()) => { /* Everything is awesome! */ }
+				Err(JobErrorOrDispatcherError::JobError(e)) => {
 					cancel_pending_tasks(&mut pending_running_tasks).await;
 
 					return Err(e.into());
 				}
+				Err(JobErrorOrDispatcherError::Dispatcher(DispatcherError::JobCanceled(_))) => {
+					return Ok(self.cancel_job(&mut pending_running_tasks).await);
+				}
+				Err(JobErrorOrDispatcherError::Dispatcher(DispatcherError::Shutdown(tasks))) => {
+					self.tasks_for_shutdown.extend(tasks);
+				}
 			}
 		}
 
-		if !self.tasks_for_shutdown.is_empty() {
-			return Ok(ReturnStatus::Shutdown(
-				SerializableJob::<OuterCtx>::serialize(self).await,
-			));
+		if self.verify_integrity {
+			self.split_cas_id_collisions(&ctx, device_id).await?;
 		}
 
 		// From this point onward, we are done with the job and it can't be interrupted anymore
@@ -303,9 +363,13 @@ impl Job for FileIdentifier {
 }
 
 impl FileIdentifier {
+	/// With `verify_integrity`, every identified file without an `integrity_checksum` gets one
+	/// after the orphans are identified, and objects wrongly merged by a `cas_id` collision are
+	/// split apart. It reads whole files, so it's much slower than identifying them.
 	pub fn new(
 		location: location::Data,
 		sub_path: Option<PathBuf>,
+		verify_integrity: bool,
 	) -> Result<Self, file_identifier::Error> {
 		Ok(Self {
 			location_path: maybe_missing(&location.path, "location.path")
@@ -313,9 +377,12 @@ impl FileIdentifier {
 				.map(Arc::new)?,
 			location: Arc::new(location),
 			sub_path,
+			verify_integrity,
 			file_paths_accumulator: HashMap::new(),
 			file_paths_ids_with_priority: HashSet::new(),
 			last_orphan_file_path_id: None,
+			last_checksummed_file_path_id: None,
+			objects_to_verify: HashSet::new(),
 			phase: Phase::default(),
 			metadata: Metadata::default(),
 			errors: Vec::new(),
@@ -445,15 +512,20 @@ impl FileIdentifier {
 					self.phase = Phase::IdentifyingFiles;
 				}
 
+				Phase::VerifyingIntegrity => {
+					self.dispatch_checksummer_tasks(ctx, dispatcher, pending_running_tasks)
+						.await?;
+				}
+
 				_ => {}
 			}
 
 			ctx.progress(vec![
-				ProgressUpdate::TaskCount(if matches!(self.phase, Phase::IdentifyingFiles) {
-					u64::from(self.metadata.total_identifier_tasks)
-				} else {
-					u64::from(self.metadata.total_object_processor_tasks)
-				}),
+				ProgressUpdate::TaskCount(u64::from(match self.phase {
+					Phase::IdentifyingFiles => self.metadata.total_identifier_tasks,
+					Phase::VerifyingIntegrity => self.metadata.total_checksummer_tasks,
+					_ => self.metadata.total_object_processor_tasks,
+				})),
 				ProgressUpdate::phase(self.phase),
 				ProgressUpdate::Message(format!(
 					"{} files to be identified",
@@ -504,6 +576,15 @@ impl FileIdentifier {
 				ctx,
 			)
 			.await;
+		} else if any_task_output.is::<checksummer::Output>() {
+			self.process_checksummer_output(
+				task_id,
+				*any_task_output
+					.downcast::<checksummer::Output>()
+					.expect("just checked"),
+				ctx,
+			)
+			.await;
 		} else {
 			unreachable!("Unexpected task output type: <id='{task_id}'>");
 		}
@@ -669,6 +750,52 @@ impl FileIdentifier {
 		);
 	}
 
+	#[instrument(skip(self, object_ids, errors, ctx))]
+	async fn process_checksummer_output<OuterCtx: OuterContext>(
+		&mut self,
+		task_id: TaskId,
+		checksummer::Output {
+			object_ids,
+			checksum_time,
+			save_db_time,
+			checksummed_files_count,
+			errors,
+		}: checksummer::Output,
+		ctx: &impl JobContext<OuterCtx>,
+	) {
+		self.metadata.mean_checksum_time += checksum_time;
+		self.metadata.mean_save_db_time_on_checksummer_tasks += save_db_time;
+		self.metadata.checksummed_files_count += checksummed_files_count;
+
+		self.objects_to_verify.extend(object_ids);
+
+		if !errors.is_empty() {
+			warn!(
+				?errors,
+				"Non critical errors while computing integrity checksums;"
+			);
+			self.errors.extend(errors);
+		}
+
+		self.metadata.completed_checksummer_tasks += 1;
+
+		ctx.progress(vec![
+			ProgressUpdate::CompletedTaskCount(u64::from(
+				self.metadata.completed_checksummer_tasks,
+			)),
+			ProgressUpdate::Message(format!(
+				"Checksummed {} of {} files",
+				self.metadata.checksummed_files_count, self.metadata.total_files_to_checksum
+			)),
+		])
+		.await;
+
+		debug!(
+			"Processed ({}/{}) checksummer tasks, took: {checksum_time:?};",
+			self.metadata.completed_checksummer_tasks, self.metadata.total_checksummer_tasks,
+		);
+	}
+
 	async fn dispatch_priority_identifier_tasks<OuterCtx: OuterContext>(
 		&mut self,
 		sub_iso_file_path: &IsolatedFilePathData<'static>,
@@ -742,6 +869,7 @@ impl FileIdentifier {
 						Arc::clone(ctx.db()),
 						ctx.sync().clone(),
 						device_id,
+						ctx.io_throttle().clone(),
 					))
 					.await?,
 			);
@@ -827,6 +955,7 @@ impl FileIdentifier {
 						Arc::clone(ctx.db()),
 						ctx.sync().clone(),
 						device_id,
+						ctx.io_throttle().clone(),
 					))
 					.await?,
 			);
@@ -835,6 +964,119 @@ impl FileIdentifier {
 		Ok(())
 	}
 
+	async fn dispatch_checksummer_tasks<OuterCtx: OuterContext>(
+		&mut self,
+		ctx: &impl JobContext<OuterCtx>,
+		dispatcher: &JobTaskDispatcher,
+		pending_running_tasks: &FuturesUnordered<TaskHandle<Error>>,
+	) -> Result<(), JobErrorOrDispatcherError<file_identifier::Error>> {
+		let db = ctx.db();
+		let maybe_sub_iso_file_path =
+			maybe_get_iso_file_path_from_sub_path::<file_identifier::Error>(
+				self.location.id,
+				self.sub_path.as_ref(),
+				&*self.location_path,
+				db,
+			)
+			.await?;
+
+		loop {
+			#[allow(clippy::cast_possible_wrap)]
+			// SAFETY: we know that CHUNK_SIZE is a valid i64
+			let file_paths = db
+				.file_path()
+				.find_many(integrity_checksum_filters(
+					self.location.id,
+					self.last_checksummed_file_path_id,
+					maybe_sub_iso_file_path.as_ref(),
+				))
+				.order_by(file_path::id::order(SortOrder::Asc))
+				.take(CHUNK_SIZE as i64)
+				.select(file_path_for_file_identifier::select())
+				.exec()
+				.await
+				.map_err(file_identifier::Error::from)?;
+
+			if file_paths.is_empty() {
+				break;
+			}
+
+			self.last_checksummed_file_path_id =
+				Some(file_paths.last().expect("file_paths is not empty").id);
+
+			self.metadata.total_files_to_checksum += file_paths.len() as u64;
+			self.metadata.total_checksummer_tasks += 1;
+
+			ctx.progress(vec![
+				ProgressUpdate::TaskCount(u64::from(self.metadata.total_checksummer_tasks)),
+				ProgressUpdate::Message(format!(
+					"Found {} files to checksum",
+					self.metadata.total_files_to_checksum
+				)),
+			])
+			.await;
+
+			pending_running_tasks.push(
+				dispatcher
+					.dispatch(tasks::Checksummer::new(
+						Arc::clone(&self.location),
+						Arc::clone(&self.location_path),
+						file_paths,
+						Arc::clone(db),
+						ctx.sync().clone(),
+						ctx.io_throttle().clone(),
+					))
+					.await?,
+			);
+		}
+
+		Ok(())
+	}
+
+	/// Runs after every checksummer task is done, so all files of the verified objects in this
+	/// location already have their checksums
+	async fn split_cas_id_collisions<OuterCtx: OuterContext>(
+		&mut self,
+		ctx: &impl JobContext<OuterCtx>,
+		device_id: device::id::Type,
+	) -> Result<(), file_identifier::Error> {
+		let start = Instant::now();
+
+		ctx.progress(vec![ProgressUpdate::Message(format!(
+			"Checking {} objects for cas_id collisions",
+			self.objects_to_verify.len()
+		))])
+		.await;
+
+		let object_ids = mem::take(&mut self.objects_to_verify)
+			.into_iter()
+			.collect::<Vec<_>>();
+
+		for chunk in object_ids.chunks(CHUNK_SIZE) {
+			let collisions = tasks::split_objects_by_integrity_checksum(
+				chunk.to_vec(),
+				ctx.db(),
+				ctx.sync(),
+				device_id,
+			)
+			.await?;
+
+			if !collisions.is_empty() {
+				warn!(
+					?collisions,
+					"Split objects wrongly merged by cas_id collisions;"
+				);
+				self.metadata.split_objects_count += collisions.len() as u64;
+				self.errors
+					.extend(collisions.into_iter().map(NonCriticalError::from));
+			}
+		}
+
+		self.metadata.split_collisions_time = start.elapsed();
+
+		Ok(())
+	}
+
 	async fn cancel_job(
 		&mut self,
 		pending_running_tasks: &mut FuturesUnordered<TaskHandle<Error>>,
@@ -854,6 +1096,7 @@ impl FileIdentifier {
 enum TaskKind {
 	Identifier,
 	ObjectProcessor,
+	Checksummer,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -861,10 +1104,16 @@ struct SaveState {
 	location: Arc<location::Data>,
 	location_path: Arc<PathBuf>,
 	sub_path: Option<PathBuf>,
+	#[serde(default)]
+	verify_integrity: bool,
 
 	file_paths_accumulator: HashMap<CasId<'static>, Vec<FilePathToCreateOrLinkObject>>,
 	file_paths_ids_with_priority: HashSet<file_path::id::Type>,
 	last_orphan_file_path_id: Option<i32>,
+	#[serde(default)]
+	last_checksummed_file_path_id: Option<file_path::id::Type>,
+	#[serde(default)]
+	objects_to_verify: HashSet<object::id::Type>,
 
 	phase: Phase,
 	metadata: Metadata,
@@ -875,6 +1124,7 @@ struct SaveState {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Metadata {
 	mean_extract_metadata_time: Duration,
 	mean_save_db_time_on_identifier_tasks: Duration,
@@ -890,6 +1140,14 @@ pub struct Metadata {
 	completed_identifier_tasks: u32,
 	total_object_processor_tasks: u32,
 	completed_object_processor_tasks: u32,
+	mean_checksum_time: Duration,
+	mean_save_db_time_on_checksummer_tasks: Duration,
+	split_collisions_time: Duration,
+	total_files_to_checksum: u64,
+	checksummed_files_count: u64,
+	split_objects_count: u64,
+	total_checksummer_tasks: u32,
+	completed_checksummer_tasks: u32,
 }
 
 impl From<Metadata> for Vec<ReportOutputMetadata> {
@@ -909,6 +1167,14 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 			completed_identifier_tasks,
 			total_object_processor_tasks,
 			completed_object_processor_tasks,
+			mut mean_checksum_time,
+			mut mean_save_db_time_on_checksummer_tasks,
+			split_collisions_time,
+			total_files_to_checksum,
+			checksummed_files_count,
+			split_objects_count,
+			total_checksummer_tasks,
+			completed_checksummer_tasks,
 		}: Metadata,
 	) -> Self {
 		// To avoid division by zero
@@ -919,6 +1185,9 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 		mean_assign_to_existing_object_time /= u32::max(total_object_processor_tasks, 1);
 		mean_create_object_time /= u32::max(total_object_processor_tasks, 1);
 
+		mean_checksum_time /= u32::max(total_checksummer_tasks, 1);
+		mean_save_db_time_on_checksummer_tasks /= u32::max(total_checksummer_tasks, 1);
+
 		vec![
 			ReportOutputMetadata::FileIdentifier {
 				total_orphan_paths: u64_to_frontend(total_found_orphans),
@@ -970,6 +1239,29 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 					"completed_object_processor_tasks".into(),
 					json!(completed_object_processor_tasks),
 				),
+				("mean_checksum_time".into(), json!(mean_checksum_time)),
+				(
+					"mean_save_db_time_on_checksummer_tasks".into(),
+					json!(mean_save_db_time_on_checksummer_tasks),
+				),
+				("split_collisions_time".into(), json!(split_collisions_time)),
+				(
+					"total_files_to_checksum".into(),
+					json!(total_files_to_checksum),
+				),
+				(
+					"checksummed_files_count".into(),
+					json!(checksummed_files_count),
+				),
+				("split_objects_count".into(), json!(split_objects_count)),
+				(
+					"total_checksummer_tasks".into(),
+					json!(total_checksummer_tasks),
+				),
+				(
+					"completed_checksummer_tasks".into(),
+					json!(completed_checksummer_tasks),
+				),
 			])),
 		]
 	}
@@ -981,9 +1273,12 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for FileIdentifier {
 			location,
 			location_path,
 			sub_path,
+			verify_integrity,
 			file_paths_accumulator,
 			file_paths_ids_with_priority,
 			last_orphan_file_path_id,
+			last_checksummed_file_path_id,
+			objects_to_verify,
 			phase,
 			metadata,
 			errors,
@@ -1006,6 +1301,12 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for FileIdentifier {
 						.serialize()
 						.await
 						.map(|bytes| (TaskKind::ObjectProcessor, bytes))
+				} else if task.is::<tasks::Checksummer>() {
+					task.downcast::<tasks::Checksummer>()
+						.expect("just checked")
+						.serialize()
+						.await
+						.map(|bytes| (TaskKind::Checksummer, bytes))
 				} else {
 					unreachable!("Unexpected task type")
 				}
@@ -1024,9 +1325,12 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for FileIdentifier {
 			location,
 			location_path,
 			sub_path,
+			verify_integrity,
 			file_paths_accumulator,
 			file_paths_ids_with_priority,
 			last_orphan_file_path_id,
+			last_checksummed_file_path_id,
+			objects_to_verify,
 			phase,
 			metadata,
 			errors,
@@ -1043,9 +1347,12 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for FileIdentifier {
 			location,
 			location_path,
 			sub_path,
+			verify_integrity,
 			file_paths_accumulator,
 			file_paths_ids_with_priority,
 			last_orphan_file_path_id,
+			last_checksummed_file_path_id,
+			objects_to_verify,
 			phase,
 			metadata,
 			errors,
@@ -1057,9 +1364,12 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for FileIdentifier {
 				location,
 				location_path,
 				sub_path,
+				verify_integrity,
 				file_paths_accumulator,
 				file_paths_ids_with_priority,
 				last_orphan_file_path_id,
+				last_checksummed_file_path_id,
+				objects_to_verify,
 				phase,
 				metadata,
 				errors,
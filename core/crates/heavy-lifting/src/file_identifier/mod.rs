@@ -1,4 +1,4 @@
-use crate::{utils::sub_path, OuterContext};
+use crate::{utils::sub_path, IoThrottle, OuterContext};
 
 use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
 use sd_core_prisma_helpers::CasId;
@@ -26,11 +26,20 @@ use tracing::trace;
 use uuid::Uuid;
 
 mod cas_id;
+mod detected_extension;
+mod integrity_checksum;
 pub mod job;
 mod shallow;
 mod tasks;
 
-pub use cas_id::generate_cas_id;
+pub use cas_id::{
+	cas_id_from_integrity_checksum, generate_cas_id, generate_cas_id_from_reader,
+	hashed_bytes_count,
+};
+pub use detected_extension::{
+	detect_extension, has_mismatched_magic_bytes, read_header, sniffed_bytes_count,
+};
+pub use integrity_checksum::generate_integrity_checksum;
 
 pub use job::FileIdentifier;
 pub use shallow::shallow;
@@ -86,27 +95,48 @@ pub enum NonCriticalFileIdentifierError {
 	},
 	#[error("file path without is_dir field: <file_path_id='{0}'>")]
 	FilePathWithoutIsDirField(file_path::id::Type),
+	#[error("failed to compute integrity checksum: {0}")]
+	FailedToComputeIntegrityChecksum(String),
+	#[error(
+		"files with the same cas_id have different contents, moved {} file paths to new objects: \
+		<cas_id='{cas_id}', object_pub_id='{object_pub_id}'>",
+		moved_file_path_ids.len()
+	)]
+	CasIdCollision {
+		cas_id: String,
+		object_pub_id: Uuid,
+		moved_file_path_ids: Vec<file_path::id::Type>,
+	},
 }
 
 #[derive(Debug, Clone)]
 pub struct FileMetadata {
 	pub cas_id: Option<CasId<'static>>,
 	pub kind: ObjectKind,
+	/// Extension told by the file contents, only for files without an extension, with an
+	/// unknown one or with one whose magic bytes don't match their contents
+	pub detected_extension: Option<Extension>,
 	pub fs_metadata: Metadata,
 }
 
 impl FileMetadata {
 	/// Fetch metadata from the file system and generate a cas id for the file
-	/// if it's not empty.
+	/// if it's not empty, waiting on the I/O budget of a job if one is given.
+	/// Files without a known extension or misnamed have their contents sniffed to tell their kind.
 	///
 	/// # Panics
 	/// Will panic if the file is a directory.
 	pub async fn new(
 		location_path: impl AsRef<Path> + Send,
 		iso_file_path: &IsolatedFilePathData<'_>,
+		io_throttle: Option<&IoThrottle>,
 	) -> Result<Self, FileIOError> {
 		let path = location_path.as_ref().join(iso_file_path);
 
+		if let Some(io_throttle) = io_throttle {
+			io_throttle.stat_files(1).await;
+		}
+
 		let fs_metadata = fs::metadata(&path)
 			.await
 			.map_err(|e| FileIOError::from((&path, e)))?;
@@ -116,39 +146,77 @@ impl FileMetadata {
 			return Ok(Self {
 				cas_id: None,
 				kind: ObjectKind::Folder,
+				detected_extension: None,
 				fs_metadata,
 			});
 		}
 
 		// derive Object kind
-		let kind = Extension::resolve_conflicting(&path, false)
-			.await
-			.map_or(ObjectKind::Unknown, Into::into);
+		let extension = Extension::resolve_conflicting(&path, false).await;
 
 		let cas_id = if fs_metadata.len() != 0 {
+			if let Some(io_throttle) = io_throttle {
+				io_throttle
+					.hash_bytes(hashed_bytes_count(fs_metadata.len()))
+					.await;
+			}
+
 			generate_cas_id(&path, fs_metadata.len())
 				.await
 				.map_err(|e| FileIOError::from((&path, e)))?
 		} else {
 			// We can't do shit with empty files
+			let kind = extension.map_or(ObjectKind::Unknown, Into::into);
 			trace!(path = %path.display(), %kind, "Skipping empty file;");
 			return Ok(Self {
 				cas_id: None,
 				kind,
+				detected_extension: None,
 				fs_metadata,
 			});
 		};
 
+		// Extensionless files or ones which can be misnamed, so let's look at their contents
+		let detected_extension = if extension
+			.as_ref()
+			.map_or(true, Extension::has_known_magic_bytes)
+		{
+			if let Some(io_throttle) = io_throttle {
+				io_throttle
+					.hash_bytes(sniffed_bytes_count(fs_metadata.len()))
+					.await;
+			}
+
+			let header = read_header(&path)
+				.await
+				.map_err(|e| FileIOError::from((&path, e)))?;
+
+			match &extension {
+				Some(extension) if !has_mismatched_magic_bytes(&header, extension) => None,
+				_ => detect_extension(&header, fs_metadata.len()),
+			}
+		} else {
+			None
+		};
+
+		// Contents tell the kind of misnamed files better than their extension
+		let kind = detected_extension
+			.clone()
+			.or(extension)
+			.map_or(ObjectKind::Unknown, Into::into);
+
 		trace!(
 			path = %path.display(),
 			?cas_id,
 			%kind,
+			?detected_extension,
 			"Analyzed file;",
 		);
 
 		Ok(Self {
 			cas_id: Some(cas_id),
 			kind,
+			detected_extension,
 			fs_metadata,
 		})
 	}
@@ -173,6 +241,36 @@ fn orphan_path_filters_shallow(
 	)
 }
 
+/// Identified files still without a full content checksum
+fn integrity_checksum_filters(
+	location_id: location::id::Type,
+	file_path_id: Option<file_path::id::Type>,
+	maybe_sub_iso_file_path: Option<&IsolatedFilePathData<'_>>,
+) -> Vec<file_path::WhereParam> {
+	sd_utils::chain_optional_iter(
+		[
+			file_path::location_id::equals(Some(location_id)),
+			file_path::is_dir::equals(Some(false)),
+			file_path::symlink_target::equals(None),
+			// Archives members can only be read through their archives
+			file_path::archive_member_path::equals(None),
+			file_path::cas_id::not(None),
+			file_path::object_id::not(None),
+			file_path::integrity_checksum::equals(None),
+		],
+		[
+			file_path_id.map(file_path::id::gt),
+			maybe_sub_iso_file_path.as_ref().map(|sub_iso_file_path| {
+				file_path::materialized_path::starts_with(
+					sub_iso_file_path
+						.materialized_path_for_children()
+						.expect("sub path iso_file_path must be a directory"),
+				)
+			}),
+		],
+	)
+}
+
 fn orphan_path_filters_deep(
 	location_id: location::id::Type,
 	file_path_id: Option<file_path::id::Type>,
@@ -1,5 +1,5 @@
 use crate::{
-	file_identifier, utils::sub_path::maybe_get_iso_file_path_from_sub_path, Error,
+	file_identifier, utils::sub_path::maybe_get_iso_file_path_from_sub_path, Error, IoThrottle,
 	NonCriticalError, OuterContext, UpdateEvent,
 };
 
@@ -118,6 +118,7 @@ pub async fn shallow(
 				Arc::clone(ctx.db()),
 				ctx.sync().clone(),
 				device_id,
+				IoThrottle::default(),
 			))
 			.await
 		else {
@@ -0,0 +1,340 @@
+use crate::{
+	file_identifier::{self, generate_integrity_checksum, NonCriticalFileIdentifierError},
+	Error, IoThrottle, NonCriticalError,
+};
+
+use sd_core_prisma_helpers::{file_path_for_file_identifier, FilePathPubId};
+use sd_core_sync::SyncManager;
+
+use sd_prisma::{
+	prisma::{file_path, location, object, PrismaClient},
+	prisma_sync,
+};
+use sd_sync::{sync_db_entry, OperationFactory};
+use sd_task_system::{
+	ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
+};
+
+use std::{
+	collections::{HashMap, HashSet},
+	future::IntoFuture,
+	mem,
+	path::PathBuf,
+	pin::pin,
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use futures_concurrency::stream::Merge;
+use serde::{Deserialize, Serialize};
+use tokio::{io, time::Instant};
+use tracing::{error, instrument, trace, Level};
+
+use super::identifier::try_iso_file_path_extraction;
+
+/// Computes full content checksums of already identified files, saving them as their
+/// `integrity_checksum`
+#[derive(Debug)]
+pub struct Checksummer {
+	// Task control
+	id: TaskId,
+
+	// Received input args
+	location: Arc<location::Data>,
+	location_path: Arc<PathBuf>,
+	file_paths_by_id: HashMap<FilePathPubId, file_path_for_file_identifier::Data>,
+
+	// Inner state
+	checksums_by_id: HashMap<FilePathPubId, String>,
+	object_ids: HashSet<object::id::Type>,
+
+	// Out collector
+	output: Output,
+
+	// Dependencies
+	db: Arc<PrismaClient>,
+	sync: SyncManager,
+	io_throttle: IoThrottle,
+}
+
+/// Output from the `[Checksummer]` task
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Output {
+	/// Objects of the checksummed files, which must be checked for `cas_id` collisions
+	pub object_ids: Vec<object::id::Type>,
+
+	/// Collected metric about time elapsed reading and hashing files
+	pub checksum_time: Duration,
+
+	/// Collected metric about time spent saving checksums on disk
+	pub save_db_time: Duration,
+
+	/// Total number of files that got an `integrity_checksum`
+	pub checksummed_files_count: u64,
+
+	/// Non critical errors that happened during the task execution
+	pub errors: Vec<NonCriticalError>,
+}
+
+#[async_trait::async_trait]
+impl Task<Error> for Checksummer {
+	fn id(&self) -> TaskId {
+		self.id
+	}
+
+	#[instrument(
+		skip(self, interrupter),
+		fields(
+			task_id = %self.id,
+			location_id = %self.location.id,
+			location_path = %self.location_path.display(),
+			files_count = %self.file_paths_by_id.len(),
+		),
+		ret(level = Level::TRACE),
+		err,
+	)]
+	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
+	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
+		enum StreamMessage {
+			Processed(FilePathPubId, Result<String, (PathBuf, io::Error)>),
+			Interrupt(InterruptionKind),
+		}
+
+		let Self {
+			location,
+			location_path,
+			file_paths_by_id,
+			checksums_by_id,
+			object_ids,
+			output,
+			io_throttle,
+			..
+		} = self;
+
+		if !file_paths_by_id.is_empty() {
+			let start_time = Instant::now();
+			let io_throttle = &*io_throttle;
+
+			let checksum_futures = file_paths_by_id
+				.iter()
+				.filter_map(|(file_path_id, file_path)| {
+					try_iso_file_path_extraction(
+						location.id,
+						file_path_id.clone(),
+						file_path,
+						Arc::clone(location_path),
+						&mut output.errors,
+					)
+				})
+				.map(|(file_path_id, iso_file_path, location_path)| async move {
+					let path = location_path.join(&iso_file_path);
+
+					StreamMessage::Processed(
+						file_path_id,
+						generate_integrity_checksum(&path, io_throttle)
+							.await
+							.map_err(|e| (path, e)),
+					)
+				})
+				.collect::<FuturesUnordered<_>>();
+
+			let mut msg_stream = pin!((
+				checksum_futures,
+				stream::once(interrupter.into_future()).map(StreamMessage::Interrupt)
+			)
+				.merge());
+
+			while let Some(msg) = msg_stream.next().await {
+				match msg {
+					StreamMessage::Processed(file_path_pub_id, res) => {
+						let file_path = file_paths_by_id
+							.remove(&file_path_pub_id)
+							.expect("file_path must be here");
+
+						match res {
+							Ok(checksum) => {
+								object_ids.extend(file_path.object_id);
+								checksums_by_id.insert(file_path_pub_id, checksum);
+							}
+							Err((path, e)) => {
+								error!(
+									path = %path.display(),
+									?e,
+									"Failed to compute integrity checksum;",
+								);
+								output.errors.push(
+									NonCriticalFileIdentifierError::FailedToComputeIntegrityChecksum(
+										format!("<file_path_pub_id='{file_path_pub_id}', error={e}>"),
+									)
+									.into(),
+								);
+							}
+						}
+
+						if file_paths_by_id.is_empty() {
+							// All files have been processed so we can end this merged stream
+							// and don't keep waiting an interrupt signal
+							break;
+						}
+					}
+
+					StreamMessage::Interrupt(kind) => {
+						trace!(?kind, "Interrupted;");
+						output.checksum_time += start_time.elapsed();
+						return Ok(match kind {
+							InterruptionKind::Pause => ExecStatus::Paused,
+							InterruptionKind::Cancel => ExecStatus::Canceled,
+						});
+					}
+				}
+			}
+
+			output.checksum_time += start_time.elapsed();
+		}
+
+		if !checksums_by_id.is_empty() {
+			let start_time = Instant::now();
+
+			output.checksummed_files_count =
+				save_integrity_checksums(checksums_by_id, &self.db, &self.sync).await?;
+
+			output.save_db_time = start_time.elapsed();
+
+			trace!(save_db_time = ?output.save_db_time, "Integrity checksums saved to db;");
+		}
+
+		output.object_ids = object_ids.drain().collect();
+
+		Ok(ExecStatus::Done(mem::take(output).into_output()))
+	}
+}
+
+impl Checksummer {
+	#[must_use]
+	pub fn new(
+		location: Arc<location::Data>,
+		location_path: Arc<PathBuf>,
+		file_paths: Vec<file_path_for_file_identifier::Data>,
+		db: Arc<PrismaClient>,
+		sync: SyncManager,
+		io_throttle: IoThrottle,
+	) -> Self {
+		Self {
+			id: TaskId::new_v4(),
+			location,
+			location_path,
+			file_paths_by_id: file_paths
+				.into_iter()
+				.map(|file_path| (file_path.pub_id.as_slice().into(), file_path))
+				.collect(),
+			checksums_by_id: HashMap::new(),
+			object_ids: HashSet::new(),
+			output: Output::default(),
+			db,
+			sync,
+			io_throttle,
+		}
+	}
+}
+
+#[instrument(skip_all, err, fields(checksums_count = checksums_by_id.len()))]
+async fn save_integrity_checksums(
+	checksums_by_id: &mut HashMap<FilePathPubId, String>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, file_identifier::Error> {
+	let (ops, queries) = checksums_by_id
+		.drain()
+		.map(|(pub_id, checksum)| {
+			let (sync_param, db_param) = sync_db_entry!(checksum, file_path::integrity_checksum);
+
+			(
+				sync.shared_update(
+					prisma_sync::file_path::SyncId {
+						pub_id: pub_id.to_db(),
+					},
+					[sync_param],
+				),
+				db.file_path()
+					.update(file_path::pub_id::equals(pub_id.to_db()), vec![db_param])
+					.select(file_path::select!({ id })),
+			)
+		})
+		.unzip::<_, _, Vec<_>, Vec<_>>();
+
+	sync.write_ops(db, (ops, queries))
+		.await
+		.map(|updated| updated.len() as u64)
+		.map_err(Into::into)
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveState {
+	id: TaskId,
+	location: Arc<location::Data>,
+	location_path: Arc<PathBuf>,
+	file_paths_by_id: HashMap<FilePathPubId, file_path_for_file_identifier::Data>,
+	checksums_by_id: HashMap<FilePathPubId, String>,
+	object_ids: HashSet<object::id::Type>,
+	output: Output,
+}
+
+impl SerializableTask<Error> for Checksummer {
+	type SerializeError = rmp_serde::encode::Error;
+
+	type DeserializeError = rmp_serde::decode::Error;
+
+	type DeserializeCtx = (Arc<PrismaClient>, SyncManager, IoThrottle);
+
+	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
+		let Self {
+			id,
+			location,
+			location_path,
+			file_paths_by_id,
+			checksums_by_id,
+			object_ids,
+			output,
+			..
+		} = self;
+
+		rmp_serde::to_vec_named(&SaveState {
+			id,
+			location,
+			location_path,
+			file_paths_by_id,
+			checksums_by_id,
+			object_ids,
+			output,
+		})
+	}
+
+	async fn deserialize(
+		data: &[u8],
+		(db, sync, io_throttle): Self::DeserializeCtx,
+	) -> Result<Self, Self::DeserializeError> {
+		rmp_serde::from_slice::<SaveState>(data).map(
+			|SaveState {
+			     id,
+			     location,
+			     location_path,
+			     file_paths_by_id,
+			     checksums_by_id,
+			     object_ids,
+			     output,
+			 }| Self {
+				id,
+				location,
+				location_path,
+				file_paths_by_id,
+				checksums_by_id,
+				object_ids,
+				output,
+				db,
+				sync,
+				io_throttle,
+			},
+		)
+	}
+}
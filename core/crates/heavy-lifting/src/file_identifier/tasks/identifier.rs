@@ -1,18 +1,18 @@
 use crate::{
 	file_identifier::{self, FileMetadata},
-	Error, NonCriticalError,
+	Error, IoThrottle, NonCriticalError,
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::{file_path_for_file_identifier, CasId, FilePathPubId};
 use sd_core_sync::SyncManager;
 
-use sd_file_ext::kind::ObjectKind;
+use sd_file_ext::{extensions::Extension, kind::ObjectKind, magic::ExtensionPossibility};
 use sd_prisma::{
 	prisma::{device, file_path, location, PrismaClient},
 	prisma_sync,
 };
-use sd_sync::{sync_db_entry, OperationFactory};
+use sd_sync::{sync_db_entry, sync_db_nullable_entry, OperationFactory};
 use sd_task_system::{
 	ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
 };
@@ -36,6 +36,7 @@ struct IdentifiedFile {
 	file_path: file_path_for_file_identifier::Data,
 	cas_id: CasId<'static>,
 	kind: ObjectKind,
+	detected_extension: Option<String>,
 }
 
 impl IdentifiedFile {
@@ -43,11 +44,13 @@ impl IdentifiedFile {
 		file_path: file_path_for_file_identifier::Data,
 		cas_id: impl Into<CasId<'static>>,
 		kind: ObjectKind,
+		detected_extension: Option<Extension>,
 	) -> Self {
 		Self {
 			file_path,
 			cas_id: cas_id.into(),
 			kind,
+			detected_extension: detected_extension.map(|extension| extension.to_string()),
 		}
 	}
 }
@@ -74,6 +77,7 @@ pub struct Identifier {
 	// Dependencies
 	db: Arc<PrismaClient>,
 	sync: SyncManager,
+	io_throttle: IoThrottle,
 }
 
 /// Output from the `[Identifier]` task
@@ -141,106 +145,118 @@ impl Task<Error> for Identifier {
 			file_paths_without_cas_id,
 			identified_files,
 			output,
+			io_throttle,
 			..
 		} = self;
 
-		if !file_paths_by_id.is_empty() {
-			let start_time = Instant::now();
-
-			let extraction_futures = file_paths_by_id
-				.iter()
-				.filter_map(|(file_path_id, file_path)| {
-					try_iso_file_path_extraction(
-						location.id,
-						file_path_id.clone(),
-						file_path,
-						Arc::clone(location_path),
-						&mut output.errors,
-					)
-				})
-				.map(|(file_path_id, iso_file_path, location_path)| async move {
-					StreamMessage::Processed(
-						file_path_id,
-						FileMetadata::new(&*location_path, &iso_file_path).await,
-					)
-				})
-				.collect::<FuturesUnordered<_>>();
+		if !file_paths_by_id.is_empty() || !identified_files.is_empty() {
+			// Archives members are already identified, so only files on disk are read here
+			if !file_paths_by_id.is_empty() {
+				let start_time = Instant::now();
+				let io_throttle = &*io_throttle;
+
+				let extraction_futures = file_paths_by_id
+					.iter()
+					.filter_map(|(file_path_id, file_path)| {
+						try_iso_file_path_extraction(
+							location.id,
+							file_path_id.clone(),
+							file_path,
+							Arc::clone(location_path),
+							&mut output.errors,
+						)
+					})
+					.map(|(file_path_id, iso_file_path, location_path)| async move {
+						StreamMessage::Processed(
+							file_path_id,
+							FileMetadata::new(&*location_path, &iso_file_path, Some(io_throttle))
+								.await,
+						)
+					})
+					.collect::<FuturesUnordered<_>>();
 
-			let mut msg_stream = pin!((
-				extraction_futures,
-				stream::once(interrupter.into_future()).map(StreamMessage::Interrupt)
-			)
-				.merge());
-
-			while let Some(msg) = msg_stream.next().await {
-				match msg {
-					StreamMessage::Processed(file_path_pub_id, res) => {
-						let file_path = file_paths_by_id
-							.remove(&file_path_pub_id)
-							.expect("file_path must be here");
-
-						trace!(
-							files_remaining = file_paths_by_id.len(),
-							%file_path_pub_id,
-							"Processed file;",
-						);
-
-						match res {
-							Ok(FileMetadata {
-								cas_id: Some(cas_id),
-								kind,
-								..
-							}) => {
-								identified_files.insert(
-									file_path_pub_id,
-									IdentifiedFile::new(file_path, cas_id, kind),
-								);
-							}
-							Ok(FileMetadata {
-								cas_id: None, kind, ..
-							}) => {
-								let file_path_for_file_identifier::Data {
-									id,
-									pub_id,
-									date_created,
-									..
-								} = file_path;
-								file_paths_without_cas_id.push(FilePathToCreateOrLinkObject {
-									id,
-									file_path_pub_id: pub_id.into(),
+				let mut msg_stream = pin!((
+					extraction_futures,
+					stream::once(interrupter.into_future()).map(StreamMessage::Interrupt)
+				)
+					.merge());
+
+				while let Some(msg) = msg_stream.next().await {
+					match msg {
+						StreamMessage::Processed(file_path_pub_id, res) => {
+							let file_path = file_paths_by_id
+								.remove(&file_path_pub_id)
+								.expect("file_path must be here");
+
+							trace!(
+								files_remaining = file_paths_by_id.len(),
+								%file_path_pub_id,
+								"Processed file;",
+							);
+
+							match res {
+								Ok(FileMetadata {
+									cas_id: Some(cas_id),
 									kind,
-									created_at: date_created,
-								});
+									detected_extension,
+									..
+								}) => {
+									identified_files.insert(
+										file_path_pub_id,
+										IdentifiedFile::new(
+											file_path,
+											cas_id,
+											kind,
+											detected_extension,
+										),
+									);
+								}
+								Ok(FileMetadata {
+									cas_id: None, kind, ..
+								}) => {
+									let file_path_for_file_identifier::Data {
+										id,
+										pub_id,
+										date_created,
+										..
+									} = file_path;
+									file_paths_without_cas_id.push(FilePathToCreateOrLinkObject {
+										id,
+										file_path_pub_id: pub_id.into(),
+										kind,
+										created_at: date_created,
+									});
+								}
+								Err(e) => {
+									handle_non_critical_errors(
+										file_path_pub_id,
+										&e,
+										&mut output.errors,
+									);
+								}
 							}
-							Err(e) => {
-								handle_non_critical_errors(
-									file_path_pub_id,
-									&e,
-									&mut output.errors,
-								);
+
+							if file_paths_by_id.is_empty() {
+								trace!("All files have been processed");
+								// All files have been processed so we can end this merged stream
+								// and don't keep waiting an interrupt signal
+								break;
 							}
 						}
 
-						if file_paths_by_id.is_empty() {
-							trace!("All files have been processed");
-							// All files have been processed so we can end this merged stream
-							// and don't keep waiting an interrupt signal
-							break;
+						StreamMessage::Interrupt(kind) => {
+							trace!(?kind, "Interrupted;");
+							output.extract_metadata_time += start_time.elapsed();
+							return Ok(match kind {
+								InterruptionKind::Pause => ExecStatus::Paused,
+								InterruptionKind::Cancel => ExecStatus::Canceled,
+							});
 						}
 					}
-
-					StreamMessage::Interrupt(kind) => {
-						trace!(?kind, "Interrupted;");
-						output.extract_metadata_time += start_time.elapsed();
-						return Ok(match kind {
-							InterruptionKind::Pause => ExecStatus::Paused,
-							InterruptionKind::Cancel => ExecStatus::Canceled,
-						});
-					}
 				}
-			}
 
-			output.extract_metadata_time = start_time.elapsed();
+				output.extract_metadata_time = start_time.elapsed();
+			}
 
 			output.total_identified_files =
 				identified_files.len() as u64 + file_paths_without_cas_id.len() as u64;
@@ -280,6 +296,7 @@ impl Task<Error> for Identifier {
 							file_path_for_file_identifier::Data {
 								id, date_created, ..
 							},
+						..
 					},
 				)| {
 					map.entry(cas_id)
@@ -330,70 +347,123 @@ impl Identifier {
 		db: Arc<PrismaClient>,
 		sync: SyncManager,
 		device_id: device::id::Type,
+		io_throttle: IoThrottle,
 	) -> Self {
 		let mut output = Output::default();
 
 		let file_paths_count = file_paths.len();
-		let directories_count = file_paths
+		let directories_and_aliases_count = file_paths
 			.iter()
-			.filter(|file_path| file_path.is_dir.is_some_and(identity))
+			.filter(|file_path| {
+				file_path.is_dir.is_some_and(identity) || file_path.symlink_target.is_some()
+			})
 			.count();
 
-		let (file_paths_by_id, file_paths_without_cas_id) = file_paths.into_iter().fold(
-			(
-				HashMap::with_capacity(file_paths_count - directories_count),
-				Vec::with_capacity(directories_count),
-			),
-			|(mut file_paths_by_id, mut directory_file_paths), file_path| {
-				match file_path.is_dir {
-					Some(true) => {
-						let file_path_for_file_identifier::Data {
-							id,
-							pub_id,
-							date_created,
-							..
-						} = file_path;
-						directory_file_paths.push(FilePathToCreateOrLinkObject {
-							id,
-							file_path_pub_id: pub_id.into(),
-							kind: ObjectKind::Folder,
-							created_at: date_created,
-						});
-					}
-					Some(false) => {
-						file_paths_by_id.insert(file_path.pub_id.as_slice().into(), file_path);
-					}
-					None => {
-						warn!(%file_path.id, "file path without is_dir field, skipping;");
-						output.errors.push(
+		let (file_paths_by_id, identified_files, file_paths_without_cas_id) =
+			file_paths.into_iter().fold(
+				(
+					HashMap::with_capacity(file_paths_count - directories_and_aliases_count),
+					HashMap::new(),
+					Vec::with_capacity(directories_and_aliases_count),
+				),
+				|(mut file_paths_by_id, mut identified_files, mut file_paths_without_cas_id),
+				 file_path| {
+					let is_alias = file_path.symlink_target.is_some();
+
+					match file_path.is_dir {
+						// Recorded symbolic links don't have a cas_id, as hashing them would hash
+						// their targets instead
+						Some(is_dir) if is_dir || is_alias => {
+							let file_path_for_file_identifier::Data {
+								id,
+								pub_id,
+								date_created,
+								..
+							} = file_path;
+							file_paths_without_cas_id.push(FilePathToCreateOrLinkObject {
+								id,
+								file_path_pub_id: pub_id.into(),
+								kind: if is_dir {
+									ObjectKind::Folder
+								} else {
+									ObjectKind::Alias
+								},
+								created_at: date_created,
+							});
+						}
+						// Archives members aren't on disk, their cas_ids were generated when their
+						// archives were listed
+						Some(false) if file_path.archive_member_path.is_some() => {
+							let kind = kind_from_extension(file_path.extension.as_deref());
+
+							if let Some(cas_id) = file_path.cas_id.clone() {
+								identified_files.insert(
+									file_path.pub_id.as_slice().into(),
+									IdentifiedFile::new(file_path, cas_id, kind, None),
+								);
+							} else {
+								let file_path_for_file_identifier::Data {
+									id,
+									pub_id,
+									date_created,
+									..
+								} = file_path;
+								file_paths_without_cas_id.push(FilePathToCreateOrLinkObject {
+									id,
+									file_path_pub_id: pub_id.into(),
+									kind,
+									created_at: date_created,
+								});
+							}
+						}
+						Some(false) => {
+							file_paths_by_id.insert(file_path.pub_id.as_slice().into(), file_path);
+						}
+						None => {
+							warn!(%file_path.id, "file path without is_dir field, skipping;");
+							output.errors.push(
 						file_identifier::NonCriticalFileIdentifierError::FilePathWithoutIsDirField(
 							file_path.id,
 						)
 						.into(),
 					);
-					}
-				};
+						}
+					};
 
-				(file_paths_by_id, directory_file_paths)
-			},
-		);
+					(
+						file_paths_by_id,
+						identified_files,
+						file_paths_without_cas_id,
+					)
+				},
+			);
 
 		Self {
 			id: TaskId::new_v4(),
 			location,
 			location_path,
 			device_id,
-			identified_files: HashMap::with_capacity(file_paths_count - directories_count),
+			identified_files,
 			file_paths_without_cas_id,
 			file_paths_by_id,
 			output,
 			with_priority,
 			db,
 			sync,
+			io_throttle,
 		}
 	}
 }
 
+/// Kind of an archive member told by its extension alone, as its contents aren't on disk
+fn kind_from_extension(extension: Option<&str>) -> ObjectKind {
+	match extension.and_then(Extension::from_str) {
+		Some(ExtensionPossibility::Known(extension)) => extension.into(),
+		// Telling these apart requires reading the file
+		Some(ExtensionPossibility::Conflicts(_)) | None => ObjectKind::Unknown,
+	}
+}
+
 #[instrument(skip_all, err, fields(identified_files_count = identified_files.len()))]
 async fn assign_cas_id_to_file_paths(
 	identified_files: &HashMap<FilePathPubId, IdentifiedFile>,
@@ -402,23 +472,42 @@ async fn assign_cas_id_to_file_paths(
 ) -> Result<(), file_identifier::Error> {
 	let (ops, queries) = identified_files
 		.iter()
-		.map(|(pub_id, IdentifiedFile { cas_id, .. })| {
-			let (sync_param, db_param) = sync_db_entry!(cas_id, file_path::cas_id);
-
-			(
-				sync.shared_update(
-					prisma_sync::file_path::SyncId {
-						pub_id: pub_id.to_db(),
-					},
-					[sync_param],
-				),
-				db.file_path()
-					.update(file_path::pub_id::equals(pub_id.to_db()), vec![db_param])
-					// We don't need any data here, just the id avoids receiving the entire object
-					// as we can't pass an empty select macro call
-					.select(file_path::select!({ id })),
-			)
-		})
+		.map(
+			|(
+				pub_id,
+				IdentifiedFile {
+					cas_id,
+					detected_extension,
+					..
+				},
+			)| {
+				// Always set, so a previously detected extension no longer told by the contents
+				// is cleared
+				let (sync_params, db_params) = [
+					sync_db_entry!(cas_id, file_path::cas_id),
+					sync_db_nullable_entry!(
+						detected_extension.clone(),
+						file_path::detected_extension
+					),
+				]
+				.into_iter()
+				.unzip::<_, _, Vec<_>, Vec<_>>();
+
+				(
+					sync.shared_update(
+						prisma_sync::file_path::SyncId {
+							pub_id: pub_id.to_db(),
+						},
+						sync_params,
+					),
+					db.file_path()
+						.update(file_path::pub_id::equals(pub_id.to_db()), db_params)
+						// We don't need any data here, just the id avoids receiving the entire object
+						// as we can't pass an empty select macro call
+						.select(file_path::select!({ id })),
+				)
+			},
+		)
 		.unzip::<_, _, Vec<_>, Vec<_>>();
 
 	if !ops.is_empty() && !queries.is_empty() {
@@ -477,7 +566,7 @@ fn handle_non_critical_errors(
 		extension = ?file_path.extension,
 	)
 )]
-fn try_iso_file_path_extraction(
+pub(super) fn try_iso_file_path_extraction(
 	location_id: location::id::Type,
 	file_path_pub_id: FilePathPubId,
 	file_path: &file_path_for_file_identifier::Data,
@@ -517,7 +606,7 @@ impl SerializableTask<Error> for Identifier {
 
 	type DeserializeError = rmp_serde::decode::Error;
 
-	type DeserializeCtx = (Arc<PrismaClient>, SyncManager);
+	type DeserializeCtx = (Arc<PrismaClient>, SyncManager, IoThrottle);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -547,7 +636,7 @@ impl SerializableTask<Error> for Identifier {
 
 	async fn deserialize(
 		data: &[u8],
-		(db, sync): Self::DeserializeCtx,
+		(db, sync, io_throttle): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice::<SaveState>(data).map(
 			|SaveState {
@@ -572,6 +661,7 @@ impl SerializableTask<Error> for Identifier {
 				output,
 				db,
 				sync,
+				io_throttle,
 			},
 		)
 	}
@@ -1,6 +1,8 @@
-use crate::file_identifier;
+use crate::file_identifier::{
+	self, cas_id_from_integrity_checksum, NonCriticalFileIdentifierError,
+};
 
-use sd_core_prisma_helpers::{file_path_id, FilePathPubId, ObjectPubId};
+use sd_core_prisma_helpers::{file_path_id, CasId, FilePathPubId, ObjectPubId};
 use sd_core_sync::SyncManager;
 
 use sd_file_ext::kind::ObjectKind;
@@ -11,16 +13,21 @@ use sd_prisma::{
 use sd_sync::{option_sync_db_entry, sync_db_entry, sync_entry, CRDTOperation, OperationFactory};
 use sd_utils::chain_optional_iter;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, HashSet},
+};
 
 use chrono::{DateTime, FixedOffset};
 use prisma_client_rust::Select;
 use serde::{Deserialize, Serialize};
 use tracing::{instrument, trace, Level};
 
+pub mod checksummer;
 pub mod identifier;
 pub mod object_processor;
 
+pub use checksummer::Checksummer;
 pub use identifier::Identifier;
 pub use object_processor::ObjectProcessor;
 
@@ -66,6 +73,66 @@ fn connect_file_path_to_object<'db>(
 	)
 }
 
+fn set_file_path_cas_id<'db>(
+	file_path_pub_id: &FilePathPubId,
+	cas_id: &CasId<'_>,
+	db: &'db PrismaClient,
+	sync: &SyncManager,
+) -> (CRDTOperation, Select<'db, file_path_id::Data>) {
+	let (sync_param, db_param) = sync_db_entry!(cas_id, file_path::cas_id);
+
+	(
+		sync.shared_update(
+			prisma_sync::file_path::SyncId {
+				pub_id: file_path_pub_id.to_db(),
+			},
+			[sync_param],
+		),
+		db.file_path()
+			.update(
+				file_path::pub_id::equals(file_path_pub_id.to_db()),
+				vec![db_param],
+			)
+			.select(file_path_id::select()),
+	)
+}
+
+fn create_object(
+	object_pub_id: &ObjectPubId,
+	kind: i32,
+	created_at: Option<DateTime<FixedOffset>>,
+	sync: &SyncManager,
+	device_id: device::id::Type,
+) -> (CRDTOperation, object::CreateUnchecked) {
+	let (sync_params, db_params) = chain_optional_iter(
+		[
+			(
+				sync_entry!(
+					prisma_sync::device::SyncId {
+						pub_id: sync.device_pub_id.to_db(),
+					},
+					object::device
+				),
+				object::device_id::set(Some(device_id)),
+			),
+			sync_db_entry!(kind, object::kind),
+		],
+		[option_sync_db_entry!(created_at, object::date_created)],
+	)
+	.into_iter()
+	.unzip::<_, _, Vec<_>, Vec<_>>();
+
+	(
+		sync.shared_create(
+			prisma_sync::object::SyncId {
+				pub_id: object_pub_id.to_db(),
+			},
+			sync_params,
+		),
+		object::create_unchecked(object_pub_id.to_db(), db_params),
+	)
+}
+
 #[instrument(skip_all, ret(level = Level::TRACE), err)]
 async fn create_objects_and_update_file_paths(
 	files_and_kinds: impl IntoIterator<Item = FilePathToCreateOrLinkObject> + Send,
@@ -85,38 +152,8 @@ async fn create_objects_and_update_file_paths(
 			 }| {
 				let object_pub_id = ObjectPubId::new();
 
-				let kind = kind as i32;
-
-				let device_pub_id = sync.device_pub_id.to_db();
-
-				let (sync_params, db_params) = chain_optional_iter(
-					[
-						(
-							sync_entry!(
-								prisma_sync::device::SyncId {
-									pub_id: device_pub_id,
-								},
-								object::device
-							),
-							object::device_id::set(Some(device_id)),
-						),
-						sync_db_entry!(kind, object::kind),
-					],
-					[option_sync_db_entry!(created_at, object::date_created)],
-				)
-				.into_iter()
-				.unzip::<_, _, Vec<_>, Vec<_>>();
-
 				(
-					(
-						sync.shared_create(
-							prisma_sync::object::SyncId {
-								pub_id: object_pub_id.to_db(),
-							},
-							sync_params,
-						),
-						object::create_unchecked(object_pub_id.to_db(), db_params),
-					),
+					create_object(&object_pub_id, kind as i32, created_at, sync, device_id),
 					(
 						(id, object_pub_id.clone()),
 						connect_file_path_to_object(&file_path_pub_id, &object_pub_id, db, sync),
@@ -185,3 +222,220 @@ async fn create_objects_and_update_file_paths(
 		Ok(HashMap::new())
 	}
 }
+
+/// Groups file paths by their `integrity_checksum`, the group keeping the object first: the
+/// biggest one, ties going to the group with the oldest file path. File paths without a checksum
+/// can't be told apart, so they're left out.
+fn groups_by_integrity_checksum<T>(
+	file_paths: impl IntoIterator<Item = T>,
+	id_and_checksum: impl Fn(&T) -> (file_path::id::Type, Option<&str>),
+) -> Vec<(String, Vec<T>)> {
+	let mut groups = file_paths
+		.into_iter()
+		.fold(HashMap::<_, Vec<_>>::new(), |mut groups, file_path| {
+			if let (_, Some(checksum)) = id_and_checksum(&file_path) {
+				groups
+					.entry(checksum.to_string())
+					.or_default()
+					.push(file_path);
+			}
+			groups
+		})
+		.into_iter()
+		.collect::<Vec<_>>();
+
+	groups.sort_by_key(|(_, file_paths)| {
+		(
+			Reverse(file_paths.len()),
+			file_paths
+				.iter()
+				.map(|file_path| id_and_checksum(file_path).0)
+				.min(),
+		)
+	});
+
+	groups
+}
+
+/// Objects are merged by their sampled `cas_id`, so files with different contents can end up in
+/// the same object. Once their `integrity_checksum`s tell them apart, the biggest group of equal
+/// checksums keeps the object and every other group is moved out of it.
+///
+/// Moved file paths get a `cas_id` [derived from their checksum](cas_id_from_integrity_checksum),
+/// so they don't share thumbnails with the kept group and are linked to the object of a group
+/// already split with the same contents, or else to a new object of the same kind.
+#[instrument(skip_all, fields(objects_count = object_ids.len()), err)]
+pub(super) async fn split_objects_by_integrity_checksum(
+	object_ids: Vec<object::id::Type>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+	device_id: device::id::Type,
+) -> Result<Vec<NonCriticalFileIdentifierError>, file_identifier::Error> {
+	let objects = db
+		.object()
+		.find_many(vec![object::id::in_vec(object_ids)])
+		.select(object::select!({
+			pub_id
+			kind
+			file_paths: select { id pub_id cas_id integrity_checksum date_created }
+		}))
+		.exec()
+		.await?;
+
+	let mut collisions = vec![];
+
+	for object in objects {
+		let groups = groups_by_integrity_checksum(object.file_paths, |file_path| {
+			(file_path.id, file_path.integrity_checksum.as_deref())
+		});
+
+		if groups.len() < 2 {
+			continue;
+		}
+
+		let cas_id = groups[0]
+			.1
+			.iter()
+			.find_map(|file_path| file_path.cas_id.clone())
+			.unwrap_or_default();
+
+		let mut object_create_args = vec![];
+		let mut moved_file_path_ids = vec![];
+		let mut file_path_update_args = vec![];
+
+		for (checksum, file_paths) in groups.into_iter().skip(1) {
+			let split_cas_id = cas_id_from_integrity_checksum(&checksum);
+
+			let object_pub_id = if let Some(existing_object) = db
+				.object()
+				.find_first(vec![object::file_paths::some(vec![
+					file_path::cas_id::equals((&split_cas_id).into()),
+				])])
+				.select(object::select!({ pub_id }))
+				.exec()
+				.await?
+			{
+				ObjectPubId::from(existing_object.pub_id)
+			} else {
+				let object_pub_id = ObjectPubId::new();
+
+				object_create_args.push(create_object(
+					&object_pub_id,
+					object.kind.unwrap_or_default(),
+					file_paths
+						.iter()
+						.filter_map(|file_path| file_path.date_created)
+						.min(),
+					sync,
+					device_id,
+				));
+
+				object_pub_id
+			};
+
+			for file_path in file_paths {
+				let file_path_pub_id = FilePathPubId::from(file_path.pub_id.as_slice());
+
+				moved_file_path_ids.push(file_path.id);
+				file_path_update_args.push(set_file_path_cas_id(
+					&file_path_pub_id,
+					&split_cas_id,
+					db,
+					sync,
+				));
+				file_path_update_args.push(connect_file_path_to_object(
+					&file_path_pub_id,
+					&object_pub_id,
+					db,
+					sync,
+				));
+			}
+		}
+
+		if !object_create_args.is_empty() {
+			sync.write_ops(db, {
+				let (sync, db_params) = object_create_args
+					.into_iter()
+					.unzip::<_, _, Vec<_>, Vec<_>>();
+
+				(sync, db.object().create_many(db_params))
+			})
+			.await?;
+		}
+
+		sync.write_ops(
+			db,
+			file_path_update_args
+				.into_iter()
+				.unzip::<_, _, Vec<_>, Vec<_>>(),
+		)
+		.await?;
+
+		trace!(
+			%cas_id,
+			moved_file_paths_count = moved_file_path_ids.len(),
+			"Split object with colliding cas_id;",
+		);
+
+		collisions.push(NonCriticalFileIdentifierError::CasIdCollision {
+			cas_id,
+			object_pub_id: ObjectPubId::from(object.pub_id).into(),
+			moved_file_path_ids,
+		});
+	}
+
+	Ok(collisions)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn split_groups_biggest_first_without_unchecked_paths() {
+		let groups = groups_by_integrity_checksum(
+			[
+				(1, Some("a")),
+				(2, Some("b")),
+				(3, None),
+				(4, Some("b")),
+				(5, Some("c")),
+				(6, Some("a")),
+				(7, Some("b")),
+			],
+			|&(id, checksum)| (id, checksum),
+		);
+
+		assert_eq!(
+			groups
+				.iter()
+				.map(|(checksum, file_paths)| {
+					(
+						checksum.as_str(),
+						file_paths.iter().map(|&(id, _)| id).collect::<Vec<_>>(),
+					)
+				})
+				.collect::<Vec<_>>(),
+			[("b", vec![2, 4, 7]), ("a", vec![1, 6]), ("c", vec![5])]
+		);
+	}
+
+	#[test]
+	fn split_ties_kept_by_oldest_file_path() {
+		let groups =
+			groups_by_integrity_checksum([(3, Some("a")), (2, Some("b"))], |&(id, checksum)| {
+				(id, checksum)
+			});
+
+		assert_eq!(groups[0].0, "b");
+	}
+
+	#[test]
+	fn split_cas_ids_follow_contents() {
+		let cas_id = cas_id_from_integrity_checksum("a");
+
+		assert_eq!(cas_id.as_str().len(), 16);
+		assert_eq!(cas_id, cas_id_from_integrity_checksum("a"));
+		assert_ne!(cas_id, cas_id_from_integrity_checksum("b"));
+	}
+}
@@ -0,0 +1,1034 @@
+//! Archives contents are indexed as virtual `file_path`s beneath their archive, as if it was a
+//! directory, so their files can be searched and identified like any other file.
+//!
+//! These members don't exist on disk by themselves, so they keep their path inside the archive,
+//! which is used to read them straight from it. Their `cas_id`s are generated while listing the
+//! archive, as they can't be hashed later without reading the whole archive again. 7z archives
+//! are listed from their headers alone, as solid ones would have to be decompressed in full to
+//! hash their members, so their members don't get `cas_id`s.
+
+use crate::{file_identifier::generate_cas_id_from_reader, indexer, IoThrottle, NonCriticalError};
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::{CasId, FilePathPubId};
+use sd_core_sync::SyncManager;
+
+use sd_prisma::{
+	prisma::{device, file_path, location, PrismaClient, SortOrder},
+	prisma_sync,
+};
+use sd_sync::{option_sync_db_entry, sync_db_entry, sync_entry, OperationFactory};
+use sd_utils::{
+	chain_optional_iter,
+	db::{size_in_bytes_from_db, size_in_bytes_to_db},
+	from_bytes_to_uuid,
+};
+
+use std::{
+	borrow::Cow,
+	collections::{BTreeMap, HashSet},
+	fs::File,
+	io::{self, BufReader, Read, Write},
+	path::{Component, Path},
+	time::SystemTime,
+};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use flate2::read::GzDecoder;
+use prisma_client_rust::operator::{not, or};
+use sevenz_rust::{Password, SevenZReader};
+use tokio::task::spawn_blocking;
+use tracing::{instrument, trace, warn};
+use zip::ZipArchive;
+
+use super::{NonCriticalIndexerError, BATCH_SIZE};
+
+/// Archives are fetched from database in smaller batches, as each one is read in full
+const ARCHIVES_BATCH_SIZE: i64 = 100;
+
+/// Extensions of the archives we can list. Extensions are stored as found on disk, so they're
+/// matched with `LIKE`, which is case insensitive, and then checked by [`ArchiveFormat`]
+const ARCHIVES_EXTENSIONS: [&str; 5] = ["zip", "tar", "tgz", "gz", "7z"];
+
+/// Archives with more members than this aren't listed, as all their members are kept in memory
+const MAX_ARCHIVE_MEMBERS: usize = 50_000;
+
+/// Nor are archives whose files add up to more than this, as we read them to generate cas_ids
+const MAX_ARCHIVE_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+	Zip,
+	Tar,
+	TarGz,
+	SevenZip,
+}
+
+impl ArchiveFormat {
+	/// Only gzipped tarballs are listed, as other gzipped files hold a single unnamed file
+	#[must_use]
+	pub fn from_name_and_extension(name: &str, extension: &str) -> Option<Self> {
+		match extension.to_lowercase().as_str() {
+			"zip" => Some(Self::Zip),
+			"tar" => Some(Self::Tar),
+			"tgz" => Some(Self::TarGz),
+			"gz" if name.to_lowercase().ends_with(".tar") => Some(Self::TarGz),
+			"7z" => Some(Self::SevenZip),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+		let path = path.as_ref();
+
+		Self::from_name_and_extension(path.file_stem()?.to_str()?, path.extension()?.to_str()?)
+	}
+}
+
+#[derive(Debug)]
+struct ArchiveEntry {
+	is_dir: bool,
+	size_in_bytes: u64,
+	file_count: u64,
+	modified_at: Option<DateTime<Utc>>,
+	cas_id: Option<CasId<'static>>,
+}
+
+impl ArchiveEntry {
+	fn new(
+		reader: impl Read,
+		is_dir: bool,
+		size_in_bytes: u64,
+		modified_at: Option<DateTime<Utc>>,
+	) -> io::Result<Self> {
+		Ok(Self {
+			is_dir,
+			size_in_bytes,
+			file_count: 0,
+			modified_at,
+			// Same as files on disk, empty files don't get a cas_id
+			cas_id: if is_dir || size_in_bytes == 0 {
+				None
+			} else {
+				Some(generate_cas_id_from_reader(reader, size_in_bytes)?)
+			},
+		})
+	}
+
+	const fn without_contents(
+		is_dir: bool,
+		size_in_bytes: u64,
+		modified_at: Option<DateTime<Utc>>,
+	) -> Self {
+		Self {
+			is_dir,
+			size_in_bytes,
+			file_count: 0,
+			modified_at,
+			cas_id: None,
+		}
+	}
+
+	const fn directory() -> Self {
+		Self {
+			is_dir: true,
+			size_in_bytes: 0,
+			file_count: 0,
+			modified_at: None,
+			cas_id: None,
+		}
+	}
+}
+
+/// Keeps track of how many members and bytes of an archive we went through, so we can give up on
+/// archives too big to be listed
+#[derive(Debug, Default)]
+struct ListingLimits {
+	members_count: usize,
+	bytes_count: u64,
+}
+
+impl ListingLimits {
+	/// Accounts for a member whose contents will be read for `size_in_bytes`
+	fn add_member(&mut self, size_in_bytes: u64) -> io::Result<()> {
+		self.members_count += 1;
+		self.bytes_count = self.bytes_count.saturating_add(size_in_bytes);
+
+		if self.members_count > MAX_ARCHIVE_MEMBERS {
+			return Err(io::Error::other(format!(
+				"archive has more than {MAX_ARCHIVE_MEMBERS} members"
+			)));
+		}
+
+		if self.bytes_count > MAX_ARCHIVE_BYTES {
+			return Err(io::Error::other(format!(
+				"archive files add up to more than {MAX_ARCHIVE_BYTES} bytes"
+			)));
+		}
+
+		Ok(())
+	}
+}
+
+/// Path of an entry inside its archive with `/` as separator, rejecting absolute paths and `..`
+/// components, as they could point outside the archive
+fn member_path(path: &Path) -> Option<String> {
+	let mut components = vec![];
+
+	for component in path.components() {
+		match component {
+			Component::Normal(name) => components.push(name.to_str()?),
+			Component::CurDir => {}
+			_ => return None,
+		}
+	}
+
+	(!components.is_empty()).then(|| components.join("/"))
+}
+
+fn zip_date_time(date_time: zip::DateTime) -> Option<DateTime<Utc>> {
+	// Zip archives keep local times without a timezone, so UTC is our best guess
+	NaiveDate::from_ymd_opt(
+		date_time.year().into(),
+		date_time.month().into(),
+		date_time.day().into(),
+	)?
+	.and_hms_opt(
+		date_time.hour().into(),
+		date_time.minute().into(),
+		date_time.second().into(),
+	)
+	.map(|naive| naive.and_utc())
+}
+
+fn list_zip(archive_path: &Path) -> io::Result<Vec<(String, ArchiveEntry)>> {
+	let mut archive = ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+
+	// Zip archives tell us how many members they have upfront, so we don't even start
+	if archive.len() > MAX_ARCHIVE_MEMBERS {
+		return Err(io::Error::other(format!(
+			"archive has more than {MAX_ARCHIVE_MEMBERS} members"
+		)));
+	}
+
+	let mut limits = ListingLimits::default();
+	let mut entries = Vec::with_capacity(archive.len());
+
+	for idx in 0..archive.len() {
+		let file = archive.by_index(idx)?;
+
+		let Some(path) = file.enclosed_name().as_deref().and_then(member_path) else {
+			warn!(
+				name = file.name(),
+				"Skipping zip entry with an unsafe path;"
+			);
+			continue;
+		};
+
+		let is_dir = file.is_dir();
+		let size_in_bytes = file.size();
+		let modified_at = file.last_modified().and_then(zip_date_time);
+
+		limits.add_member(size_in_bytes)?;
+
+		entries.push((
+			path,
+			ArchiveEntry::new(file, is_dir, size_in_bytes, modified_at)?,
+		));
+	}
+
+	Ok(entries)
+}
+
+fn list_tar(reader: impl Read) -> io::Result<Vec<(String, ArchiveEntry)>> {
+	let mut archive = tar::Archive::new(reader);
+	let mut limits = ListingLimits::default();
+	let mut entries = vec![];
+
+	for entry in archive.entries()? {
+		let entry = entry?;
+		let entry_type = entry.header().entry_type();
+
+		// Links and special files don't have contents of their own
+		if !entry_type.is_file() && !entry_type.is_dir() {
+			continue;
+		}
+
+		let Some(path) = member_path(&entry.path()?) else {
+			warn!(
+				name = %String::from_utf8_lossy(&entry.path_bytes()),
+				"Skipping tar entry with an unsafe path;",
+			);
+			continue;
+		};
+
+		let size_in_bytes = entry.size();
+		let modified_at = entry
+			.header()
+			.mtime()
+			.ok()
+			.and_then(|mtime| DateTime::from_timestamp(i64::try_from(mtime).ok()?, 0));
+
+		limits.add_member(size_in_bytes)?;
+
+		entries.push((
+			path,
+			ArchiveEntry::new(entry, entry_type.is_dir(), size_in_bytes, modified_at)?,
+		));
+	}
+
+	Ok(entries)
+}
+
+fn list_7z(archive_path: &Path) -> io::Result<Vec<(String, ArchiveEntry)>> {
+	// Only the headers are read when opening, so we list members without decompressing them
+	let archive = SevenZReader::open(archive_path, Password::empty())
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+	let mut limits = ListingLimits::default();
+	let mut entries = vec![];
+
+	for entry in &archive.archive().files {
+		let Some(path) = member_path(Path::new(entry.name())) else {
+			warn!(
+				name = entry.name(),
+				"Skipping 7z entry with an unsafe path;"
+			);
+			continue;
+		};
+
+		// Nothing is read from 7z members, so only their count matters
+		limits.add_member(0)?;
+
+		let modified_at = entry
+			.has_last_modified_date
+			.then(|| DateTime::<Utc>::from(SystemTime::from(entry.last_modified_date())));
+
+		entries.push((
+			path,
+			ArchiveEntry::without_contents(entry.is_directory(), entry.size(), modified_at),
+		));
+	}
+
+	Ok(entries)
+}
+
+fn list_archive(
+	archive_path: &Path,
+	format: ArchiveFormat,
+) -> io::Result<Vec<(String, ArchiveEntry)>> {
+	match format {
+		ArchiveFormat::Zip => list_zip(archive_path),
+		ArchiveFormat::Tar => list_tar(BufReader::new(File::open(archive_path)?)),
+		ArchiveFormat::TarGz => list_tar(GzDecoder::new(BufReader::new(File::open(archive_path)?))),
+		ArchiveFormat::SevenZip => list_7z(archive_path),
+	}
+}
+
+fn read_tar_member(reader: impl Read, member: &str, out: &mut impl Write) -> io::Result<bool> {
+	let mut archive = tar::Archive::new(reader);
+
+	for entry in archive.entries()? {
+		let mut entry = entry?;
+
+		if entry.header().entry_type().is_file()
+			&& member_path(&entry.path()?).as_deref() == Some(member)
+		{
+			io::copy(&mut entry, out)?;
+			return Ok(true);
+		}
+	}
+
+	Ok(false)
+}
+
+/// Writes the contents of a file inside an archive, returning `false` if the archive doesn't have
+/// it. This function blocks, as archives are read with synchronous readers.
+pub fn read_member(
+	archive_path: impl AsRef<Path>,
+	format: ArchiveFormat,
+	member: &str,
+	out: &mut impl Write,
+) -> io::Result<bool> {
+	let archive_path = archive_path.as_ref();
+
+	match format {
+		ArchiveFormat::Zip => {
+			let mut archive = ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+
+			for idx in 0..archive.len() {
+				let mut file = archive.by_index(idx)?;
+
+				if !file.is_dir()
+					&& file
+						.enclosed_name()
+						.as_deref()
+						.and_then(member_path)
+						.as_deref() == Some(member)
+				{
+					io::copy(&mut file, out)?;
+					return Ok(true);
+				}
+			}
+
+			Ok(false)
+		}
+
+		ArchiveFormat::Tar => {
+			read_tar_member(BufReader::new(File::open(archive_path)?), member, out)
+		}
+
+		ArchiveFormat::TarGz => read_tar_member(
+			GzDecoder::new(BufReader::new(File::open(archive_path)?)),
+			member,
+			out,
+		),
+
+		ArchiveFormat::SevenZip => {
+			let mut found = false;
+
+			SevenZReader::open(archive_path, Password::empty())
+				.and_then(|mut archive| {
+					archive.for_each_entries(|entry, reader| {
+						if !entry.is_directory()
+							&& member_path(Path::new(entry.name())).as_deref() == Some(member)
+						{
+							io::copy(reader, out)?;
+							found = true;
+
+							// No need to keep decompressing the remaining entries
+							return Ok(false);
+						}
+
+						io::copy(reader, &mut io::sink())?;
+
+						Ok(true)
+					})
+				})
+				.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+			Ok(found)
+		}
+	}
+}
+
+/// Adds the directories missing from the archive listing, as many archives only list their files,
+/// and sums the sizes and counts the files beneath each directory
+fn with_directories_totals(entries: Vec<(String, ArchiveEntry)>) -> BTreeMap<String, ArchiveEntry> {
+	let (directories, files) = entries
+		.into_iter()
+		.partition::<Vec<_>, _>(|(_, entry)| entry.is_dir);
+
+	let mut members = directories
+		.into_iter()
+		.map(|(path, mut directory)| {
+			directory.size_in_bytes = 0;
+			(path, directory)
+		})
+		.collect::<BTreeMap<_, _>>();
+
+	for (path, file) in &files {
+		let mut current_path = path.as_str();
+		while let Some((parent_path, _)) = current_path.rsplit_once('/') {
+			let directory = members
+				.entry(parent_path.to_string())
+				.or_insert_with(ArchiveEntry::directory);
+
+			directory.size_in_bytes += file.size_in_bytes;
+			directory.file_count += 1;
+
+			current_path = parent_path;
+		}
+	}
+
+	members.extend(files);
+
+	members
+}
+
+/// Number of archives listed and members created on [`index_archives_contents`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IndexedArchives {
+	pub archives_count: u64,
+	pub members_count: u64,
+}
+
+/// Lists the contents of the archives inside `root_iso_file_path` modified since they were last
+/// listed, replacing their members on database. Archives that can't be read are reported as non
+/// critical errors, as they may be encrypted, corrupted or in a format variant we don't support.
+#[instrument(skip_all, fields(root = %root_iso_file_path), err)]
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn index_archives_contents(
+	root_iso_file_path: &IsolatedFilePathData<'_>,
+	location_path: &Path,
+	location_pub_id: &location::pub_id::Type,
+	device_id: device::id::Type,
+	db: &PrismaClient,
+	sync: &SyncManager,
+	io_throttle: &IoThrottle,
+	errors: &mut Vec<NonCriticalError>,
+) -> Result<IndexedArchives, indexer::Error> {
+	let location_id = root_iso_file_path.location_id();
+	let root_materialized_path = root_iso_file_path
+		.materialized_path_for_children()
+		.expect("the indexed root must be a directory");
+
+	let mut indexed = IndexedArchives::default();
+	let mut listed_archives = HashSet::new();
+	let mut cursor = 0;
+
+	loop {
+		let archives = db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::equals(Some(location_id)),
+				file_path::id::gt(cursor),
+				file_path::is_dir::equals(Some(false)),
+				file_path::archive_member_path::equals(None),
+				file_path::symlink_target::equals(None),
+				file_path::materialized_path::starts_with(root_materialized_path.clone()),
+				or(ARCHIVES_EXTENSIONS
+					.into_iter()
+					.map(|extension| file_path::extension::starts_with(extension.to_string()))
+					.collect()),
+			])
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.take(ARCHIVES_BATCH_SIZE)
+			.select(file_path::select!({
+				id
+				pub_id
+				materialized_path
+				name
+				extension
+				size_in_bytes_bytes
+				date_modified
+			}))
+			.exec()
+			.await?;
+
+		#[allow(clippy::cast_possible_truncation)] // Safe because we are using a constant
+		let should_stop = archives.len() < ARCHIVES_BATCH_SIZE as usize;
+
+		if let Some(last) = archives.last() {
+			cursor = last.id;
+		} else {
+			break;
+		}
+
+		for archive in archives {
+			let (Some(materialized_path), Some(name), Some(extension)) =
+				(archive.materialized_path, archive.name, archive.extension)
+			else {
+				errors.push(
+					NonCriticalIndexerError::MissingFilePathData(format!(
+						"Found an archive missing data: <pub_id='{}'>",
+						from_bytes_to_uuid(&archive.pub_id)
+					))
+					.into(),
+				);
+				continue;
+			};
+
+			// LIKE is case insensitive and takes `_` as a wildcard, so it can match files outside root
+			if !materialized_path.starts_with(&root_materialized_path) {
+				continue;
+			}
+
+			let Some(format) = ArchiveFormat::from_name_and_extension(&name, &extension) else {
+				continue;
+			};
+
+			let members_materialized_path = format!("{materialized_path}{name}.{extension}/");
+			listed_archives.insert(members_materialized_path.clone());
+
+			if !has_outdated_members(
+				&members_materialized_path,
+				archive.date_modified,
+				location_id,
+				db,
+			)
+			.await?
+			{
+				continue;
+			}
+
+			let archive_path = location_path.join(IsolatedFilePathData::from_db_data(
+				location_id,
+				false,
+				Cow::Borrowed(materialized_path.as_str()),
+				Cow::Borrowed(name.as_str()),
+				Cow::Borrowed(extension.as_str()),
+			));
+
+			io_throttle
+				.hash_bytes(
+					archive
+						.size_in_bytes_bytes
+						.as_deref()
+						.map_or(0, size_in_bytes_from_db),
+				)
+				.await;
+
+			let entries = match spawn_blocking({
+				let archive_path = archive_path.clone();
+				move || list_archive(&archive_path, format)
+			})
+			.await
+			{
+				Ok(Ok(entries)) => entries,
+				Ok(Err(e)) => {
+					errors.push(
+						NonCriticalIndexerError::ArchiveContents(format!(
+							"<path='{}'>: {e}",
+							archive_path.display()
+						))
+						.into(),
+					);
+					continue;
+				}
+				Err(e) => {
+					errors.push(
+						NonCriticalIndexerError::ArchiveContents(format!(
+							"<path='{}'>: listing task failed: {e}",
+							archive_path.display()
+						))
+						.into(),
+					);
+					continue;
+				}
+			};
+
+			trace!(
+				archive_path = %archive_path.display(),
+				entries_count = entries.len(),
+				"Listed archive;",
+			);
+
+			remove_members(&members_materialized_path, location_id, db, sync).await?;
+
+			indexed.archives_count += 1;
+			indexed.members_count += create_members(
+				&members_materialized_path,
+				archive.date_modified,
+				with_directories_totals(entries),
+				location_id,
+				location_pub_id,
+				device_id,
+				db,
+				sync,
+			)
+			.await?;
+		}
+
+		if should_stop {
+			break;
+		}
+	}
+
+	remove_orphan_members(root_iso_file_path, &listed_archives, db, sync).await?;
+
+	Ok(indexed)
+}
+
+/// Members are listed again when their archive was modified after they were indexed, or when there
+/// are no members at all, as the archive may have never been listed
+async fn has_outdated_members(
+	members_materialized_path: &str,
+	archive_modified_at: Option<DateTime<FixedOffset>>,
+	location_id: location::id::Type,
+	db: &PrismaClient,
+) -> Result<bool, indexer::Error> {
+	let newest_member = db
+		.file_path()
+		.find_first(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::archive_member_path::not(None),
+			file_path::materialized_path::starts_with(members_materialized_path.to_string()),
+		])
+		.order_by(file_path::date_indexed::order(SortOrder::Desc))
+		.select(file_path::select!({ date_indexed }))
+		.exec()
+		.await?;
+
+	Ok(
+		match (
+			newest_member.and_then(|member| member.date_indexed),
+			archive_modified_at,
+		) {
+			(Some(indexed_at), Some(modified_at)) => indexed_at < modified_at,
+			(Some(_), None) => false,
+			(None, _) => true,
+		},
+	)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_members(
+	members_materialized_path: &str,
+	archive_modified_at: Option<DateTime<FixedOffset>>,
+	members: BTreeMap<String, ArchiveEntry>,
+	location_id: location::id::Type,
+	location_pub_id: &location::pub_id::Type,
+	device_id: device::id::Type,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, indexer::Error> {
+	let date_indexed = Utc::now();
+
+	let mut to_create = members
+		.into_iter()
+		.map(
+			|(
+				member_path,
+				ArchiveEntry {
+					is_dir,
+					size_in_bytes,
+					file_count,
+					modified_at,
+					cas_id,
+				},
+			)| {
+				let (parent_path, file_name) = member_path
+					.rsplit_once('/')
+					.unwrap_or(("", member_path.as_str()));
+
+				let materialized_path = if parent_path.is_empty() {
+					members_materialized_path.to_string()
+				} else {
+					format!("{members_materialized_path}{parent_path}/")
+				};
+
+				let (name, extension) = if is_dir {
+					(file_name, "")
+				} else {
+					IsolatedFilePathData::separate_name_and_extension_from_str(file_name)
+						.unwrap_or((file_name, ""))
+				};
+
+				// Archives entries don't always have a date, so they take the one from their archive
+				let modified_at = modified_at
+					.map(DateTime::<FixedOffset>::from)
+					.or(archive_modified_at);
+
+				let pub_id = FilePathPubId::new();
+
+				let (sync_params, db_params) = chain_optional_iter(
+					[
+						(
+							sync_entry!(
+								prisma_sync::location::SyncId {
+									pub_id: location_pub_id.clone()
+								},
+								file_path::location
+							),
+							file_path::location_id::set(Some(location_id)),
+						),
+						sync_db_entry!(materialized_path, file_path::materialized_path),
+						sync_db_entry!(name.to_string(), file_path::name),
+						sync_db_entry!(is_dir, file_path::is_dir),
+						sync_db_entry!(extension.to_string(), file_path::extension),
+						sync_db_entry!(
+							size_in_bytes_to_db(size_in_bytes),
+							file_path::size_in_bytes_bytes
+						),
+						sync_db_entry!(date_indexed, file_path::date_indexed),
+						sync_db_entry!(name.starts_with('.'), file_path::hidden),
+						sync_db_entry!(member_path.clone(), file_path::archive_member_path),
+						(
+							sync_entry!(
+								prisma_sync::device::SyncId {
+									pub_id: sync.device_pub_id.to_db(),
+								},
+								file_path::device
+							),
+							file_path::device_id::set(Some(device_id)),
+						),
+					],
+					[
+						option_sync_db_entry!(cas_id.map(String::from), file_path::cas_id),
+						option_sync_db_entry!(modified_at, file_path::date_created),
+						option_sync_db_entry!(modified_at, file_path::date_modified),
+						option_sync_db_entry!(
+							is_dir.then(|| i64::try_from(file_count).unwrap_or(i64::MAX)),
+							file_path::file_count
+						),
+					],
+				)
+				.into_iter()
+				.unzip::<_, _, Vec<_>, Vec<_>>();
+
+				(
+					sync.shared_create(
+						prisma_sync::file_path::SyncId {
+							pub_id: pub_id.to_db(),
+						},
+						sync_params,
+					),
+					file_path::create_unchecked(pub_id.to_db(), db_params),
+				)
+			},
+		)
+		.collect::<Vec<_>>();
+
+	let mut created_count = 0;
+
+	while !to_create.is_empty() {
+		let (ops, creates) = to_create
+			.drain(..to_create.len().min(BATCH_SIZE))
+			.unzip::<_, _, Vec<_>, Vec<_>>();
+
+		#[allow(clippy::cast_sign_loss)]
+		{
+			created_count += sync
+				.write_ops(
+					db,
+					(ops, db.file_path().create_many(creates).skip_duplicates()),
+				)
+				.await? as u64;
+		}
+	}
+
+	Ok(created_count)
+}
+
+/// Removes all members beneath `members_materialized_path`, which belong to a single archive
+async fn remove_members(
+	members_materialized_path: &str,
+	location_id: location::id::Type,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, indexer::Error> {
+	let mut to_remove = db
+		.file_path()
+		.find_many(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::archive_member_path::not(None),
+			file_path::materialized_path::starts_with(members_materialized_path.to_string()),
+		])
+		.select(file_path::select!({ pub_id materialized_path }))
+		.exec()
+		.await?
+		.into_iter()
+		// Same as above, `starts_with` can bring members of other archives
+		.filter(|member| {
+			member
+				.materialized_path
+				.as_deref()
+				.is_some_and(|path| path.starts_with(members_materialized_path))
+		})
+		.map(|member| {
+			(
+				sync.shared_delete(prisma_sync::file_path::SyncId {
+					pub_id: member.pub_id.clone(),
+				}),
+				member.pub_id,
+			)
+		})
+		.collect::<Vec<_>>();
+
+	let mut removed_count = 0;
+
+	while !to_remove.is_empty() {
+		let (ops, pub_ids) = to_remove
+			.drain(..to_remove.len().min(BATCH_SIZE))
+			.unzip::<_, _, Vec<_>, Vec<_>>();
+
+		#[allow(clippy::cast_sign_loss)]
+		{
+			removed_count += sync
+				.write_ops(
+					db,
+					(
+						ops,
+						db.file_path()
+							.delete_many(vec![file_path::pub_id::in_vec(pub_ids)]),
+					),
+				)
+				.await? as u64;
+		}
+	}
+
+	Ok(removed_count)
+}
+
+/// Removes the members of archives inside `root_iso_file_path` that aren't among
+/// `listed_archives`, as they were removed from disk or their contents aren't indexed anymore
+#[instrument(skip_all, fields(root = %root_iso_file_path), err)]
+pub(super) async fn remove_orphan_members(
+	root_iso_file_path: &IsolatedFilePathData<'_>,
+	listed_archives: &HashSet<String>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, indexer::Error> {
+	let location_id = root_iso_file_path.location_id();
+	let root_materialized_path = root_iso_file_path
+		.materialized_path_for_children()
+		.expect("the indexed root must be a directory");
+
+	// Every archive has members right beneath it, with its path as their materialized path
+	let orphans_materialized_paths = db
+		.file_path()
+		.find_many(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::archive_member_path::not(None),
+			not(vec![file_path::archive_member_path::contains(
+				"/".to_string(),
+			)]),
+			file_path::materialized_path::starts_with(root_materialized_path.clone()),
+		])
+		.select(file_path::select!({ materialized_path }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|member| member.materialized_path)
+		.filter(|materialized_path| {
+			materialized_path.starts_with(&root_materialized_path)
+				&& !listed_archives.contains(materialized_path)
+		})
+		.collect::<HashSet<_>>();
+
+	let mut removed_count = 0;
+
+	for materialized_path in orphans_materialized_paths {
+		removed_count += remove_members(&materialized_path, location_id, db, sync).await?;
+	}
+
+	Ok(removed_count)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::io::Cursor;
+
+	use tempfile::tempdir;
+	use zip::{write::SimpleFileOptions, ZipWriter};
+
+	fn paths_and_sizes(entries: &[(String, ArchiveEntry)]) -> Vec<(&str, bool, u64)> {
+		entries
+			.iter()
+			.map(|(path, entry)| (path.as_str(), entry.is_dir, entry.size_in_bytes))
+			.collect()
+	}
+
+	#[test]
+	fn member_paths_stay_inside_the_archive() {
+		assert_eq!(
+			member_path(Path::new("photos/./trip/beach.jpg")).as_deref(),
+			Some("photos/trip/beach.jpg")
+		);
+		assert_eq!(member_path(Path::new("photos/")).as_deref(), Some("photos"));
+
+		assert_eq!(member_path(Path::new("../beach.jpg")), None);
+		assert_eq!(member_path(Path::new("photos/../../beach.jpg")), None);
+		assert_eq!(member_path(Path::new("/etc/passwd")), None);
+		assert_eq!(member_path(Path::new(".")), None);
+	}
+
+	#[test]
+	fn archive_formats_by_name_and_extension() {
+		assert_eq!(
+			ArchiveFormat::from_name_and_extension("photos", "ZIP"),
+			Some(ArchiveFormat::Zip)
+		);
+		assert_eq!(
+			ArchiveFormat::from_name_and_extension("photos", "tar"),
+			Some(ArchiveFormat::Tar)
+		);
+		assert_eq!(
+			ArchiveFormat::from_name_and_extension("photos", "tgz"),
+			Some(ArchiveFormat::TarGz)
+		);
+		assert_eq!(
+			ArchiveFormat::from_name_and_extension("photos.TAR", "gz"),
+			Some(ArchiveFormat::TarGz)
+		);
+		assert_eq!(
+			ArchiveFormat::from_name_and_extension("photos", "7z"),
+			Some(ArchiveFormat::SevenZip)
+		);
+
+		assert_eq!(ArchiveFormat::from_name_and_extension("photo", "gz"), None);
+		assert_eq!(ArchiveFormat::from_name_and_extension("photo", "jpg"), None);
+
+		assert_eq!(
+			ArchiveFormat::from_path("/backups/photos.tar.gz"),
+			Some(ArchiveFormat::TarGz)
+		);
+	}
+
+	#[test]
+	fn list_zip_members() {
+		let dir = tempdir().unwrap();
+		let archive_path = dir.path().join("photos.zip");
+
+		let mut writer = ZipWriter::new(File::create(&archive_path).unwrap());
+		let options = SimpleFileOptions::default();
+
+		writer.add_directory("trip/", options).unwrap();
+		writer.start_file("trip/beach.jpg", options).unwrap();
+		writer.write_all(b"sand and sea").unwrap();
+		writer.start_file("../outside.txt", options).unwrap();
+		writer.write_all(b"unsafe").unwrap();
+		writer.start_file("empty.txt", options).unwrap();
+		writer.finish().unwrap();
+
+		let entries = list_archive(&archive_path, ArchiveFormat::Zip).unwrap();
+
+		assert_eq!(
+			paths_and_sizes(&entries),
+			[
+				("trip", true, 0),
+				("trip/beach.jpg", false, 12),
+				("empty.txt", false, 0)
+			]
+		);
+
+		// Empty files and directories don't get a cas_id, same as files on disk
+		assert!(entries[0].1.cas_id.is_none());
+		assert!(entries[1].1.cas_id.is_some());
+		assert!(entries[2].1.cas_id.is_none());
+	}
+
+	#[test]
+	fn list_tar_members() {
+		let mut builder = tar::Builder::new(Vec::new());
+
+		let mut header = tar::Header::new_gnu();
+		header.set_entry_type(tar::EntryType::Directory);
+		header.set_size(0);
+		header.set_mode(0o755);
+		builder
+			.append_data(&mut header, "trip/", io::empty())
+			.unwrap();
+
+		let mut header = tar::Header::new_gnu();
+		header.set_size(12);
+		header.set_mode(0o644);
+		builder
+			.append_data(&mut header, "trip/beach.jpg", &b"sand and sea"[..])
+			.unwrap();
+
+		// The builder refuses `..` components, so the name is written by hand
+		let mut header = tar::Header::new_gnu();
+		header.set_size(6);
+		header.set_mode(0o644);
+		header.as_old_mut().name[..14].copy_from_slice(b"../outside.txt");
+		header.set_cksum();
+		builder.append(&header, &b"unsafe"[..]).unwrap();
+
+		let entries = list_tar(Cursor::new(builder.into_inner().unwrap())).unwrap();
+
+		assert_eq!(
+			paths_and_sizes(&entries),
+			[("trip", true, 0), ("trip/beach.jpg", false, 12)]
+		);
+
+		let members = with_directories_totals(entries);
+
+		assert_eq!(members["trip"].size_in_bytes, 12);
+		assert_eq!(members["trip"].file_count, 1);
+	}
+}
@@ -45,12 +45,15 @@ use tokio::time::Instant;
 use tracing::{debug, instrument, trace, warn, Level};
 
 use super::{
-	remove_non_existing_file_paths, reverse_update_directories_sizes,
+	archive::{index_archives_contents, remove_orphan_members, IndexedArchives},
+	has_directories_without_totals, remove_non_existing_file_paths,
+	reverse_update_directories_sizes,
 	tasks::{
 		self, saver, updater,
-		walker::{self, WalkedEntry},
+		walker::{self, IsoFilePathFactory as _, WalkedEntry},
 	},
-	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
+	update_all_directories_sizes, update_directories_sizes, update_location_size,
+	IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
 };
 
 #[derive(Debug)]
@@ -58,6 +61,7 @@ pub struct Indexer {
 	// Received arguments
 	location: location_with_indexer_rules::Data,
 	sub_path: Option<PathBuf>,
+	is_paranoid: bool,
 
 	// Derived from received arguments
 	iso_file_path_factory: IsoFilePathFactory,
@@ -67,7 +71,7 @@ pub struct Indexer {
 	// Inner state
 	ancestors_needing_indexing: HashSet<WalkedEntry>,
 	ancestors_already_indexed: HashSet<IsolatedFilePathData<'static>>,
-	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'static>, u64>,
+	changed_directories: HashSet<IsolatedFilePathData<'static>>,
 
 	// Optimizations
 	processing_first_directory: bool,
@@ -113,6 +117,7 @@ impl Job for Indexer {
 											db: Arc::clone(ctx.db()),
 										},
 										iso_file_path_factory.clone(),
+										ctx.io_throttle().clone(),
 									),
 								)
 								.await
@@ -154,6 +159,7 @@ impl Job for Indexer {
 			location_id = self.location.id,
 			location_path = ?self.location.path,
 			sub_path = ?self.sub_path.as_ref().map(|path| path.display()),
+			is_paranoid = self.is_paranoid,
 		),
 		ret(level = Level::TRACE),
 		err,
@@ -249,18 +255,69 @@ impl Job for Indexer {
 			mut metadata,
 			iso_file_path_factory,
 			walker_root_path,
-			iso_paths_and_sizes,
+			changed_directories,
 			mut errors,
 			tasks_for_shutdown,
 			..
 		} = self;
 
-		if metadata.indexed_count > 0 || metadata.removed_count > 0 || metadata.updated_count > 0 {
+		let root_path = walker_root_path.expect("must be set");
+		let root_iso_file_path = iso_file_path_factory
+			.build(&*root_path, true)
+			.map_err(indexer::Error::from)?;
+
+		let start_archive_contents_time = Instant::now();
+
+		if location.index_archive_contents.unwrap_or(false) {
+			ctx.progress_msg("Listing archives contents").await;
+
+			let IndexedArchives {
+				archives_count,
+				members_count,
+			} = index_archives_contents(
+				&root_iso_file_path,
+				&iso_file_path_factory.location_path,
+				&location.pub_id,
+				device_id,
+				ctx.db(),
+				ctx.sync(),
+				ctx.io_throttle(),
+				&mut errors,
+			)
+			.await?;
+
+			metadata.indexed_archives_count = archives_count;
+			metadata.archive_members_count = members_count;
+		} else {
+			metadata.removed_count +=
+				remove_orphan_members(&root_iso_file_path, &HashSet::new(), ctx.db(), ctx.sync())
+					.await?;
+		}
+
+		metadata.archive_contents_time = start_archive_contents_time.elapsed();
+
+		if metadata.indexed_count > 0
+			|| metadata.removed_count > 0
+			|| metadata.updated_count > 0
+			|| has_directories_without_totals(&root_iso_file_path, ctx.db()).await?
+		{
 			let start_size_update_time = Instant::now();
 
-			update_directory_sizes(iso_paths_and_sizes, ctx.db(), ctx.sync()).await?;
+			update_directories_sizes(
+				&root_iso_file_path,
+				&*iso_file_path_factory.location_path,
+				&changed_directories,
+				ctx.db(),
+				ctx.sync(),
+				&mut errors,
+			)
+			.await?;
+
+			// Only a full pass finds the directories indexed before their totals existed
+			if has_directories_without_totals(&root_iso_file_path, ctx.db()).await? {
+				update_all_directories_sizes(&root_iso_file_path, ctx.db(), ctx.sync()).await?;
+			}
 
-			let root_path = walker_root_path.expect("must be set");
 			if root_path != iso_file_path_factory.location_path {
 				reverse_update_directories_sizes(
 					&*root_path,
@@ -282,7 +339,10 @@ impl Job for Indexer {
 			// TODO: Dispatch a task to remove orphan objects
 		}
 
-		if metadata.indexed_count > 0 || metadata.removed_count > 0 {
+		if metadata.indexed_count > 0
+			|| metadata.removed_count > 0
+			|| metadata.archive_members_count > 0
+		{
 			ctx.invalidate_query("search.paths");
 		}
 
@@ -321,9 +381,15 @@ impl Job for Indexer {
 }
 
 impl Indexer {
+	/// A paranoid indexer walks through every directory, instead of skipping the files of
+	/// directories with the same inode and modification time as the last time they were indexed.
+	///
+	/// Modifying a file in place doesn't change its directory modification time, and neither do
+	/// changes in the indexer rules, so a paranoid scan is needed to pick those up.
 	pub fn new(
 		location: location_with_indexer_rules::Data,
 		sub_path: Option<PathBuf>,
+		is_paranoid: bool,
 	) -> Result<Self, indexer::Error> {
 		Ok(Self {
 			indexer_ruler: location
@@ -341,9 +407,10 @@ impl Indexer {
 			walker_root_path: None,
 			ancestors_needing_indexing: HashSet::new(),
 			ancestors_already_indexed: HashSet::new(),
-			iso_paths_and_sizes: HashMap::new(),
+			changed_directories: HashSet::new(),
 			location,
 			sub_path,
+			is_paranoid,
 			metadata: Metadata::default(),
 
 			processing_first_directory: true,
@@ -416,7 +483,6 @@ impl Indexer {
 			accepted_ancestors_count = accepted_ancestors.len(),
 			directory_iso_file_path = %directory_iso_file_path.as_ref().display(),
 			more_walker_tasks_count = keep_walking_tasks.len(),
-			%total_size,
 			?scan_time,
 		)
 	)]
@@ -429,7 +495,6 @@ impl Indexer {
 			accepted_ancestors,
 			errors,
 			directory_iso_file_path,
-			total_size,
 			keep_walking_tasks,
 			scan_time,
 			..
@@ -447,29 +512,6 @@ impl Indexer {
 
 		let (to_create_count, to_update_count) = (to_create.len(), to_update.len());
 
-		*self
-			.iso_paths_and_sizes
-			.entry(directory_iso_file_path)
-			.or_default() += total_size;
-
-		for ancestor_iso_file_path in accepted_ancestors
-			.iter()
-			.map(|ancestor_entry| &ancestor_entry.iso_file_path)
-		{
-			if self
-				.iso_paths_and_sizes
-				.contains_key(ancestor_iso_file_path)
-			{
-				*self
-					.iso_paths_and_sizes
-					.get_mut(ancestor_iso_file_path)
-					.expect("we just checked") += total_size;
-			} else {
-				self.iso_paths_and_sizes
-					.insert(ancestor_iso_file_path.clone(), total_size);
-			}
-		}
-
 		// First we add ancestors, filtering out ancestors already indexed in previous iterations
 		self.ancestors_needing_indexing
 			.extend(accepted_ancestors.into_iter().filter(|ancestor_entry| {
@@ -486,6 +528,19 @@ impl Indexer {
 				.map(|WalkedEntry { iso_file_path, .. }| iso_file_path.clone()),
 		);
 
+		// Directories with changed children and new ones have their totals computed at the end,
+		// their ancestors along with them
+		if !to_create.is_empty() || !to_update.is_empty() || !to_remove.is_empty() {
+			self.changed_directories.insert(directory_iso_file_path);
+		}
+		self.changed_directories.extend(
+			to_create
+				.iter()
+				.chain(self.ancestors_needing_indexing.iter())
+				.filter(|&WalkedEntry { iso_file_path, .. }| iso_file_path.is_dir())
+				.map(|WalkedEntry { iso_file_path, .. }| iso_file_path.clone()),
+		);
+
 		if !errors.is_empty() {
 			warn!(?errors, "Non critical errors while indexing;");
 			self.errors.extend(errors);
@@ -667,6 +722,10 @@ impl Indexer {
 							location_id: self.location.id,
 							db: Arc::clone(ctx.db()),
 						},
+						ctx.io_throttle().clone(),
+						self.is_paranoid,
+						// Unset on locations created before this setting, which always followed them
+						self.location.follow_symlinks.unwrap_or(true),
 					)?)
 					.await?,
 			);
@@ -961,6 +1020,12 @@ pub struct Metadata {
 	indexed_count: u64,
 	updated_count: u64,
 	removed_count: u64,
+	#[serde(default)]
+	archive_contents_time: Duration,
+	#[serde(default)]
+	indexed_archives_count: u64,
+	#[serde(default)]
+	archive_members_count: u64,
 }
 
 impl From<Metadata> for Vec<ReportOutputMetadata> {
@@ -978,6 +1043,9 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 			indexed_count,
 			updated_count,
 			removed_count,
+			archive_contents_time,
+			indexed_archives_count,
+			archive_members_count,
 		}: Metadata,
 	) -> Self {
 		mean_scan_read_time /= u32::max(total_walk_tasks, 1); // To avoid division by zero
@@ -1000,6 +1068,12 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 				("indexed_count".into(), json!(indexed_count)),
 				("updated_count".into(), json!(updated_count)),
 				("removed_count".into(), json!(removed_count)),
+				("archive_contents_time".into(), json!(archive_contents_time)),
+				(
+					"indexed_archives_count".into(),
+					json!(indexed_archives_count),
+				),
+				("archive_members_count".into(), json!(archive_members_count)),
 			])),
 		]
 	}
@@ -1016,6 +1090,7 @@ enum TaskKind {
 struct SaveState {
 	location: location_with_indexer_rules::Data,
 	sub_path: Option<PathBuf>,
+	is_paranoid: bool,
 
 	iso_file_path_factory: IsoFilePathFactory,
 	indexer_ruler: IndexerRuler,
@@ -1023,7 +1098,7 @@ struct SaveState {
 
 	ancestors_needing_indexing: HashSet<WalkedEntry>,
 	ancestors_already_indexed: HashSet<IsolatedFilePathData<'static>>,
-	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'static>, u64>,
+	changed_directories: HashSet<IsolatedFilePathData<'static>>,
 
 	processing_first_directory: bool,
 	to_create_buffer: VecDeque<WalkedEntry>,
@@ -1040,13 +1115,14 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		let Self {
 			location,
 			sub_path,
+			is_paranoid,
 			metadata,
 			iso_file_path_factory,
 			indexer_ruler,
 			walker_root_path,
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
-			iso_paths_and_sizes,
+			changed_directories,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1093,12 +1169,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		rmp_serde::to_vec_named(&SaveState {
 			location,
 			sub_path,
+			is_paranoid,
 			iso_file_path_factory,
 			indexer_ruler,
 			walker_root_path,
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
-			iso_paths_and_sizes,
+			changed_directories,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1116,12 +1193,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		let SaveState {
 			location,
 			sub_path,
+			is_paranoid,
 			iso_file_path_factory,
 			indexer_ruler,
 			walker_root_path,
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
-			iso_paths_and_sizes,
+			changed_directories,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1134,13 +1212,14 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 			Self {
 				location,
 				sub_path,
+				is_paranoid,
 				metadata,
 				iso_file_path_factory,
 				indexer_ruler,
 				walker_root_path,
 				ancestors_needing_indexing,
 				ancestors_already_indexed,
-				iso_paths_and_sizes,
+				changed_directories,
 				processing_first_directory,
 				to_create_buffer,
 				to_update_buffer,
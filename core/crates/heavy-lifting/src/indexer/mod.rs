@@ -2,7 +2,8 @@ use crate::{utils::sub_path, OuterContext};
 
 use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
 use sd_core_prisma_helpers::{
-	file_path_pub_and_cas_ids, file_path_to_isolate_with_pub_id, file_path_walker,
+	file_path_for_directory_totals, file_path_pub_and_cas_ids, file_path_to_isolate_with_pub_id,
+	file_path_walker,
 };
 use sd_core_sync::{DevicePubId, SyncManager};
 
@@ -18,31 +19,53 @@ use sd_utils::{
 };
 
 use std::{
+	cmp::Reverse,
 	collections::{HashMap, HashSet},
-	hash::BuildHasher,
-	mem,
+	iter, mem,
+	ops::AddAssign,
 	path::{Path, PathBuf},
 	sync::Arc,
 };
 
 use itertools::Itertools;
-use prisma_client_rust::{operator::or, QueryError, Select};
+use prisma_client_rust::{operator::or, raw, PrismaValue, QueryError, Raw, Select};
 use rspc::ErrorCode;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tracing::{instrument, warn};
 
+mod archive;
 pub mod job;
 mod shallow;
 mod tasks;
 
+pub use archive::{read_member, ArchiveFormat};
 pub use shallow::shallow;
+pub use tasks::walker::fetch_metadata;
 
 use tasks::walker;
 
 /// `BATCH_SIZE` is the number of files to index at each task, writing the chunk of files metadata in the database.
 const BATCH_SIZE: usize = 1000;
 
+/// SQL condition matching a `file_path` which is a hard link to another file indexed before it on
+/// the same location. Each link is indexed with the whole size of the file, so only the first one
+/// is accounted on sizes and counts.
+///
+/// Links share the same inode, but inodes are only unique on a single device, so their size and
+/// modification date must match too, to not take files of other devices mounted inside the
+/// location as links.
+pub const REPEATED_HARD_LINK: &str = "EXISTS (
+	SELECT 1 FROM file_path AS link
+	WHERE
+		link.location_id = file_path.location_id
+		AND link.inode = file_path.inode
+		AND link.size_in_bytes_int IS file_path.size_in_bytes_int
+		AND link.date_modified IS file_path.date_modified
+		AND COALESCE(link.is_dir, 0) = 0
+		AND link.id < file_path.id
+)";
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
 	// Not Found errors
@@ -111,6 +134,10 @@ pub enum NonCriticalIndexerError {
 	DispatchKeepWalking(String),
 	#[error("missing file_path data on database: {0}")]
 	MissingFilePathData(String),
+	#[error("failed to list archive contents: {0}")]
+	ArchiveContents(String),
+	#[error("failed to read ignore file: {0}")]
+	IgnoreFile(String),
 }
 
 fn chunk_db_queries<'db, 'iso>(
@@ -132,39 +159,96 @@ fn chunk_db_queries<'db, 'iso>(
 		.collect::<Vec<_>>()
 }
 
-#[allow(clippy::missing_panics_doc)] // Can't actually panic as we use the hashmap to fetch entries from db
-async fn update_directory_sizes(
-	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'_>, u64, impl BuildHasher + Send>,
+/// Recursive size and file count of a directory, as stored on its `file_path`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct DirectoryTotals {
+	size_in_bytes: u64,
+	file_count: u64,
+}
+
+impl AddAssign for DirectoryTotals {
+	fn add_assign(&mut self, other: Self) {
+		self.size_in_bytes += other.size_in_bytes;
+		self.file_count += other.file_count;
+	}
+}
+
+impl From<&file_path_for_directory_totals::Data> for DirectoryTotals {
+	fn from(file_path: &file_path_for_directory_totals::Data) -> Self {
+		let size_in_bytes = file_path.size_in_bytes_bytes.as_deref().map_or_else(
+			|| {
+				warn!("Got a file_path missing its size in bytes");
+				0
+			},
+			size_in_bytes_from_db,
+		);
+
+		if file_path.is_dir.unwrap_or(false) {
+			Self {
+				size_in_bytes,
+				file_count: file_path.file_count.map_or(0, |file_count| {
+					u64::try_from(file_count).unwrap_or_default()
+				}),
+			}
+		} else {
+			Self {
+				size_in_bytes,
+				file_count: 1,
+			}
+		}
+	}
+}
+
+/// The children materialized path of the directory holding the one received, `None` for the
+/// location root
+fn parent_materialized_path(materialized_path: &str) -> Option<&str> {
+	materialized_path
+		.trim_end_matches('/')
+		.rfind('/')
+		.map(|idx| &materialized_path[..=idx])
+}
+
+async fn write_directories_totals(
+	directories: impl IntoIterator<Item = (file_path::pub_id::Type, DirectoryTotals)> + Send,
 	db: &PrismaClient,
 	sync: &SyncManager,
 ) -> Result<(), Error> {
-	let (ops, queries) = db
-		._batch(chunk_db_queries(iso_paths_and_sizes.keys(), db))
-		.await?
+	let (ops, queries) = directories
 		.into_iter()
-		.flatten()
-		.map(|file_path| {
-			let size_bytes = iso_paths_and_sizes
-				.get(&IsolatedFilePathData::try_from(&file_path)?)
-				.map(|size| size_in_bytes_to_db(*size))
-				.expect("must be here");
-
-			let (sync_param, db_param) = sync_db_entry!(size_bytes, file_path::size_in_bytes_bytes);
+		.map(
+			|(
+				pub_id,
+				DirectoryTotals {
+					size_in_bytes,
+					file_count,
+				},
+			)| {
+				let (sync_params, db_params) = [
+					sync_db_entry!(
+						size_in_bytes_to_db(size_in_bytes),
+						file_path::size_in_bytes_bytes
+					),
+					sync_db_entry!(
+						i64::try_from(file_count).unwrap_or(i64::MAX),
+						file_path::file_count
+					),
+				]
+				.into_iter()
+				.unzip::<_, _, Vec<_>, Vec<_>>();
 
-			Ok((
-				sync.shared_update(
-					prisma_sync::file_path::SyncId {
-						pub_id: file_path.pub_id.clone(),
-					},
-					[sync_param],
-				),
-				db.file_path()
-					.update(file_path::pub_id::equals(file_path.pub_id), vec![db_param])
-					.select(file_path::select!({ id })),
-			))
-		})
-		.collect::<Result<Vec<_>, Error>>()?
-		.into_iter()
+				(
+					sync.shared_update(
+						prisma_sync::file_path::SyncId {
+							pub_id: pub_id.clone(),
+						},
+						sync_params,
+					),
+					db.file_path()
+						.update(file_path::pub_id::equals(pub_id), db_params)
+						.select(file_path::select!({ id })),
+				)
+			},
+		)
 		.unzip::<_, _, Vec<_>, Vec<_>>();
 
 	if !ops.is_empty() && !queries.is_empty() {
@@ -174,6 +258,319 @@ async fn update_directory_sizes(
 	Ok(())
 }
 
+#[derive(Deserialize)]
+struct RawFilesTotals {
+	materialized_path: String,
+	size_in_bytes: i64,
+	file_count: i64,
+}
+
+/// Every received directory inside `root_materialized_path` with each one of its ancestors up to
+/// the root, as children materialized paths
+fn with_ancestors(
+	materialized_paths: impl IntoIterator<Item = String>,
+	root_materialized_path: &str,
+) -> HashSet<String> {
+	let mut all_materialized_paths = HashSet::new();
+
+	for materialized_path in materialized_paths {
+		if !materialized_path.starts_with(root_materialized_path) {
+			continue;
+		}
+
+		let mut current_path = materialized_path.as_str();
+		// A path already inserted had its ancestors inserted with it
+		while all_materialized_paths.insert(current_path.to_string()) {
+			match parent_materialized_path(current_path) {
+				Some(parent_path) if current_path.len() > root_materialized_path.len() => {
+					current_path = parent_path;
+				}
+				_ => break,
+			}
+		}
+	}
+
+	all_materialized_paths
+}
+
+/// Recomputes the totals of the directories changed by the walk inside `root_iso_file_path`
+/// and of each one of their ancestors up to the root.
+///
+/// Like on [`reverse_update_directories_sizes`], only their direct children are fetched, the
+/// unchanged directories among them being accounted with their totals on database.
+#[instrument(
+	skip_all,
+	fields(root = %root_iso_file_path, changed_directories = changed_directories.len()),
+	err,
+)]
+async fn update_directories_sizes(
+	root_iso_file_path: &IsolatedFilePathData<'_>,
+	location_path: impl AsRef<Path> + Send,
+	changed_directories: &HashSet<IsolatedFilePathData<'static>>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+	errors: &mut Vec<crate::NonCriticalError>,
+) -> Result<(), Error> {
+	let location_id = root_iso_file_path.location_id();
+	let location_path = location_path.as_ref();
+
+	let materialized_paths = with_ancestors(
+		changed_directories
+			.iter()
+			.filter_map(IsolatedFilePathData::materialized_path_for_children),
+		&root_iso_file_path
+			.materialized_path_for_children()
+			.expect("the indexed root must be a directory"),
+	);
+
+	// The location root doesn't have a file_path, its size is kept on the location itself
+	let iso_file_paths = materialized_paths
+		.iter()
+		.filter(|materialized_path| materialized_path.as_str() != "/")
+		.map(|materialized_path| {
+			IsolatedFilePathData::new(
+				location_id,
+				location_path,
+				location_path.join(materialized_path[1..].trim_end_matches('/')),
+				true,
+			)
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let mut totals_by_materialized_path = directories_pub_ids(&iso_file_paths, db, errors).await?;
+
+	compute_sizes(
+		location_id,
+		materialized_paths.into_iter().collect(),
+		&mut totals_by_materialized_path,
+		db,
+		errors,
+	)
+	.await?;
+
+	write_directories_totals(totals_by_materialized_path.into_values(), db, sync).await
+}
+
+/// Sums the sizes and counts the files beneath every directory inside `root_iso_file_path`,
+/// itself included, writing on database only the directories whose totals changed.
+///
+/// The totals are always computed from the files on database, so directories skipped by the
+/// walker or with files removed by other means still end up with the right values. Archives
+/// members aren't counted, as their archive size already accounts for them, neither are
+/// [repeated hard links](REPEATED_HARD_LINK).
+#[instrument(skip_all, fields(root = %root_iso_file_path), err)]
+async fn update_all_directories_sizes(
+	root_iso_file_path: &IsolatedFilePathData<'_>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<(), Error> {
+	let location_id = root_iso_file_path.location_id();
+	let root_materialized_path = root_iso_file_path
+		.materialized_path_for_children()
+		.expect("the indexed root must be a directory");
+
+	let mut directories = db
+		.file_path()
+		.find_many(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::is_dir::equals(Some(true)),
+			file_path::materialized_path::starts_with(root_materialized_path.clone()),
+			// Archives members have their totals computed when their archive is listed
+			file_path::archive_member_path::equals(None),
+		])
+		.select(file_path_for_directory_totals::select())
+		.exec()
+		.await?;
+
+	// The location root doesn't have a file_path, its size is kept on the location itself
+	if !root_iso_file_path.is_root() {
+		directories.extend(
+			db.file_path()
+				.find_unique(root_iso_file_path.into())
+				.select(file_path_for_directory_totals::select())
+				.exec()
+				.await?,
+		);
+	}
+
+	let mut totals_by_materialized_path = HashMap::<String, DirectoryTotals>::new();
+
+	for RawFilesTotals {
+		materialized_path,
+		size_in_bytes,
+		file_count,
+	} in db
+		._query_raw::<RawFilesTotals>(raw!(
+			&format!(
+				"SELECT
+				materialized_path,
+				COALESCE(SUM(size_in_bytes_int), 0) AS size_in_bytes,
+				COUNT(*) AS file_count
+			FROM file_path
+			WHERE
+				location_id={{}}
+				AND COALESCE(is_dir, 0)=0
+				AND archive_member_path IS NULL
+				AND materialized_path LIKE {{}}
+				AND NOT {REPEATED_HARD_LINK}
+			GROUP BY materialized_path"
+			),
+			PrismaValue::Int(location_id),
+			PrismaValue::String(format!("{root_materialized_path}%"))
+		))
+		.exec()
+		.await?
+	{
+		// LIKE is case insensitive and takes `_` as a wildcard, so it can match files outside root
+		if !materialized_path.starts_with(&root_materialized_path) {
+			continue;
+		}
+
+		let totals = DirectoryTotals {
+			size_in_bytes: u64::try_from(size_in_bytes).unwrap_or_default(),
+			file_count: u64::try_from(file_count).unwrap_or_default(),
+		};
+
+		// The files are accounted on their directory and on each one above it, up to the root
+		let mut current_path = materialized_path.as_str();
+		loop {
+			*totals_by_materialized_path
+				.entry(current_path.to_string())
+				.or_default() += totals;
+
+			match parent_materialized_path(current_path) {
+				Some(parent_path) if current_path.len() > root_materialized_path.len() => {
+					current_path = parent_path;
+				}
+				_ => break,
+			}
+		}
+	}
+
+	write_directories_totals(
+		directories.into_iter().filter_map(|directory| {
+			let (Some(materialized_path), Some(name)) =
+				(&directory.materialized_path, &directory.name)
+			else {
+				warn!(
+					pub_id = %from_bytes_to_uuid(&directory.pub_id),
+					"Got a directory missing its materialized path or name, ignoring;",
+				);
+				return None;
+			};
+
+			let children_path = format!("{materialized_path}{name}/");
+
+			// Same as with files, `starts_with` can bring directories outside root
+			if !children_path.starts_with(&root_materialized_path) {
+				return None;
+			}
+
+			let totals = totals_by_materialized_path
+				.get(&children_path)
+				.copied()
+				.unwrap_or_default();
+
+			(directory.file_count.is_none() || DirectoryTotals::from(&directory) != totals)
+				.then(|| (directory.pub_id, totals))
+		}),
+		db,
+		sync,
+	)
+	.await
+}
+
+/// Directories indexed before their file counts existed must have their totals computed again
+async fn has_directories_without_totals(
+	root_iso_file_path: &IsolatedFilePathData<'_>,
+	db: &PrismaClient,
+) -> Result<bool, Error> {
+	db.file_path()
+		.count(vec![
+			file_path::location_id::equals(Some(root_iso_file_path.location_id())),
+			file_path::is_dir::equals(Some(true)),
+			file_path::materialized_path::starts_with(
+				root_iso_file_path
+					.materialized_path_for_children()
+					.expect("the indexed root must be a directory"),
+			),
+			file_path::file_count::equals(None),
+		])
+		.exec()
+		.await
+		.map(|count| count > 0)
+		.map_err(Into::into)
+}
+
+#[derive(Deserialize)]
+struct RawLocationSize {
+	size_in_bytes: i64,
+}
+
+/// Sums the sizes of every file on a location, counting hard links once, the same way directories
+/// totals are computed. Archives members aren't counted, as their archive size already accounts
+/// for them.
+pub async fn location_size(
+	location_id: location::id::Type,
+	db: &PrismaClient,
+) -> Result<u64, QueryError> {
+	db._query_raw::<RawLocationSize>(raw!(
+		&format!(
+			"SELECT COALESCE(SUM(size_in_bytes_int), 0) AS size_in_bytes
+			FROM file_path
+			WHERE
+				location_id={{}}
+				AND COALESCE(is_dir, 0)=0
+				AND archive_member_path IS NULL
+				AND NOT {REPEATED_HARD_LINK}"
+		),
+		PrismaValue::Int(location_id)
+	))
+	.exec()
+	.await
+	.map(|rows| {
+		rows.first().map_or(0, |RawLocationSize { size_in_bytes }| {
+			u64::try_from(*size_in_bytes).unwrap_or_default()
+		})
+	})
+}
+
+#[derive(Deserialize)]
+struct RawFilePathId {
+	id: file_path::id::Type,
+}
+
+/// Files directly inside these directories which are hard links to a file already accounted
+async fn repeated_hard_links(
+	location_id: location::id::Type,
+	materialized_paths: &[String],
+	db: &PrismaClient,
+) -> Result<HashSet<file_path::id::Type>, QueryError> {
+	if materialized_paths.is_empty() {
+		return Ok(HashSet::new());
+	}
+
+	db._query_raw::<RawFilePathId>(Raw::new(
+		&format!(
+			"SELECT id
+			FROM file_path
+			WHERE
+				location_id={{}}
+				AND COALESCE(is_dir, 0)=0
+				AND inode IS NOT NULL
+				AND materialized_path IN ({})
+				AND {REPEATED_HARD_LINK}",
+			vec!["{}"; materialized_paths.len()].join(",")
+		),
+		iter::once(PrismaValue::Int(location_id))
+			.chain(materialized_paths.iter().cloned().map(PrismaValue::String))
+			.collect(),
+	))
+	.exec()
+	.await
+	.map(|rows| rows.into_iter().map(|RawFilePathId { id }| id).collect())
+}
+
 async fn update_location_size(
 	location_id: location::id::Type,
 	location_pub_id: location::pub_id::Type,
@@ -182,23 +579,7 @@ async fn update_location_size(
 	let db = ctx.db();
 	let sync = ctx.sync();
 
-	let total_size = size_in_bytes_to_db(
-		db.file_path()
-			.find_many(vec![
-				file_path::location_id::equals(Some(location_id)),
-				file_path::materialized_path::equals(Some("/".to_string())),
-			])
-			.select(file_path::select!({ size_in_bytes_bytes }))
-			.exec()
-			.await?
-			.into_iter()
-			.filter_map(|file_path| {
-				file_path
-					.size_in_bytes_bytes
-					.map(|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes))
-			})
-			.sum::<u64>(),
-	);
+	let total_size = size_in_bytes_to_db(location_size(location_id, db).await?);
 
 	let (sync_param, db_param) = sync_db_entry!(total_size, location::size_in_bytes);
 
@@ -296,8 +677,43 @@ pub async fn reverse_update_directories_sizes(
 		})
 		.collect::<Result<HashMap<_, _>, _>>()?;
 
-	let mut pub_id_by_ancestor_materialized_path = db
-		._batch(chunk_db_queries(ancestors.keys(), db))
+	let mut totals_by_ancestor_materialized_path =
+		directories_pub_ids(ancestors.keys(), db, errors).await?;
+
+	compute_sizes(
+		location_id,
+		ancestors.values().cloned().collect(),
+		&mut totals_by_ancestor_materialized_path,
+		db,
+		errors,
+	)
+	.await?;
+
+	write_directories_totals(
+		ancestors.into_values().filter_map(|materialized_path| {
+			let entry = totals_by_ancestor_materialized_path.remove(&materialized_path);
+
+			if entry.is_none() {
+				warn!("Got a missing ancestor for a file_path in the database, ignoring...");
+			}
+
+			entry
+		}),
+		db,
+		sync,
+	)
+	.await
+}
+
+/// The received directories found on database, keyed by their children materialized path, with
+/// zeroed totals to be computed
+async fn directories_pub_ids<'iso>(
+	iso_file_paths: impl IntoIterator<Item = &'iso IsolatedFilePathData<'iso>>,
+	db: &PrismaClient,
+	errors: &mut Vec<crate::NonCriticalError>,
+) -> Result<HashMap<String, (file_path::pub_id::Type, DirectoryTotals)>, QueryError> {
+	Ok(db
+		._batch(chunk_db_queries(iso_file_paths, db))
 		.await?
 		.into_iter()
 		.flatten()
@@ -318,101 +734,96 @@ pub async fn reverse_update_directories_sizes(
 						iso_file_path
 							.materialized_path_for_children()
 							.expect("we know it's a directory"),
-						(pub_id, 0),
+						(pub_id, DirectoryTotals::default()),
 					)
 				})
 				.ok()
 		})
-		.collect::<HashMap<_, _>>();
-
-	compute_sizes(
-		location_id,
-		ancestors.values().cloned().collect(),
-		&mut pub_id_by_ancestor_materialized_path,
-		db,
-		errors,
-	)
-	.await?;
-
-	let (sync_ops, update_queries) = ancestors
-		.into_values()
-		.filter_map(|materialized_path| {
-			if let Some((pub_id, size)) =
-				pub_id_by_ancestor_materialized_path.remove(&materialized_path)
-			{
-				let size_bytes = size_in_bytes_to_db(size);
-
-				let (sync_param, db_param) =
-					sync_db_entry!(size_bytes, file_path::size_in_bytes_bytes);
-
-				Some((
-					sync.shared_update(
-						prisma_sync::file_path::SyncId {
-							pub_id: pub_id.clone(),
-						},
-						[sync_param],
-					),
-					db.file_path()
-						.update(file_path::pub_id::equals(pub_id), vec![db_param])
-						.select(file_path::select!({ id })),
-				))
-			} else {
-				warn!("Got a missing ancestor for a file_path in the database, ignoring...");
-				None
-			}
-		})
-		.unzip::<_, _, Vec<_>, Vec<_>>();
-
-	if !sync_ops.is_empty() && !update_queries.is_empty() {
-		sync.write_ops(db, (sync_ops, update_queries)).await?;
-	}
-
-	Ok(())
+		.collect())
 }
 
 async fn compute_sizes(
 	location_id: location::id::Type,
 	materialized_paths: Vec<String>,
-	pub_id_by_ancestor_materialized_path: &mut HashMap<String, (file_path::pub_id::Type, u64)>,
+	totals_by_ancestor_materialized_path: &mut HashMap<
+		String,
+		(file_path::pub_id::Type, DirectoryTotals),
+	>,
 	db: &PrismaClient,
 	errors: &mut Vec<crate::NonCriticalError>,
 ) -> Result<(), QueryError> {
-	for file_path in db
-		.file_path()
-		.find_many(vec![
-			file_path::location_id::equals(Some(location_id)),
-			file_path::materialized_path::in_vec(materialized_paths),
-		])
-		.select(file_path::select!({ pub_id materialized_path size_in_bytes_bytes }))
-		.exec()
-		.await?
-	{
-		if let Some(materialized_path) = file_path.materialized_path {
-			if let Some((_, size)) =
-				pub_id_by_ancestor_materialized_path.get_mut(&materialized_path)
+	// Ancestors are summed into each other below, as their totals on database are outdated
+	let ancestors_pub_ids = totals_by_ancestor_materialized_path
+		.values()
+		.map(|(pub_id, _)| pub_id.clone())
+		.collect::<HashSet<_>>();
+
+	for materialized_paths in materialized_paths.chunks(BATCH_SIZE) {
+		let repeated_hard_links = repeated_hard_links(location_id, materialized_paths, db).await?;
+
+		for file_path in db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::equals(Some(location_id)),
+				file_path::materialized_path::in_vec(materialized_paths.to_vec()),
+			])
+			.select(file_path_for_directory_totals::select())
+			.exec()
+			.await?
+		{
+			if ancestors_pub_ids.contains(&file_path.pub_id)
+				|| repeated_hard_links.contains(&file_path.id)
 			{
-				*size += file_path.size_in_bytes_bytes.map_or_else(
-					|| {
-						warn!("Got a directory missing its size in bytes");
-						0
-					},
-					|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes),
-				);
+				continue;
 			}
-		} else {
-			errors.push(
-				NonCriticalIndexerError::MissingFilePathData(format!(
+
+			if let Some(materialized_path) = &file_path.materialized_path {
+				if let Some((_, totals)) =
+					totals_by_ancestor_materialized_path.get_mut(materialized_path)
+				{
+					*totals += DirectoryTotals::from(&file_path);
+				}
+			} else {
+				errors.push(
+					NonCriticalIndexerError::MissingFilePathData(format!(
 						"Corrupt database possessing a file_path entry without materialized_path: <pub_id='{:#?}'>",
 						from_bytes_to_uuid(&file_path.pub_id)
 					))
-				.into(),
-			);
+					.into(),
+				);
+			}
 		}
 	}
 
+	roll_up_totals(totals_by_ancestor_materialized_path);
+
 	Ok(())
 }
 
+/// Adds the totals of each directory to the one holding it, deepest first, so each one is
+/// complete when added to the one above it
+fn roll_up_totals(
+	totals_by_materialized_path: &mut HashMap<String, (file_path::pub_id::Type, DirectoryTotals)>,
+) {
+	let mut materialized_paths = totals_by_materialized_path
+		.keys()
+		.cloned()
+		.collect::<Vec<_>>();
+	materialized_paths.sort_unstable_by_key(|materialized_path| Reverse(materialized_path.len()));
+
+	for materialized_path in materialized_paths {
+		let Some(parent_path) = parent_materialized_path(&materialized_path) else {
+			continue;
+		};
+
+		let totals = totals_by_materialized_path[&materialized_path].1;
+
+		if let Some((_, parent_totals)) = totals_by_materialized_path.get_mut(parent_path) {
+			*parent_totals += totals;
+		}
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct IsoFilePathFactory {
 	pub location_id: location::id::Type,
@@ -563,4 +974,93 @@ impl walker::WalkerDBProxy for WalkerDBProxy {
 
 		Ok(to_remove)
 	}
+
+	async fn fetch_inner_directories(
+		&self,
+		parent_iso_file_path: &IsolatedFilePathData<'_>,
+	) -> Result<Vec<file_path_walker::Data>, Error> {
+		self.db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::equals(Some(self.location_id)),
+				file_path::materialized_path::equals(Some(
+					parent_iso_file_path
+						.materialized_path_for_children()
+						.expect("the received isolated file path must be from a directory"),
+				)),
+				file_path::is_dir::equals(Some(true)),
+			])
+			.select(file_path_walker::select())
+			.exec()
+			.await
+			.map_err(Into::into)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn totals(size_in_bytes: u64, file_count: u64) -> DirectoryTotals {
+		DirectoryTotals {
+			size_in_bytes,
+			file_count,
+		}
+	}
+
+	#[test]
+	fn parent_of_materialized_paths() {
+		assert_eq!(parent_materialized_path("/a/b/c/"), Some("/a/b/"));
+		assert_eq!(parent_materialized_path("/a/"), Some("/"));
+		assert_eq!(parent_materialized_path("/"), None);
+	}
+
+	#[test]
+	fn changed_directories_with_ancestors_up_to_root() {
+		assert_eq!(
+			with_ancestors(
+				["/a/b/c/", "/a/b/d/", "/a/e/", "/f/"].map(String::from),
+				"/a/"
+			),
+			["/a/b/c/", "/a/b/d/", "/a/b/", "/a/e/", "/a/"]
+				.map(String::from)
+				.into_iter()
+				.collect()
+		);
+
+		assert_eq!(
+			with_ancestors(["/a/b/"].map(String::from), "/"),
+			["/a/b/", "/a/", "/"]
+				.map(String::from)
+				.into_iter()
+				.collect()
+		);
+	}
+
+	#[test]
+	fn totals_rolled_up_deepest_first() {
+		let mut totals_by_materialized_path = [
+			("/a/", totals(1, 1)),
+			("/a/b/", totals(10, 2)),
+			("/a/b/c/", totals(100, 3)),
+			("/a/d/", totals(1000, 4)),
+		]
+		.into_iter()
+		.map(|(materialized_path, totals)| {
+			(
+				materialized_path.to_string(),
+				(materialized_path.as_bytes().to_vec(), totals),
+			)
+		})
+		.collect::<HashMap<_, _>>();
+
+		roll_up_totals(&mut totals_by_materialized_path);
+
+		let rolled_up = |materialized_path: &str| totals_by_materialized_path[materialized_path].1;
+
+		assert_eq!(rolled_up("/a/b/c/"), totals(100, 3));
+		assert_eq!(rolled_up("/a/b/"), totals(110, 5));
+		assert_eq!(rolled_up("/a/d/"), totals(1000, 4));
+		assert_eq!(rolled_up("/a/"), totals(1111, 10));
+	}
 }
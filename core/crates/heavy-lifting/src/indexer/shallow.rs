@@ -1,5 +1,6 @@
 use crate::{
-	indexer, utils::sub_path::get_full_path_from_sub_path, Error, NonCriticalError, OuterContext,
+	indexer, utils::sub_path::get_full_path_from_sub_path, Error, IoThrottle, NonCriticalError,
+	OuterContext,
 };
 
 use sd_core_indexer_rules::{IndexerRule, IndexerRuler};
@@ -11,7 +12,6 @@ use sd_task_system::{BaseTaskDispatcher, CancelTaskOnDrop, IntoTask, TaskDispatc
 use sd_utils::db::maybe_missing;
 
 use std::{
-	collections::HashMap,
 	path::{Path, PathBuf},
 	sync::Arc,
 };
@@ -26,7 +26,7 @@ use super::{
 		self, saver, updater,
 		walker::{self, ToWalkEntry, WalkedEntry},
 	},
-	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
+	update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
 };
 
 #[instrument(
@@ -79,8 +79,6 @@ pub async fn shallow(
 		to_remove,
 		non_indexed_paths,
 		mut errors,
-		directory_iso_file_path,
-		total_size,
 		..
 	}) = walk(
 		&location,
@@ -117,13 +115,7 @@ pub async fn shallow(
 	};
 
 	if indexed_count > 0 || removed_count > 0 || updated_count > 0 {
-		update_directory_sizes(
-			HashMap::from([(directory_iso_file_path, total_size)]),
-			db,
-			sync,
-		)
-		.await?;
-
+		// Inner directories weren't walked, so their totals on database are still good to sum
 		if to_walk_path != location_path {
 			reverse_update_directories_sizes(
 				&*to_walk_path,
@@ -176,6 +168,10 @@ async fn walk(
 				location_id: location.id,
 				db,
 			},
+			// Shallow walks are for directories being browsed, so they're never throttled
+			IoThrottle::default(),
+			// Unset on locations created before this setting, which always followed them
+			location.follow_symlinks.unwrap_or(true),
 		)?)
 		.await
 	else {
@@ -7,9 +7,12 @@ use sd_prisma::{
 	prisma::{device, file_path, location, PrismaClient},
 	prisma_sync,
 };
-use sd_sync::{sync_db_entry, sync_entry, OperationFactory};
+use sd_sync::{option_sync_db_entry, sync_db_entry, sync_entry, OperationFactory};
 use sd_task_system::{ExecStatus, Interrupter, IntoAnyTaskOutput, SerializableTask, Task, TaskId};
-use sd_utils::db::{inode_to_db, size_in_bytes_to_db};
+use sd_utils::{
+	chain_optional_iter,
+	db::{inode_to_db, size_in_bytes_to_db},
+};
 
 use std::{sync::Arc, time::Duration};
 
@@ -73,7 +76,7 @@ impl Task<Error> for Saver {
 		use file_path::{
 			create_unchecked, date_created, date_indexed, date_modified, device, device_id,
 			extension, hidden, inode, is_dir, location, location_id, materialized_path, name,
-			size_in_bytes_bytes,
+			size_in_bytes_bytes, symlink_target,
 		};
 
 		let start_time = Instant::now();
@@ -103,6 +106,7 @@ impl Task<Error> for Saver {
 				             modified_at,
 				             hidden,
 				         },
+				     symlink_target,
 				 }| {
 					let IsolatedFilePathDataParts {
 						materialized_path,
@@ -118,36 +122,43 @@ impl Task<Error> for Saver {
 						new file_paths and they were not identified yet"
 					);
 
-					let (sync_params, db_params) = [
-						(
-							sync_entry!(
-								prisma_sync::location::SyncId {
-									pub_id: location_pub_id.clone()
-								},
-								location
+					let (sync_params, db_params) = chain_optional_iter(
+						[
+							(
+								sync_entry!(
+									prisma_sync::location::SyncId {
+										pub_id: location_pub_id.clone()
+									},
+									location
+								),
+								location_id::set(Some(*location_id)),
 							),
-							location_id::set(Some(*location_id)),
-						),
-						sync_db_entry!(materialized_path, materialized_path),
-						sync_db_entry!(name, name),
-						sync_db_entry!(is_dir, is_dir),
-						sync_db_entry!(extension, extension),
-						sync_db_entry!(size_in_bytes_to_db(size_in_bytes), size_in_bytes_bytes),
-						sync_db_entry!(inode_to_db(inode), inode),
-						sync_db_entry!(created_at, date_created),
-						sync_db_entry!(modified_at, date_modified),
-						sync_db_entry!(Utc::now(), date_indexed),
-						sync_db_entry!(hidden, hidden),
-						(
-							sync_entry!(
-								prisma_sync::device::SyncId {
-									pub_id: sync.device_pub_id.to_db(),
-								},
-								device
+							sync_db_entry!(materialized_path, materialized_path),
+							sync_db_entry!(name, name),
+							sync_db_entry!(is_dir, is_dir),
+							sync_db_entry!(extension, extension),
+							// Directories sizes are summed from their files once these are indexed
+							sync_db_entry!(
+								size_in_bytes_to_db(if is_dir { 0 } else { size_in_bytes }),
+								size_in_bytes_bytes
 							),
-							device_id::set(Some(*device_id)),
-						),
-					]
+							sync_db_entry!(inode_to_db(inode), inode),
+							sync_db_entry!(created_at, date_created),
+							sync_db_entry!(modified_at, date_modified),
+							sync_db_entry!(Utc::now(), date_indexed),
+							sync_db_entry!(hidden, hidden),
+							(
+								sync_entry!(
+									prisma_sync::device::SyncId {
+										pub_id: sync.device_pub_id.to_db(),
+									},
+									device
+								),
+								device_id::set(Some(*device_id)),
+							),
+						],
+						[option_sync_db_entry!(symlink_target, symlink_target)],
+					)
 					.into_iter()
 					.unzip::<_, _, Vec<_>, Vec<_>>();
 
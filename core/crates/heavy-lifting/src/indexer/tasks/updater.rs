@@ -7,7 +7,7 @@ use sd_prisma::{
 	prisma::{file_path, object, PrismaClient},
 	prisma_sync,
 };
-use sd_sync::{sync_db_entry, OperationFactory};
+use sd_sync::{sync_db_entry, sync_db_nullable_entry, OperationFactory};
 use sd_task_system::{
 	check_interruption, ExecStatus, Interrupter, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
 };
@@ -75,8 +75,8 @@ impl Task<Error> for Updater {
 	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
 	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
 		use file_path::{
-			cas_id, date_created, date_modified, hidden, inode, is_dir, object, object_id,
-			size_in_bytes_bytes,
+			cas_id, date_created, date_modified, hidden, inode, integrity_checksum, is_dir, object,
+			object_id, size_in_bytes_bytes, symlink_target,
 		};
 
 		let start_time = Instant::now();
@@ -108,6 +108,7 @@ impl Task<Error> for Updater {
 				             modified_at,
 				             hidden,
 				         },
+				     symlink_target,
 				 }| {
 					let IsolatedFilePathDataParts { is_dir, .. } = &iso_file_path.to_parts();
 
@@ -118,12 +119,18 @@ impl Task<Error> for Updater {
 					let (sync_params, db_params) = chain_optional_iter(
 						[
 							((cas_id::NAME, msgpack!(nil)), cas_id::set(None)),
+							// The contents changed, so the file identifier must checksum it again
+							(
+								(integrity_checksum::NAME, msgpack!(nil)),
+								integrity_checksum::set(None),
+							),
 							sync_db_entry!(*is_dir, is_dir),
 							sync_db_entry!(size_in_bytes_to_db(size_in_bytes), size_in_bytes_bytes),
 							sync_db_entry!(inode_to_db(inode), inode),
 							sync_db_entry!(created_at, date_created),
 							sync_db_entry!(modified_at, date_modified),
 							sync_db_entry!(hidden, hidden),
+							sync_db_nullable_entry!(symlink_target, symlink_target),
 						],
 						[
 							// As this file was updated while Spacedrive was offline, we mark the object_id and cas_id as null
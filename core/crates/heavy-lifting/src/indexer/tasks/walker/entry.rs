@@ -10,6 +10,8 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use super::metadata::InnerMetadata;
+
 /// `WalkedEntry` represents a single path in the filesystem
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalkedEntry {
@@ -17,6 +19,8 @@ pub struct WalkedEntry {
 	pub maybe_object_id: file_path::object_id::Type,
 	pub iso_file_path: IsolatedFilePathData<'static>,
 	pub metadata: FilePathMetadata,
+	/// Set when this entry is a symbolic link recorded as an alias
+	pub symlink_target: Option<String>,
 }
 
 impl PartialEq for WalkedEntry {
@@ -37,6 +41,17 @@ impl Hash for WalkedEntry {
 pub(super) struct WalkingEntry {
 	pub(super) iso_file_path: IsolatedFilePathData<'static>,
 	pub(super) metadata: FilePathMetadata,
+	pub(super) symlink_target: Option<String>,
+}
+
+impl From<(IsolatedFilePathData<'static>, InnerMetadata)> for WalkingEntry {
+	fn from((iso_file_path, mut metadata): (IsolatedFilePathData<'static>, InnerMetadata)) -> Self {
+		Self {
+			iso_file_path,
+			symlink_target: metadata.symlink_target.take(),
+			metadata: metadata.into(),
+		}
+	}
 }
 
 impl From<WalkingEntry> for WalkedEntry {
@@ -44,6 +59,7 @@ impl From<WalkingEntry> for WalkedEntry {
 		WalkingEntry {
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}: WalkingEntry,
 	) -> Self {
 		Self {
@@ -51,6 +67,7 @@ impl From<WalkingEntry> for WalkedEntry {
 			maybe_object_id: None,
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}
 	}
 }
@@ -65,6 +82,7 @@ impl<PubId: Into<FilePathPubId>> From<(PubId, file_path::object_id::Type, Walkin
 			WalkingEntry {
 				iso_file_path,
 				metadata,
+				symlink_target,
 			},
 		): (PubId, file_path::object_id::Type, WalkingEntry),
 	) -> Self {
@@ -73,6 +91,7 @@ impl<PubId: Into<FilePathPubId>> From<(PubId, file_path::object_id::Type, Walkin
 			maybe_object_id,
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}
 	}
 }
@@ -81,6 +100,13 @@ impl<PubId: Into<FilePathPubId>> From<(PubId, file_path::object_id::Type, Walkin
 pub struct ToWalkEntry {
 	pub(super) path: PathBuf,
 	pub(super) parent_dir_accepted_by_its_children: Option<bool>,
+	/// Set by the parent directory walker when this directory has the same inode and modification
+	/// time as the last time it was indexed
+	pub(super) unchanged_since_last_scan: bool,
+	/// Set by the parent directory walker when this directory changed since the last time it was
+	/// indexed. Its new inode and modification time are only written once it's walked, otherwise
+	/// an interrupted scan would make it look unchanged to the next one, which would skip it
+	pub(super) pending_update: Option<WalkedEntry>,
 }
 
 impl<P: AsRef<Path>> From<P> for ToWalkEntry {
@@ -88,6 +114,8 @@ impl<P: AsRef<Path>> From<P> for ToWalkEntry {
 		Self {
 			path: path.as_ref().into(),
 			parent_dir_accepted_by_its_children: None,
+			unchanged_since_last_scan: false,
+			pending_update: None,
 		}
 	}
 }
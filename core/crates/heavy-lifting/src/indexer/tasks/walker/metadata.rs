@@ -1,9 +1,12 @@
 use crate::indexer;
 
-use sd_core_file_path_helper::FilePathMetadata;
+use sd_core_file_path_helper::{hard_links_count, FilePathMetadata};
 use sd_core_indexer_rules::MetadataForIndexerRules;
 
-use std::{fs::Metadata, path::Path};
+use std::{
+	fs::Metadata,
+	path::{Path, PathBuf},
+};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -11,7 +14,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub(super) struct InnerMetadata {
 	pub is_dir: bool,
-	pub is_symlink: bool,
+	/// Only set for symbolic links recorded as aliases, the followed ones have their target metadata
+	pub symlink_target: Option<String>,
+	pub hard_links_count: u64,
 	pub inode: u64,
 	pub size_in_bytes: u64,
 	pub hidden: bool,
@@ -23,6 +28,7 @@ impl InnerMetadata {
 	pub fn new(
 		path: impl AsRef<Path> + Copy,
 		metadata: &Metadata,
+		symlink_target: Option<PathBuf>,
 	) -> Result<Self, indexer::NonCriticalIndexerError> {
 		let FilePathMetadata {
 			inode,
@@ -33,9 +39,22 @@ impl InnerMetadata {
 		} = FilePathMetadata::from_path(path, metadata)
 			.map_err(|e| indexer::NonCriticalIndexerError::FilePathMetadata(e.to_string()))?;
 
+		let symlink_target = symlink_target
+			.map(|target| {
+				target.into_os_string().into_string().map_err(|target| {
+					indexer::NonCriticalIndexerError::FilePathMetadata(format!(
+						"symbolic link target isn't valid UTF-8: <path='{}', target='{}'>",
+						path.as_ref().display(),
+						Path::new(&target).display()
+					))
+				})
+			})
+			.transpose()?;
+
 		Ok(Self {
 			is_dir: metadata.is_dir(),
-			is_symlink: metadata.is_symlink(),
+			symlink_target,
+			hard_links_count: hard_links_count(metadata),
 			inode,
 			size_in_bytes,
 			hidden,
@@ -49,6 +68,14 @@ impl MetadataForIndexerRules for InnerMetadata {
 	fn is_dir(&self) -> bool {
 		self.is_dir
 	}
+
+	fn size_in_bytes(&self) -> u64 {
+		self.size_in_bytes
+	}
+
+	fn modified_at(&self) -> Option<DateTime<Utc>> {
+		Some(self.modified_at)
+	}
 }
 
 impl From<InnerMetadata> for FilePathMetadata {
@@ -3,37 +3,39 @@ use crate::{
 		self,
 		tasks::walker::rules::{apply_indexer_rules, process_rules_results},
 	},
-	Error, NonCriticalError,
+	Error, IoThrottle, NonCriticalError,
 };
 
-use sd_core_file_path_helper::{FilePathError, FilePathMetadata, IsolatedFilePathData};
+use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
 use sd_core_indexer_rules::{
-	seed::{GitIgnoreRules, GITIGNORE},
+	seed::{GitIgnoreRules, SpacedriveIgnoreRules, GITIGNORE},
 	IndexerRuler, MetadataForIndexerRules, RuleKind,
 };
 use sd_core_prisma_helpers::{file_path_pub_and_cas_ids, file_path_walker};
 
 use sd_prisma::prisma::file_path;
 use sd_task_system::{
-	check_interruption, ExecStatus, Interrupter, IntoAnyTaskOutput, Task, TaskId,
+	check_interruption, ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, Task, TaskId,
 };
 use sd_utils::{
-	db::{inode_from_db, inode_to_db},
+	db::{inode_from_db, inode_to_db, size_in_bytes_from_db},
 	error::FileIOError,
 };
 
 use std::{
 	collections::{HashMap, HashSet},
 	fmt,
-	future::Future,
-	mem,
+	fs::Metadata,
+	future::{Future, IntoFuture},
+	io, mem,
 	path::{Path, PathBuf},
 	sync::Arc,
 	time::Duration,
 };
 
 use chrono::{DateTime, Duration as ChronoDuration, FixedOffset};
-use futures_concurrency::future::Join;
+use futures::FutureExt;
+use futures_concurrency::future::{Join, Race};
 use tokio::{fs, time::Instant};
 use tokio_stream::{wrappers::ReadDirStream, StreamExt};
 use tracing::{instrument, trace, Level};
@@ -48,6 +50,10 @@ pub use entry::{ToWalkEntry, WalkedEntry};
 use entry::WalkingEntry;
 use metadata::InnerMetadata;
 
+/// Metadata is collected in chunks of this many paths, so a walker waiting on its I/O budget
+/// doesn't have a future per path and can be interrupted between chunks
+const METADATA_CHUNK_SIZE: usize = 100;
+
 pub trait IsoFilePathFactory: Clone + Send + Sync + fmt::Debug + 'static {
 	fn build(
 		&self,
@@ -70,6 +76,13 @@ pub trait WalkerDBProxy: Clone + Send + Sync + fmt::Debug + 'static {
 	) -> impl Future<
 		Output = Result<Vec<file_path_pub_and_cas_ids::Data>, indexer::NonCriticalIndexerError>,
 	> + Send;
+
+	/// Directories already indexed directly inside the received one, used to keep walking through
+	/// directories unchanged since the last scan without reading their entries
+	fn fetch_inner_directories(
+		&self,
+		parent_iso_file_path: &IsolatedFilePathData<'_>,
+	) -> impl Future<Output = Result<Vec<file_path_walker::Data>, indexer::Error>> + Send;
 }
 
 #[derive(Debug)]
@@ -81,6 +94,10 @@ where
 	// Task control
 	id: TaskId,
 	is_shallow: bool,
+	// Walk through every directory, even the ones unchanged since the last scan
+	is_paranoid: bool,
+	// Walk through symbolic links instead of recording them as aliases
+	follow_symlinks: bool,
 
 	// Received input args
 	entry: ToWalkEntry,
@@ -94,6 +111,7 @@ where
 	// Dependencies
 	iso_file_path_factory: IsoPathFactory,
 	db_proxy: DBProxy,
+	io_throttle: IoThrottle,
 
 	// Non critical errors that happened during the task execution
 	errors: Vec<NonCriticalError>,
@@ -123,8 +141,6 @@ where
 	pub errors: Vec<NonCriticalError>,
 	/// Directory that was indexed
 	pub directory_iso_file_path: IsolatedFilePathData<'static>,
-	/// Total size of the directory that was indexed
-	pub total_size: u64,
 	/// Task handles that were dispatched to run `WalkDir` tasks for inner directories
 	pub keep_walking_tasks: Vec<Walker<DBProxy, IsoPathFactory>>,
 	/// Time spent walking through the received directory
@@ -159,16 +175,22 @@ where
 	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
 	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
 		let is_shallow = self.is_shallow;
+		let is_paranoid = self.is_paranoid;
+		let follow_symlinks = self.follow_symlinks;
 		let Self {
 			root,
-			entry: ToWalkEntry {
-				path,
-				parent_dir_accepted_by_its_children,
-			},
+			entry:
+				ToWalkEntry {
+					path,
+					parent_dir_accepted_by_its_children,
+					unchanged_since_last_scan,
+					pending_update,
+				},
 			entry_iso_file_path,
 			iso_file_path_factory,
 			indexer_ruler,
 			db_proxy,
+			io_throttle,
 			stage,
 			errors,
 			scan_time,
@@ -183,7 +205,6 @@ where
 			to_remove,
 			non_indexed_paths,
 			accepted_ancestors,
-			total_size,
 			keep_walking_tasks,
 		) = loop {
 			match stage {
@@ -198,18 +219,64 @@ where
 						}
 					}
 
-					*stage = WalkerStage::Walking {
-						read_dir_stream: ReadDirStream::new(fs::read_dir(&path).await.map_err(
-							|e| {
-								indexer::Error::FileIO(
-									(&path, e, "Failed to open directory to read its entries")
-										.into(),
-								)
-							},
-						)?),
-						found_paths: Vec::new(),
-					};
-					trace!("Starting to walk!");
+					let (rules, ignore_errors) = SpacedriveIgnoreRules::get_rules(
+						root.as_ref(),
+						path,
+						indexer_ruler.spacedrive_ignore_files(),
+					)
+					.await;
+
+					errors.extend(ignore_errors.into_iter().map(|e| {
+						indexer::NonCriticalIndexerError::IgnoreFile(e.to_string()).into()
+					}));
+
+					if let Some(rules) = rules {
+						trace!("Found spacedriveignore rules to follow");
+						indexer_ruler.extend([rules.into()]);
+					}
+
+					if *unchanged_since_last_scan {
+						// Nothing was added, removed or renamed directly inside this directory,
+						// but its inner directories must still be checked as their changes don't
+						// change the modification time of this one
+						trace!(
+							"Directory unchanged since last scan, only walking inner directories"
+						);
+						*stage = WalkerStage::CollectingMetadata {
+							paths_and_metadatas: HashMap::new(),
+							found_paths: db_proxy
+								.fetch_inner_directories(entry_iso_file_path)
+								.await?
+								.into_iter()
+								.filter_map(|file_path| {
+									IsolatedFilePathData::try_from(file_path)
+										.map(|iso_file_path| path.join(iso_file_path.full_name()))
+										.map_err(|e| {
+											errors.push(
+												indexer::NonCriticalIndexerError::IsoFilePath(
+													e.to_string(),
+												)
+												.into(),
+											);
+										})
+										.ok()
+								})
+								.collect(),
+						};
+					} else {
+						*stage = WalkerStage::Walking {
+							read_dir_stream: ReadDirStream::new(
+								fs::read_dir(&path).await.map_err(|e| {
+									indexer::Error::FileIO(
+										(&path, e, "Failed to open directory to read its entries")
+											.into(),
+									)
+								})?,
+							),
+							found_paths: Vec::new(),
+						};
+						trace!("Starting to walk!");
+					}
 				}
 
 				WalkerStage::Walking {
@@ -243,15 +310,53 @@ where
 
 					*stage = WalkerStage::CollectingMetadata {
 						found_paths: mem::take(found_paths),
+						paths_and_metadatas: HashMap::new(),
 					};
 
 					check_interruption!(interrupter, start_time, scan_time);
 				}
 
-				WalkerStage::CollectingMetadata { found_paths } => {
+				WalkerStage::CollectingMetadata {
+					found_paths,
+					paths_and_metadatas,
+				} => {
 					trace!("Collecting metadata for found paths");
+					while !found_paths.is_empty() {
+						let chunk_size = found_paths.len().min(METADATA_CHUNK_SIZE);
+
+						// A tight budget can keep us waiting for a long while, so we wait for it
+						// while listening for interruptions
+						if let Some(kind) = (
+							io_throttle
+								.stat_files(chunk_size_to_u32(chunk_size))
+								.map(|()| None),
+							interrupter.into_future().map(Some),
+						)
+							.race()
+							.await
+						{
+							*scan_time += start_time.elapsed();
+							return Ok(match kind {
+								InterruptionKind::Pause => ExecStatus::Paused,
+								InterruptionKind::Cancel => ExecStatus::Canceled,
+							});
+						}
+
+						paths_and_metadatas.extend(
+							collect_metadata(
+								found_paths.drain(..chunk_size),
+								root,
+								follow_symlinks,
+								errors,
+							)
+							.await,
+						);
+
+						check_interruption!(interrupter, start_time, scan_time);
+					}
+
 					*stage = WalkerStage::CheckingIndexerRules {
-						paths_and_metadatas: collect_metadata(found_paths, errors).await,
+						paths_and_metadatas: mem::take(paths_and_metadatas),
 					};
 					trace!("Finished collecting metadata!");
 
@@ -322,6 +427,7 @@ where
 						entry_iso_file_path,
 						iso_file_path_factory,
 						db_proxy,
+						*unchanged_since_last_scan,
 						errors,
 					)
 					.await;
@@ -347,14 +453,18 @@ where
 					non_indexed_paths,
 				} => {
 					trace!("Segregating creates and updates");
-					let (to_create, to_update, total_size) =
+					let (to_create, mut to_update, unchanged_dirs, mut changed_dirs) =
 						segregate_creates_and_updates(walking_entries, db_proxy).await?;
+
+					// This directory was walked, so it's now safe to record it as it is now
+					to_update.extend(pending_update.take());
+
 					trace!(
 						total_to_create = to_create.len(),
 						total_to_update = to_update.len(),
 						total_to_remove = to_remove_entries.len(),
 						total_non_indexed_paths = non_indexed_paths.len(),
-						total_size,
+						total_unchanged_dirs = unchanged_dirs.len(),
 						"Finished segregating creates and updates!;"
 					);
 
@@ -363,7 +473,12 @@ where
 						indexer_ruler,
 						iso_file_path_factory,
 						db_proxy,
+						io_throttle,
 						maybe_to_keep_walking.as_mut(),
+						is_paranoid,
+						follow_symlinks,
+						&unchanged_dirs,
+						&mut changed_dirs,
 						errors,
 					);
 
@@ -373,7 +488,6 @@ where
 						mem::take(to_remove_entries),
 						mem::take(non_indexed_paths),
 						mem::take(accepted_ancestors),
-						total_size,
 						keep_walking_tasks,
 					);
 				}
@@ -392,7 +506,6 @@ where
 				accepted_ancestors,
 				errors: mem::take(errors),
 				directory_iso_file_path: mem::take(entry_iso_file_path),
-				total_size,
 				keep_walking_tasks,
 				scan_time: *scan_time,
 			}
@@ -410,6 +523,7 @@ enum WalkerStage {
 	},
 	CollectingMetadata {
 		found_paths: Vec<PathBuf>,
+		paths_and_metadatas: HashMap<PathBuf, InnerMetadata>,
 	},
 	CheckingIndexerRules {
 		paths_and_metadatas: HashMap<PathBuf, InnerMetadata>,
@@ -444,6 +558,9 @@ where
 		indexer_ruler: IndexerRuler,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
+		io_throttle: IoThrottle,
+		is_paranoid: bool,
+		follow_symlinks: bool,
 	) -> Result<Self, indexer::Error> {
 		let entry = entry.into();
 		Ok(Self {
@@ -453,9 +570,12 @@ where
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
+			io_throttle,
 			stage: WalkerStage::Start,
 			entry,
 			is_shallow: false,
+			is_paranoid,
+			follow_symlinks,
 			errors: Vec::new(),
 			scan_time: Duration::ZERO,
 		})
@@ -473,6 +593,8 @@ where
 		indexer_ruler: IndexerRuler,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
+		io_throttle: IoThrottle,
+		follow_symlinks: bool,
 	) -> Result<Self, indexer::Error> {
 		let entry = entry.into();
 		Ok(Self {
@@ -482,9 +604,12 @@ where
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
+			io_throttle,
 			stage: WalkerStage::Start,
 			entry,
 			is_shallow: true,
+			is_paranoid: false,
+			follow_symlinks,
 			errors: Vec::new(),
 			scan_time: Duration::ZERO,
 		})
@@ -499,9 +624,17 @@ where
 async fn segregate_creates_and_updates(
 	walking_entries: &mut Vec<WalkingEntry>,
 	db_proxy: &impl WalkerDBProxy,
-) -> Result<(Vec<WalkedEntry>, Vec<WalkedEntry>, u64), Error> {
+) -> Result<
+	(
+		Vec<WalkedEntry>,
+		Vec<WalkedEntry>,
+		HashSet<IsolatedFilePathData<'static>>,
+		HashMap<IsolatedFilePathData<'static>, WalkedEntry>,
+	),
+	Error,
+> {
 	if walking_entries.is_empty() {
-		Ok((vec![], vec![], 0))
+		Ok((vec![], vec![], HashSet::new(), HashMap::new()))
 	} else {
 		let iso_paths_already_in_db = db_proxy
 			.fetch_file_paths(
@@ -519,53 +652,66 @@ async fn segregate_creates_and_updates(
 			.collect::<HashMap<_, _>>();
 
 		Ok(walking_entries.drain(..).fold(
-			(Vec::new(), Vec::new(), 0),
-			|(mut to_create, mut to_update, mut total_size), entry| {
-				let WalkingEntry {
-					iso_file_path,
-					metadata,
-				} = &entry;
-
-				total_size += metadata.size_in_bytes;
-
-				if let Some(file_path) = iso_paths_already_in_db.get(iso_file_path) {
+			(Vec::new(), Vec::new(), HashSet::new(), HashMap::new()),
+			|(mut to_create, mut to_update, mut unchanged_dirs, mut changed_dirs), mut entry| {
+				if let Some(file_path) = iso_paths_already_in_db.get(&entry.iso_file_path) {
 					if let (Some(inode), Some(date_modified)) =
 						(&file_path.inode, &file_path.date_modified)
 					{
-						if (
-									inode_from_db(&inode[0..8]) != metadata.inode
-									// Datetimes stored in DB loses a bit of precision,
-									// so we need to check against a delta
-									// instead of using != operator
-									|| (
-										DateTime::<FixedOffset>::from(metadata.modified_at) - *date_modified
-											> ChronoDuration::milliseconds(1)
-										)
-									|| file_path.hidden.is_none()
-									|| metadata.hidden != file_path.hidden.unwrap_or_default()
-								)
-								// We ignore the size of directories because it is not reliable, we need to
-								// calculate it ourselves later
-								&& !(
-									iso_file_path.to_parts().is_dir
-									&& metadata.size_in_bytes
-										!= file_path
-											.size_in_bytes_bytes
-											.as_ref()
-											.map(|size_in_bytes_bytes| {
-												u64::from_be_bytes([
-													size_in_bytes_bytes[0],
-													size_in_bytes_bytes[1],
-													size_in_bytes_bytes[2],
-													size_in_bytes_bytes[3],
-													size_in_bytes_bytes[4],
-													size_in_bytes_bytes[5],
-													size_in_bytes_bytes[6],
-													size_in_bytes_bytes[7],
-												])
-											})
-											.unwrap_or_default()
-								) {
+						let is_dir = entry.iso_file_path.to_parts().is_dir;
+
+						let db_inode = inode_from_db(&inode[0..8]);
+
+						let is_unchanged = db_inode == entry.metadata.inode
+							// Datetimes stored in DB loses a bit of precision,
+							// so we need to check against a delta
+							// instead of using != operator
+							&& DateTime::<FixedOffset>::from(entry.metadata.modified_at)
+								- *date_modified <= ChronoDuration::milliseconds(1);
+
+						let has_other_changes = file_path.symlink_target != entry.symlink_target
+							|| file_path.hidden.is_none()
+							|| entry.metadata.hidden != file_path.hidden.unwrap_or_default();
+
+						if is_dir {
+							// We ignore the size of directories because it is not reliable,
+							// we calculate it ourselves later, so we keep the one in DB
+							entry.metadata.size_in_bytes = file_path
+								.size_in_bytes_bytes
+								.as_deref()
+								.map(size_in_bytes_from_db)
+								.unwrap_or_default();
+
+							if is_unchanged {
+								unchanged_dirs.insert(entry.iso_file_path.clone());
+							} else {
+								// Only written by the walker of this directory, once it's done
+								changed_dirs.insert(
+									entry.iso_file_path.clone(),
+									WalkedEntry::from((
+										&file_path.pub_id,
+										file_path.object_id,
+										WalkingEntry {
+											iso_file_path: entry.iso_file_path.clone(),
+											metadata: entry.metadata,
+											symlink_target: entry.symlink_target.clone(),
+										},
+									)),
+								);
+
+								// Keeping what the database has until then
+								entry.metadata.inode = db_inode;
+								entry.metadata.modified_at = (*date_modified).into();
+							}
+
+							if has_other_changes {
+								to_update.push(WalkedEntry::from((
+									&file_path.pub_id,
+									file_path.object_id,
+									entry,
+								)));
+							}
+						} else if !is_unchanged || has_other_changes {
 							to_update.push(WalkedEntry::from((
 								&file_path.pub_id,
 								file_path.object_id,
@@ -577,7 +723,7 @@ async fn segregate_creates_and_updates(
 					to_create.push(WalkedEntry::from(entry));
 				}
 
-				(to_create, to_update, total_size)
+				(to_create, to_update, unchanged_dirs, changed_dirs)
 			},
 		))
 	}
@@ -588,7 +734,12 @@ fn keep_walking<DBProxy, IsoPathFactory>(
 	indexer_ruler: &IndexerRuler,
 	iso_file_path_factory: &IsoPathFactory,
 	db_proxy: &DBProxy,
+	io_throttle: &IoThrottle,
 	maybe_to_keep_walking: Option<&mut Vec<ToWalkEntry>>,
+	is_paranoid: bool,
+	follow_symlinks: bool,
+	unchanged_dirs: &HashSet<IsolatedFilePathData<'static>>,
+	changed_dirs: &mut HashMap<IsolatedFilePathData<'static>, WalkedEntry>,
 	errors: &mut Vec<NonCriticalError>,
 ) -> Vec<Walker<DBProxy, IsoPathFactory>>
 where
@@ -606,7 +757,17 @@ where
 						indexer_ruler.clone(),
 						iso_file_path_factory.clone(),
 						db_proxy.clone(),
+						io_throttle.clone(),
+						is_paranoid,
+						follow_symlinks,
 					)
+					.map(|mut walker| {
+						walker.entry.unchanged_since_last_scan =
+							!is_paranoid && unchanged_dirs.contains(&walker.entry_iso_file_path);
+						walker.entry.pending_update =
+							changed_dirs.remove(&walker.entry_iso_file_path);
+						walker
+					})
 					.map_err(|e| {
 						indexer::NonCriticalIndexerError::DispatchKeepWalking(e.to_string())
 					})
@@ -617,22 +778,29 @@ where
 		.unwrap_or_default()
 }
 
+#[allow(clippy::cast_possible_truncation)]
+const fn chunk_size_to_u32(chunk_size: usize) -> u32 {
+	// SAFETY: chunks are never bigger than `METADATA_CHUNK_SIZE`
+	chunk_size as u32
+}
+
 async fn collect_metadata(
-	found_paths: &mut Vec<PathBuf>,
+	found_paths: impl Iterator<Item = PathBuf> + Send,
+	root: &Path,
+	follow_symlinks: bool,
 	errors: &mut Vec<NonCriticalError>,
 ) -> HashMap<PathBuf, InnerMetadata> {
 	found_paths
-		.drain(..)
 		.map(|current_path| async move {
-			fs::metadata(&current_path)
+			fetch_metadata(&current_path, root, follow_symlinks)
 				.await
 				.map_err(|e| {
 					indexer::NonCriticalIndexerError::Metadata(
 						FileIOError::from((&current_path, e)).to_string(),
 					)
 				})
-				.and_then(|metadata| {
-					InnerMetadata::new(&current_path, &metadata)
+				.and_then(|(metadata, symlink_target)| {
+					InnerMetadata::new(&current_path, &metadata, symlink_target)
 						.map(|metadata| (current_path, metadata))
 				})
 		})
@@ -644,11 +812,75 @@ async fn collect_metadata(
 		.collect()
 }
 
+/// Symbolic links are followed when asked to, unless they're broken or lead back to one of the
+/// directories holding them, as we would walk in circles. Otherwise they're recorded as aliases,
+/// returning their target alongside their own metadata.
+///
+/// # Errors
+///
+/// Errors when the path itself can't be stat'ed, or when a link we don't follow can't be read
+pub async fn fetch_metadata(
+	path: &Path,
+	root: &Path,
+	follow_symlinks: bool,
+) -> Result<(Metadata, Option<PathBuf>), io::Error> {
+	let metadata = fs::symlink_metadata(path).await?;
+
+	if !metadata.is_symlink() {
+		return Ok((metadata, None));
+	}
+
+	if follow_symlinks {
+		if let Ok(target_metadata) = fs::metadata(path).await {
+			if !target_metadata.is_dir() || !leads_to_an_ancestor(path, root).await {
+				return Ok((target_metadata, None));
+			}
+
+			trace!(
+				path = %path.display(),
+				"Symbolic link leads to one of its ancestors, recording it instead of following;",
+			);
+		}
+	}
+
+	fs::read_link(path)
+		.await
+		.map(|target| (metadata, Some(target)))
+}
+
+async fn leads_to_an_ancestor(path: &Path, root: &Path) -> bool {
+	// Can't tell when canonicalizing fails, so it's safer to not follow
+	let (Ok(target), Ok(canonical_root)) =
+		(fs::canonicalize(path).await, fs::canonicalize(root).await)
+	else {
+		return true;
+	};
+
+	// Directories holding the root would walk into it all over again
+	if canonical_root.starts_with(&target) {
+		return true;
+	}
+
+	for ancestor in path
+		.ancestors()
+		.skip(1)
+		.take_while(|ancestor| *ancestor != root)
+	{
+		match fs::canonicalize(ancestor).await {
+			Ok(ancestor) if ancestor != target => {}
+			_ => return true,
+		}
+	}
+
+	false
+}
+
 async fn gather_file_paths_to_remove(
 	accepted_paths: &mut HashMap<PathBuf, InnerMetadata>,
 	entry_iso_file_path: &IsolatedFilePathData<'_>,
 	iso_file_path_factory: &impl IsoFilePathFactory,
 	db_proxy: &impl WalkerDBProxy,
+	unchanged_since_last_scan: bool,
 	errors: &mut Vec<NonCriticalError>,
 ) -> (Vec<WalkingEntry>, Vec<file_path_pub_and_cas_ids::Data>) {
 	let mut existing_inodes = HashSet::new();
@@ -660,15 +892,14 @@ async fn gather_file_paths_to_remove(
 				.build(&path, metadata.is_dir())
 				.map(|iso_file_path| {
 					let params = file_path::WhereParam::from(&iso_file_path);
-					existing_inodes.insert(inode_to_db(metadata.inode));
-
-					(
-						WalkingEntry {
-							iso_file_path,
-							metadata: FilePathMetadata::from(metadata),
-						},
-						params,
-					)
+
+					// Other paths with the inode of a hard linked file are its other links, not
+					// the same file moved here
+					if metadata.hard_links_count <= 1 {
+						existing_inodes.insert(inode_to_db(metadata.inode));
+					}
+
+					(WalkingEntry::from((iso_file_path, metadata)), params)
 				})
 				.map_err(|e| {
 					errors
@@ -678,6 +909,11 @@ async fn gather_file_paths_to_remove(
 		})
 		.unzip::<_, _, Vec<_>, Vec<_>>();
 
+	// Only inner directories were collected, so every file would be seen as removed
+	if unchanged_since_last_scan {
+		return (walking, vec![]);
+	}
+
 	// We continue the function even if we fail to fetch `file_path`s to remove,
 	// the DB will have old `file_path`s but at least this is better than
 	// don't adding the newly indexed paths
@@ -694,9 +930,10 @@ async fn gather_file_paths_to_remove(
 mod tests {
 	use super::*;
 
+	use sd_core_file_path_helper::{FilePathMetadata, IsolatedFilePathDataParts};
 	use sd_core_indexer_rules::{IndexerRule, RulePerKind};
 	use sd_core_prisma_helpers::FilePathPubId;
-	use sd_task_system::{TaskOutput, TaskStatus, TaskSystem};
+	use sd_task_system::{SerializableTask, TaskOutput, TaskStatus, TaskSystem};
 
 	use chrono::Utc;
 	use futures::stream::FuturesUnordered;
@@ -741,6 +978,102 @@ mod tests {
 		) -> Result<Vec<file_path_pub_and_cas_ids::Data>, indexer::NonCriticalIndexerError> {
 			Ok(vec![])
 		}
+
+		async fn fetch_inner_directories(
+			&self,
+			_: &IsolatedFilePathData<'_>,
+		) -> Result<Vec<file_path_walker::Data>, indexer::Error> {
+			Ok(vec![])
+		}
+	}
+
+	/// Pretends the received directories were indexed in a previous scan, the changed ones with an
+	/// older modification time than the current one
+	#[derive(Debug, Clone)]
+	struct IndexedDirsDBProxy {
+		indexed_dirs: Arc<Vec<file_path_walker::Data>>,
+	}
+
+	impl IndexedDirsDBProxy {
+		async fn new(root_path: &Path, unchanged_dirs: &[&str], changed_dirs: &[&str]) -> Self {
+			let mut indexed_dirs = vec![];
+
+			for (dir, changed) in unchanged_dirs
+				.iter()
+				.map(|dir| (dir, false))
+				.chain(changed_dirs.iter().map(|dir| (dir, true)))
+			{
+				let path = root_path.join(dir);
+				let FilePathMetadata {
+					inode,
+					modified_at,
+					hidden,
+					..
+				} = FilePathMetadata::from_path(&path, &fs::metadata(&path).await.unwrap()).unwrap();
+				let iso_file_path = IsolatedFilePathData::new(0, root_path, &path, true).unwrap();
+				let IsolatedFilePathDataParts {
+					materialized_path,
+					name,
+					extension,
+					..
+				} = iso_file_path.to_parts();
+
+				indexed_dirs.push(file_path_walker::Data {
+					pub_id: FilePathPubId::new().to_db(),
+					location_id: Some(0),
+					object_id: None,
+					materialized_path: Some(materialized_path.to_string()),
+					is_dir: Some(true),
+					name: Some(name.to_string()),
+					extension: Some(extension.to_string()),
+					date_modified: Some(if changed {
+						(modified_at - ChronoDuration::hours(1)).into()
+					} else {
+						modified_at.into()
+					}),
+					inode: Some(inode_to_db(inode)),
+					size_in_bytes_bytes: None,
+					hidden: Some(hidden),
+					symlink_target: None,
+				});
+			}
+
+			Self {
+				indexed_dirs: Arc::new(indexed_dirs),
+			}
+		}
+	}
+
+	impl WalkerDBProxy for IndexedDirsDBProxy {
+		async fn fetch_file_paths(
+			&self,
+			_: Vec<file_path::WhereParam>,
+		) -> Result<Vec<file_path_walker::Data>, indexer::Error> {
+			Ok(self.indexed_dirs.as_ref().clone())
+		}
+
+		async fn fetch_file_paths_to_remove(
+			&self,
+			_: &IsolatedFilePathData<'_>,
+			_: HashSet<Vec<u8>>,
+			_: Vec<file_path::WhereParam>,
+		) -> Result<Vec<file_path_pub_and_cas_ids::Data>, indexer::NonCriticalIndexerError> {
+			Ok(vec![])
+		}
+
+		async fn fetch_inner_directories(
+			&self,
+			parent_iso_file_path: &IsolatedFilePathData<'_>,
+		) -> Result<Vec<file_path_walker::Data>, indexer::Error> {
+			let materialized_path = parent_iso_file_path.materialized_path_for_children();
+
+			Ok(self
+				.indexed_dirs
+				.iter()
+				.filter(|file_path| file_path.materialized_path == materialized_path)
+				.cloned()
+				.collect())
+		}
 	}
 
 	fn new_indexer_rule(
@@ -876,6 +1209,25 @@ mod tests {
 		indexer_ruler: IndexerRuler,
 		expected: HashSet<WalkedEntry>,
 	) {
+		run_test_with_db_proxy(
+			root_path,
+			indexer_ruler,
+			DummyDBProxy,
+			false,
+			false,
+			expected,
+		)
+		.await;
+	}
+
+	async fn run_test_with_db_proxy<DBProxy: WalkerDBProxy>(
+		root_path: &Path,
+		indexer_ruler: IndexerRuler,
+		db_proxy: DBProxy,
+		is_paranoid: bool,
+		follow_symlinks: bool,
+		expected: HashSet<WalkedEntry>,
+	) -> HashSet<WalkedEntry> {
 		let system = TaskSystem::new();
 
 		let handle = system
@@ -887,7 +1239,10 @@ mod tests {
 					DummyIsoPathFactory {
 						root_path: Arc::new(root_path.to_path_buf()),
 					},
-					DummyDBProxy,
+					db_proxy,
+					IoThrottle::default(),
+					is_paranoid,
+					follow_symlinks,
 				)
 				.unwrap(),
 			)
@@ -916,7 +1271,7 @@ mod tests {
 				keep_walking_tasks,
 				..
 			} = *output
-				.downcast::<Output<DummyDBProxy, DummyIsoPathFactory>>()
+				.downcast::<Output<DBProxy, DummyIsoPathFactory>>()
 				.unwrap();
 
 			assert!(errors.is_empty(), "errors: {errors:#?}");
@@ -943,6 +1298,8 @@ mod tests {
 			expected.difference(&actual_set),
 			actual_set.difference(&expected)
 		);
+
+		actual_set
 	}
 
 	#[tokio::test]
@@ -965,29 +1322,29 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1015,10 +1372,10 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1061,24 +1418,24 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1117,21 +1474,21 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1173,4 +1530,271 @@ mod tests {
 		)
 		.await;
 	}
+
+	#[tokio::test]
+	#[traced_test]
+	async fn skip_unchanged_directories() {
+		let root = prepare_location().await;
+		let root_path = root.path();
+
+		let metadata = FilePathMetadata {
+			inode: 0,
+			size_in_bytes: 0,
+			created_at: Utc::now(),
+			modified_at: Utc::now(),
+			hidden: false,
+		};
+
+		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
+		let pub_id = FilePathPubId::new();
+		let maybe_object_id = None;
+
+		// Something changed inside `node_project`, which doesn't change the modification time of
+		// `inner`, so `inner` must still be checked to reach it
+		let db_proxy =
+			IndexedDirsDBProxy::new(root_path, &["photos", "inner"], &["inner/node_project"]).await;
+
+		#[rustfmt::skip]
+		let expected_changes = || [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata, symlink_target: None },
+		]
+		.into_iter();
+
+		// Nothing changed directly inside `photos`, so its files aren't even read
+		run_test_with_db_proxy(
+			root_path,
+			IndexerRuler::default(),
+			db_proxy.clone(),
+			false,
+			false,
+			expected_changes().collect(),
+		)
+		.await;
+
+		// Paranoid walks don't trust the directories modification times
+		#[rustfmt::skip]
+		let expected = expected_changes()
+			.chain([
+				WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata, symlink_target: None },
+				WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata, symlink_target: None },
+				WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata, symlink_target: None },
+				WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata, symlink_target: None },
+			])
+			.collect();
+
+		run_test_with_db_proxy(
+			root_path,
+			IndexerRuler::default(),
+			db_proxy,
+			true,
+			false,
+			expected,
+		)
+		.await;
+	}
+
+	async fn walk_once<DBProxy: WalkerDBProxy>(
+		system: &TaskSystem<Error>,
+		walker: Walker<DBProxy, DummyIsoPathFactory>,
+	) -> Output<DBProxy, DummyIsoPathFactory> {
+		let TaskStatus::Done((_task_id, TaskOutput::Out(output))) =
+			system.dispatch(walker).await.unwrap().await.unwrap()
+		else {
+			panic!("unexpected task output")
+		};
+
+		*output
+			.downcast::<Output<DBProxy, DummyIsoPathFactory>>()
+			.unwrap()
+	}
+
+	#[tokio::test]
+	#[traced_test]
+	async fn update_changed_directories_only_once_walked() {
+		let root = prepare_location().await;
+		let root_path = root.path();
+
+		let photos_path = root_path.join("photos");
+		let photos = IsolatedFilePathData::new(0, root_path, &photos_path, true).unwrap();
+		let FilePathMetadata {
+			inode, modified_at, ..
+		} = FilePathMetadata::from_path(&photos_path, &fs::metadata(&photos_path).await.unwrap())
+			.unwrap();
+
+		let db_proxy = IndexedDirsDBProxy::new(root_path, &[], &["photos"]).await;
+		let iso_file_path_factory = DummyIsoPathFactory {
+			root_path: Arc::new(root_path.to_path_buf()),
+		};
+
+		let system = TaskSystem::new();
+
+		let Output {
+			to_update,
+			keep_walking_tasks,
+			..
+		} = walk_once(
+			&system,
+			Walker::new_deep(
+				root_path.to_path_buf(),
+				Arc::new(root_path.to_path_buf()),
+				IndexerRuler::default(),
+				iso_file_path_factory.clone(),
+				db_proxy.clone(),
+				IoThrottle::default(),
+				false,
+				false,
+			)
+			.unwrap(),
+		)
+		.await;
+
+		// Recording `photos` as it is now before walking it would make the next scan skip it
+		assert!(to_update.iter().all(|entry| entry.iso_file_path != photos));
+
+		let photos_walker = keep_walking_tasks
+			.into_iter()
+			.find(|walker| walker.entry_iso_file_path == photos)
+			.unwrap();
+
+		// The job is interrupted before walking `photos`, so its walker is saved and restored later
+		let photos_walker = Walker::deserialize(
+			&photos_walker.serialize().await.unwrap(),
+			(
+				IndexerRuler::default(),
+				db_proxy,
+				iso_file_path_factory,
+				IoThrottle::default(),
+			),
+		)
+		.await
+		.unwrap();
+
+		let Output { to_update, .. } = walk_once(&system, photos_walker).await;
+
+		let photos_update = to_update
+			.iter()
+			.find(|entry| entry.iso_file_path == photos)
+			.unwrap();
+
+		assert_eq!(photos_update.metadata.inode, inode);
+		assert_eq!(photos_update.metadata.modified_at, modified_at);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	#[traced_test]
+	async fn record_or_follow_symlinks() {
+		// root
+		// |__ docs
+		// |   |__ readme.md
+		// |__ readme_link -> docs/readme.md
+		// |__ docs_link -> docs
+		// |__ loop -> .
+		let root = tempdir().unwrap();
+		let root_path = root.path();
+
+		fs::create_dir(root_path.join("docs")).await.unwrap();
+		fs::File::create(root_path.join("docs/readme.md"))
+			.await
+			.unwrap();
+		fs::symlink("docs/readme.md", root_path.join("readme_link"))
+			.await
+			.unwrap();
+		fs::symlink("docs", root_path.join("docs_link"))
+			.await
+			.unwrap();
+		fs::symlink(".", root_path.join("loop")).await.unwrap();
+
+		let metadata = FilePathMetadata {
+			inode: 0,
+			size_in_bytes: 0,
+			created_at: Utc::now(),
+			modified_at: Utc::now(),
+			hidden: false,
+		};
+
+		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
+		let pub_id = FilePathPubId::new();
+		let maybe_object_id = None;
+
+		let symlink_target = |actual: &HashSet<WalkedEntry>, path| {
+			actual
+				.iter()
+				.find(|entry| entry.iso_file_path == f(root_path.join(path), false))
+				.and_then(|entry| entry.symlink_target.clone())
+		};
+
+		// Links are recorded as aliases to their targets
+		#[rustfmt::skip]
+		let expected = [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme.md"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("readme_link"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs_link"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("loop"), false), metadata, symlink_target: None },
+		]
+		.into_iter()
+		.collect();
+
+		let actual = run_test_with_db_proxy(
+			root_path,
+			IndexerRuler::default(),
+			DummyDBProxy,
+			false,
+			false,
+			expected,
+		)
+		.await;
+
+		assert_eq!(
+			symlink_target(&actual, "readme_link").as_deref(),
+			Some("docs/readme.md")
+		);
+		assert_eq!(
+			symlink_target(&actual, "docs_link").as_deref(),
+			Some("docs")
+		);
+		assert_eq!(symlink_target(&actual, "loop").as_deref(), Some("."));
+
+		// Links are walked through, except the one which would walk forever
+		#[rustfmt::skip]
+		let expected = [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme.md"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("readme_link"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs_link"), true), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs_link/readme.md"), false), metadata, symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("loop"), false), metadata, symlink_target: None },
+		]
+		.into_iter()
+		.collect();
+
+		let actual = run_test_with_db_proxy(
+			root_path,
+			IndexerRuler::default(),
+			DummyDBProxy,
+			false,
+			true,
+			expected,
+		)
+		.await;
+
+		assert_eq!(symlink_target(&actual, "readme_link"), None);
+		assert_eq!(symlink_target(&actual, "loop").as_deref(), Some("."));
+	}
 }
@@ -27,8 +27,6 @@ pub(super) async fn apply_indexer_rules(
 ) -> HashMap<PathBuf, (InnerMetadata, HashMap<RuleKind, Vec<bool>>)> {
 	paths_and_metadatas
 		.drain()
-		// TODO: Hard ignoring symlinks for now, but this should be configurable
-		.filter(|(_, metadata)| !metadata.is_symlink)
 		.map(|(current_path, metadata)| async {
 			indexer_ruler
 				.apply_all(&current_path, &metadata)
@@ -90,6 +88,7 @@ pub(super) async fn process_rules_results(
 								WalkingEntry {
 									iso_file_path: ancestor_iso_file_path,
 									metadata,
+									symlink_target: None,
 								}
 								.into()
 							})
@@ -176,6 +175,11 @@ fn reject_path(
 ) -> bool {
 	IndexerRuler::rejected_by_reject_glob(acceptance_per_rule_kind)
 		|| IndexerRuler::rejected_by_git_ignore(acceptance_per_rule_kind)
+		|| IndexerRuler::rejected_by_spacedrive_ignore(acceptance_per_rule_kind)
+		|| IndexerRuler::rejected_by_size(acceptance_per_rule_kind)
+		|| IndexerRuler::rejected_by_extended_attributes(acceptance_per_rule_kind)
+		|| IndexerRuler::rejected_by_modified_date(acceptance_per_rule_kind)
+		|| IndexerRuler::rejected_by_object_kind(acceptance_per_rule_kind)
 		|| (metadata.is_dir()
 			&& process_and_maybe_reject_by_directory_rules(
 				current_path,
@@ -219,6 +223,9 @@ fn process_and_maybe_reject_by_directory_rules(
 		to_keep_walking.push(ToWalkEntry {
 			path: current_path.to_path_buf(),
 			parent_dir_accepted_by_its_children: *accept_by_children_dir,
+			// Only known after comparing with the database, when segregating creates and updates
+			unchanged_since_last_scan: false,
+			pending_update: None,
 		});
 	}
 
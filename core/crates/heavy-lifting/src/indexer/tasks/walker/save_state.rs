@@ -1,4 +1,4 @@
-use crate::{Error, NonCriticalError};
+use crate::{Error, IoThrottle, NonCriticalError};
 
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_indexer_rules::{IndexerRuler, RuleKind};
@@ -24,6 +24,8 @@ use super::{
 pub(super) struct WalkDirSaveState {
 	id: TaskId,
 	is_shallow: bool,
+	is_paranoid: bool,
+	follow_symlinks: bool,
 
 	entry: ToWalkEntry,
 	root: Arc<PathBuf>,
@@ -40,6 +42,8 @@ pub(super) enum WalkerStageSaveState {
 	Start,
 	CollectingMetadata {
 		found_paths: Vec<PathBuf>,
+		#[serde(default)]
+		paths_and_metadatas: HashMap<PathBuf, InnerMetadata>,
 	},
 	CheckingIndexerRules {
 		paths_and_metadatas: HashMap<PathBuf, InnerMetadata>,
@@ -68,9 +72,13 @@ impl From<WalkerStage> for WalkerStageSaveState {
 		match stage {
 			// We can't store the current state of `ReadDirStream` so we start again from the beginning
 			WalkerStage::Start | WalkerStage::Walking { .. } => Self::Start,
-			WalkerStage::CollectingMetadata { found_paths } => {
-				Self::CollectingMetadata { found_paths }
-			}
+			WalkerStage::CollectingMetadata {
+				found_paths,
+				paths_and_metadatas,
+			} => Self::CollectingMetadata {
+				found_paths,
+				paths_and_metadatas,
+			},
 			WalkerStage::CheckingIndexerRules {
 				paths_and_metadatas,
 			} => Self::CheckingIndexerRules {
@@ -113,9 +121,13 @@ impl From<WalkerStageSaveState> for WalkerStage {
 	fn from(value: WalkerStageSaveState) -> Self {
 		match value {
 			WalkerStageSaveState::Start => Self::Start,
-			WalkerStageSaveState::CollectingMetadata { found_paths } => {
-				Self::CollectingMetadata { found_paths }
-			}
+			WalkerStageSaveState::CollectingMetadata {
+				found_paths,
+				paths_and_metadatas,
+			} => Self::CollectingMetadata {
+				found_paths,
+				paths_and_metadatas,
+			},
 			WalkerStageSaveState::CheckingIndexerRules {
 				paths_and_metadatas,
 			} => Self::CheckingIndexerRules {
@@ -161,7 +173,7 @@ where
 {
 	type SerializeError = rmp_serde::encode::Error;
 	type DeserializeError = rmp_serde::decode::Error;
-	type DeserializeCtx = (IndexerRuler, DBProxy, IsoPathFactory);
+	type DeserializeCtx = (IndexerRuler, DBProxy, IsoPathFactory, IoThrottle);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -173,11 +185,15 @@ where
 			errors,
 			scan_time,
 			is_shallow,
+			is_paranoid,
+			follow_symlinks,
 			..
 		} = self;
 		rmp_serde::to_vec_named(&WalkDirSaveState {
 			id,
 			is_shallow,
+			is_paranoid,
+			follow_symlinks,
 			entry,
 			root,
 			entry_iso_file_path,
@@ -189,7 +205,7 @@ where
 
 	async fn deserialize(
 		data: &[u8],
-		(indexer_ruler, db_proxy, iso_file_path_factory): Self::DeserializeCtx,
+		(indexer_ruler, db_proxy, iso_file_path_factory, io_throttle): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice(data).map(
 			|WalkDirSaveState {
@@ -201,6 +217,8 @@ where
 			     errors,
 			     scan_time,
 			     is_shallow,
+			     is_paranoid,
+			     follow_symlinks,
 			 }| Self {
 				id,
 				entry,
@@ -209,10 +227,13 @@ where
 				indexer_ruler,
 				iso_file_path_factory,
 				db_proxy,
+				io_throttle,
 				stage: stage.into(),
 				errors,
 				scan_time,
 				is_shallow,
+				is_paranoid,
+				follow_symlinks,
 			},
 		)
 	}
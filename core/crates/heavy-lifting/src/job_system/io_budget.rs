@@ -0,0 +1,197 @@
+//! I/O budgets keep jobs from saturating the disk, limiting how many files are stat'ed and how
+//! many bytes are read to generate cas ids per second.
+//!
+//! An [`IoThrottle`] is shared by a job context and every task dispatched by its job, so a budget
+//! changed while the job is running is picked up by all of them right away.
+
+use std::{
+	sync::{Arc, Mutex, PoisonError},
+	time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::time::{sleep, Instant};
+
+/// Waits are done in slices of at most this long, so budget changes are noticed quickly
+const MAX_WAIT_SLICE: Duration = Duration::from_millis(250);
+
+/// Limits are per second and unlimited when `None`, a zero limit is the same as one
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct IoBudget {
+	pub max_files_per_second: Option<u32>,
+	pub max_hashed_bytes_per_second: Option<u32>,
+}
+
+impl IoBudget {
+	#[must_use]
+	pub const fn is_unlimited(&self) -> bool {
+		self.max_files_per_second.is_none() && self.max_hashed_bytes_per_second.is_none()
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IoThrottle(Arc<Mutex<Buckets>>);
+
+#[derive(Debug, Default)]
+struct Buckets {
+	budget: IoBudget,
+	files: TokenBucket,
+	hashed_bytes: TokenBucket,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+	available: f64,
+	last_refill: Instant,
+}
+
+impl Default for TokenBucket {
+	fn default() -> Self {
+		Self {
+			// Clamped to a full bucket on the first refill
+			available: f64::INFINITY,
+			last_refill: Instant::now(),
+		}
+	}
+}
+
+impl TokenBucket {
+	/// Takes `amount` tokens, or tells how long to wait until they're available. A full bucket
+	/// holds one second worth of tokens, and bigger amounts are taken from a full bucket, leaving
+	/// it in debt.
+	fn try_take(&mut self, amount: f64, rate: u32) -> Result<(), Duration> {
+		let rate = f64::from(rate.max(1));
+		let now = Instant::now();
+
+		self.available = now
+			.duration_since(self.last_refill)
+			.as_secs_f64()
+			.mul_add(rate, self.available)
+			.min(rate);
+		self.last_refill = now;
+
+		let needed = amount.min(rate);
+		if self.available >= needed {
+			self.available -= amount;
+			Ok(())
+		} else {
+			Err(Duration::from_secs_f64((needed - self.available) / rate))
+		}
+	}
+}
+
+impl IoThrottle {
+	#[must_use]
+	pub fn new(budget: IoBudget) -> Self {
+		Self(Arc::new(Mutex::new(Buckets {
+			budget,
+			..Default::default()
+		})))
+	}
+
+	#[must_use]
+	pub fn budget(&self) -> IoBudget {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).budget
+	}
+
+	pub fn set_budget(&self, budget: IoBudget) {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).budget = budget;
+	}
+
+	/// Waits until `count` files can be stat'ed
+	pub async fn stat_files(&self, count: u32) {
+		self.take(f64::from(count), |buckets| {
+			(buckets.budget.max_files_per_second, &mut buckets.files)
+		})
+		.await;
+	}
+
+	/// Waits until `bytes` can be read to generate a cas id
+	pub async fn hash_bytes(&self, bytes: u64) {
+		#[allow(clippy::cast_precision_loss)]
+		// SAFETY: a lost fraction of a byte doesn't matter for throttling
+		let bytes = bytes as f64;
+
+		self.take(bytes, |buckets| {
+			(
+				buckets.budget.max_hashed_bytes_per_second,
+				&mut buckets.hashed_bytes,
+			)
+		})
+		.await;
+	}
+
+	async fn take(
+		&self,
+		amount: f64,
+		pick: impl Fn(&mut Buckets) -> (Option<u32>, &mut TokenBucket) + Send,
+	) {
+		loop {
+			let wait = {
+				let mut buckets = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+				let (Some(rate), bucket) = pick(&mut buckets) else {
+					return;
+				};
+
+				match bucket.try_take(amount, rate) {
+					Ok(()) => return,
+					Err(wait) => wait,
+				}
+			};
+
+			sleep(wait.min(MAX_WAIT_SLICE)).await;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_wait(result: Result<(), Duration>, min_millis: u64, max_millis: u64) {
+		let wait = result.expect_err("should have to wait");
+
+		assert!(
+			Duration::from_millis(min_millis) <= wait && wait <= Duration::from_millis(max_millis),
+			"waiting {wait:?}"
+		);
+	}
+
+	#[test]
+	fn full_bucket_holds_one_second() {
+		let mut bucket = TokenBucket::default();
+
+		assert_eq!(bucket.try_take(10.0, 10), Ok(()));
+		assert_wait(bucket.try_take(1.0, 10), 90, 100);
+	}
+
+	#[test]
+	fn refills_with_elapsed_time() {
+		let mut bucket = TokenBucket {
+			available: 0.0,
+			last_refill: Instant::now() - Duration::from_millis(500),
+		};
+
+		assert_eq!(bucket.try_take(5.0, 10), Ok(()));
+		assert_wait(bucket.try_take(1.0, 10), 90, 100);
+	}
+
+	#[test]
+	fn amounts_above_rate_leave_debt() {
+		let mut bucket = TokenBucket::default();
+
+		assert_eq!(bucket.try_take(25.0, 10), Ok(()));
+		// 15 tokens in debt plus the one we want, at 10 per second
+		assert_wait(bucket.try_take(1.0, 10), 1_500, 1_600);
+	}
+
+	#[test]
+	fn zero_rate_is_one_per_second() {
+		let mut bucket = TokenBucket::default();
+
+		assert_eq!(bucket.try_take(1.0, 0), Ok(()));
+		assert_wait(bucket.try_take(1.0, 0), 900, 1_000);
+	}
+}
@@ -40,6 +40,7 @@ use uuid::Uuid;
 
 use super::{
 	error::DispatcherError,
+	io_budget::{IoBudget, IoThrottle},
 	report::{
 		Report, ReportBuilder, ReportInputMetadata, ReportMetadata, ReportOutputMetadata, Status,
 	},
@@ -106,7 +107,7 @@ pub trait OuterContext: Send + Sync + Clone + 'static {
 }
 
 pub trait JobContext<OuterCtx: OuterContext>: OuterContext {
-	fn new(report: Report, ctx: OuterCtx) -> Self;
+	fn new(report: Report, io_throttle: IoThrottle, ctx: OuterCtx) -> Self;
 	fn progress(
 		&self,
 		updates: impl IntoIterator<Item = ProgressUpdate> + Send,
@@ -120,6 +121,8 @@ pub trait JobContext<OuterCtx: OuterContext>: OuterContext {
 	fn report(&self) -> impl Future<Output = impl Deref<Target = Report> + Send> + Send;
 	fn report_mut(&self) -> impl Future<Output = impl DerefMut<Target = Report> + Send> + Send;
 	fn get_outer_ctx(&self) -> OuterCtx;
+	/// Shared with the job's tasks, which must wait on it before touching the disk
+	fn io_throttle(&self) -> &IoThrottle;
 }
 
 pub trait Job: Send + Sync + Hash + 'static {
@@ -165,6 +168,7 @@ where
 			job: self,
 			run_time: Duration::ZERO,
 			report: ReportBuilder::new(id, J::NAME).build(),
+			io_budget: IoBudget::default(),
 			next_jobs: VecDeque::new(),
 			_ctx: PhantomData,
 		})
@@ -311,6 +315,7 @@ where
 	id: JobId,
 	job: J,
 	report_builder: ReportBuilder,
+	io_budget: IoBudget,
 	next_jobs: VecDeque<Box<dyn DynJob<OuterCtx, JobCtx>>>,
 	_ctx: PhantomData<OuterCtx>,
 }
@@ -327,6 +332,7 @@ where
 			job: self.job,
 			run_time: Duration::ZERO,
 			report: self.report_builder.build(),
+			io_budget: self.io_budget,
 			next_jobs: self.next_jobs,
 			_ctx: self._ctx,
 		})
@@ -338,6 +344,7 @@ where
 			id,
 			job,
 			report_builder: ReportBuilder::new(id, J::NAME),
+			io_budget: IoBudget::default(),
 			next_jobs: VecDeque::new(),
 			_ctx: PhantomData,
 		}
@@ -361,6 +368,13 @@ where
 		self
 	}
 
+	/// Jobs enqueued next inherit the budget this job has when it finishes
+	#[must_use]
+	pub const fn with_io_budget(mut self, io_budget: IoBudget) -> Self {
+		self.io_budget = io_budget;
+		self
+	}
+
 	#[must_use]
 	pub fn enqueue_next(mut self, next: impl Job + SerializableJob<OuterCtx>) -> Self {
 		let next_job_order = self.next_jobs.len() + 1;
@@ -388,6 +402,7 @@ where
 	pub(super) job: J,
 	pub(super) report: Report,
 	pub(super) run_time: Duration,
+	pub(super) io_budget: IoBudget,
 	pub(super) next_jobs: VecDeque<Box<dyn DynJob<OuterCtx, JobCtx>>>,
 	pub(super) _ctx: PhantomData<OuterCtx>,
 }
@@ -678,6 +693,10 @@ pub trait DynJob<OuterCtx: OuterContext, JobCtx: JobContext<OuterCtx>>:
 
 	fn report_mut(&mut self) -> &mut Report;
 
+	fn io_budget(&self) -> IoBudget;
+
+	fn set_io_budget(&mut self, io_budget: IoBudget);
+
 	fn set_next_jobs(&mut self, next_jobs: VecDeque<Box<dyn DynJob<OuterCtx, JobCtx>>>);
 
 	fn next_jobs(&self) -> &VecDeque<Box<dyn DynJob<OuterCtx, JobCtx>>>;
@@ -726,6 +745,14 @@ where
 		&mut self.report
 	}
 
+	fn io_budget(&self) -> IoBudget {
+		self.io_budget
+	}
+
+	fn set_io_budget(&mut self, io_budget: IoBudget) {
+		self.io_budget = io_budget;
+	}
+
 	fn set_next_jobs(&mut self, next_jobs: VecDeque<Box<dyn DynJob<OuterCtx, JobCtx>>>) {
 		self.next_jobs = next_jobs;
 	}
@@ -747,7 +774,7 @@ where
 	) -> JobHandle<OuterCtx, JobCtx> {
 		let (commands_tx, commands_rx) = chan::bounded(8);
 
-		let ctx = JobCtx::new(self.report, ctx);
+		let ctx = JobCtx::new(self.report, IoThrottle::new(self.io_budget), ctx);
 
 		trace!("Dispatching job");
 
@@ -802,7 +829,7 @@ where
 	) -> JobHandle<OuterCtx, JobCtx> {
 		let (commands_tx, commands_rx) = chan::bounded(8);
 
-		let ctx = JobCtx::new(self.report, ctx);
+		let ctx = JobCtx::new(self.report, IoThrottle::new(self.io_budget), ctx);
 
 		trace!("Resuming job");
 
@@ -20,6 +20,7 @@ use tracing::{debug, error, info, instrument, trace, warn};
 use uuid::Uuid;
 
 mod error;
+pub mod io_budget;
 pub mod job;
 pub mod report;
 mod runner;
@@ -27,6 +28,7 @@ mod store;
 pub mod utils;
 
 pub use error::{DispatcherError, JobErrorOrDispatcherError, JobSystemError};
+use io_budget::IoBudget;
 use job::{IntoJob, Job, JobName, JobOutput, OuterContext};
 use report::Report;
 use runner::{run, JobSystemRunner, RunnerMessage};
@@ -281,6 +283,36 @@ impl<OuterCtx: OuterContext, JobCtx: JobContext<OuterCtx>> JobSystem<OuterCtx, J
 	pub async fn cancel(&self, job_id: JobId) -> Result<(), JobSystemError> {
 		self.send_command(job_id, Command::Cancel).await
 	}
+
+	/// Change the I/O budget of a running job, or of the running job before a queued one, as
+	/// they share the same budget. Jobs from other [`OuterContext`]s are taken as not found.
+	///
+	/// # Panics
+	///
+	/// Panics only happen if internal channels are unexpectedly closed
+	pub async fn set_io_budget(
+		&self,
+		ctx: OuterCtx,
+		job_id: JobId,
+		io_budget: IoBudget,
+	) -> Result<(), JobSystemError> {
+		let ctx_id = ctx.id();
+
+		let (ack_tx, ack_rx) = oneshot::channel();
+		self.msgs_tx
+			.send(RunnerMessage::SetIoBudget {
+				ctx_id,
+				job_id,
+				io_budget,
+				ack_tx,
+			})
+			.await
+			.expect("runner msgs channel unexpectedly closed on set io budget request");
+
+		ack_rx
+			.await
+			.expect("ack channel closed before receiving set io budget response")
+	}
 }
 
 /// SAFETY: Due to usage of refcell we lost `Sync` impl, but we only use it to have a shutdown method
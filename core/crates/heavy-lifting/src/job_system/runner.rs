@@ -30,6 +30,7 @@ use tracing::{debug, error, info, instrument, trace, warn};
 use uuid::Uuid;
 
 use super::{
+	io_budget::IoBudget,
 	job::{DynJob, JobHandle, JobName, JobOutput, OuterContext, ReturnStatus},
 	report::{self, ReportOutputMetadata},
 	store::{StoredJob, StoredJobEntry},
@@ -60,6 +61,12 @@ pub(super) enum RunnerMessage<OuterCtx: OuterContext, JobCtx: JobContext<OuterCt
 		command: Command,
 		ack_tx: oneshot::Sender<Result<(), JobSystemError>>,
 	},
+	SetIoBudget {
+		ctx_id: Uuid,
+		job_id: JobId,
+		io_budget: IoBudget,
+		ack_tx: oneshot::Sender<Result<(), JobSystemError>>,
+	},
 	GetActiveReports {
 		ack_tx: oneshot::Sender<HashMap<JobId, report::Report>>,
 	},
@@ -230,6 +237,35 @@ impl<OuterCtx: OuterContext, JobCtx: JobContext<OuterCtx>> JobSystemRunner<Outer
 		}
 	}
 
+	/// Jobs waiting to run share the budget of the running job before them, so it's the one
+	/// changed when they're asked for
+	fn set_io_budget(
+		&self,
+		ctx_id: Uuid,
+		job_id: JobId,
+		io_budget: IoBudget,
+	) -> Result<(), JobSystemError> {
+		let handle = self
+			.handles
+			.get(&job_id)
+			.or_else(|| {
+				self.handles.values().find(|handle| {
+					handle
+						.next_jobs
+						.iter()
+						.any(|next_job| next_job.id() == job_id)
+				})
+			})
+			.filter(|handle| handle.ctx.id() == ctx_id)
+			.ok_or(JobSystemError::NotFound(job_id))?;
+
+		trace!(%job_id, running_job_id = %handle.id, ?io_budget, "Changing job I/O budget;");
+
+		handle.ctx.io_throttle().set_budget(io_budget);
+
+		Ok(())
+	}
+
 	fn is_empty(&self) -> bool {
 		self.handles.is_empty()
 			&& self.worktables.job_hashes.is_empty()
@@ -330,6 +366,7 @@ impl<OuterCtx: OuterContext, JobCtx: JobContext<OuterCtx>> JobSystemRunner<Outer
 									id: job_id,
 									run_time: handle.start_time.elapsed(),
 									name,
+									io_budget: handle.ctx.io_throttle().budget(),
 									serialized_job,
 								},
 								next_jobs: serialize_next_jobs_to_shutdown(
@@ -498,6 +535,7 @@ async fn serialize_next_jobs_to_shutdown<OuterCtx: OuterContext, JobCtx: JobCont
 		.map(|next_job| async move {
 			let next_id = next_job.id();
 			let next_name = next_job.job_name();
+			let next_io_budget = next_job.io_budget();
 			next_job
 				.serialize()
 				.await
@@ -506,6 +544,7 @@ async fn serialize_next_jobs_to_shutdown<OuterCtx: OuterContext, JobCtx: JobCont
 						id: next_id,
 						run_time: Duration::ZERO,
 						name: next_name,
+						io_budget: next_io_budget,
 						serialized_job,
 					})
 				})
@@ -545,7 +584,7 @@ async fn try_dispatch_next_job<OuterCtx: OuterContext, JobCtx: JobContext<OuterC
 	handles: &mut HashMap<JobId, JobHandle<OuterCtx, JobCtx>>,
 	job_return_status_tx: chan::Sender<(JobId, Result<ReturnStatus, Error>)>,
 ) -> Result<(), JobSystemError> {
-	if let Some(next) = handle.next_jobs.pop_front() {
+	if let Some(mut next) = handle.next_jobs.pop_front() {
 		let next_id = next.id();
 		let next_hash = next.hash();
 		let next_name = next.job_name();
@@ -558,6 +597,8 @@ async fn try_dispatch_next_job<OuterCtx: OuterContext, JobCtx: JobContext<OuterC
 			running_jobs_by_job_id.insert(next_id, (next_name, location_id));
 			running_jobs_set.insert((next_name, location_id));
 
+			next.set_io_budget(handle.ctx.io_throttle().budget());
+
 			let mut next_handle = next.dispatch(
 				base_dispatcher,
 				handle.ctx.get_outer_ctx(),
@@ -668,6 +709,17 @@ pub(super) async fn run<OuterCtx: OuterContext, JobCtx: JobContext<OuterCtx>>(
 				ack_tx,
 			}) => runner.process_command(id, command, ack_tx).await,
 
+			StreamMessage::RunnerMessage(RunnerMessage::SetIoBudget {
+				ctx_id,
+				job_id,
+				io_budget,
+				ack_tx,
+			}) => {
+				ack_tx
+					.send(runner.set_io_budget(ctx_id, job_id, io_budget))
+					.expect("ack channel closed before sending set io budget response");
+			}
+
 			StreamMessage::RunnerMessage(RunnerMessage::Shutdown) => {
 				runner.on_shutdown_mode = true;
 				// Consuming all pending return status messages
@@ -15,6 +15,7 @@ use futures_concurrency::future::TryJoin;
 use serde::{Deserialize, Serialize};
 
 use super::{
+	io_budget::IoBudget,
 	job::{DynJob, Job, JobHolder, JobName, OuterContext},
 	report::{Report, ReportError},
 	JobId, JobSystemError,
@@ -51,6 +52,8 @@ pub struct StoredJob {
 	pub(super) id: JobId,
 	pub(super) name: JobName,
 	pub(super) run_time: Duration,
+	#[serde(default)]
+	pub(super) io_budget: IoBudget,
 	pub(super) serialized_job: Vec<u8>,
 }
 
@@ -177,6 +180,7 @@ macro_rules! match_deserialize_job {
 			id,
 			name,
 			run_time,
+			io_budget,
 			serialized_job,
 		} = $stored_job;
 
@@ -196,6 +200,7 @@ macro_rules! match_deserialize_job {
 									job,
 									run_time,
 									report: $report,
+									io_budget,
 									next_jobs: VecDeque::new(),
 									_ctx: PhantomData,
 								}),
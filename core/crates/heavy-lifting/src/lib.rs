@@ -44,6 +44,7 @@ pub mod utils;
 use media_processor::ThumbKey;
 
 pub use job_system::{
+	io_budget::{IoBudget, IoThrottle},
 	job::{
 		IntoJob, JobContext, JobEnqueuer, JobName, JobOutput, JobOutputData, OuterContext,
 		ProgressUpdate,
@@ -4,7 +4,10 @@ use sd_core_prisma_helpers::ObjectPubId;
 use sd_core_sync::{DevicePubId, SyncManager};
 
 use sd_file_ext::extensions::{Extension, ImageExtension, ALL_IMAGE_EXTENSIONS};
-use sd_media_metadata::ExifMetadata;
+use sd_media_metadata::{
+	exif::{MediaLocation, Orientation},
+	ExifMetadata,
+};
 use sd_prisma::{
 	prisma::{device, exif_data, object, PrismaClient},
 	prisma_sync,
@@ -55,6 +58,11 @@ fn to_query(
 ) -> (Vec<(&'static str, rmpv::Value)>, exif_data::Create) {
 	let device_pub_id = device_pub_id.to_db();
 
+	let (latitude, longitude) = location
+		.as_ref()
+		.map(MediaLocation::coordinates)
+		.unzip();
+
 	let (sync_params, db_params) = chain_optional_iter(
 		[(
 			sync_entry!(
@@ -66,6 +74,18 @@ fn to_query(
 			exif_data::device::connect(device::pub_id::equals(device_pub_id)),
 		)],
 		[
+			option_sync_db_entry!(camera_data.device_make.clone(), exif_data::camera_make),
+			option_sync_db_entry!(camera_data.device_model.clone(), exif_data::camera_model),
+			option_sync_db_entry!(camera_data.lens_model.clone(), exif_data::lens_model),
+			option_sync_db_entry!(camera_data.iso, exif_data::iso),
+			option_sync_db_entry!(camera_data.aperture, exif_data::aperture),
+			option_sync_db_entry!(camera_data.exposure_time, exif_data::exposure_time),
+			option_sync_db_entry!(
+				orientation_to_db(&camera_data.orientation),
+				exif_data::orientation
+			),
+			option_sync_db_entry!(latitude, exif_data::latitude),
+			option_sync_db_entry!(longitude, exif_data::longitude),
 			option_sync_db_entry!(
 				serde_json::to_vec(&camera_data).ok(),
 				exif_data::camera_data
@@ -98,6 +118,15 @@ fn to_query(
 	)
 }
 
+/// How an [`Orientation`] is stored in the `orientation` column, the same as its serialized form
+/// inside `camera_data`
+#[must_use]
+pub fn orientation_to_db(orientation: &Orientation) -> Option<String> {
+	serde_json::to_value(orientation)
+		.ok()
+		.and_then(|value| value.as_str().map(ToString::to_string))
+}
+
 pub async fn extract(
 	path: impl AsRef<Path> + Send,
 ) -> Result<Option<ExifMetadata>, media_processor::NonCriticalMediaProcessorError> {
@@ -174,17 +174,20 @@ async fn create_ffmpeg_data(
 	object_id: i32,
 	db: &PrismaClient,
 ) -> Result<ffmpeg_data::id::Type, QueryError> {
+	let bit_rate = i64::from(bit_rate_high) << 32 | i64::from(bit_rate_low);
+	let maybe_duration = maybe_duration.map(|(duration_high, duration_low)| {
+		i64::from(duration_high) << 32 | i64::from(duration_low)
+	});
+
 	db.ffmpeg_data()
 		.create(
 			formats.join(","),
-			ffmpeg_data_field_to_db(i64::from(bit_rate_high) << 32 | i64::from(bit_rate_low)),
+			ffmpeg_data_field_to_db(bit_rate),
 			object::id::equals(object_id),
 			vec![
-				ffmpeg_data::duration::set(maybe_duration.map(|(duration_high, duration_low)| {
-					ffmpeg_data_field_to_db(
-						i64::from(duration_high) << 32 | i64::from(duration_low),
-					)
-				})),
+				ffmpeg_data::bit_rate_int::set(Some(bit_rate)),
+				ffmpeg_data::duration::set(maybe_duration.map(ffmpeg_data_field_to_db)),
+				ffmpeg_data::duration_int::set(maybe_duration),
 				ffmpeg_data::start_time::set(maybe_start_time.map(
 					|(start_time_high, start_time_low)| {
 						ffmpeg_data_field_to_db(
@@ -508,6 +511,7 @@ async fn create_ffmpeg_audio_props(
 							sample_format,
 							bit_per_sample,
 							channel_layout,
+							channels,
 						},
 					)| ffmpeg_media_audio_props::CreateUnchecked {
 						delay,
@@ -518,6 +522,7 @@ async fn create_ffmpeg_audio_props(
 							ffmpeg_media_audio_props::sample_format::set(sample_format),
 							ffmpeg_media_audio_props::bit_per_sample::set(bit_per_sample),
 							ffmpeg_media_audio_props::channel_layout::set(channel_layout),
+							ffmpeg_media_audio_props::channels::set(channels),
 						],
 					},
 				)
@@ -731,6 +736,7 @@ fn codec_from_prisma_data(
 						sample_format,
 						bit_per_sample,
 						channel_layout,
+						channels,
 						..
 					}),
 					None,
@@ -741,6 +747,7 @@ fn codec_from_prisma_data(
 					sample_format,
 					bit_per_sample,
 					channel_layout,
+					channels,
 				})),
 				(
 					None,
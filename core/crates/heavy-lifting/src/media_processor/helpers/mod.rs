@@ -1,6 +1,10 @@
 pub mod exif_media_data;
 pub mod ffmpeg_media_data;
+pub mod perceptual_hash;
+pub mod text_content;
 pub mod thumbnailer;
+#[cfg(feature = "ffmpeg")]
+pub mod video_preview;
 
 #[must_use]
 fn from_slice_option_to_option<T: serde::Serialize + serde::de::DeserializeOwned>(
@@ -0,0 +1,96 @@
+use sd_prisma::prisma::{file_path, object, PrismaClient};
+
+use image::{imageops::FilterType, DynamicImage};
+use prisma_client_rust::QueryError;
+
+/// The hash is computed over a grayscale version of the image shrunk to this many columns,
+/// comparing each pixel with its right neighbour, so we get 8 bits per row and 64 bits in total
+const HASH_WIDTH: u32 = 9;
+const HASH_HEIGHT: u32 = 8;
+
+/// Computes the difference hash (dHash) of an image, the thumbnailer calls it on the image it
+/// already decoded, so we don't need to read the file again.
+///
+/// Resized or re-encoded copies of the same picture end up with hashes only a few bits apart,
+/// see [`distance`].
+#[must_use]
+pub fn dhash(img: &DynamicImage) -> u64 {
+	let small = img
+		.resize_exact(HASH_WIDTH, HASH_HEIGHT, FilterType::Triangle)
+		.into_luma8();
+
+	(0..HASH_HEIGHT)
+		.flat_map(|y| (0..HASH_WIDTH - 1).map(move |x| (x, y)))
+		.fold(0, |hash, (x, y)| {
+			(hash << 1) | u64::from(small.get_pixel(x, y).0[0] > small.get_pixel(x + 1, y).0[0])
+		})
+}
+
+/// How many bits differ between two hashes, 0 to 64
+#[must_use]
+pub const fn distance(a: u64, b: u64) -> u32 {
+	(a ^ b).count_ones()
+}
+
+/// Hashes are stored in a `BIGINT` column, so we keep their bits as they are in an i64
+#[must_use]
+pub const fn to_db(hash: u64) -> i64 {
+	i64::from_be_bytes(hash.to_be_bytes())
+}
+
+#[must_use]
+pub const fn from_db(hash: i64) -> u64 {
+	u64::from_be_bytes(hash.to_be_bytes())
+}
+
+/// Stores the hashes computed while generating thumbnails on the objects of their file paths
+pub async fn save(
+	hashes: impl IntoIterator<Item = (file_path::id::Type, u64)> + Send,
+	db: &PrismaClient,
+) -> Result<(), QueryError> {
+	db._batch(
+		hashes
+			.into_iter()
+			.map(|(file_path_id, hash)| {
+				db.object().update_many(
+					vec![object::file_paths::some(vec![file_path::id::equals(
+						file_path_id,
+					)])],
+					vec![object::perceptual_hash::set(Some(to_db(hash)))],
+				)
+			})
+			.collect::<Vec<_>>(),
+	)
+	.await
+	.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::{GrayImage, Luma};
+
+	fn gradient(width: u32, height: u32) -> DynamicImage {
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+			Luma([(255.0 * f64::from(x * y) / f64::from(width * height)) as u8])
+		}))
+	}
+
+	#[test]
+	fn resized_copies_hash_alike() {
+		let original = dhash(&gradient(1024, 768));
+		let resized = dhash(&gradient(300, 225));
+
+		assert!(distance(original, resized) <= 2);
+		assert!(distance(original, !original) == 64);
+	}
+
+	#[test]
+	fn db_round_trip() {
+		for hash in [0, 1, u64::MAX, 0x8000_0000_0000_0000] {
+			assert_eq!(from_db(to_db(hash)), hash);
+		}
+	}
+}
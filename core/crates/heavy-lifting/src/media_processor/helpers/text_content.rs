@@ -0,0 +1,144 @@
+use crate::media_processor::{self, media_data_extractor};
+
+use sd_file_ext::{
+	extensions::{Extension, ALL_CODE_EXTENSIONS, ALL_CONFIG_EXTENSIONS, ALL_TEXT_EXTENSIONS},
+	text::is_text,
+};
+use sd_prisma::prisma::{object, PrismaClient};
+
+use std::{path::Path, sync::LazyLock};
+
+use futures_concurrency::future::TryJoin;
+use prisma_client_rust::{raw, PrismaValue, QueryError};
+use serde::Deserialize;
+use tokio::{fs::File, io::AsyncReadExt};
+
+/// Name of the `SQLite` FTS5 virtual table holding extracted text, its `rowid` is the object id.
+/// Files that aren't actually text get an empty row, which never matches a search.
+pub const TEXT_CONTENT_TABLE: &str = "object_text_content";
+
+/// We only index the beginning of really big text files, as the index would explode in size otherwise
+pub const MAX_INDEXED_TEXT_SIZE: u64 = 1024 * 1024; // 1 MiB
+
+pub static AVAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
+	ALL_TEXT_EXTENSIONS
+		.iter()
+		.copied()
+		.map(Extension::Text)
+		.chain(ALL_CODE_EXTENSIONS.iter().copied().map(Extension::Code))
+		.chain(ALL_CONFIG_EXTENSIONS.iter().copied().map(Extension::Config))
+		.collect()
+});
+
+/// Reads the first [`MAX_INDEXED_TEXT_SIZE`] bytes of a file and decodes them as text.
+///
+/// Returns `Ok(None)` for files that aren't actually text, like compiled `AppleScript` files
+/// that share their extension with plain text sources.
+pub async fn extract(
+	path: impl AsRef<Path> + Send,
+) -> Result<Option<String>, media_processor::NonCriticalMediaProcessorError> {
+	let path = path.as_ref();
+
+	let map_err = |e: std::io::Error| -> media_processor::NonCriticalMediaProcessorError {
+		media_data_extractor::NonCriticalMediaDataExtractorError::FailedToExtractTextContent(
+			path.to_path_buf(),
+			e.to_string(),
+		)
+		.into()
+	};
+
+	let file = File::open(path).await.map_err(map_err)?;
+
+	let mut buf = Vec::new();
+	let read = file
+		.take(MAX_INDEXED_TEXT_SIZE)
+		.read_to_end(&mut buf)
+		.await
+		.map_err(map_err)?;
+
+	Ok(decode(&buf, read as u64 == MAX_INDEXED_TEXT_SIZE))
+}
+
+fn decode(buf: &[u8], partial: bool) -> Option<String> {
+	let text = match is_text(buf, partial)? {
+		"utf-8" => String::from_utf8_lossy(buf.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(buf))
+			.into_owned(),
+		"utf-16le" => decode_utf16(buf, u16::from_le_bytes),
+		"utf-16be" => decode_utf16(buf, u16::from_be_bytes),
+		"iso-8859-1" => buf.iter().copied().map(char::from).collect(),
+		// UTF-32 text files are rare enough in the wild that we don't bother with them
+		_ => return None,
+	};
+
+	(!text.trim().is_empty()).then_some(text)
+}
+
+fn decode_utf16(buf: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+	char::decode_utf16(
+		buf.chunks_exact(2)
+			.map(|pair| from_bytes([pair[0], pair[1]]))
+			.skip_while(|&unit| unit == 0xFEFF),
+	)
+	.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+	.collect()
+}
+
+pub async fn fetch_objects_with_text_content(
+	object_ids: Vec<object::id::Type>,
+	db: &PrismaClient,
+) -> Result<Vec<object::id::Type>, QueryError> {
+	#[derive(Deserialize)]
+	struct ObjectId {
+		object_id: object::id::Type,
+	}
+
+	if object_ids.is_empty() {
+		return Ok(vec![]);
+	}
+
+	// FIXME: Had to use format! macro because PCR doesn't support IN with Vec for SQLite
+	// We only have integers coming from the database here, so this is sql injection safe
+	db._query_raw::<ObjectId>(raw!(&format!(
+		"SELECT rowid AS object_id FROM {TEXT_CONTENT_TABLE} WHERE rowid IN ({})",
+		object_ids
+			.iter()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>()
+			.join(",")
+	)))
+	.exec()
+	.await
+	.map(|rows| rows.into_iter().map(|ObjectId { object_id }| object_id).collect())
+}
+
+/// Saves the extracted texts, returning how many weren't empty markers
+pub async fn save(
+	text_contents: impl IntoIterator<Item = (String, object::id::Type)> + Send,
+	db: &PrismaClient,
+) -> Result<u64, QueryError> {
+	text_contents
+		.into_iter()
+		.map(|(content, object_id)| async move {
+			let is_marker = content.is_empty();
+
+			db._execute_raw(raw!(
+				&format!(
+					"INSERT OR REPLACE INTO {TEXT_CONTENT_TABLE} (rowid, content) VALUES ({{}}, {{}})"
+				),
+				PrismaValue::Int(object_id),
+				PrismaValue::String(content)
+			))
+			.exec()
+			.await
+			.map(|_| !is_marker)
+		})
+		.collect::<Vec<_>>()
+		.try_join()
+		.await
+		.map(|inserted_vec| {
+			inserted_vec
+				.into_iter()
+				.filter(|&inserted| inserted)
+				.count() as u64
+		})
+}
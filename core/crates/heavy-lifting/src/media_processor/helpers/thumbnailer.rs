@@ -10,7 +10,9 @@ use sd_media_metadata::exif::Orientation;
 use sd_utils::error::FileIOError;
 
 #[cfg(feature = "ffmpeg")]
-use sd_file_ext::extensions::{VideoExtension, ALL_VIDEO_EXTENSIONS};
+use sd_file_ext::extensions::{
+	AudioExtension, VideoExtension, ALL_AUDIO_EXTENSIONS, ALL_VIDEO_EXTENSIONS,
+};
 
 use std::{
 	ops::Deref,
@@ -35,6 +37,8 @@ use tracing::{error, instrument, trace};
 use uuid::Uuid;
 use webp::{Encoder, WebPConfig};
 
+use super::perceptual_hash;
+
 // Files names constants
 pub const THUMBNAIL_CACHE_DIR_NAME: &str = "thumbnails";
 pub const WEBP_EXTENSION: &str = "webp";
@@ -65,6 +69,16 @@ pub static THUMBNAILABLE_VIDEO_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::
 		.collect()
 });
 
+#[cfg(feature = "ffmpeg")]
+pub static THUMBNAILABLE_AUDIO_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
+	ALL_AUDIO_EXTENSIONS
+		.iter()
+		.copied()
+		.filter(|&ext| can_generate_thumbnail_for_audio(ext))
+		.map(Extension::Audio)
+		.collect()
+});
+
 pub static THUMBNAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
 	ALL_IMAGE_EXTENSIONS
 		.iter()
@@ -87,6 +101,7 @@ pub static ALL_THUMBNAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::ne
 		.iter()
 		.cloned()
 		.chain(THUMBNAILABLE_VIDEO_EXTENSIONS.iter().cloned())
+		.chain(THUMBNAILABLE_AUDIO_EXTENSIONS.iter().cloned())
 		.collect();
 
 	#[cfg(not(feature = "ffmpeg"))]
@@ -176,15 +191,24 @@ pub struct GenerateThumbnailArgs<'cas_id> {
 	pub extension: String,
 	pub cas_id: CasId<'cas_id>,
 	pub path: PathBuf,
+	/// Images whose object has no perceptual hash yet are hashed even if their thumbnail exists
+	#[serde(default)]
+	pub needs_perceptual_hash: bool,
 }
 
 impl<'cas_id> GenerateThumbnailArgs<'cas_id> {
 	#[must_use]
-	pub const fn new(extension: String, cas_id: CasId<'cas_id>, path: PathBuf) -> Self {
+	pub const fn new(
+		extension: String,
+		cas_id: CasId<'cas_id>,
+		path: PathBuf,
+		needs_perceptual_hash: bool,
+	) -> Self {
 		Self {
 			extension,
 			cas_id,
 			path,
+			needs_perceptual_hash,
 		}
 	}
 }
@@ -213,6 +237,14 @@ pub const fn can_generate_thumbnail_for_video(video_extension: VideoExtension) -
 	!matches!(video_extension, Mpg | Swf | M2v | Hevc | M2ts | Mts | Ts)
 }
 
+#[cfg(feature = "ffmpeg")]
+#[must_use]
+pub const fn can_generate_thumbnail_for_audio(audio_extension: AudioExtension) -> bool {
+	use AudioExtension::Mid;
+	// MIDI files only have instructions to play notes, there is no sound to decode
+	!matches!(audio_extension, Mid)
+}
+
 #[must_use]
 pub const fn can_generate_thumbnail_for_image(image_extension: ImageExtension) -> bool {
 	use ImageExtension::{
@@ -238,6 +270,8 @@ pub enum GenerationStatus {
 	Skipped,
 }
 
+/// Also returns the perceptual hash of images, computed from the image decoded for the thumbnail.
+/// When the thumbnail already exists, images are only decoded for their hash if it was requested.
 #[instrument(skip(thumbnails_directory, cas_id, should_regenerate, kind))]
 pub async fn generate_thumbnail(
 	thumbnails_directory: &Path,
@@ -245,12 +279,13 @@ pub async fn generate_thumbnail(
 		extension,
 		cas_id,
 		path,
+		needs_perceptual_hash,
 	}: &GenerateThumbnailArgs<'_>,
 	kind: &ThumbnailKind,
 	should_regenerate: bool,
 ) -> (
 	Duration,
-	Result<(ThumbKey, GenerationStatus), thumbnailer::NonCriticalThumbnailerError>,
+	Result<(ThumbKey, GenerationStatus, Option<u64>), thumbnailer::NonCriticalThumbnailerError>,
 ) {
 	trace!("Generating thumbnail");
 	let start = Instant::now();
@@ -274,20 +309,38 @@ pub async fn generate_thumbnail(
 	// Otherwise we good, thumbnail doesn't exist so we can generate it
 	} else if !should_regenerate {
 		trace!("Skipping thumbnail generation because it already exists");
+
+		let perceptual_hash = match ImageExtension::from_str(extension) {
+			Ok(extension)
+				if *needs_perceptual_hash && can_generate_thumbnail_for_image(extension) =>
+			{
+				trace!("Computing perceptual hash of image with an existing thumbnail");
+				match compute_image_perceptual_hash(path).await {
+					Ok(hash) => Some(hash),
+					Err(e) => return (start.elapsed(), Err(e)),
+				}
+			}
+			_ => None,
+		};
+
 		return (
 			start.elapsed(),
 			Ok((
 				ThumbKey::new(cas_id.to_owned(), kind),
 				GenerationStatus::Skipped,
+				perceptual_hash,
 			)),
 		);
 	}
 
+	let mut perceptual_hash = None;
+
 	if let Ok(extension) = ImageExtension::from_str(extension) {
 		if can_generate_thumbnail_for_image(extension) {
 			trace!("Generating image thumbnail");
-			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
-				return (start.elapsed(), Err(e));
+			match generate_image_thumbnail(&path, &output_path).await {
+				Ok(hash) => perceptual_hash = Some(hash),
+				Err(e) => return (start.elapsed(), Err(e)),
 			}
 			trace!("Generated image thumbnail");
 		}
@@ -303,8 +356,10 @@ pub async fn generate_thumbnail(
 
 	#[cfg(feature = "ffmpeg")]
 	{
-		use crate::media_processor::helpers::thumbnailer::can_generate_thumbnail_for_video;
-		use sd_file_ext::extensions::VideoExtension;
+		use crate::media_processor::helpers::thumbnailer::{
+			can_generate_thumbnail_for_audio, can_generate_thumbnail_for_video,
+		};
+		use sd_file_ext::extensions::{AudioExtension, VideoExtension};
 
 		if let Ok(extension) = VideoExtension::from_str(extension) {
 			if can_generate_thumbnail_for_video(extension) {
@@ -314,6 +369,14 @@ pub async fn generate_thumbnail(
 				}
 				trace!("Generated video thumbnail");
 			}
+		} else if let Ok(extension) = AudioExtension::from_str(extension) {
+			if can_generate_thumbnail_for_audio(extension) {
+				trace!("Generating audio thumbnail");
+				if let Err(e) = generate_audio_thumbnail(&path, &output_path).await {
+					return (start.elapsed(), Err(e));
+				}
+				trace!("Generated audio thumbnail");
+			}
 		}
 	}
 
@@ -324,13 +387,15 @@ pub async fn generate_thumbnail(
 		Ok((
 			ThumbKey::new(cas_id.to_owned(), kind),
 			GenerationStatus::Generated,
+			perceptual_hash,
 		)),
 	)
 }
 
-fn inner_generate_image_thumbnail(
+/// Decodes the image, scaled down to the thumbnail size and with its orientation corrected
+fn format_image_for_thumbnail(
 	file_path: &PathBuf,
-) -> Result<Vec<u8>, thumbnailer::NonCriticalThumbnailerError> {
+) -> Result<DynamicImage, thumbnailer::NonCriticalThumbnailerError> {
 	let mut img = format_image(file_path).map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::FormatImage(file_path.clone(), e.to_string())
 	})?;
@@ -361,6 +426,17 @@ fn inner_generate_image_thumbnail(
 		}
 	}
 
+	Ok(img)
+}
+
+fn inner_generate_image_thumbnail(
+	file_path: &PathBuf,
+) -> Result<(Vec<u8>, u64), thumbnailer::NonCriticalThumbnailerError> {
+	let img = format_image_for_thumbnail(file_path)?;
+
+	// Hashing the corrected image, so a rotated export still matches its original
+	let perceptual_hash = perceptual_hash::dhash(&img);
+
 	// Create the WebP encoder for the above image
 	let encoder = Encoder::from_image(&img).map_err(|reason| {
 		thumbnailer::NonCriticalThumbnailerError::WebPEncoding(
@@ -379,7 +455,43 @@ fn inner_generate_image_thumbnail(
 	// Type `WebPMemory` is !Send, which makes the `Future` in this function `!Send`,
 	// this make us `deref` to have a `&[u8]` and then `to_owned` to make a `Vec<u8>`
 	// which implies on a unwanted clone...
-	Ok(thumb.deref().to_owned())
+	Ok((thumb.deref().to_owned(), perceptual_hash))
+}
+
+/// Hashes the image the same way we do when generating its thumbnail, for images which already
+/// have one
+#[instrument(skip_all, fields(input_path = %file_path.display()))]
+async fn compute_image_perceptual_hash(
+	file_path: &Path,
+) -> Result<u64, thumbnailer::NonCriticalThumbnailerError> {
+	let file_path = file_path.to_path_buf();
+
+	spawn_blocking({
+		let file_path = file_path.clone();
+
+		move || {
+			panic::catch_unwind(|| {
+				format_image_for_thumbnail(&file_path).map(|img| perceptual_hash::dhash(&img))
+			})
+			.unwrap_or_else(move |_| {
+				Err(
+					thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+						file_path,
+						"Internal panic on third party crate".to_string(),
+					),
+				)
+			})
+		}
+	})
+	.await
+	.unwrap_or_else(|e| {
+		Err(
+			thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+				file_path,
+				e.to_string(),
+			),
+		)
+	})
 }
 
 #[instrument(
@@ -392,7 +504,7 @@ fn inner_generate_image_thumbnail(
 async fn generate_image_thumbnail(
 	file_path: impl AsRef<Path> + Send,
 	output_path: impl AsRef<Path> + Send,
-) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
+) -> Result<u64, thumbnailer::NonCriticalThumbnailerError> {
 	let file_path = file_path.as_ref().to_path_buf();
 
 	let (tx, rx) = oneshot::channel();
@@ -420,7 +532,7 @@ async fn generate_image_thumbnail(
 		}
 	});
 
-	let webp = if let Ok(res) = rx.await {
+	let (webp, perceptual_hash) = if let Ok(res) = rx.await {
 		res?
 	} else {
 		error!("Failed to generate thumbnail");
@@ -473,7 +585,7 @@ async fn generate_image_thumbnail(
 	})?;
 
 	trace!("Wrote thumbnail to disk");
-	return Ok(());
+	return Ok(perceptual_hash);
 }
 
 #[instrument(
@@ -507,14 +619,43 @@ async fn generate_video_thumbnail(
 	})
 }
 
+#[instrument(
+	skip_all,
+	fields(
+		input_path = %file_path.as_ref().display(),
+		output_path = %output_path.as_ref().display()
+	)
+)]
+#[cfg(feature = "ffmpeg")]
+async fn generate_audio_thumbnail(
+	file_path: impl AsRef<Path> + Send,
+	output_path: impl AsRef<Path> + Send,
+) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
+	use sd_ffmpeg::to_audio_thumbnail;
+
+	let file_path = file_path.as_ref();
+
+	to_audio_thumbnail(file_path, output_path, 1024, TARGET_QUALITY)
+		.await
+		.map_err(|e| {
+			thumbnailer::NonCriticalThumbnailerError::AudioThumbnailGenerationFailed(
+				file_path.to_path_buf(),
+				e.to_string(),
+			)
+		})
+}
+
 /// WARNING!!!! DON'T USE THIS FUNCTION IN A LOOP!!!!!!!!!!!!! It will be pretty slow on purpose!
+///
+/// Returns the perceptual hash of images, even when their thumbnail already existed, for the
+/// caller to store on their object
 pub async fn generate_single_thumbnail(
 	thumbnails_directory: impl AsRef<Path> + Send,
 	extension: String,
 	cas_id: CasId<'static>,
 	path: impl AsRef<Path> + Send,
 	kind: ThumbnailKind,
-) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
+) -> Result<Option<u64>, thumbnailer::NonCriticalThumbnailerError> {
 	let mut last_single_thumb_generated_guard = LAST_SINGLE_THUMB_GENERATED_LOCK.lock().await;
 
 	let elapsed = Instant::now() - *last_single_thumb_generated_guard;
@@ -530,18 +671,19 @@ pub async fn generate_single_thumbnail(
 			extension,
 			cas_id,
 			path: path.as_ref().to_path_buf(),
+			needs_perceptual_hash: true,
 		},
 		&kind,
 		false,
 	)
 	.await;
 
-	let (_thumb_key, status) = res?;
+	let (_thumb_key, status, perceptual_hash) = res?;
 
 	if matches!(status, GenerationStatus::Generated) {
 		*last_single_thumb_generated_guard = Instant::now();
 		drop(last_single_thumb_generated_guard); // Clippy was weirdly complaining about not doing an "early" drop here
 	}
 
-	Ok(())
+	Ok(perceptual_hash)
 }
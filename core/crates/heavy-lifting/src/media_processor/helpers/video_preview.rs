@@ -0,0 +1,106 @@
+use crate::media_processor::{
+	helpers::thumbnailer::{
+		get_shard_hex, GenerateThumbnailArgs, GenerationStatus, EPHEMERAL_DIR, TARGET_QUALITY,
+		WEBP_EXTENSION,
+	},
+	thumbnailer::NonCriticalThumbnailerError,
+	ThumbnailKind,
+};
+
+use sd_core_prisma_helpers::CasId;
+
+use sd_ffmpeg::{to_video_previews, ThumbnailSize};
+
+use std::{
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use tokio::{
+	fs,
+	time::{timeout, Instant},
+};
+use tracing::{instrument, trace};
+
+/// How many frames a thumbstrip has, the explorer splits it by this same count when scrubbing.
+/// The animated preview is made of the same frames.
+pub const THUMBSTRIP_FRAMES_COUNT: u32 = 10;
+pub const THUMBSTRIP_SUFFIX: &str = "thumbstrip";
+const VIDEO_PREVIEW_FRAME_SIZE: ThumbnailSize = ThumbnailSize::Scale(256);
+
+pub const ANIMATED_PREVIEW_SUFFIX: &str = "preview";
+const ANIMATED_PREVIEW_FRAME_DURATION: Duration = Duration::from_millis(250);
+
+/// How much time we allow for the previews of a single video, as the previewer goes through
+/// videos one at a time, a single broken video would otherwise eat up the whole task timeout
+const VIDEO_PREVIEW_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Video previews live in the same shard directory as the video's thumbnail, named
+/// `<cas_id>_<suffix>.webp`, so `custom_uri` serves them just like thumbnails
+fn video_preview_path(
+	thumbnails_directory: &Path,
+	cas_id: &CasId<'_>,
+	kind: &ThumbnailKind,
+	suffix: &str,
+) -> PathBuf {
+	let mut output_path = match kind {
+		ThumbnailKind::Ephemeral => thumbnails_directory.join(EPHEMERAL_DIR),
+		ThumbnailKind::Indexed(library_id) => thumbnails_directory.join(library_id.to_string()),
+	};
+
+	output_path.push(get_shard_hex(cas_id));
+	output_path.push(format!("{}_{suffix}.{WEBP_EXTENSION}", cas_id.as_str()));
+
+	output_path
+}
+
+#[instrument(skip(thumbnails_directory, cas_id, should_regenerate, kind))]
+pub async fn generate_video_previews(
+	thumbnails_directory: &Path,
+	GenerateThumbnailArgs { cas_id, path, .. }: &GenerateThumbnailArgs<'_>,
+	kind: &ThumbnailKind,
+	should_regenerate: bool,
+) -> (
+	Duration,
+	Result<GenerationStatus, NonCriticalThumbnailerError>,
+) {
+	trace!("Generating video previews");
+	let start = Instant::now();
+
+	let thumbstrip_path = video_preview_path(thumbnails_directory, cas_id, kind, THUMBSTRIP_SUFFIX);
+	let animated_preview_path =
+		video_preview_path(thumbnails_directory, cas_id, kind, ANIMATED_PREVIEW_SUFFIX);
+
+	if !should_regenerate
+		&& matches!(fs::try_exists(&thumbstrip_path).await, Ok(true))
+		&& matches!(fs::try_exists(&animated_preview_path).await, Ok(true))
+	{
+		trace!("Skipping video previews generation because they already exist");
+		return (start.elapsed(), Ok(GenerationStatus::Skipped));
+	}
+
+	let res = timeout(
+		VIDEO_PREVIEW_TIMEOUT,
+		to_video_previews(
+			path,
+			&thumbstrip_path,
+			&animated_preview_path,
+			THUMBSTRIP_FRAMES_COUNT,
+			VIDEO_PREVIEW_FRAME_SIZE,
+			ANIMATED_PREVIEW_FRAME_DURATION,
+			TARGET_QUALITY,
+		),
+	)
+	.await
+	.map_err(|_| format!("timed out after {VIDEO_PREVIEW_TIMEOUT:?}"))
+	.and_then(|res| res.map_err(|e| e.to_string()))
+	.map(|()| {
+		trace!("Generated video previews");
+		GenerationStatus::Generated
+	})
+	.map_err(|reason| {
+		NonCriticalThumbnailerError::VideoPreviewGenerationFailed(path.clone(), reason)
+	});
+
+	(start.elapsed(), res)
+}
@@ -56,6 +56,8 @@ use super::{
 enum TaskKind {
 	MediaDataExtractor,
 	Thumbnailer,
+	#[cfg(feature = "ffmpeg")]
+	VideoPreviewer,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -129,17 +131,30 @@ impl Job for MediaProcessor {
 								TaskKind::MediaDataExtractor => {
 									tasks::MediaDataExtractor::deserialize(
 										&task_bytes,
-										(Arc::clone(ctx.db()), ctx.sync().clone()),
+										(
+											Arc::clone(ctx.db()),
+											ctx.sync().clone(),
+											ctx.io_throttle().clone(),
+										),
 									)
 									.await
 									.map(IntoTask::into_task)
 								}
 
-								TaskKind::Thumbnailer => {
-									tasks::Thumbnailer::deserialize(&task_bytes, reporter)
-										.await
-										.map(IntoTask::into_task)
-								}
+								TaskKind::Thumbnailer => tasks::Thumbnailer::deserialize(
+									&task_bytes,
+									(reporter, ctx.io_throttle().clone()),
+								)
+								.await
+								.map(IntoTask::into_task),
+
+								#[cfg(feature = "ffmpeg")]
+								TaskKind::VideoPreviewer => tasks::VideoPreviewer::deserialize(
+									&task_bytes,
+									ctx.io_throttle().clone(),
+								)
+								.await
+								.map(IntoTask::into_task),
 							}
 						}
 					})
@@ -542,8 +557,24 @@ impl MediaProcessor {
 				total_time,
 				mean_time_acc,
 				std_dev_acc,
+				perceptual_hashes,
 			} = *any_task_output.downcast().expect("just checked");
 
+			if !perceptual_hashes.is_empty() {
+				if let Err(e) =
+					helpers::perceptual_hash::save(perceptual_hashes, job_ctx.db()).await
+				{
+					self.errors.push(
+						media_processor::NonCriticalMediaProcessorError::from(
+							thumbnailer::NonCriticalThumbnailerError::SavePerceptualHashes(
+								e.to_string(),
+							),
+						)
+						.into(),
+					);
+				}
+			}
+
 			self.metadata.thumbnailer_metrics_acc.generated += generated;
 			self.metadata.thumbnailer_metrics_acc.skipped += skipped;
 			self.metadata.thumbnailer_metrics_acc.mean_total_time += total_time;
@@ -588,6 +619,36 @@ impl MediaProcessor {
 		// 	]).await;
 		// }
 		} else {
+			#[cfg(feature = "ffmpeg")]
+			if any_task_output.is::<tasks::video_previewer::Output>() {
+				let tasks::video_previewer::Output {
+					generated,
+					skipped,
+					errors,
+					total_time,
+				} = *any_task_output.downcast().expect("just checked");
+
+				self.metadata.video_previews_metrics.generated += generated;
+				self.metadata.video_previews_metrics.skipped += skipped;
+				self.metadata.video_previews_metrics.mean_total_time += total_time;
+				self.metadata.video_previews_metrics.total_successful_tasks += 1;
+
+				if !errors.is_empty() {
+					warn!(
+						?errors,
+						"Non critical errors while generating video previews;"
+					);
+					self.errors.extend(errors);
+				}
+
+				debug!(
+					"Processed {} video previewer tasks, took: {total_time:?}",
+					self.metadata.video_previews_metrics.total_successful_tasks,
+				);
+
+				return;
+			}
+
 			unreachable!("Unexpected task output type: <id='{task_id}'>");
 		}
 	}
@@ -616,7 +677,7 @@ impl MediaProcessor {
 		let db = job_ctx.db();
 		let sync = job_ctx.sync();
 
-		let (extract_exif_file_paths, extract_ffmpeg_file_paths) = (
+		let (extract_exif_file_paths, extract_ffmpeg_file_paths, extract_text_file_paths) = (
 			get_all_children_files_by_extensions(
 				parent_iso_file_path,
 				&helpers::exif_media_data::AVAILABLE_EXTENSIONS,
@@ -627,11 +688,18 @@ impl MediaProcessor {
 				&helpers::ffmpeg_media_data::AVAILABLE_EXTENSIONS,
 				db,
 			),
+			get_all_children_files_by_extensions(
+				parent_iso_file_path,
+				&helpers::text_content::AVAILABLE_EXTENSIONS,
+				db,
+			),
 		)
 			.try_join()
 			.await?;
 
-		let files_count = (extract_exif_file_paths.len() + extract_ffmpeg_file_paths.len()) as u64;
+		let files_count = (extract_exif_file_paths.len()
+			+ extract_ffmpeg_file_paths.len()
+			+ extract_text_file_paths.len()) as u64;
 
 		let tasks = extract_exif_file_paths
 			.into_iter()
@@ -645,6 +713,7 @@ impl MediaProcessor {
 					Arc::clone(&self.location_path),
 					Arc::clone(db),
 					sync.clone(),
+					job_ctx.io_throttle().clone(),
 				)
 			})
 			.map(IntoTask::into_task)
@@ -661,6 +730,25 @@ impl MediaProcessor {
 							Arc::clone(&self.location_path),
 							Arc::clone(db),
 							sync.clone(),
+							job_ctx.io_throttle().clone(),
+						)
+					})
+					.map(IntoTask::into_task),
+			)
+			.chain(
+				extract_text_file_paths
+					.into_iter()
+					.chunks(BATCH_SIZE)
+					.into_iter()
+					.map(Iterator::collect::<Vec<_>>)
+					.map(|chunked_file_paths| {
+						tasks::MediaDataExtractor::new_text_content(
+							&chunked_file_paths,
+							parent_iso_file_path.location_id(),
+							Arc::clone(&self.location_path),
+							Arc::clone(db),
+							sync.clone(),
+							job_ctx.io_throttle().clone(),
 						)
 					})
 					.map(IntoTask::into_task),
@@ -693,12 +781,12 @@ impl MediaProcessor {
 			.map_err(Into::into)
 	}
 
-	async fn dispatch_thumbnailer_tasks(
+	async fn dispatch_thumbnailer_tasks<OuterCtx: OuterContext>(
 		&mut self,
 		parent_iso_file_path: &IsolatedFilePathData<'_>,
 		should_regenerate: bool,
 		dispatcher: &JobTaskDispatcher,
-		ctx: &impl OuterContext,
+		ctx: &impl JobContext<OuterCtx>,
 	) -> Result<Vec<TaskHandle<Error>>, JobErrorOrDispatcherError<media_processor::Error>> {
 		let thumbnails_directory_path =
 			Arc::new(ctx.get_data_directory().join(THUMBNAIL_CACHE_DIR_NAME));
@@ -748,6 +836,7 @@ impl MediaProcessor {
 					should_regenerate,
 					true,
 					Arc::clone(&reporter),
+					ctx.io_throttle().clone(),
 				)
 			})
 			.map(IntoTask::into_task)
@@ -766,6 +855,7 @@ impl MediaProcessor {
 					should_regenerate,
 					false,
 					Arc::clone(&reporter),
+					ctx.io_throttle().clone(),
 				)
 			})
 			.map(IntoTask::into_task)
@@ -781,6 +871,43 @@ impl MediaProcessor {
 		self.total_thumbnailer_tasks = (priority_tasks.len() + non_priority_tasks.len()) as u64;
 		self.total_thumbnailer_files = thumbs_count;
 
+		// Thumbstrips and animated previews for videos, they aren't reported as
+		// thumbnails, so they don't count towards the thumbnails phase progress
+		#[cfg(feature = "ffmpeg")]
+		let non_priority_tasks = {
+			let video_previewer_tasks = get_all_children_files_by_extensions(
+				parent_iso_file_path,
+				&helpers::thumbnailer::THUMBNAILABLE_VIDEO_EXTENSIONS,
+				db,
+			)
+			.await?
+			.into_iter()
+			.chunks(BATCH_SIZE)
+			.into_iter()
+			.map(|chunk| {
+				tasks::VideoPreviewer::new_indexed(
+					Arc::clone(&thumbnails_directory_path),
+					&chunk.collect::<Vec<_>>(),
+					(location_id, &self.location_path),
+					library_id,
+					should_regenerate,
+					ctx.io_throttle().clone(),
+				)
+			})
+			.map(IntoTask::into_task)
+			.collect::<Vec<_>>();
+
+			debug!(
+				video_previewer_tasks_count = video_previewer_tasks.len(),
+				"Dispatching video previews to be processed;",
+			);
+
+			non_priority_tasks
+				.into_iter()
+				.chain(video_previewer_tasks)
+				.collect::<Vec<_>>()
+		};
+
 		dispatcher
 			.dispatch_many_boxed(priority_tasks.into_iter().chain(non_priority_tasks))
 			.await
@@ -792,6 +919,8 @@ impl MediaProcessor {
 struct Metadata {
 	media_data_metrics: MediaExtractorMetrics,
 	thumbnailer_metrics_acc: ThumbnailerMetricsAccumulator,
+	#[serde(default)]
+	video_previews_metrics: VideoPreviewsMetrics,
 }
 
 impl From<Metadata> for Vec<ReportOutputMetadata> {
@@ -799,6 +928,7 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 		Metadata {
 			media_data_metrics,
 			thumbnailer_metrics_acc: thumbnailer_metrics_accumulator,
+			video_previews_metrics,
 		}: Metadata,
 	) -> Self {
 		let thumbnailer_metrics = ThumbnailerMetrics::from(thumbnailer_metrics_accumulator);
@@ -822,6 +952,13 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 				// Thumbnailer
 				//
 				("thumbnailer_metrics".into(), json!(thumbnailer_metrics)),
+				//
+				// Video previews
+				//
+				(
+					"video_previews_metrics".into(),
+					json!(video_previews_metrics),
+				),
 			])),
 		]
 	}
@@ -838,6 +975,14 @@ struct MediaExtractorMetrics {
 	total_successful_tasks: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct VideoPreviewsMetrics {
+	generated: u64,
+	skipped: u64,
+	mean_total_time: Duration,
+	total_successful_tasks: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct ThumbnailerMetricsAccumulator {
 	generated: u64,
@@ -925,15 +1070,18 @@ async fn get_all_children_files_by_extensions(
 				file_path.is_dir,
 				file_path.name,
 				file_path.extension,
+				file_path.detected_extension,
 				file_path.cas_id,
 				object.id as 'object_id',
-				object.pub_id as 'object_pub_id'
+				object.pub_id as 'object_pub_id',
+				object.perceptual_hash as 'object_perceptual_hash'
 			FROM file_path
 			INNER JOIN object ON object.id = file_path.object_id
 			WHERE
 				file_path.location_id={{}}
 				AND file_path.cas_id IS NOT NULL
-				AND LOWER(file_path.extension) IN ({})
+				AND file_path.archive_member_path IS NULL
+				AND LOWER(COALESCE(file_path.detected_extension, file_path.extension)) IN ({})
 				AND file_path.materialized_path LIKE {{}}
 			ORDER BY materialized_path ASC, name ASC",
 				// Ordering by materialized_path so we can prioritize processing the first files
@@ -1016,6 +1164,16 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 						.await
 						.map(|bytes| (TaskKind::Thumbnailer, bytes))
 				} else {
+					#[cfg(feature = "ffmpeg")]
+					if task.is::<tasks::VideoPreviewer>() {
+						return task
+							.downcast::<tasks::VideoPreviewer>()
+							.expect("just checked")
+							.serialize()
+							.await
+							.map(|bytes| (TaskKind::VideoPreviewer, bytes));
+					}
+
 					unreachable!("Unexpected task type: <task='{task:#?}'>")
 				}
 			})
@@ -24,7 +24,7 @@ pub use tasks::{
 };
 
 pub use helpers::{
-	exif_media_data, ffmpeg_media_data,
+	exif_media_data, ffmpeg_media_data, perceptual_hash, text_content,
 	thumbnailer::{
 		can_generate_thumbnail_for_document, can_generate_thumbnail_for_image,
 		generate_single_thumbnail, get_shard_hex, get_thumbnails_directory, GenerateThumbnailArgs,
@@ -33,7 +33,11 @@ pub use helpers::{
 };
 
 #[cfg(feature = "ffmpeg")]
-pub use helpers::thumbnailer::can_generate_thumbnail_for_video;
+pub use helpers::thumbnailer::{
+	can_generate_thumbnail_for_audio, can_generate_thumbnail_for_video,
+};
+#[cfg(feature = "ffmpeg")]
+pub use tasks::video_previewer::{self, VideoPreviewer};
 
 pub use shallow::shallow;
 
@@ -103,9 +107,11 @@ struct RawFilePathForMediaProcessor {
 	is_dir: file_path::is_dir::Type,
 	name: file_path::name::Type,
 	extension: file_path::extension::Type,
+	detected_extension: file_path::detected_extension::Type,
 	cas_id: file_path::cas_id::Type,
 	object_id: object::id::Type,
 	object_pub_id: object::pub_id::Type,
+	object_perceptual_hash: object::perceptual_hash::Type,
 }
 
 impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data {
@@ -116,9 +122,11 @@ impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data
 			is_dir,
 			name,
 			extension,
+			detected_extension,
 			cas_id,
 			object_id,
 			object_pub_id,
+			object_perceptual_hash,
 		}: RawFilePathForMediaProcessor,
 	) -> Self {
 		Self {
@@ -127,10 +135,12 @@ impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data
 			is_dir,
 			name,
 			extension,
+			detected_extension,
 			cas_id,
 			object: Some(file_path_for_media_processor::object::Data {
 				id: object_id,
 				pub_id: object_pub_id,
+				perceptual_hash: object_perceptual_hash,
 			}),
 		}
 	}
@@ -152,15 +162,18 @@ async fn get_direct_children_files_by_extensions(
 				file_path.is_dir,
 				file_path.name,
 				file_path.extension,
+				file_path.detected_extension,
 				file_path.cas_id,
 				object.id as 'object_id',
-				object.pub_id as 'object_pub_id'
+				object.pub_id as 'object_pub_id',
+				object.perceptual_hash as 'object_perceptual_hash'
 			FROM file_path
 			INNER JOIN object ON object.id = file_path.object_id
 			WHERE
 				location_id={{}}
 				AND cas_id IS NOT NULL
-				AND LOWER(extension) IN ({})
+				AND archive_member_path IS NULL
+				AND LOWER(COALESCE(detected_extension, extension)) IN ({})
 				AND materialized_path = {{}}
 			ORDER BY name ASC",
 				extensions
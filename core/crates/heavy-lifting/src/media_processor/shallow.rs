@@ -1,5 +1,5 @@
 use crate::{
-	media_processor, utils::sub_path::maybe_get_iso_file_path_from_sub_path, Error,
+	media_processor, utils::sub_path::maybe_get_iso_file_path_from_sub_path, Error, IoThrottle,
 	NonCriticalError, OuterContext,
 };
 
@@ -25,7 +25,10 @@ use tracing::{debug, warn};
 
 use super::{
 	get_direct_children_files_by_extensions,
-	helpers::{self, exif_media_data, ffmpeg_media_data, thumbnailer::THUMBNAIL_CACHE_DIR_NAME},
+	helpers::{
+		self, exif_media_data, ffmpeg_media_data, perceptual_hash, text_content,
+		thumbnailer::THUMBNAIL_CACHE_DIR_NAME,
+	},
 	tasks::{
 		self, media_data_extractor,
 		thumbnailer::{self, NewThumbnailReporter},
@@ -120,11 +123,25 @@ pub async fn shallow(
 					let thumbnailer::Output {
 						total_time,
 						errors: new_errors,
+						perceptual_hashes,
 						..
 					} = *out.downcast::<thumbnailer::Output>().expect("just checked");
 
 					errors.extend(new_errors);
 
+					if !perceptual_hashes.is_empty() {
+						if let Err(e) = perceptual_hash::save(perceptual_hashes, ctx.db()).await {
+							errors.push(
+								media_processor::NonCriticalMediaProcessorError::from(
+									thumbnailer::NonCriticalThumbnailerError::SavePerceptualHashes(
+										e.to_string(),
+									),
+								)
+								.into(),
+							);
+						}
+					}
+
 					completed_thumbnailer_tasks += 1;
 
 					debug!(
@@ -159,7 +176,7 @@ async fn dispatch_media_data_extractor_tasks(
 	location_path: &Arc<PathBuf>,
 	dispatcher: &BaseTaskDispatcher<Error>,
 ) -> Result<Vec<TaskHandle<Error>>, Error> {
-	let (extract_exif_file_paths, extract_ffmpeg_file_paths) = (
+	let (extract_exif_file_paths, extract_ffmpeg_file_paths, extract_text_file_paths) = (
 		get_direct_children_files_by_extensions(
 			parent_iso_file_path,
 			&exif_media_data::AVAILABLE_EXTENSIONS,
@@ -170,6 +187,11 @@ async fn dispatch_media_data_extractor_tasks(
 			&ffmpeg_media_data::AVAILABLE_EXTENSIONS,
 			db,
 		),
+		get_direct_children_files_by_extensions(
+			parent_iso_file_path,
+			&text_content::AVAILABLE_EXTENSIONS,
+			db,
+		),
 	)
 		.try_join()
 		.await?;
@@ -186,6 +208,7 @@ async fn dispatch_media_data_extractor_tasks(
 				Arc::clone(location_path),
 				Arc::clone(db),
 				sync.clone(),
+				IoThrottle::default(),
 			)
 		})
 		.map(IntoTask::into_task)
@@ -202,6 +225,25 @@ async fn dispatch_media_data_extractor_tasks(
 						Arc::clone(location_path),
 						Arc::clone(db),
 						sync.clone(),
+						IoThrottle::default(),
+					)
+				})
+				.map(IntoTask::into_task),
+		)
+		.chain(
+			extract_text_file_paths
+				.into_iter()
+				.chunks(BATCH_SIZE)
+				.into_iter()
+				.map(Iterator::collect::<Vec<_>>)
+				.map(|chunked_file_paths| {
+					tasks::MediaDataExtractor::new_text_content(
+						&chunked_file_paths,
+						parent_iso_file_path.location_id(),
+						Arc::clone(location_path),
+						Arc::clone(db),
+						sync.clone(),
+						IoThrottle::default(),
 					)
 				})
 				.map(IntoTask::into_task),
@@ -254,6 +296,7 @@ async fn dispatch_thumbnailer_tasks(
 				should_regenerate,
 				true,
 				Arc::clone(&reporter),
+				IoThrottle::default(),
 			)
 		})
 		.map(IntoTask::into_task)
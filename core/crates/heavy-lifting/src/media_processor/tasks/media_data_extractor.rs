@@ -1,9 +1,9 @@
 use crate::{
 	media_processor::{
 		self,
-		helpers::{exif_media_data, ffmpeg_media_data},
+		helpers::{exif_media_data, ffmpeg_media_data, text_content},
 	},
-	Error,
+	Error, IoThrottle,
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
@@ -38,6 +38,8 @@ use tracing::{debug, instrument, trace, Level};
 pub enum NonCriticalMediaDataExtractorError {
 	#[error("failed to extract media data from <file='{}'>: {1}", .0.display())]
 	FailedToExtractImageMediaData(PathBuf, String),
+	#[error("failed to extract text content from <file='{}'>: {1}", .0.display())]
+	FailedToExtractTextContent(PathBuf, String),
 	#[error("file path missing object id: <file_path_id='{0}'>")]
 	FilePathMissingObjectId(file_path::id::Type),
 	#[error("failed to construct isolated file path data: <file_path_id='{0}'>: {1}")]
@@ -48,6 +50,7 @@ pub enum NonCriticalMediaDataExtractorError {
 enum Kind {
 	Exif,
 	FFmpeg,
+	TextContent,
 }
 
 #[derive(Debug)]
@@ -70,6 +73,7 @@ pub struct MediaDataExtractor {
 	// Dependencies
 	db: Arc<PrismaClient>,
 	sync: SyncManager,
+	io_throttle: IoThrottle,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,11 +84,15 @@ enum Stage {
 		paths_by_id: HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)>,
 		exif_media_datas: Vec<(ExifMetadata, object::id::Type, ObjectPubId)>,
 		ffmpeg_media_datas: Vec<(FFmpegMetadata, object::id::Type)>,
+		#[serde(default)]
+		text_contents: Vec<(String, object::id::Type)>,
 		extract_ids_to_remove_from_map: Vec<file_path::id::Type>,
 	},
 	SaveMediaData {
 		exif_media_datas: Vec<(ExifMetadata, object::id::Type, ObjectPubId)>,
 		ffmpeg_media_datas: Vec<(FFmpegMetadata, object::id::Type)>,
+		#[serde(default)]
+		text_contents: Vec<(String, object::id::Type)>,
 	},
 }
 
@@ -188,6 +196,11 @@ impl Task<Error> for MediaDataExtractor {
 						} else {
 							Vec::new()
 						},
+						text_contents: if self.kind == Kind::TextContent {
+							Vec::with_capacity(paths_by_id.len())
+						} else {
+							Vec::new()
+						},
 						paths_by_id,
 					};
 				}
@@ -196,6 +209,7 @@ impl Task<Error> for MediaDataExtractor {
 					paths_by_id,
 					exif_media_datas,
 					ffmpeg_media_datas,
+					text_contents,
 					extract_ids_to_remove_from_map,
 				} => {
 					{
@@ -208,6 +222,7 @@ impl Task<Error> for MediaDataExtractor {
 						let mut futures = pin!(prepare_extraction_futures(
 							self.kind,
 							paths_by_id,
+							&self.io_throttle,
 							interrupter
 						));
 
@@ -218,6 +233,7 @@ impl Task<Error> for MediaDataExtractor {
 										out,
 										exif_media_datas,
 										ffmpeg_media_datas,
+										text_contents,
 										extract_ids_to_remove_from_map,
 										&mut self.output,
 									);
@@ -237,18 +253,21 @@ impl Task<Error> for MediaDataExtractor {
 					self.stage = Stage::SaveMediaData {
 						exif_media_datas: mem::take(exif_media_datas),
 						ffmpeg_media_datas: mem::take(ffmpeg_media_datas),
+						text_contents: mem::take(text_contents),
 					};
 				}
 
 				Stage::SaveMediaData {
 					exif_media_datas,
 					ffmpeg_media_datas,
+					text_contents,
 				} => {
 					let db_write_start = Instant::now();
 					self.output.extracted = save(
 						self.kind,
 						exif_media_datas,
 						ffmpeg_media_datas,
+						text_contents,
 						&self.db,
 						&self.sync,
 					)
@@ -276,6 +295,7 @@ impl MediaDataExtractor {
 		location_path: Arc<PathBuf>,
 		db: Arc<PrismaClient>,
 		sync: SyncManager,
+		io_throttle: IoThrottle,
 	) -> Self {
 		let mut output = Output::default();
 
@@ -306,6 +326,7 @@ impl MediaDataExtractor {
 			stage: Stage::Starting,
 			db,
 			sync,
+			io_throttle,
 			output,
 		}
 	}
@@ -317,8 +338,17 @@ impl MediaDataExtractor {
 		location_path: Arc<PathBuf>,
 		db: Arc<PrismaClient>,
 		sync: SyncManager,
+		io_throttle: IoThrottle,
 	) -> Self {
-		Self::new(Kind::Exif, file_paths, location_id, location_path, db, sync)
+		Self::new(
+			Kind::Exif,
+			file_paths,
+			location_id,
+			location_path,
+			db,
+			sync,
+			io_throttle,
+		)
 	}
 
 	#[must_use]
@@ -328,6 +358,7 @@ impl MediaDataExtractor {
 		location_path: Arc<PathBuf>,
 		db: Arc<PrismaClient>,
 		sync: SyncManager,
+		io_throttle: IoThrottle,
 	) -> Self {
 		Self::new(
 			Kind::FFmpeg,
@@ -336,6 +367,27 @@ impl MediaDataExtractor {
 			location_path,
 			db,
 			sync,
+			io_throttle,
+		)
+	}
+
+	#[must_use]
+	pub fn new_text_content(
+		file_paths: &[file_path_for_media_processor::Data],
+		location_id: location::id::Type,
+		location_path: Arc<PathBuf>,
+		db: Arc<PrismaClient>,
+		sync: SyncManager,
+		io_throttle: IoThrottle,
+	) -> Self {
+		Self::new(
+			Kind::TextContent,
+			file_paths,
+			location_id,
+			location_path,
+			db,
+			sync,
+			io_throttle,
 		)
 	}
 }
@@ -369,6 +421,10 @@ async fn fetch_objects_already_with_media_data(
 			.await
 			.map(|object_ids| object_ids.into_iter().map(|data| data.object_id).collect())
 			.map_err(Into::into),
+
+		Kind::TextContent => text_content::fetch_objects_with_text_content(object_ids, db)
+			.await
+			.map_err(Into::into),
 	}
 }
 
@@ -428,6 +484,7 @@ fn filter_files_to_extract_media_data(
 enum ExtractionOutputKind {
 	Exif(Result<Option<ExifMetadata>, media_processor::NonCriticalMediaProcessorError>),
 	FFmpeg(Result<FFmpegMetadata, media_processor::NonCriticalMediaProcessorError>),
+	TextContent(Result<Option<String>, media_processor::NonCriticalMediaProcessorError>),
 }
 
 struct ExtractionOutput {
@@ -451,12 +508,15 @@ enum InterruptRace {
 fn prepare_extraction_futures<'a>(
 	kind: Kind,
 	paths_by_id: &'a HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)>,
+	io_throttle: &'a IoThrottle,
 	interrupter: &'a Interrupter,
 ) -> FuturesUnordered<impl Future<Output = InterruptRace> + 'a> {
 	paths_by_id
 		.iter()
 		.map(
 			|(file_path_id, (path, object_id, object_pub_id))| async move {
+				io_throttle.stat_files(1).await;
+
 				InterruptRace::Processed(ExtractionOutput {
 					file_path_id: *file_path_id,
 					object_id: *object_id,
@@ -468,6 +528,9 @@ fn prepare_extraction_futures<'a>(
 						Kind::FFmpeg => {
 							ExtractionOutputKind::FFmpeg(ffmpeg_media_data::extract(path).await)
 						}
+						Kind::TextContent => {
+							ExtractionOutputKind::TextContent(text_content::extract(path).await)
+						}
 					},
 				})
 			},
@@ -493,6 +556,7 @@ fn process_output(
 	}: ExtractionOutput,
 	exif_media_datas: &mut Vec<(ExifMetadata, object::id::Type, ObjectPubId)>,
 	ffmpeg_media_datas: &mut Vec<(FFmpegMetadata, object::id::Type)>,
+	text_contents: &mut Vec<(String, object::id::Type)>,
 	extract_ids_to_remove_from_map: &mut Vec<file_path::id::Type>,
 	output: &mut Output,
 ) {
@@ -509,7 +573,17 @@ fn process_output(
 		ExtractionOutputKind::FFmpeg(Ok(ffmpeg_data)) => {
 			ffmpeg_media_datas.push((ffmpeg_data, object_id));
 		}
-		ExtractionOutputKind::Exif(Err(e)) | ExtractionOutputKind::FFmpeg(Err(e)) => {
+		ExtractionOutputKind::TextContent(Ok(Some(text))) => {
+			text_contents.push((text, object_id));
+		}
+		ExtractionOutputKind::TextContent(Ok(None)) => {
+			// Not actually a text file, saving an empty marker so we don't read it again on every run
+			text_contents.push((String::new(), object_id));
+			output.skipped += 1;
+		}
+		ExtractionOutputKind::Exif(Err(e))
+		| ExtractionOutputKind::FFmpeg(Err(e))
+		| ExtractionOutputKind::TextContent(Err(e)) => {
 			output.errors.push(e.into());
 		}
 	}
@@ -522,16 +596,23 @@ async fn save(
 	kind: Kind,
 	exif_media_datas: &mut Vec<(ExifMetadata, object::id::Type, ObjectPubId)>,
 	ffmpeg_media_datas: &mut Vec<(FFmpegMetadata, object::id::Type)>,
+	text_contents: &mut Vec<(String, object::id::Type)>,
 	db: &PrismaClient,
 	sync: &SyncManager,
 ) -> Result<u64, media_processor::Error> {
 	trace!("Saving media data on database");
 
 	match kind {
-		Kind::Exif => exif_media_data::save(mem::take(exif_media_datas), db, sync).await,
-		Kind::FFmpeg => ffmpeg_media_data::save(mem::take(ffmpeg_media_datas), db).await,
+		Kind::Exif => exif_media_data::save(mem::take(exif_media_datas), db, sync)
+			.await
+			.map_err(Into::into),
+		Kind::FFmpeg => ffmpeg_media_data::save(mem::take(ffmpeg_media_datas), db)
+			.await
+			.map_err(Into::into),
+		Kind::TextContent => text_content::save(mem::take(text_contents), db)
+			.await
+			.map_err(Into::into),
 	}
-	.map_err(Into::into)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -550,7 +631,7 @@ impl SerializableTask<Error> for MediaDataExtractor {
 
 	type DeserializeError = rmp_serde::decode::Error;
 
-	type DeserializeCtx = (Arc<PrismaClient>, SyncManager);
+	type DeserializeCtx = (Arc<PrismaClient>, SyncManager, IoThrottle);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -577,7 +658,7 @@ impl SerializableTask<Error> for MediaDataExtractor {
 
 	async fn deserialize(
 		data: &[u8],
-		(db, sync): Self::DeserializeCtx,
+		(db, sync, io_throttle): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice(data).map(
 			|SaveState {
@@ -598,6 +679,7 @@ impl SerializableTask<Error> for MediaDataExtractor {
 				output,
 				db,
 				sync,
+				io_throttle,
 			},
 		)
 	}
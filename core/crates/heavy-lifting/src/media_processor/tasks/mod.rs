@@ -1,5 +1,9 @@
 pub mod media_data_extractor;
 pub mod thumbnailer;
+#[cfg(feature = "ffmpeg")]
+pub mod video_previewer;
 
 pub use media_data_extractor::MediaDataExtractor;
 pub use thumbnailer::Thumbnailer;
+#[cfg(feature = "ffmpeg")]
+pub use video_previewer::VideoPreviewer;
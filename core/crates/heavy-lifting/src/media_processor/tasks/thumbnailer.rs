@@ -16,7 +16,7 @@ use crate::{
 		},
 		ThumbKey, ThumbnailKind,
 	},
-	Error,
+	Error, IoThrottle,
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
@@ -72,6 +72,7 @@ pub struct Thumbnailer {
 
 	// Dependencies
 	reporter: Arc<dyn NewThumbnailReporter>,
+	io_throttle: IoThrottle,
 }
 
 #[async_trait::async_trait]
@@ -116,6 +117,7 @@ impl Task<Error> for Thumbnailer {
 			should_regenerate,
 			with_priority,
 			reporter,
+			io_throttle,
 			output,
 			..
 		} = self;
@@ -126,17 +128,23 @@ impl Task<Error> for Thumbnailer {
 		});
 
 		let start = Instant::now();
+		let io_throttle = &*io_throttle;
 
 		let futures = thumbnails_to_generate
 			.iter()
 			.map(|(id, generate_args)| {
-				generate_thumbnail(
+				// Futures are lazy, so nothing is read before the I/O budget allows it
+				let generation = generate_thumbnail(
 					thumbnails_directory_path,
 					generate_args,
 					thumbs_kind,
 					*should_regenerate,
-				)
-				.map(|res| InterruptRace::Processed((*id, res)))
+				);
+
+				async move {
+					io_throttle.stat_files(1).await;
+					InterruptRace::Processed((*id, generation.await))
+				}
 			})
 			.map(|fut| {
 				(
@@ -153,6 +161,7 @@ impl Task<Error> for Thumbnailer {
 			match race_output {
 				InterruptRace::Processed(out) => process_thumbnail_generation_output(
 					out,
+					thumbs_kind,
 					*with_priority,
 					reporter.as_ref(),
 					already_processed_ids,
@@ -203,6 +212,9 @@ pub struct Output {
 	pub total_time: Duration,
 	pub mean_time_acc: f64,
 	pub std_dev_acc: f64,
+	/// Perceptual hashes of the indexed images we generated thumbnails for, keyed by file path
+	#[serde(default)]
+	pub perceptual_hashes: Vec<(file_path::id::Type, u64)>,
 }
 
 #[derive(thiserror::Error, Debug, Serialize, Deserialize, Type, Clone)]
@@ -213,6 +225,10 @@ pub enum NonCriticalThumbnailerError {
 	FailedToExtractIsolatedFilePathData(file_path::id::Type, String),
 	#[error("failed to generate video file thumbnail <path='{}'>: {1}", .0.display())]
 	VideoThumbnailGenerationFailed(PathBuf, String),
+	#[error("failed to generate video previews <path='{}'>: {1}", .0.display())]
+	VideoPreviewGenerationFailed(PathBuf, String),
+	#[error("failed to generate audio thumbnail <path='{}'>: {1}", .0.display())]
+	AudioThumbnailGenerationFailed(PathBuf, String),
 	#[error("failed to format image <path='{}'>: {1}", .0.display())]
 	FormatImage(PathBuf, String),
 	#[error("failed to encode webp image <path='{}'>: {1}", .0.display())]
@@ -223,6 +239,8 @@ pub enum NonCriticalThumbnailerError {
 	CreateShardDirectory(String),
 	#[error("failed to save thumbnail <path='{}'>: {1}", .0.display())]
 	SaveThumbnail(PathBuf, String),
+	#[error("failed to save perceptual hashes: {0}")]
+	SavePerceptualHashes(String),
 	#[error("task timed out: {0}")]
 	TaskTimeout(TaskId),
 }
@@ -236,6 +254,7 @@ impl Thumbnailer {
 		should_regenerate: bool,
 		with_priority: bool,
 		reporter: Arc<dyn NewThumbnailReporter>,
+		io_throttle: IoThrottle,
 	) -> Self {
 		Self {
 			id: TaskId::new_v4(),
@@ -250,6 +269,7 @@ impl Thumbnailer {
 				..Default::default()
 			},
 			reporter,
+			io_throttle,
 		}
 	}
 
@@ -278,6 +298,8 @@ impl Thumbnailer {
 			false,
 			true,
 			reporter,
+			// Ephemeral thumbnails are for directories being browsed, so they're never throttled
+			IoThrottle::default(),
 		)
 	}
 
@@ -290,74 +312,110 @@ impl Thumbnailer {
 		should_regenerate: bool,
 		with_priority: bool,
 		reporter: Arc<dyn NewThumbnailReporter>,
+		io_throttle: IoThrottle,
 	) -> Self {
 		let mut errors = Vec::new();
 
 		Self::new(
 			ThumbnailKind::Indexed(library_id),
 			thumbnails_directory_path,
-			file_paths
-				.iter()
-				.filter_map(|file_path| {
-					if let Some(cas_id) = file_path
-						.cas_id
-						.as_ref()
-						.map(CasId::from)
-						.map(CasId::into_owned)
-					{
-						let file_path_id = file_path.id;
-						IsolatedFilePathData::try_from((location_id, file_path))
-							.map_err(|e| {
-								errors.push(
-									media_processor::NonCriticalMediaProcessorError::from(
-										NonCriticalThumbnailerError::FailedToExtractIsolatedFilePathData(
-											file_path_id,
-											e.to_string(),
-										),
-									)
-									.into(),
-								);
-							})
-							.ok()
-							.map(|iso_file_path| (file_path_id, cas_id, iso_file_path))
-					} else {
+			generate_args_from_file_paths(file_paths, (location_id, location_path), &mut errors),
+			errors,
+			should_regenerate,
+			with_priority,
+			reporter,
+			io_throttle,
+		)
+	}
+}
+
+/// Gathers what we need to generate thumbnails, or any other preview media, for these file paths.
+/// File paths that can't have them are reported on `errors`.
+pub(super) fn generate_args_from_file_paths(
+	file_paths: &[file_path_for_media_processor::Data],
+	(location_id, location_path): (location::id::Type, &Path),
+	errors: &mut Vec<crate::NonCriticalError>,
+) -> HashMap<ThumbnailId, GenerateThumbnailArgs<'static>> {
+	file_paths
+		.iter()
+		.filter_map(|file_path| {
+			if let Some(cas_id) = file_path
+				.cas_id
+				.as_ref()
+				.map(CasId::from)
+				.map(CasId::into_owned)
+			{
+				let file_path_id = file_path.id;
+				IsolatedFilePathData::try_from((location_id, file_path))
+					.map_err(|e| {
 						errors.push(
 							media_processor::NonCriticalMediaProcessorError::from(
-								NonCriticalThumbnailerError::MissingCasId(file_path.id),
+								NonCriticalThumbnailerError::FailedToExtractIsolatedFilePathData(
+									file_path_id,
+									e.to_string(),
+								),
 							)
 							.into(),
 						);
-						None
-					}
-				})
-				.map(|(file_path_id, cas_id, iso_file_path)| {
-					let full_path = location_path.join(&iso_file_path);
+					})
+					.ok()
+					.map(|iso_file_path| {
+						// Extensionless or misnamed files are thumbnailed by their contents
+						let extension = file_path
+							.detected_extension
+							.clone()
+							.unwrap_or_else(|| iso_file_path.extension().to_string());
+
+						// Images indexed before we hashed them only get their thumbnail skipped
+						let needs_perceptual_hash = file_path
+							.object
+							.as_ref()
+							.is_some_and(|object| object.perceptual_hash.is_none());
 
-					#[allow(clippy::cast_sign_loss)]
-					{
 						(
-							// SAFETY: db doesn't have negative indexes
-							file_path_id as u32,
-							GenerateThumbnailArgs::new(
-								iso_file_path.extension().to_string(),
-								cas_id,
-								full_path,
-							),
+							file_path_id,
+							cas_id,
+							iso_file_path,
+							extension,
+							needs_perceptual_hash,
 						)
-					}
-				})
-				.collect::<HashMap<_, _>>(),
-			errors,
-			should_regenerate,
-			with_priority,
-			reporter,
+					})
+			} else {
+				errors.push(
+					media_processor::NonCriticalMediaProcessorError::from(
+						NonCriticalThumbnailerError::MissingCasId(file_path.id),
+					)
+					.into(),
+				);
+				None
+			}
+		})
+		.map(
+			|(file_path_id, cas_id, iso_file_path, extension, needs_perceptual_hash)| {
+				let full_path = location_path.join(&iso_file_path);
+
+				#[allow(clippy::cast_sign_loss)]
+				{
+					(
+						// SAFETY: db doesn't have negative indexes
+						file_path_id as u32,
+						GenerateThumbnailArgs::new(
+							extension,
+							cas_id,
+							full_path,
+							needs_perceptual_hash,
+						),
+					)
+				}
+			},
 		)
-	}
+		.collect()
 }
 
 #[instrument(skip_all, fields(thumb_id = id, %generated, %skipped, ?elapsed_time, ?res))]
 fn process_thumbnail_generation_output(
 	(id, (elapsed_time, res)): ThumbnailGenerationOutput,
+	thumbs_kind: &ThumbnailKind,
 	with_priority: bool,
 	reporter: &dyn NewThumbnailReporter,
 	already_processed_ids: &mut Vec<ThumbnailId>,
@@ -367,6 +425,7 @@ fn process_thumbnail_generation_output(
 		errors,
 		mean_time_acc: mean_generation_time_accumulator,
 		std_dev_acc: std_dev_accumulator,
+		perceptual_hashes,
 		..
 	}: &mut Output,
 ) {
@@ -375,7 +434,14 @@ fn process_thumbnail_generation_output(
 	*std_dev_accumulator += elapsed_time * elapsed_time;
 
 	match res {
-		Ok((thumb_key, status)) => {
+		Ok((thumb_key, status, perceptual_hash)) => {
+			// Ephemeral thumbnails have no file path to attach the hash to
+			if let (Some(hash), ThumbnailKind::Indexed(_)) = (perceptual_hash, thumbs_kind) {
+				#[allow(clippy::cast_possible_wrap)]
+				// SAFETY: indexed thumbnail ids are file path ids
+				perceptual_hashes.push((id as file_path::id::Type, hash));
+			}
+
 			match status {
 				GenerationStatus::Generated => {
 					*generated += 1;
@@ -421,7 +487,7 @@ impl SerializableTask<Error> for Thumbnailer {
 
 	type DeserializeError = rmp_serde::decode::Error;
 
-	type DeserializeCtx = Arc<dyn NewThumbnailReporter>;
+	type DeserializeCtx = (Arc<dyn NewThumbnailReporter>, IoThrottle);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -453,7 +519,7 @@ impl SerializableTask<Error> for Thumbnailer {
 
 	async fn deserialize(
 		data: &[u8],
-		reporter: Self::DeserializeCtx,
+		(reporter, io_throttle): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice(data).map(
 			|SaveState {
@@ -467,6 +533,7 @@ impl SerializableTask<Error> for Thumbnailer {
 			 }| Self {
 				id,
 				reporter,
+				io_throttle,
 				thumbs_kind,
 				thumbnails_to_generate,
 				thumbnails_directory_path,
@@ -483,6 +550,6 @@ type ThumbnailGenerationOutput = (
 	ThumbnailId,
 	(
 		Duration,
-		Result<(ThumbKey, GenerationStatus), NonCriticalThumbnailerError>,
+		Result<(ThumbKey, GenerationStatus, Option<u64>), NonCriticalThumbnailerError>,
 	),
 );
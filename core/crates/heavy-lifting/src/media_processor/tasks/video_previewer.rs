@@ -0,0 +1,285 @@
+//! Video previews are stored next to the video's thumbnail, in the same shard directory:
+//! <`cas_id`>[0..3]/
+//! ├── <`cas_id`>.webp # the thumbnail itself
+//! ├── <`cas_id`>_thumbstrip.webp # frames spread through the video, side by side
+//! └── <`cas_id`>_preview.webp # a short looping animation
+
+use crate::{
+	media_processor::{
+		self,
+		helpers::{
+			thumbnailer::{GenerateThumbnailArgs, GenerationStatus, THUMBNAILER_TASK_TIMEOUT},
+			video_preview::generate_video_previews,
+		},
+		ThumbnailKind,
+	},
+	Error, IoThrottle,
+};
+
+use sd_core_prisma_helpers::file_path_for_media_processor;
+
+use sd_prisma::prisma::location;
+use sd_task_system::{
+	ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
+};
+
+use std::{
+	collections::HashMap,
+	future::IntoFuture,
+	mem,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::FutureExt;
+use futures_concurrency::future::Race;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::{instrument, trace, Level};
+use uuid::Uuid;
+
+use super::thumbnailer::{generate_args_from_file_paths, NonCriticalThumbnailerError, ThumbnailId};
+
+#[derive(Debug)]
+pub struct VideoPreviewer {
+	// Task control
+	id: TaskId,
+
+	// Received input args
+	thumbs_kind: ThumbnailKind,
+	thumbnails_directory_path: Arc<PathBuf>,
+	previews_to_generate: HashMap<ThumbnailId, GenerateThumbnailArgs<'static>>,
+	should_regenerate: bool,
+
+	// Inner state
+	already_processed_ids: Vec<ThumbnailId>,
+
+	// Out collector
+	output: Output,
+
+	// Dependencies
+	io_throttle: IoThrottle,
+}
+
+#[async_trait::async_trait]
+impl Task<Error> for VideoPreviewer {
+	fn id(&self) -> TaskId {
+		self.id
+	}
+
+	fn with_timeout(&self) -> Option<Duration> {
+		Some(THUMBNAILER_TASK_TIMEOUT) // The entire task must not take more than this constant
+	}
+
+	#[instrument(
+		skip_all,
+		fields(
+			task_id = %self.id,
+			thumbs_kind = ?self.thumbs_kind,
+			should_regenerate = self.should_regenerate,
+			previews_to_generate_count = self.previews_to_generate.len(),
+			already_processed_ids_count = self.already_processed_ids.len(),
+		),
+		ret(level = Level::TRACE),
+		err,
+	)]
+	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
+	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
+		enum InterruptRace {
+			Interrupted(InterruptionKind),
+			Processed(
+				(
+					Duration,
+					Result<GenerationStatus, NonCriticalThumbnailerError>,
+				),
+			),
+		}
+
+		let Self {
+			thumbs_kind,
+			thumbnails_directory_path,
+			previews_to_generate,
+			already_processed_ids,
+			should_regenerate,
+			io_throttle,
+			output,
+			..
+		} = self;
+
+		// Removing already processed previews from a possible previous run
+		already_processed_ids.drain(..).for_each(|id| {
+			previews_to_generate.remove(&id);
+		});
+
+		let start = Instant::now();
+		let io_throttle = &*io_throttle;
+
+		// Decoding dozens of frames from a video is heavy, so unlike thumbnails,
+		// we generate previews for one video at a time
+		for (id, generate_args) in previews_to_generate.iter() {
+			let generation = async {
+				io_throttle.stat_files(1).await;
+				InterruptRace::Processed(
+					generate_video_previews(
+						thumbnails_directory_path,
+						generate_args,
+						thumbs_kind,
+						*should_regenerate,
+					)
+					.await,
+				)
+			};
+
+			match (
+				generation,
+				interrupter.into_future().map(InterruptRace::Interrupted),
+			)
+				.race()
+				.await
+			{
+				InterruptRace::Processed((elapsed_time, res)) => {
+					trace!(
+						preview_id = id,
+						?elapsed_time,
+						?res,
+						"Video previews processed;"
+					);
+
+					match res {
+						Ok(GenerationStatus::Generated) => output.generated += 1,
+						Ok(GenerationStatus::Skipped) => output.skipped += 1,
+						Err(e) => {
+							output.errors.push(
+								media_processor::NonCriticalMediaProcessorError::from(e).into(),
+							);
+							output.skipped += 1;
+						}
+					}
+
+					already_processed_ids.push(*id);
+				}
+
+				InterruptRace::Interrupted(kind) => {
+					output.total_time += start.elapsed();
+					return Ok(match kind {
+						InterruptionKind::Pause => ExecStatus::Paused,
+						InterruptionKind::Cancel => ExecStatus::Canceled,
+					});
+				}
+			}
+		}
+
+		output.total_time += start.elapsed();
+
+		Ok(ExecStatus::Done(mem::take(output).into_output()))
+	}
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Output {
+	pub generated: u64,
+	pub skipped: u64,
+	pub errors: Vec<crate::NonCriticalError>,
+	pub total_time: Duration,
+}
+
+impl VideoPreviewer {
+	#[must_use]
+	pub fn new_indexed(
+		thumbnails_directory_path: Arc<PathBuf>,
+		file_paths: &[file_path_for_media_processor::Data],
+		(location_id, location_path): (location::id::Type, &Path),
+		library_id: Uuid,
+		should_regenerate: bool,
+		io_throttle: IoThrottle,
+	) -> Self {
+		let mut errors = Vec::new();
+
+		let previews_to_generate =
+			generate_args_from_file_paths(file_paths, (location_id, location_path), &mut errors);
+
+		Self {
+			id: TaskId::new_v4(),
+			thumbs_kind: ThumbnailKind::Indexed(library_id),
+			thumbnails_directory_path,
+			already_processed_ids: Vec::with_capacity(previews_to_generate.len()),
+			previews_to_generate,
+			should_regenerate,
+			output: Output {
+				errors,
+				..Default::default()
+			},
+			io_throttle,
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveState {
+	id: TaskId,
+	thumbs_kind: ThumbnailKind,
+	thumbnails_directory_path: Arc<PathBuf>,
+	previews_to_generate: HashMap<ThumbnailId, GenerateThumbnailArgs<'static>>,
+	should_regenerate: bool,
+	output: Output,
+}
+
+impl SerializableTask<Error> for VideoPreviewer {
+	type SerializeError = rmp_serde::encode::Error;
+
+	type DeserializeError = rmp_serde::decode::Error;
+
+	type DeserializeCtx = IoThrottle;
+
+	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
+		let Self {
+			id,
+			thumbs_kind,
+			thumbnails_directory_path,
+			mut previews_to_generate,
+			already_processed_ids,
+			should_regenerate,
+			output,
+			..
+		} = self;
+
+		for id in already_processed_ids {
+			previews_to_generate.remove(&id);
+		}
+
+		rmp_serde::to_vec_named(&SaveState {
+			id,
+			thumbs_kind,
+			thumbnails_directory_path,
+			previews_to_generate,
+			should_regenerate,
+			output,
+		})
+	}
+
+	async fn deserialize(
+		data: &[u8],
+		io_throttle: Self::DeserializeCtx,
+	) -> Result<Self, Self::DeserializeError> {
+		rmp_serde::from_slice(data).map(
+			|SaveState {
+			     id,
+			     thumbs_kind,
+			     thumbnails_directory_path,
+			     previews_to_generate,
+			     should_regenerate,
+			     output,
+			 }| Self {
+				id,
+				thumbs_kind,
+				thumbnails_directory_path,
+				previews_to_generate,
+				should_regenerate,
+				already_processed_ids: Vec::new(),
+				output,
+				io_throttle,
+			},
+		)
+	}
+}
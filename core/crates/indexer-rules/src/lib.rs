@@ -27,12 +27,13 @@
 #![forbid(deprecated_in_future)]
 #![allow(clippy::missing_errors_doc)]
 
+use sd_file_ext::kind::ObjectKind;
 use sd_prisma::prisma::{indexer_rule, PrismaClient};
 use sd_utils::{
 	db::{maybe_missing, MissingFieldError},
 	error::{FileIOError, NonUtf8PathError},
 };
-use seed::SystemIndexerRule;
+use seed::{SpacedriveIgnoreFiles, SystemIndexerRule};
 use serde::{Deserialize, Serialize};
 
 use std::{
@@ -55,14 +56,31 @@ use tokio::fs;
 use tracing::{debug, instrument, trace};
 use uuid::Uuid;
 
+pub mod object_kind;
+mod portable;
 pub mod seed;
 mod serde_impl;
 
+pub use object_kind::{ClassificationMethod, ObjectKindClassification};
+pub use portable::{
+	PortableIndexerRule, PortableIndexerRules, PortableRule, PORTABLE_INDEXER_RULES_VERSION,
+};
+
 #[derive(Error, Debug)]
 pub enum Error {
 	// User errors
 	#[error("invalid indexer rule kind integer: {0}")]
 	InvalidRuleKindInt(i32),
+	#[error("invalid size threshold, expected a single amount of bytes: {0:?}")]
+	InvalidSizeThreshold(Vec<String>),
+	#[error("invalid modification window, expected a single amount of seconds: {0:?}")]
+	InvalidModifiedWindow(Vec<String>),
+	#[error("invalid object kind: {0}")]
+	InvalidObjectKind(String),
+	#[error("rule kind {0:?} is built while indexing and can't be stored")]
+	NotStorableRuleKind(RuleKind),
+	#[error("invalid indexer rules file: {0}")]
+	InvalidPortableRules(String),
 	#[error("glob builder error: {0}")]
 	Glob(#[from] globset::Error),
 	#[error(transparent)]
@@ -77,6 +95,8 @@ pub enum Error {
 	AcceptByItsChildrenFileIO(FileIOError),
 	#[error("reject by its children file I/O error: {0}")]
 	RejectByItsChildrenFileIO(FileIOError),
+	#[error("extended attributes file I/O error: {0}")]
+	ExtendedAttributesFileIO(FileIOError),
 	#[error("database error: {0}")]
 	Database(#[from] prisma_client_rust::QueryError),
 	#[error("missing-field: {0}")]
@@ -86,9 +106,14 @@ pub enum Error {
 impl From<Error> for rspc::Error {
 	fn from(e: Error) -> Self {
 		match e {
-			Error::InvalidRuleKindInt(_) | Error::Glob(_) | Error::NonUtf8Path(_) => {
-				Self::with_cause(ErrorCode::BadRequest, e.to_string(), e)
-			}
+			Error::InvalidRuleKindInt(_)
+			| Error::InvalidSizeThreshold(_)
+			| Error::InvalidModifiedWindow(_)
+			| Error::InvalidObjectKind(_)
+			| Error::NotStorableRuleKind(_)
+			| Error::InvalidPortableRules(_)
+			| Error::Glob(_)
+			| Error::NonUtf8Path(_) => Self::with_cause(ErrorCode::BadRequest, e.to_string(), e),
 
 			_ => Self::with_cause(ErrorCode::InternalServerError, e.to_string(), e),
 		}
@@ -103,6 +128,20 @@ impl From<Error> for rspc::Error {
 ///
 /// In case of `RuleKind::AcceptIfChildrenDirectoriesArePresent` or `RuleKind::RejectIfChildrenDirectoriesArePresent` the
 /// `parameters` field must be a vector of strings containing the names of the directories.
+///
+/// In case of `RuleKind::RejectFilesLargerThan` or `RuleKind::AcceptFilesSmallerThan` the
+/// `parameters` field must be a vector with a single string containing the size in bytes.
+///
+/// In case of `RuleKind::AcceptIfExtendedAttributesArePresent` or
+/// `RuleKind::RejectIfExtendedAttributesArePresent` the `parameters` field must be a vector of
+/// strings containing the names of the extended attributes, a name ending with `*` matches every
+/// attribute starting with it.
+///
+/// In case of `RuleKind::RejectFilesModifiedWithin` or `RuleKind::RejectFilesNotModifiedWithin`
+/// the `parameters` field must be a vector with a single string containing the amount of seconds.
+///
+/// In case of `RuleKind::AcceptByObjectKind` or `RuleKind::RejectByObjectKind` the `parameters`
+/// field must be a vector of strings containing names of `ObjectKind` variants, like `Video`.
 #[derive(Type, Deserialize)]
 pub struct IndexerRuleCreateArgs {
 	pub name: String,
@@ -124,33 +163,7 @@ impl IndexerRuleCreateArgs {
 			},
 		);
 
-		let rules_data = rmp_serde::to_vec_named(
-			&self
-				.rules
-				.into_iter()
-				.map(|(kind, parameters)| match kind {
-					RuleKind::AcceptFilesByGlob => {
-						RulePerKind::new_accept_files_by_globs_str(parameters)
-					}
-					RuleKind::RejectFilesByGlob => {
-						RulePerKind::new_reject_files_by_globs_str(parameters)
-					}
-					RuleKind::AcceptIfChildrenDirectoriesArePresent => {
-						Ok(RulePerKind::AcceptIfChildrenDirectoriesArePresent(
-							parameters.into_iter().collect(),
-						))
-					}
-					RuleKind::RejectIfChildrenDirectoriesArePresent => {
-						Ok(RulePerKind::RejectIfChildrenDirectoriesArePresent(
-							parameters.into_iter().collect(),
-						))
-					}
-					RuleKind::IgnoredByGit => {
-						Ok(RulePerKind::IgnoredByGit(PathBuf::new(), Search::default()))
-					}
-				})
-				.collect::<Result<Vec<_>, _>>()?,
-		)?;
+		let rules_data = rules_per_kind_to_db(self.rules)?;
 
 		if self.dry_run {
 			return Ok(None);
@@ -184,13 +197,22 @@ pub enum RuleKind {
 	AcceptIfChildrenDirectoriesArePresent = 2,
 	RejectIfChildrenDirectoriesArePresent = 3,
 	IgnoredByGit = 4,
+	RejectFilesLargerThan = 5,
+	AcceptFilesSmallerThan = 6,
+	AcceptIfExtendedAttributesArePresent = 7,
+	RejectIfExtendedAttributesArePresent = 8,
+	RejectFilesModifiedWithin = 9,
+	RejectFilesNotModifiedWithin = 10,
+	IgnoredBySpacedrive = 11,
+	AcceptByObjectKind = 12,
+	RejectByObjectKind = 13,
 }
 
 impl RuleKind {
 	#[must_use]
 	pub const fn variant_count() -> usize {
 		// TODO: Use https://doc.rust-lang.org/std/mem/fn.variant_count.html if it ever gets stabilized
-		5
+		14
 	}
 }
 
@@ -202,16 +224,39 @@ impl RuleKind {
 /// In case of `ParametersPerKind::AcceptIfChildrenDirectoriesArePresent` or
 /// `ParametersPerKind::RejectIfChildrenDirectoriesArePresent`
 /// first we change the data structure to a vector, then we serialize it.
+///
+/// `ParametersPerKind::RejectFilesLargerThan` and `ParametersPerKind::AcceptFilesSmallerThan`
+/// hold a size in bytes and never apply to directories.
+///
+/// `ParametersPerKind::RejectFilesModifiedWithin` and
+/// `ParametersPerKind::RejectFilesNotModifiedWithin` hold an amount of seconds counted back from
+/// the moment the rule is applied, using both gives a modification window. They also never apply
+/// to directories, as their modification date only changes when entries are added or removed.
+///
+/// Extended attributes are only available on unix systems, elsewhere entries are considered as
+/// not having any.
+///
+/// `ParametersPerKind::AcceptByObjectKind` and `ParametersPerKind::RejectByObjectKind` look
+/// into the magic bytes of files to tell their kind, see [`object_kind`]. They never apply to
+/// directories.
 #[derive(Debug, Clone)]
 pub enum RulePerKind {
-	// TODO: Add an indexer rule that filter files based on their extended attributes
+	// TODO: Add an indexer rule that filter files based on their Windows file attributes
 	// https://learn.microsoft.com/en-us/windows/win32/fileio/file-attribute-constants
-	// https://en.wikipedia.org/wiki/Extended_file_attributes
 	AcceptFilesByGlob(Vec<Glob>, GlobSet),
 	RejectFilesByGlob(Vec<Glob>, GlobSet),
 	AcceptIfChildrenDirectoriesArePresent(HashSet<String>),
 	RejectIfChildrenDirectoriesArePresent(HashSet<String>),
 	IgnoredByGit(PathBuf, Search),
+	RejectFilesLargerThan(u64),
+	AcceptFilesSmallerThan(u64),
+	AcceptIfExtendedAttributesArePresent(HashSet<String>),
+	RejectIfExtendedAttributesArePresent(HashSet<String>),
+	RejectFilesModifiedWithin(u64),
+	RejectFilesNotModifiedWithin(u64),
+	IgnoredBySpacedrive(PathBuf, Search),
+	AcceptByObjectKind(HashSet<ObjectKind>),
+	RejectByObjectKind(HashSet<ObjectKind>),
 }
 
 impl RulePerKind {
@@ -250,14 +295,82 @@ impl RulePerKind {
 	}
 }
 
+impl TryFrom<(RuleKind, Vec<String>)> for RulePerKind {
+	type Error = Error;
+
+	fn try_from((kind, parameters): (RuleKind, Vec<String>)) -> Result<Self, Self::Error> {
+		match kind {
+			RuleKind::AcceptFilesByGlob => Self::new_accept_files_by_globs_str(parameters),
+			RuleKind::RejectFilesByGlob => Self::new_reject_files_by_globs_str(parameters),
+			RuleKind::AcceptIfChildrenDirectoriesArePresent => Ok(
+				Self::AcceptIfChildrenDirectoriesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::RejectIfChildrenDirectoriesArePresent => Ok(
+				Self::RejectIfChildrenDirectoriesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::IgnoredByGit | RuleKind::IgnoredBySpacedrive => {
+				Err(Error::NotStorableRuleKind(kind))
+			}
+			RuleKind::RejectFilesLargerThan => {
+				parse_size_threshold(parameters).map(Self::RejectFilesLargerThan)
+			}
+			RuleKind::AcceptFilesSmallerThan => {
+				parse_size_threshold(parameters).map(Self::AcceptFilesSmallerThan)
+			}
+			RuleKind::AcceptIfExtendedAttributesArePresent => Ok(
+				Self::AcceptIfExtendedAttributesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::RejectIfExtendedAttributesArePresent => Ok(
+				Self::RejectIfExtendedAttributesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::RejectFilesModifiedWithin => {
+				parse_modified_window(parameters).map(Self::RejectFilesModifiedWithin)
+			}
+			RuleKind::RejectFilesNotModifiedWithin => {
+				parse_modified_window(parameters).map(Self::RejectFilesNotModifiedWithin)
+			}
+			RuleKind::AcceptByObjectKind => {
+				parse_object_kinds(parameters).map(Self::AcceptByObjectKind)
+			}
+			RuleKind::RejectByObjectKind => {
+				parse_object_kinds(parameters).map(Self::RejectByObjectKind)
+			}
+		}
+	}
+}
+
+/// Builds the rules from their kinds and parameters, serializing them to be stored in the
+/// `rules_per_kind` column of an indexer rule
+pub fn rules_per_kind_to_db(
+	rules: impl IntoIterator<Item = (RuleKind, Vec<String>)>,
+) -> Result<Vec<u8>, Error> {
+	rmp_serde::to_vec_named(
+		&rules
+			.into_iter()
+			.map(RulePerKind::try_from)
+			.collect::<Result<Vec<_>, _>>()?,
+	)
+	.map_err(Into::into)
+}
+
 pub trait MetadataForIndexerRules: Send + Sync + 'static {
 	fn is_dir(&self) -> bool;
+	fn size_in_bytes(&self) -> u64;
+	fn modified_at(&self) -> Option<DateTime<Utc>>;
 }
 
 impl MetadataForIndexerRules for Metadata {
 	fn is_dir(&self) -> bool {
 		self.is_dir()
 	}
+
+	fn size_in_bytes(&self) -> u64 {
+		self.len()
+	}
+
+	fn modified_at(&self) -> Option<DateTime<Utc>> {
+		self.modified().ok().map(Into::into)
+	}
 }
 
 impl RulePerKind {
@@ -290,10 +403,151 @@ impl RulePerKind {
 				RuleKind::IgnoredByGit,
 				accept_by_git_pattern(source, base_dir, patterns),
 			)),
+			Self::IgnoredBySpacedrive(location_root, patterns) => Ok((
+				RuleKind::IgnoredBySpacedrive,
+				accept_by_git_pattern(source, location_root, patterns),
+			)),
+			Self::RejectFilesLargerThan(max_size) => Ok((
+				RuleKind::RejectFilesLargerThan,
+				metadata.is_dir() || metadata.size_in_bytes() <= *max_size,
+			)),
+			Self::AcceptFilesSmallerThan(size) => Ok((
+				RuleKind::AcceptFilesSmallerThan,
+				metadata.is_dir() || metadata.size_in_bytes() < *size,
+			)),
+			Self::AcceptIfExtendedAttributesArePresent(wanted) => {
+				if metadata.is_dir() {
+					return Ok((RuleKind::AcceptIfExtendedAttributesArePresent, true));
+				}
+
+				extended_attributes(source).await.map(|attributes| {
+					(
+						RuleKind::AcceptIfExtendedAttributesArePresent,
+						has_any_extended_attribute(&attributes, wanted),
+					)
+				})
+			}
+			Self::RejectIfExtendedAttributesArePresent(wanted) => {
+				extended_attributes(source).await.map(|attributes| {
+					(
+						RuleKind::RejectIfExtendedAttributesArePresent,
+						!has_any_extended_attribute(&attributes, wanted),
+					)
+				})
+			}
+			Self::RejectFilesModifiedWithin(seconds) => Ok((
+				RuleKind::RejectFilesModifiedWithin,
+				metadata.is_dir()
+					|| modified_seconds_ago(metadata).map_or(true, |ago| ago > *seconds),
+			)),
+			Self::RejectFilesNotModifiedWithin(seconds) => Ok((
+				RuleKind::RejectFilesNotModifiedWithin,
+				metadata.is_dir()
+					|| modified_seconds_ago(metadata).map_or(true, |ago| ago <= *seconds),
+			)),
+			Self::AcceptByObjectKind(kinds) => Ok((
+				RuleKind::AcceptByObjectKind,
+				metadata.is_dir()
+					|| kinds.contains(&object_kind::classify(source.as_ref(), metadata).await.kind),
+			)),
+			Self::RejectByObjectKind(kinds) => Ok((
+				RuleKind::RejectByObjectKind,
+				metadata.is_dir()
+					|| !kinds
+						.contains(&object_kind::classify(source.as_ref(), metadata).await.kind),
+			)),
 		}
 	}
 }
 
+fn parse_single_number(
+	parameters: Vec<String>,
+	error_fn: impl FnOnce(Vec<String>) -> Error,
+) -> Result<u64, Error> {
+	let number = match parameters.as_slice() {
+		[number] => number.trim().parse().ok(),
+		_ => None,
+	};
+
+	number.ok_or_else(|| error_fn(parameters))
+}
+
+fn parse_size_threshold(parameters: Vec<String>) -> Result<u64, Error> {
+	parse_single_number(parameters, Error::InvalidSizeThreshold)
+}
+
+fn parse_modified_window(parameters: Vec<String>) -> Result<u64, Error> {
+	parse_single_number(parameters, Error::InvalidModifiedWindow)
+}
+
+fn parse_object_kinds(parameters: Vec<String>) -> Result<HashSet<ObjectKind>, Error> {
+	parameters
+		.into_iter()
+		.map(|name| {
+			serde_json::from_value(serde_json::Value::String(name.trim().to_string()))
+				.map_err(|_| Error::InvalidObjectKind(name))
+		})
+		.collect()
+}
+
+/// Files with a modification date in the future count as just modified, and files without one
+/// are never rejected by modification date rules
+fn modified_seconds_ago(metadata: &impl MetadataForIndexerRules) -> Option<u64> {
+	metadata.modified_at().map(|modified_at| {
+		(Utc::now() - modified_at)
+			.num_seconds()
+			.try_into()
+			.unwrap_or_default()
+	})
+}
+
+#[cfg(unix)]
+async fn extended_attributes(source: impl AsRef<Path> + Send) -> Result<HashSet<String>, Error> {
+	use std::io;
+
+	use tokio::task::spawn_blocking;
+
+	let source = source.as_ref().to_path_buf();
+
+	spawn_blocking({
+		let source = source.clone();
+		move || xattr::list(source)
+	})
+	.await
+	.map_err(io::Error::other)
+	.and_then(|res| res)
+	.map(|names| {
+		names
+			.map(|name| name.to_string_lossy().into_owned())
+			.collect()
+	})
+	.or_else(|e| {
+		// Some file systems, like FAT or network shares, just don't support extended attributes
+		if e.kind() == io::ErrorKind::Unsupported {
+			Ok(HashSet::new())
+		} else {
+			Err(Error::ExtendedAttributesFileIO(FileIOError::from((
+				source, e,
+			))))
+		}
+	})
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unused_async)]
+async fn extended_attributes(_source: impl AsRef<Path> + Send) -> Result<HashSet<String>, Error> {
+	Ok(HashSet::new())
+}
+
+fn has_any_extended_attribute(attributes: &HashSet<String>, wanted: &HashSet<String>) -> bool {
+	wanted.iter().any(|wanted| {
+		wanted.strip_suffix('*').map_or_else(
+			|| attributes.contains(wanted),
+			|prefix| attributes.iter().any(|name| name.starts_with(prefix)),
+		)
+	})
+}
+
 fn accept_by_git_pattern(
 	source: impl AsRef<Path>,
 	base_dir: impl AsRef<Path>,
@@ -349,16 +603,46 @@ impl IndexerRule {
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
 pub enum RulerDecision {
 	Accept,
 	Reject,
 }
 
+/// What a single indexer rule thinks about a path, see [`IndexerRuler::explain_path`]
+#[derive(Debug, Serialize, Type)]
+pub struct RuleExplanation {
+	/// Rules built from `.gitignore` or `.spacedriveignore` files aren't stored, so they don't
+	/// have an id
+	pub id: Option<i32>,
+	pub name: String,
+	/// Each kind of this rule and if it accepted the path
+	pub results: Vec<(RuleKind, bool)>,
+}
+
+impl RuleExplanation {
+	/// If any kind of this rule didn't accept the path
+	#[must_use]
+	pub fn fired(&self) -> bool {
+		self.results.iter().any(|(_, accepted)| !accepted)
+	}
+}
+
+#[derive(Debug, Serialize, Type)]
+pub struct PathExplanation {
+	pub rules: Vec<RuleExplanation>,
+	pub decision: RulerDecision,
+	/// How object kind rules classified the path, if there are any of them and the path is a file
+	pub object_kind: Option<ObjectKindClassification>,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct IndexerRuler {
 	base: Arc<Vec<IndexerRule>>,
 	extra: Vec<IndexerRule>,
+	/// Shared by every clone, so each `.spacedriveignore` file is read once per walk
+	#[serde(skip)]
+	spacedrive_ignore_files: Arc<SpacedriveIgnoreFiles>,
 }
 
 impl Clone for IndexerRuler {
@@ -367,6 +651,7 @@ impl Clone for IndexerRuler {
 			base: Arc::clone(&self.base),
 			// Each instance of IndexerRules MUST have its own extra rules no clones allowed!
 			extra: Vec::new(),
+			spacedrive_ignore_files: Arc::clone(&self.spacedrive_ignore_files),
 		}
 	}
 }
@@ -377,9 +662,15 @@ impl IndexerRuler {
 		Self {
 			base: Arc::new(rules),
 			extra: Vec::new(),
+			spacedrive_ignore_files: Arc::default(),
 		}
 	}
 
+	#[must_use]
+	pub fn spacedrive_ignore_files(&self) -> &SpacedriveIgnoreFiles {
+		&self.spacedrive_ignore_files
+	}
+
 	pub async fn evaluate_path(
 		&self,
 		source: impl AsRef<Path> + Send,
@@ -406,6 +697,84 @@ impl IndexerRuler {
 		inner(self, source.as_ref(), metadata).await
 	}
 
+	/// Same as [`IndexerRuler::evaluate_path`], but also telling what each rule thinks about
+	/// the path
+	pub async fn explain_path(
+		&self,
+		source: impl AsRef<Path> + Send,
+		metadata: &impl MetadataForIndexerRules,
+	) -> Result<PathExplanation, Error> {
+		async fn inner(
+			this: &IndexerRuler,
+			source: &Path,
+			metadata: &impl MetadataForIndexerRules,
+		) -> Result<PathExplanation, Error> {
+			let rules = this
+				.base
+				.iter()
+				.chain(this.extra.iter())
+				.map(|rule| async move {
+					rule.apply(source, metadata)
+						.await
+						.map(|results| RuleExplanation {
+							id: rule.id,
+							name: rule.name.clone(),
+							results,
+						})
+				})
+				.collect::<Vec<_>>()
+				.try_join()
+				.await?;
+
+			let acceptance_per_rule_kind = rules
+				.iter()
+				.flat_map(|rule| rule.results.iter().copied())
+				.fold(
+					HashMap::<_, Vec<_>>::with_capacity(RuleKind::variant_count()),
+					|mut map, (kind, result)| {
+						map.entry(kind).or_default().push(result);
+						map
+					},
+				);
+
+			let decision = if IndexerRuler::reject_path(
+				source,
+				metadata.is_dir(),
+				&acceptance_per_rule_kind,
+			) {
+				RulerDecision::Reject
+			} else {
+				RulerDecision::Accept
+			};
+
+			let has_object_kind_rules = this
+				.base
+				.iter()
+				.chain(this.extra.iter())
+				.flat_map(|rule| rule.rules.iter())
+				.any(|rule| {
+					matches!(
+						rule,
+						RulePerKind::AcceptByObjectKind(_) | RulePerKind::RejectByObjectKind(_)
+					)
+				});
+
+			let object_kind = if has_object_kind_rules && !metadata.is_dir() {
+				Some(object_kind::classify(source, metadata).await)
+			} else {
+				None
+			};
+
+			Ok(PathExplanation {
+				rules,
+				decision,
+				object_kind,
+			})
+		}
+
+		inner(self, source.as_ref(), metadata).await
+	}
+
 	pub async fn apply_all(
 		&self,
 		source: impl AsRef<Path> + Send,
@@ -458,6 +827,11 @@ impl IndexerRuler {
 	) -> bool {
 		Self::rejected_by_reject_glob(acceptance_per_rule_kind)
 			|| Self::rejected_by_git_ignore(acceptance_per_rule_kind)
+			|| Self::rejected_by_spacedrive_ignore(acceptance_per_rule_kind)
+			|| Self::rejected_by_size(acceptance_per_rule_kind)
+			|| Self::rejected_by_extended_attributes(acceptance_per_rule_kind)
+			|| Self::rejected_by_modified_date(acceptance_per_rule_kind)
+			|| Self::rejected_by_object_kind(acceptance_per_rule_kind)
 			|| (is_dir && Self::rejected_by_children_directories(acceptance_per_rule_kind))
 			|| Self::rejected_by_accept_glob(acceptance_per_rule_kind)
 	}
@@ -523,6 +897,129 @@ impl IndexerRuler {
 
 		res
 	}
+
+	pub fn rejected_by_spacedrive_ignore(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		let res = acceptance_per_rule_kind
+			.get(&RuleKind::IgnoredBySpacedrive)
+			.map_or(false, |reject_results| {
+				reject_results.iter().any(|reject| !reject)
+			});
+
+		if res {
+			trace!("Rejected by `RuleKind::IgnoredBySpacedrive`");
+		}
+
+		res
+	}
+
+	pub fn rejected_by_size(acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>) -> bool {
+		let rejected_by_max_size = acceptance_per_rule_kind
+			.get(&RuleKind::RejectFilesLargerThan)
+			.map_or(false, |reject_results| {
+				reject_results.iter().any(|reject| !reject)
+			});
+
+		if rejected_by_max_size {
+			trace!("Rejected by `RuleKind::RejectFilesLargerThan`");
+			return true;
+		}
+
+		let rejected_by_accept_size = acceptance_per_rule_kind
+			.get(&RuleKind::AcceptFilesSmallerThan)
+			.map_or(false, |accept_rules| {
+				accept_rules.iter().all(|accept| !accept)
+			});
+
+		if rejected_by_accept_size {
+			trace!(
+				"Reject because it didn't passed in any `RuleKind::AcceptFilesSmallerThan` rules"
+			);
+		}
+
+		rejected_by_accept_size
+	}
+
+	pub fn rejected_by_extended_attributes(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		let rejected_by_attributes = acceptance_per_rule_kind
+			.get(&RuleKind::RejectIfExtendedAttributesArePresent)
+			.map_or(false, |reject_results| {
+				reject_results.iter().any(|reject| !reject)
+			});
+
+		if rejected_by_attributes {
+			trace!("Rejected by `RuleKind::RejectIfExtendedAttributesArePresent`");
+			return true;
+		}
+
+		let rejected_by_accept_attributes = acceptance_per_rule_kind
+			.get(&RuleKind::AcceptIfExtendedAttributesArePresent)
+			.map_or(false, |accept_rules| {
+				accept_rules.iter().all(|accept| !accept)
+			});
+
+		if rejected_by_accept_attributes {
+			trace!(
+				"Reject because it didn't passed in any \
+				`RuleKind::AcceptIfExtendedAttributesArePresent` rules"
+			);
+		}
+
+		rejected_by_accept_attributes
+	}
+
+	pub fn rejected_by_modified_date(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		[
+			RuleKind::RejectFilesModifiedWithin,
+			RuleKind::RejectFilesNotModifiedWithin,
+		]
+		.into_iter()
+		.any(|kind| {
+			let res = acceptance_per_rule_kind
+				.get(&kind)
+				.map_or(false, |reject_results| {
+					reject_results.iter().any(|reject| !reject)
+				});
+
+			if res {
+				trace!("Rejected by `RuleKind::{kind:?}`");
+			}
+
+			res
+		})
+	}
+
+	pub fn rejected_by_object_kind(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		let rejected_by_kind = acceptance_per_rule_kind
+			.get(&RuleKind::RejectByObjectKind)
+			.map_or(false, |reject_results| {
+				reject_results.iter().any(|reject| !reject)
+			});
+
+		if rejected_by_kind {
+			trace!("Rejected by `RuleKind::RejectByObjectKind`");
+			return true;
+		}
+
+		let rejected_by_accept_kind = acceptance_per_rule_kind
+			.get(&RuleKind::AcceptByObjectKind)
+			.map_or(false, |accept_rules| {
+				accept_rules.iter().all(|accept| !accept)
+			});
+
+		if rejected_by_accept_kind {
+			trace!("Reject because it didn't passed in any `RuleKind::AcceptByObjectKind` rules");
+		}
+
+		rejected_by_accept_kind
+	}
 }
 
 impl TryFrom<&indexer_rule::Data> for IndexerRule {
@@ -903,8 +1400,38 @@ mod tests {
 				| (
 					Self::RejectIfChildrenDirectoriesArePresent(self_childrens),
 					Self::RejectIfChildrenDirectoriesArePresent(other_childrens),
+				)
+				| (
+					Self::AcceptIfExtendedAttributesArePresent(self_childrens),
+					Self::AcceptIfExtendedAttributesArePresent(other_childrens),
+				)
+				| (
+					Self::RejectIfExtendedAttributesArePresent(self_childrens),
+					Self::RejectIfExtendedAttributesArePresent(other_childrens),
 				) => self_childrens == other_childrens,
 
+				(Self::AcceptByObjectKind(self_kinds), Self::AcceptByObjectKind(other_kinds))
+				| (Self::RejectByObjectKind(self_kinds), Self::RejectByObjectKind(other_kinds)) => {
+					self_kinds == other_kinds
+				}
+
+				(
+					Self::RejectFilesLargerThan(self_size),
+					Self::RejectFilesLargerThan(other_size),
+				)
+				| (
+					Self::AcceptFilesSmallerThan(self_size),
+					Self::AcceptFilesSmallerThan(other_size),
+				)
+				| (
+					Self::RejectFilesModifiedWithin(self_size),
+					Self::RejectFilesModifiedWithin(other_size),
+				)
+				| (
+					Self::RejectFilesNotModifiedWithin(self_size),
+					Self::RejectFilesNotModifiedWithin(other_size),
+				) => self_size == other_size,
+
 				_ => false,
 			}
 		}
@@ -944,4 +1471,433 @@ mod tests {
 
 		assert_eq!(actual, expected);
 	}
+
+	#[tokio::test]
+	async fn test_reject_files_by_size() {
+		let root = tempdir().unwrap();
+
+		let small = root.path().join("small.txt");
+		let big = root.path().join("big.img");
+		let dir = root.path().join("dir");
+
+		fs::write(&small, vec![0; 10]).await.unwrap();
+		fs::write(&big, vec![0; 1000]).await.unwrap();
+		fs::create_dir(&dir).await.unwrap();
+
+		for rule in [
+			RulePerKind::RejectFilesLargerThan(100),
+			RulePerKind::AcceptFilesSmallerThan(100),
+		] {
+			let ruler = IndexerRuler::new(vec![IndexerRule::new(
+				"no big files".to_string(),
+				false,
+				vec![rule],
+			)]);
+
+			for (path, expected) in [
+				(&small, RulerDecision::Accept),
+				(&big, RulerDecision::Reject),
+				(&dir, RulerDecision::Accept),
+			] {
+				assert_eq!(
+					ruler
+						.evaluate_path(path, &fs::metadata(path).await.unwrap())
+						.await
+						.unwrap(),
+					expected
+				);
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn test_object_kind() {
+		const PNG: [u8; 12] = [
+			0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+		];
+		const ZIP: [u8; 6] = [0x50, 0x4B, 0x03, 0x04, 0x14, 0x00];
+
+		let root = tempdir().unwrap();
+
+		let picture = root.path().join("picture.png");
+		let extensionless_picture = root.path().join("download");
+		let misnamed_archive = root.path().join("archive.jpg");
+		let empty_song = root.path().join("song.mp3");
+		let notes = root.path().join("notes.md");
+		let dir = root.path().join("dir");
+
+		fs::write(&picture, PNG).await.unwrap();
+		fs::write(&extensionless_picture, PNG).await.unwrap();
+		fs::write(&misnamed_archive, ZIP).await.unwrap();
+		fs::write(&empty_song, b"").await.unwrap();
+		fs::write(&notes, "# Notes").await.unwrap();
+		fs::create_dir(&dir).await.unwrap();
+
+		for (path, kind, method) in [
+			(
+				&picture,
+				ObjectKind::Image,
+				ClassificationMethod::MagicBytes,
+			),
+			(
+				&extensionless_picture,
+				ObjectKind::Image,
+				ClassificationMethod::MagicBytes,
+			),
+			(
+				&misnamed_archive,
+				ObjectKind::Archive,
+				ClassificationMethod::MagicBytes,
+			),
+			(
+				&empty_song,
+				ObjectKind::Audio,
+				ClassificationMethod::Extension,
+			),
+			(&notes, ObjectKind::Text, ClassificationMethod::Extension),
+		] {
+			assert_eq!(
+				object_kind::classify(path, &fs::metadata(path).await.unwrap()).await,
+				ObjectKindClassification { kind, method },
+				"{}",
+				path.display()
+			);
+		}
+
+		let only_images = IndexerRuler::new(vec![IndexerRule::new(
+			"only images".to_string(),
+			false,
+			vec![RulePerKind::AcceptByObjectKind(HashSet::from([
+				ObjectKind::Image,
+			]))],
+		)]);
+
+		let no_images = IndexerRuler::new(vec![IndexerRule::new(
+			"no images".to_string(),
+			false,
+			vec![RulePerKind::RejectByObjectKind(HashSet::from([
+				ObjectKind::Image,
+			]))],
+		)]);
+
+		for (path, expected_only_images, expected_no_images) in [
+			(&picture, RulerDecision::Accept, RulerDecision::Reject),
+			(
+				&extensionless_picture,
+				RulerDecision::Accept,
+				RulerDecision::Reject,
+			),
+			(
+				&misnamed_archive,
+				RulerDecision::Reject,
+				RulerDecision::Accept,
+			),
+			(&notes, RulerDecision::Reject, RulerDecision::Accept),
+			(&dir, RulerDecision::Accept, RulerDecision::Accept),
+		] {
+			let metadata = fs::metadata(path).await.unwrap();
+
+			assert_eq!(
+				only_images.evaluate_path(path, &metadata).await.unwrap(),
+				expected_only_images
+			);
+			assert_eq!(
+				no_images.evaluate_path(path, &metadata).await.unwrap(),
+				expected_no_images
+			);
+		}
+	}
+
+	struct FakeMetadata {
+		is_dir: bool,
+		modified_at: Option<DateTime<Utc>>,
+	}
+
+	impl MetadataForIndexerRules for FakeMetadata {
+		fn is_dir(&self) -> bool {
+			self.is_dir
+		}
+
+		fn size_in_bytes(&self) -> u64 {
+			0
+		}
+
+		fn modified_at(&self) -> Option<DateTime<Utc>> {
+			self.modified_at
+		}
+	}
+
+	#[tokio::test]
+	async fn test_modification_window() {
+		const DAY: u64 = 24 * 60 * 60;
+
+		// Only files modified between 5 years and a day ago
+		let ruler = IndexerRuler::new(vec![IndexerRule::new(
+			"modification window".to_string(),
+			false,
+			vec![
+				RulePerKind::RejectFilesModifiedWithin(DAY),
+				RulePerKind::RejectFilesNotModifiedWithin(5 * 365 * DAY),
+			],
+		)]);
+
+		let now = Utc::now();
+
+		for (is_dir, modified_at, expected) in [
+			(false, Some(now), RulerDecision::Reject),
+			(
+				false,
+				Some(now + chrono::Duration::days(1)),
+				RulerDecision::Reject,
+			),
+			(
+				false,
+				Some(now - chrono::Duration::days(30)),
+				RulerDecision::Accept,
+			),
+			(
+				false,
+				Some(now - chrono::Duration::days(6 * 365)),
+				RulerDecision::Reject,
+			),
+			(false, None, RulerDecision::Accept),
+			(
+				true,
+				Some(now - chrono::Duration::days(6 * 365)),
+				RulerDecision::Accept,
+			),
+		] {
+			assert_eq!(
+				ruler
+					.evaluate_path(
+						"file",
+						&FakeMetadata {
+							is_dir,
+							modified_at
+						}
+					)
+					.await
+					.unwrap(),
+				expected
+			);
+		}
+	}
+
+	#[test]
+	fn test_extended_attributes_matching() {
+		let attributes = HashSet::from([
+			"user.spacedrive.ignore".to_string(),
+			"com.apple.metadata:kMDItemWhereFroms".to_string(),
+		]);
+
+		for (wanted, expected) in [
+			(vec!["user.spacedrive.ignore"], true),
+			(vec!["user.spacedrive"], false),
+			(vec!["user.spacedrive.*"], true),
+			(vec!["com.apple.metadata:*"], true),
+			(vec!["com.apple.quarantine", "user.other"], false),
+			(vec![], false),
+		] {
+			assert_eq!(
+				has_any_extended_attribute(
+					&attributes,
+					&wanted.into_iter().map(ToString::to_string).collect()
+				),
+				expected
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_spacedrive_ignore() {
+		let root = tempdir().unwrap();
+
+		let photos = root.path().join("photos");
+		let raws = photos.join("raws");
+
+		fs::create_dir_all(&raws).await.unwrap();
+		fs::create_dir(root.path().join("cache")).await.unwrap();
+
+		fs::write(
+			root.path().join(seed::SPACEDRIVE_IGNORE_FILE_NAME),
+			"*.log\ncache/\n",
+		)
+		.await
+		.unwrap();
+		fs::write(
+			photos.join(seed::SPACEDRIVE_IGNORE_FILE_NAME),
+			"*.xmp\n!keep.log\n",
+		)
+		.await
+		.unwrap();
+
+		for (current, file, expected) in [
+			(root.path(), "app.log", RulerDecision::Reject),
+			(root.path(), "cache", RulerDecision::Reject),
+			(root.path(), "notes.xmp", RulerDecision::Accept),
+			(photos.as_path(), "app.log", RulerDecision::Reject),
+			(photos.as_path(), "keep.log", RulerDecision::Accept),
+			(photos.as_path(), "photo.xmp", RulerDecision::Reject),
+			(raws.as_path(), "photo.xmp", RulerDecision::Reject),
+			(raws.as_path(), "photo.cr2", RulerDecision::Accept),
+		] {
+			let path = current.join(file);
+			if !path.exists() {
+				fs::write(&path, "").await.unwrap();
+			}
+
+			let mut ruler = IndexerRuler::default();
+			let (rules, errors) = seed::SpacedriveIgnoreRules::get_rules(
+				root.path(),
+				current,
+				ruler.spacedrive_ignore_files(),
+			)
+			.await;
+			assert!(errors.is_empty());
+			ruler.extend(rules.map(Into::into));
+
+			assert_eq!(
+				ruler
+					.evaluate_path(&path, &fs::metadata(&path).await.unwrap())
+					.await
+					.unwrap(),
+				expected,
+				"{}",
+				path.display()
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_unreadable_spacedrive_ignore() {
+		let root = tempdir().unwrap();
+
+		let photos = root.path().join("photos");
+
+		fs::create_dir_all(&photos).await.unwrap();
+		fs::write(
+			root.path().join(seed::SPACEDRIVE_IGNORE_FILE_NAME),
+			"*.log\n",
+		)
+		.await
+		.unwrap();
+		// A directory with the ignore file name can't be read as one
+		fs::create_dir(photos.join(seed::SPACEDRIVE_IGNORE_FILE_NAME))
+			.await
+			.unwrap();
+
+		let ruler = IndexerRuler::default();
+
+		let (rules, errors) = seed::SpacedriveIgnoreRules::get_rules(
+			root.path(),
+			&photos,
+			ruler.spacedrive_ignore_files(),
+		)
+		.await;
+		assert!(rules.is_some());
+		assert_eq!(errors.len(), 1);
+
+		// The unreadable file is only reported once per walk, even by clones of the ruler
+		let cloned_ruler = ruler.clone();
+		let (rules, errors) = seed::SpacedriveIgnoreRules::get_rules(
+			root.path(),
+			&photos,
+			cloned_ruler.spacedrive_ignore_files(),
+		)
+		.await;
+		assert!(rules.is_some());
+		assert!(errors.is_empty());
+	}
+
+	#[test]
+	fn new_rule_kinds_serde() {
+		let actual = IndexerRule::new(
+			"No Big Files".to_string(),
+			false,
+			vec![
+				RulePerKind::RejectFilesLargerThan(1024),
+				RulePerKind::AcceptFilesSmallerThan(4096),
+				RulePerKind::AcceptIfExtendedAttributesArePresent(HashSet::from([
+					"user.spacedrive.keep".to_string(),
+				])),
+				RulePerKind::RejectIfExtendedAttributesArePresent(HashSet::from([
+					"user.spacedrive.ignore".to_string(),
+				])),
+				RulePerKind::RejectFilesModifiedWithin(60),
+				RulePerKind::RejectFilesNotModifiedWithin(3600),
+				RulePerKind::AcceptByObjectKind(HashSet::from([ObjectKind::Video])),
+				RulePerKind::RejectByObjectKind(HashSet::from([ObjectKind::Executable])),
+			],
+		);
+
+		let expected =
+			rmp_serde::from_slice::<IndexerRule>(&rmp_serde::to_vec_named(&actual).unwrap())
+				.unwrap();
+
+		assert_eq!(actual, expected);
+	}
+
+	#[test]
+	fn portable_rules_round_trip() {
+		let rule = IndexerRule::new(
+			"Everything".to_string(),
+			false,
+			vec![
+				RulePerKind::new_accept_files_by_globs_str(["**/*.rs", "**/*.toml"]).unwrap(),
+				RulePerKind::new_reject_files_by_globs_str(["**/target"]).unwrap(),
+				RulePerKind::AcceptIfChildrenDirectoriesArePresent(HashSet::from([
+					".git".to_string(),
+					"src".to_string(),
+				])),
+				RulePerKind::RejectIfChildrenDirectoriesArePresent(HashSet::from([
+					"node_modules".to_string()
+				])),
+				RulePerKind::RejectFilesLargerThan(1024),
+				RulePerKind::AcceptFilesSmallerThan(4096),
+				RulePerKind::AcceptIfExtendedAttributesArePresent(HashSet::from([
+					"user.spacedrive.*".to_string(),
+				])),
+				RulePerKind::RejectIfExtendedAttributesArePresent(HashSet::from([
+					"com.apple.quarantine".to_string(),
+				])),
+				RulePerKind::RejectFilesModifiedWithin(60),
+				RulePerKind::RejectFilesNotModifiedWithin(3600),
+				RulePerKind::AcceptByObjectKind(HashSet::from([
+					ObjectKind::Image,
+					ObjectKind::Video,
+				])),
+				RulePerKind::RejectByObjectKind(HashSet::from([ObjectKind::Executable])),
+			],
+		);
+
+		let json = PortableIndexerRules::new([PortableIndexerRule::from(&rule)])
+			.to_json()
+			.unwrap();
+
+		let imported = PortableIndexerRules::from_json(&json).unwrap();
+		assert_eq!(imported.rules.len(), 1);
+		assert_eq!(imported.rules[0].name, rule.name);
+
+		let rules = imported.rules[0]
+			.kinds_and_parameters()
+			.into_iter()
+			.map(RulePerKind::try_from)
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+
+		assert_eq!(rules, rule.rules);
+	}
+
+	#[test]
+	fn portable_rules_rejects_unknown_versions_and_dynamic_kinds() {
+		assert!(matches!(
+			PortableIndexerRules::from_json(r#"{ "version": 999, "rules": [] }"#),
+			Err(Error::InvalidPortableRules(_))
+		));
+
+		assert!(matches!(
+			rules_per_kind_to_db([(RuleKind::IgnoredByGit, vec![])]),
+			Err(Error::NotStorableRuleKind(RuleKind::IgnoredByGit))
+		));
+	}
 }
@@ -0,0 +1,147 @@
+//! Tells the kind of a file for [`RulePerKind::AcceptByObjectKind`](super::RulePerKind) and
+//! [`RulePerKind::RejectByObjectKind`](super::RulePerKind) rules.
+//!
+//! File names can't be trusted, so the magic bytes of the file are checked against the ones
+//! expected for its extension, and files without an extension or with a wrong one are identified
+//! by their magic bytes alone. If reading the file fails or takes too long, like on slow network
+//! shares, only the extension is used.
+
+use sd_file_ext::{
+	extensions::Extension,
+	kind::ObjectKind,
+	magic::{ExtensionPossibility, MAGIC_BYTES_HEADER_LEN},
+};
+
+use std::{
+	ffi::OsStr,
+	io::{self, SeekFrom},
+	path::Path,
+	time::Duration,
+};
+
+use serde::Serialize;
+use specta::Type;
+use tokio::{
+	fs::File,
+	io::{AsyncReadExt, AsyncSeekExt},
+	time::timeout,
+};
+use tracing::trace;
+
+use super::MetadataForIndexerRules;
+
+/// Reading a file for its magic bytes is given up after this long
+const MAGIC_BYTES_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub enum ClassificationMethod {
+	/// The contents of the file were checked
+	MagicBytes,
+	/// Only the extension of the file was used
+	Extension,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+pub struct ObjectKindClassification {
+	pub kind: ObjectKind,
+	pub method: ClassificationMethod,
+}
+
+impl ObjectKindClassification {
+	fn new(extension: Extension, method: ClassificationMethod) -> Self {
+		Self {
+			kind: extension.into(),
+			method,
+		}
+	}
+}
+
+/// Classifies a file, directories must not be given here as they're always
+/// [`ObjectKind::Folder`]
+pub async fn classify(
+	source: &Path,
+	metadata: &impl MetadataForIndexerRules,
+) -> ObjectKindClassification {
+	// Empty files don't have any magic bytes to check
+	if metadata.size_in_bytes() == 0 {
+		return classify_by_extension(source);
+	}
+
+	let classification = match timeout(MAGIC_BYTES_TIMEOUT, classify_by_content(source)).await {
+		Ok(Ok(classification)) => classification,
+		Ok(Err(e)) => {
+			trace!(
+				source = %source.display(),
+				?e,
+				"Failed to read magic bytes, classifying by extension;",
+			);
+			classify_by_extension(source)
+		}
+		Err(_) => {
+			trace!(
+				source = %source.display(),
+				"Reading magic bytes took too long, classifying by extension;",
+			);
+			classify_by_extension(source)
+		}
+	};
+
+	trace!(source = %source.display(), ?classification, "Classified file;");
+
+	classification
+}
+
+async fn classify_by_content(source: &Path) -> Result<ObjectKindClassification, io::Error> {
+	let extension = Extension::resolve_conflicting(source, false).await;
+
+	let mut file = File::open(source).await?;
+
+	if let Some(extension) = extension {
+		match extension.check_magic_bytes(&mut file).await {
+			Some(true) => {
+				return Ok(ObjectKindClassification::new(
+					extension,
+					ClassificationMethod::MagicBytes,
+				))
+			}
+			// Text files, source code and the like don't have magic bytes, so their extension
+			// is the best we have
+			None => {
+				return Ok(ObjectKindClassification::new(
+					extension,
+					ClassificationMethod::Extension,
+				))
+			}
+			// Misnamed file, let's see what it really is
+			Some(false) => {}
+		}
+	}
+
+	let mut header = Vec::with_capacity(MAGIC_BYTES_HEADER_LEN);
+	file.seek(SeekFrom::Start(0)).await?;
+	file.take(MAGIC_BYTES_HEADER_LEN as u64)
+		.read_to_end(&mut header)
+		.await?;
+
+	Ok(Extension::from_magic_bytes(&header).map_or_else(
+		|| classify_by_extension(source),
+		|extension| ObjectKindClassification::new(extension, ClassificationMethod::MagicBytes),
+	))
+}
+
+fn classify_by_extension(source: &Path) -> ObjectKindClassification {
+	let kind = source
+		.extension()
+		.and_then(OsStr::to_str)
+		.and_then(Extension::from_str)
+		.map_or(ObjectKind::Unknown, |possibility| match possibility {
+			ExtensionPossibility::Known(extension) => extension.into(),
+			// Telling these apart requires reading the file
+			ExtensionPossibility::Conflicts(_) => ObjectKind::Unknown,
+		});
+
+	ObjectKindClassification {
+		kind,
+		method: ClassificationMethod::Extension,
+	}
+}
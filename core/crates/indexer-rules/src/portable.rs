@@ -0,0 +1,139 @@
+//! Human readable format to share indexer rules between libraries.
+//!
+//! Each rule is written with the same kinds and parameters received by
+//! [`IndexerRuleCreateArgs`](super::IndexerRuleCreateArgs), so files can also be written by hand.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Error, IndexerRule, RuleKind, RulePerKind};
+
+/// Bumped whenever the format changes in a way older versions can't read
+pub const PORTABLE_INDEXER_RULES_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortableIndexerRules {
+	pub version: u32,
+	pub rules: Vec<PortableIndexerRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PortableIndexerRule {
+	pub name: String,
+	pub rules: Vec<PortableRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PortableRule {
+	pub kind: RuleKind,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub parameters: Vec<String>,
+}
+
+impl PortableIndexerRules {
+	#[must_use]
+	pub fn new(rules: impl IntoIterator<Item = PortableIndexerRule>) -> Self {
+		Self {
+			version: PORTABLE_INDEXER_RULES_VERSION,
+			rules: rules.into_iter().collect(),
+		}
+	}
+
+	pub fn to_json(&self) -> Result<String, Error> {
+		serde_json::to_string_pretty(self).map_err(|e| Error::InvalidPortableRules(e.to_string()))
+	}
+
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		let rules = serde_json::from_str::<Self>(json)
+			.map_err(|e| Error::InvalidPortableRules(e.to_string()))?;
+
+		if rules.version > PORTABLE_INDEXER_RULES_VERSION {
+			return Err(Error::InvalidPortableRules(format!(
+				"unsupported version {}, the newest supported one is {PORTABLE_INDEXER_RULES_VERSION}",
+				rules.version
+			)));
+		}
+
+		Ok(rules)
+	}
+}
+
+impl PortableIndexerRule {
+	/// The kinds and parameters in the shape expected by
+	/// [`IndexerRuleCreateArgs`](super::IndexerRuleCreateArgs)
+	#[must_use]
+	pub fn kinds_and_parameters(&self) -> Vec<(RuleKind, Vec<String>)> {
+		self.rules
+			.iter()
+			.map(|rule| (rule.kind, rule.parameters.clone()))
+			.collect()
+	}
+}
+
+impl From<&IndexerRule> for PortableIndexerRule {
+	fn from(rule: &IndexerRule) -> Self {
+		Self {
+			name: rule.name.clone(),
+			rules: rule.rules.iter().map(PortableRule::from).collect(),
+		}
+	}
+}
+
+impl From<&RulePerKind> for PortableRule {
+	fn from(rule: &RulePerKind) -> Self {
+		fn sorted(items: impl IntoIterator<Item = impl ToString>) -> Vec<String> {
+			let mut items = items
+				.into_iter()
+				.map(|item| item.to_string())
+				.collect::<Vec<_>>();
+			items.sort_unstable();
+			items
+		}
+
+		let (kind, parameters) = match rule {
+			RulePerKind::AcceptFilesByGlob(globs, _) => (
+				RuleKind::AcceptFilesByGlob,
+				globs.iter().map(|glob| glob.glob().to_string()).collect(),
+			),
+			RulePerKind::RejectFilesByGlob(globs, _) => (
+				RuleKind::RejectFilesByGlob,
+				globs.iter().map(|glob| glob.glob().to_string()).collect(),
+			),
+			RulePerKind::AcceptIfChildrenDirectoriesArePresent(children) => (
+				RuleKind::AcceptIfChildrenDirectoriesArePresent,
+				sorted(children),
+			),
+			RulePerKind::RejectIfChildrenDirectoriesArePresent(children) => (
+				RuleKind::RejectIfChildrenDirectoriesArePresent,
+				sorted(children),
+			),
+			RulePerKind::IgnoredByGit(_, _) => (RuleKind::IgnoredByGit, vec![]),
+			RulePerKind::RejectFilesLargerThan(size) => {
+				(RuleKind::RejectFilesLargerThan, vec![size.to_string()])
+			}
+			RulePerKind::AcceptFilesSmallerThan(size) => {
+				(RuleKind::AcceptFilesSmallerThan, vec![size.to_string()])
+			}
+			RulePerKind::AcceptIfExtendedAttributesArePresent(attributes) => (
+				RuleKind::AcceptIfExtendedAttributesArePresent,
+				sorted(attributes),
+			),
+			RulePerKind::RejectIfExtendedAttributesArePresent(attributes) => (
+				RuleKind::RejectIfExtendedAttributesArePresent,
+				sorted(attributes),
+			),
+			RulePerKind::RejectFilesModifiedWithin(seconds) => (
+				RuleKind::RejectFilesModifiedWithin,
+				vec![seconds.to_string()],
+			),
+			RulePerKind::RejectFilesNotModifiedWithin(seconds) => (
+				RuleKind::RejectFilesNotModifiedWithin,
+				vec![seconds.to_string()],
+			),
+			RulePerKind::IgnoredBySpacedrive(_, _) => (RuleKind::IgnoredBySpacedrive, vec![]),
+			RulePerKind::AcceptByObjectKind(kinds) => (RuleKind::AcceptByObjectKind, sorted(kinds)),
+			RulePerKind::RejectByObjectKind(kinds) => (RuleKind::RejectByObjectKind, sorted(kinds)),
+		};
+
+		Self { kind, parameters }
+	}
+}
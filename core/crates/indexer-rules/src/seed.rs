@@ -1,8 +1,9 @@
 use sd_prisma::prisma::{indexer_rule, PrismaClient};
 
 use std::{
+	collections::HashMap,
 	path::{Path, PathBuf},
-	sync::LazyLock,
+	sync::{LazyLock, Mutex, PoisonError},
 };
 
 use chrono::Utc;
@@ -21,6 +22,8 @@ pub enum SeederError {
 	DatabaseError(#[from] prisma_client_rust::QueryError),
 	#[error("Failed to parse indexer rules based on external system")]
 	InheritedExternalRules,
+	#[error("Failed to read ignore file <path='{}'>: {1}", .0.display())]
+	IgnoreFile(PathBuf, String),
 }
 
 #[derive(Debug)]
@@ -129,6 +132,122 @@ impl From<GitIgnoreRules> for IndexerRule {
 	}
 }
 
+/// Name of the files users can drop in any directory of a location to ignore entries inside it,
+/// following the `.gitignore` syntax
+pub const SPACEDRIVE_IGNORE_FILE_NAME: &str = ".spacedriveignore";
+
+#[derive(Debug)]
+pub struct SpacedriveIgnoreRules {
+	rules: RulePerKind,
+}
+
+impl SpacedriveIgnoreRules {
+	/// Gathers every `.spacedriveignore` file from `current` up to `location_root`, each one only
+	/// applying to the directory it's in and its subdirectories. Files which couldn't be read are
+	/// returned as errors alongside the rules from the other ones
+	pub async fn get_rules(
+		location_root: &Path,
+		current: &Path,
+		ignore_files: &SpacedriveIgnoreFiles,
+	) -> (Option<Self>, Vec<SeederError>) {
+		let mut lists = Vec::new();
+		let mut errors = Vec::new();
+
+		for ancestor in current
+			.ancestors()
+			.take_while(|&path| path.starts_with(location_root))
+		{
+			let ignore = ancestor.join(SPACEDRIVE_IGNORE_FILE_NAME);
+
+			let list = if let Some(list) = ignore_files.get(&ignore) {
+				list
+			} else {
+				let list = Self::parse_ignore(location_root.to_owned(), ignore.clone())
+					.await
+					.unwrap_or_else(|e| {
+						errors.push(e);
+						None
+					});
+
+				ignore_files.insert(ignore, list.clone());
+
+				list
+			};
+
+			lists.extend(list);
+		}
+
+		if lists.is_empty() {
+			return (None, errors);
+		}
+
+		// Patterns from lists added later take precedence, so the deepest file must be the last one
+		lists.reverse();
+
+		let mut search = Search::default();
+		search.patterns.extend(lists);
+
+		(
+			Some(Self {
+				rules: RulePerKind::IgnoredBySpacedrive(location_root.to_owned(), search),
+			}),
+			errors,
+		)
+	}
+
+	async fn parse_ignore(
+		location_root: PathBuf,
+		ignore: PathBuf,
+	) -> Result<Option<List<Ignore>>, SeederError> {
+		let path = ignore.clone();
+
+		tokio::task::spawn_blocking(move || {
+			let mut buf = Vec::with_capacity(30);
+			// Giving the location root makes the patterns relative to the file's own directory,
+			// and missing files are read as `None`
+			List::from_file(&path, Some(&location_root), true, &mut buf)
+				.map_err(|e| SeederError::IgnoreFile(path, e.to_string()))
+		})
+		.await
+		.map_err(|e| SeederError::IgnoreFile(ignore, e.to_string()))?
+	}
+}
+
+/// `.spacedriveignore` files already read during a walk, including the ones missing or which
+/// couldn't be read, so each file is read once instead of once for every directory below it
+#[derive(Debug, Default)]
+pub struct SpacedriveIgnoreFiles(Mutex<HashMap<PathBuf, Option<List<Ignore>>>>);
+
+impl SpacedriveIgnoreFiles {
+	fn get(&self, ignore: &Path) -> Option<Option<List<Ignore>>> {
+		self.0
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.get(ignore)
+			.cloned()
+	}
+
+	fn insert(&self, ignore: PathBuf, list: Option<List<Ignore>>) {
+		self.0
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(ignore, list);
+	}
+}
+
+impl From<SpacedriveIgnoreRules> for IndexerRule {
+	fn from(spacedrive_ignore: SpacedriveIgnoreRules) -> Self {
+		Self {
+			id: None,
+			name: ".spacedriveignore'd".to_owned(),
+			default: true,
+			date_created: Utc::now(),
+			date_modified: Utc::now(),
+			rules: vec![spacedrive_ignore.rules],
+		}
+	}
+}
+
 #[derive(Debug)]
 pub struct SystemIndexerRule {
 	name: &'static str,
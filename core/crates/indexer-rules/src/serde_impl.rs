@@ -1,3 +1,5 @@
+use sd_file_ext::kind::ObjectKind;
+
 use std::{collections::HashSet, marker::PhantomData};
 
 use globset::{Glob, GlobSetBuilder};
@@ -31,9 +33,62 @@ impl Serialize for RulePerKind {
 					"RejectIfChildrenDirectoriesArePresent",
 					children,
 				),
+			Self::RejectFilesLargerThan(max_size) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				4,
+				"RejectFilesLargerThan",
+				&max_size,
+			),
+			Self::AcceptFilesSmallerThan(size) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				5,
+				"AcceptFilesSmallerThan",
+				&size,
+			),
+			Self::AcceptIfExtendedAttributesArePresent(ref attributes) => serializer
+				.serialize_newtype_variant(
+					"ParametersPerKind",
+					6,
+					"AcceptIfExtendedAttributesArePresent",
+					attributes,
+				),
+			Self::RejectIfExtendedAttributesArePresent(ref attributes) => serializer
+				.serialize_newtype_variant(
+					"ParametersPerKind",
+					7,
+					"RejectIfExtendedAttributesArePresent",
+					attributes,
+				),
+			Self::RejectFilesModifiedWithin(seconds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				8,
+				"RejectFilesModifiedWithin",
+				&seconds,
+			),
+			Self::RejectFilesNotModifiedWithin(seconds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				9,
+				"RejectFilesNotModifiedWithin",
+				&seconds,
+			),
+			Self::AcceptByObjectKind(ref kinds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				10,
+				"AcceptByObjectKind",
+				kinds,
+			),
+			Self::RejectByObjectKind(ref kinds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				11,
+				"RejectByObjectKind",
+				kinds,
+			),
 			Self::IgnoredByGit(_, _) => {
 				unreachable!("git ignore rules are dynamic and not serialized")
 			}
+			Self::IgnoredBySpacedrive(_, _) => {
+				unreachable!("spacedrive ignore rules are dynamic and not serialized")
+			}
 		}
 	}
 }
@@ -49,6 +104,14 @@ impl<'de> Deserialize<'de> for RulePerKind {
 			"RejectFilesByGlob",
 			"AcceptIfChildrenDirectoriesArePresent",
 			"RejectIfChildrenDirectoriesArePresent",
+			"RejectFilesLargerThan",
+			"AcceptFilesSmallerThan",
+			"AcceptIfExtendedAttributesArePresent",
+			"RejectIfExtendedAttributesArePresent",
+			"RejectFilesModifiedWithin",
+			"RejectFilesNotModifiedWithin",
+			"AcceptByObjectKind",
+			"RejectByObjectKind",
 		];
 
 		enum Fields {
@@ -56,6 +119,14 @@ impl<'de> Deserialize<'de> for RulePerKind {
 			RejectFilesByGlob,
 			AcceptIfChildrenDirectoriesArePresent,
 			RejectIfChildrenDirectoriesArePresent,
+			RejectFilesLargerThan,
+			AcceptFilesSmallerThan,
+			AcceptIfExtendedAttributesArePresent,
+			RejectIfExtendedAttributesArePresent,
+			RejectFilesModifiedWithin,
+			RejectFilesNotModifiedWithin,
+			AcceptByObjectKind,
+			RejectByObjectKind,
 		}
 
 		struct FieldsVisitor;
@@ -68,7 +139,15 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					"`AcceptFilesByGlob` \
 				or `RejectFilesByGlob` \
 				or `AcceptIfChildrenDirectoriesArePresent` \
-				or `RejectIfChildrenDirectoriesArePresent`",
+				or `RejectIfChildrenDirectoriesArePresent` \
+				or `RejectFilesLargerThan` \
+				or `AcceptFilesSmallerThan` \
+				or `AcceptIfExtendedAttributesArePresent` \
+				or `RejectIfExtendedAttributesArePresent` \
+				or `RejectFilesModifiedWithin` \
+				or `RejectFilesNotModifiedWithin` \
+				or `AcceptByObjectKind` \
+				or `RejectByObjectKind`",
 				)
 			}
 
@@ -81,9 +160,17 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					1 => Ok(Fields::RejectFilesByGlob),
 					2 => Ok(Fields::AcceptIfChildrenDirectoriesArePresent),
 					3 => Ok(Fields::RejectIfChildrenDirectoriesArePresent),
+					4 => Ok(Fields::RejectFilesLargerThan),
+					5 => Ok(Fields::AcceptFilesSmallerThan),
+					6 => Ok(Fields::AcceptIfExtendedAttributesArePresent),
+					7 => Ok(Fields::RejectIfExtendedAttributesArePresent),
+					8 => Ok(Fields::RejectFilesModifiedWithin),
+					9 => Ok(Fields::RejectFilesNotModifiedWithin),
+					10 => Ok(Fields::AcceptByObjectKind),
+					11 => Ok(Fields::RejectByObjectKind),
 					_ => Err(de::Error::invalid_value(
 						de::Unexpected::Unsigned(value),
-						&"variant index 0 <= i < 3",
+						&"variant index 0 <= i < 12",
 					)),
 				}
 			}
@@ -100,6 +187,18 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					"RejectIfChildrenDirectoriesArePresent" => {
 						Ok(Fields::RejectIfChildrenDirectoriesArePresent)
 					}
+					"RejectFilesLargerThan" => Ok(Fields::RejectFilesLargerThan),
+					"AcceptFilesSmallerThan" => Ok(Fields::AcceptFilesSmallerThan),
+					"AcceptIfExtendedAttributesArePresent" => {
+						Ok(Fields::AcceptIfExtendedAttributesArePresent)
+					}
+					"RejectIfExtendedAttributesArePresent" => {
+						Ok(Fields::RejectIfExtendedAttributesArePresent)
+					}
+					"RejectFilesModifiedWithin" => Ok(Fields::RejectFilesModifiedWithin),
+					"RejectFilesNotModifiedWithin" => Ok(Fields::RejectFilesNotModifiedWithin),
+					"AcceptByObjectKind" => Ok(Fields::AcceptByObjectKind),
+					"RejectByObjectKind" => Ok(Fields::RejectByObjectKind),
 					_ => Err(de::Error::unknown_variant(value, VARIANTS)),
 				}
 			}
@@ -116,6 +215,18 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					b"RejectIfChildrenDirectoriesArePresent" => {
 						Ok(Fields::RejectIfChildrenDirectoriesArePresent)
 					}
+					b"RejectFilesLargerThan" => Ok(Fields::RejectFilesLargerThan),
+					b"AcceptFilesSmallerThan" => Ok(Fields::AcceptFilesSmallerThan),
+					b"AcceptIfExtendedAttributesArePresent" => {
+						Ok(Fields::AcceptIfExtendedAttributesArePresent)
+					}
+					b"RejectIfExtendedAttributesArePresent" => {
+						Ok(Fields::RejectIfExtendedAttributesArePresent)
+					}
+					b"RejectFilesModifiedWithin" => Ok(Fields::RejectFilesModifiedWithin),
+					b"RejectFilesNotModifiedWithin" => Ok(Fields::RejectFilesNotModifiedWithin),
+					b"AcceptByObjectKind" => Ok(Fields::AcceptByObjectKind),
+					b"RejectByObjectKind" => Ok(Fields::RejectByObjectKind),
 					_ => Err(de::Error::unknown_variant(
 						&String::from_utf8_lossy(bytes),
 						VARIANTS,
@@ -201,6 +312,48 @@ impl<'de> Deserialize<'de> for RulePerKind {
 						reject_if_children_directories_are_present,
 					)
 					.map(Self::Value::RejectIfChildrenDirectoriesArePresent),
+					(Fields::RejectFilesLargerThan, reject_files_larger_than) => {
+						de::VariantAccess::newtype_variant::<u64>(reject_files_larger_than)
+							.map(Self::Value::RejectFilesLargerThan)
+					}
+					(Fields::AcceptFilesSmallerThan, accept_files_smaller_than) => {
+						de::VariantAccess::newtype_variant::<u64>(accept_files_smaller_than)
+							.map(Self::Value::AcceptFilesSmallerThan)
+					}
+					(
+						Fields::AcceptIfExtendedAttributesArePresent,
+						accept_if_extended_attributes_are_present,
+					) => de::VariantAccess::newtype_variant::<HashSet<String>>(
+						accept_if_extended_attributes_are_present,
+					)
+					.map(Self::Value::AcceptIfExtendedAttributesArePresent),
+					(
+						Fields::RejectIfExtendedAttributesArePresent,
+						reject_if_extended_attributes_are_present,
+					) => de::VariantAccess::newtype_variant::<HashSet<String>>(
+						reject_if_extended_attributes_are_present,
+					)
+					.map(Self::Value::RejectIfExtendedAttributesArePresent),
+					(Fields::RejectFilesModifiedWithin, reject_files_modified_within) => {
+						de::VariantAccess::newtype_variant::<u64>(reject_files_modified_within)
+							.map(Self::Value::RejectFilesModifiedWithin)
+					}
+					(Fields::RejectFilesNotModifiedWithin, reject_files_not_modified_within) => {
+						de::VariantAccess::newtype_variant::<u64>(reject_files_not_modified_within)
+							.map(Self::Value::RejectFilesNotModifiedWithin)
+					}
+					(Fields::AcceptByObjectKind, accept_by_object_kind) => {
+						de::VariantAccess::newtype_variant::<HashSet<ObjectKind>>(
+							accept_by_object_kind,
+						)
+						.map(Self::Value::AcceptByObjectKind)
+					}
+					(Fields::RejectByObjectKind, reject_by_object_kind) => {
+						de::VariantAccess::newtype_variant::<HashSet<ObjectKind>>(
+							reject_by_object_kind,
+						)
+						.map(Self::Value::RejectByObjectKind)
+					}
 				})
 			}
 		}
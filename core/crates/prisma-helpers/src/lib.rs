@@ -53,6 +53,9 @@ file_path::select!(file_path_for_file_identifier {
 	name
 	extension
 	object_id
+	symlink_target
+	cas_id
+	archive_member_path
 });
 file_path::select!(file_path_for_object_validator {
 	pub_id
@@ -68,10 +71,12 @@ file_path::select!(file_path_for_media_processor {
 	is_dir
 	name
 	extension
+	detected_extension
 	cas_id
 	object: select {
 		id
 		pub_id
+		perceptual_hash
 	}
 });
 file_path::select!(file_path_watcher_remove {
@@ -111,6 +116,14 @@ file_path::select!(file_path_to_isolate_with_id {
 	name
 	extension
 });
+file_path::select!(file_path_to_resolve_alias {
+	location_id
+	materialized_path
+	is_dir
+	name
+	extension
+	symlink_target
+});
 file_path::select!(file_path_walker {
 	pub_id
 	location_id
@@ -123,6 +136,16 @@ file_path::select!(file_path_walker {
 	inode
 	size_in_bytes_bytes
 	hidden
+	symlink_target
+});
+file_path::select!(file_path_for_directory_totals {
+	id
+	pub_id
+	materialized_path
+	is_dir
+	name
+	size_in_bytes_bytes
+	file_count
 });
 file_path::select!(file_path_to_handle_custom_uri {
 	pub_id
@@ -130,6 +153,7 @@ file_path::select!(file_path_to_handle_custom_uri {
 	is_dir
 	name
 	extension
+	archive_member_path
 	location: select {
 		id
 		path
@@ -282,8 +306,12 @@ impl From<location_with_indexer_rules::Data> for location::Data {
 			generate_preview_media: data.generate_preview_media,
 			sync_preview_media: data.sync_preview_media,
 			hidden: data.hidden,
+			follow_symlinks: data.follow_symlinks,
+			index_archive_contents: data.index_archive_contents,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			io_max_files_per_second: data.io_max_files_per_second,
+			io_max_hashed_bytes_per_second: data.io_max_hashed_bytes_per_second,
 			file_paths: None,
 			indexer_rules: None,
 			device: None,
@@ -308,8 +336,12 @@ impl From<&location_with_indexer_rules::Data> for location::Data {
 			generate_preview_media: data.generate_preview_media,
 			sync_preview_media: data.sync_preview_media,
 			hidden: data.hidden,
+			follow_symlinks: data.follow_symlinks,
+			index_archive_contents: data.index_archive_contents,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			io_max_files_per_second: data.io_max_files_per_second,
+			io_max_hashed_bytes_per_second: data.io_max_hashed_bytes_per_second,
 			file_paths: None,
 			indexer_rules: None,
 			device: None,
@@ -285,6 +285,19 @@ async fn paginate_locations(
 									location::sync_preview_media
 								),
 								option_sync_entry!(l.hidden, location::hidden),
+								option_sync_entry!(l.follow_symlinks, location::follow_symlinks),
+								option_sync_entry!(
+									l.index_archive_contents,
+									location::index_archive_contents
+								),
+								option_sync_entry!(
+									l.io_max_files_per_second,
+									location::io_max_files_per_second
+								),
+								option_sync_entry!(
+									l.io_max_hashed_bytes_per_second,
+									location::io_max_hashed_bytes_per_second
+								),
 								option_sync_entry!(l.date_created, location::date_created),
 								option_sync_entry!(
 									l.device.map(|device| {
@@ -407,6 +420,15 @@ async fn paginate_exif_datas(
 								option_sync_entry!(ed.copyright, exif_data::copyright),
 								option_sync_entry!(ed.exif_version, exif_data::exif_version),
 								option_sync_entry!(ed.epoch_time, exif_data::epoch_time),
+								option_sync_entry!(ed.camera_make, exif_data::camera_make),
+								option_sync_entry!(ed.camera_model, exif_data::camera_model),
+								option_sync_entry!(ed.lens_model, exif_data::lens_model),
+								option_sync_entry!(ed.iso, exif_data::iso),
+								option_sync_entry!(ed.aperture, exif_data::aperture),
+								option_sync_entry!(ed.exposure_time, exif_data::exposure_time),
+								option_sync_entry!(ed.orientation, exif_data::orientation),
+								option_sync_entry!(ed.latitude, exif_data::latitude),
+								option_sync_entry!(ed.longitude, exif_data::longitude),
 								option_sync_entry!(
 									ed.device.map(|device| {
 										prisma_sync::device::SyncId {
@@ -483,11 +505,21 @@ async fn paginate_file_paths(
 								option_sync_entry!(fp.name, file_path::name),
 								option_sync_entry!(fp.extension, file_path::extension),
 								option_sync_entry!(fp.hidden, file_path::hidden),
+								option_sync_entry!(
+									fp.detected_extension,
+									file_path::detected_extension
+								),
+								option_sync_entry!(
+									fp.archive_member_path,
+									file_path::archive_member_path
+								),
 								option_sync_entry!(
 									fp.size_in_bytes_bytes,
 									file_path::size_in_bytes_bytes
 								),
+								option_sync_entry!(fp.file_count, file_path::file_count),
 								option_sync_entry!(fp.inode, file_path::inode),
+								option_sync_entry!(fp.symlink_target, file_path::symlink_target),
 								option_sync_entry!(fp.date_created, file_path::date_created),
 								option_sync_entry!(fp.date_modified, file_path::date_modified),
 								option_sync_entry!(fp.date_indexed, file_path::date_indexed),
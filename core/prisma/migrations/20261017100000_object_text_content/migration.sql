@@ -0,0 +1,14 @@
+-- CreateVirtualTable
+-- Full-text index over the content of text, code and config files, `rowid` is the object id.
+-- Prisma doesn't support virtual tables, so this table is only accessed through raw queries.
+CREATE VIRTUAL TABLE IF NOT EXISTS "object_text_content" USING fts5(
+    "content",
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- CreateTrigger
+CREATE TRIGGER IF NOT EXISTS "object_text_content_delete_object"
+AFTER DELETE ON "object"
+BEGIN
+    DELETE FROM "object_text_content" WHERE rowid = old."id";
+END;
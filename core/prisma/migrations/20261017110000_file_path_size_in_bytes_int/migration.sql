@@ -0,0 +1,87 @@
+-- AlterTable
+-- Integer mirror of `size_in_bytes_bytes` so sizes can be filtered and indexed, it's kept up to date
+-- by the triggers below, so it must never be written directly.
+ALTER TABLE "file_path" ADD COLUMN "size_in_bytes_int" BIGINT;
+
+-- CreateIndex
+CREATE INDEX "file_path_size_in_bytes_int_idx" ON "file_path"("size_in_bytes_int");
+
+-- Backfill
+-- `size_in_bytes_bytes` is a big-endian u64, SQLite has no way to cast a blob into an integer,
+-- so we decode it one hex digit at a time.
+UPDATE "file_path"
+SET "size_in_bytes_int" = (
+        ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 1, 1)) - 1) << 60)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 2, 1)) - 1) << 56)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 3, 1)) - 1) << 52)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 4, 1)) - 1) << 48)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 5, 1)) - 1) << 44)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 6, 1)) - 1) << 40)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 7, 1)) - 1) << 36)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 8, 1)) - 1) << 32)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 9, 1)) - 1) << 28)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 10, 1)) - 1) << 24)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 11, 1)) - 1) << 20)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 12, 1)) - 1) << 16)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 13, 1)) - 1) << 12)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 14, 1)) - 1) << 8)
+        + ((instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 15, 1)) - 1) << 4)
+        + (instr('0123456789ABCDEF', substr(hex("size_in_bytes_bytes"), 16, 1)) - 1)
+    )
+WHERE length("size_in_bytes_bytes") = 8;
+
+-- CreateTrigger
+CREATE TRIGGER IF NOT EXISTS "file_path_size_in_bytes_int_insert"
+AFTER INSERT ON "file_path"
+WHEN length(new."size_in_bytes_bytes") = 8
+BEGIN
+    UPDATE "file_path"
+    SET "size_in_bytes_int" = (
+        ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 1, 1)) - 1) << 60)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 2, 1)) - 1) << 56)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 3, 1)) - 1) << 52)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 4, 1)) - 1) << 48)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 5, 1)) - 1) << 44)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 6, 1)) - 1) << 40)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 7, 1)) - 1) << 36)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 8, 1)) - 1) << 32)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 9, 1)) - 1) << 28)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 10, 1)) - 1) << 24)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 11, 1)) - 1) << 20)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 12, 1)) - 1) << 16)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 13, 1)) - 1) << 12)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 14, 1)) - 1) << 8)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 15, 1)) - 1) << 4)
+        + (instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 16, 1)) - 1)
+    )
+    WHERE "id" = new."id";
+END;
+
+-- CreateTrigger
+CREATE TRIGGER IF NOT EXISTS "file_path_size_in_bytes_int_update"
+AFTER UPDATE OF "size_in_bytes_bytes" ON "file_path"
+BEGIN
+    UPDATE "file_path"
+    SET "size_in_bytes_int" = CASE
+        WHEN length(new."size_in_bytes_bytes") = 8 THEN (
+        ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 1, 1)) - 1) << 60)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 2, 1)) - 1) << 56)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 3, 1)) - 1) << 52)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 4, 1)) - 1) << 48)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 5, 1)) - 1) << 44)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 6, 1)) - 1) << 40)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 7, 1)) - 1) << 36)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 8, 1)) - 1) << 32)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 9, 1)) - 1) << 28)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 10, 1)) - 1) << 24)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 11, 1)) - 1) << 20)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 12, 1)) - 1) << 16)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 13, 1)) - 1) << 12)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 14, 1)) - 1) << 8)
+        + ((instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 15, 1)) - 1) << 4)
+        + (instr('0123456789ABCDEF', substr(hex(new."size_in_bytes_bytes"), 16, 1)) - 1)
+        )
+        ELSE NULL
+    END
+    WHERE "id" = new."id";
+END;
@@ -0,0 +1,34 @@
+-- AlterTable
+ALTER TABLE "exif_data" ADD COLUMN "camera_make" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "camera_model" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "lens_model" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "iso" INTEGER;
+ALTER TABLE "exif_data" ADD COLUMN "aperture" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "exposure_time" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "orientation" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "latitude" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "longitude" REAL;
+
+-- Backfill
+-- `camera_data` and `media_location` are JSON stored as blobs, aperture and exposure time weren't
+-- extracted before, so they stay empty until the media processor runs again for those files.
+UPDATE "exif_data"
+SET
+    "camera_make" = json_extract(CAST("camera_data" AS TEXT), '$.device_make'),
+    "camera_model" = json_extract(CAST("camera_data" AS TEXT), '$.device_model'),
+    "lens_model" = json_extract(CAST("camera_data" AS TEXT), '$.lens_model'),
+    "iso" = json_extract(CAST("camera_data" AS TEXT), '$.iso'),
+    "orientation" = json_extract(CAST("camera_data" AS TEXT), '$.orientation')
+WHERE json_valid(CAST("camera_data" AS TEXT));
+
+UPDATE "exif_data"
+SET
+    "latitude" = json_extract(CAST("media_location" AS TEXT), '$.latitude'),
+    "longitude" = json_extract(CAST("media_location" AS TEXT), '$.longitude')
+WHERE json_valid(CAST("media_location" AS TEXT));
+
+-- CreateIndex
+CREATE INDEX "exif_data_iso_idx" ON "exif_data"("iso");
+
+-- CreateIndex
+CREATE INDEX "exif_data_latitude_longitude_idx" ON "exif_data"("latitude", "longitude");
@@ -0,0 +1,72 @@
+-- AlterTable
+ALTER TABLE "ffmpeg_data" ADD COLUMN "bit_rate_int" BIGINT;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "duration_int" BIGINT;
+
+-- AlterTable
+ALTER TABLE "ffmpeg_media_audio_props" ADD COLUMN "channels" INTEGER;
+
+-- Backfill
+-- `bit_rate` and `duration` are big-endian i64, SQLite has no way to cast a blob into an integer,
+-- so we decode them one hex digit at a time. Both are never negative in practice.
+UPDATE "ffmpeg_data"
+SET "bit_rate_int" = (
+        ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 1, 1)) - 1) << 60)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 2, 1)) - 1) << 56)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 3, 1)) - 1) << 52)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 4, 1)) - 1) << 48)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 5, 1)) - 1) << 44)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 6, 1)) - 1) << 40)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 7, 1)) - 1) << 36)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 8, 1)) - 1) << 32)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 9, 1)) - 1) << 28)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 10, 1)) - 1) << 24)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 11, 1)) - 1) << 20)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 12, 1)) - 1) << 16)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 13, 1)) - 1) << 12)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 14, 1)) - 1) << 8)
+        + ((instr('0123456789ABCDEF', substr(hex("bit_rate"), 15, 1)) - 1) << 4)
+        + (instr('0123456789ABCDEF', substr(hex("bit_rate"), 16, 1)) - 1)
+    )
+WHERE length("bit_rate") = 8;
+
+UPDATE "ffmpeg_data"
+SET "duration_int" = (
+        ((instr('0123456789ABCDEF', substr(hex("duration"), 1, 1)) - 1) << 60)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 2, 1)) - 1) << 56)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 3, 1)) - 1) << 52)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 4, 1)) - 1) << 48)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 5, 1)) - 1) << 44)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 6, 1)) - 1) << 40)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 7, 1)) - 1) << 36)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 8, 1)) - 1) << 32)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 9, 1)) - 1) << 28)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 10, 1)) - 1) << 24)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 11, 1)) - 1) << 20)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 12, 1)) - 1) << 16)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 13, 1)) - 1) << 12)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 14, 1)) - 1) << 8)
+        + ((instr('0123456789ABCDEF', substr(hex("duration"), 15, 1)) - 1) << 4)
+        + (instr('0123456789ABCDEF', substr(hex("duration"), 16, 1)) - 1)
+    )
+WHERE length("duration") = 8;
+
+-- Backfill
+-- Channel counts weren't extracted before, but they can be inferred from the most common layouts
+-- described by FFmpeg, like `stereo`, `5.1(side)` or `4 channels`
+UPDATE "ffmpeg_media_audio_props"
+SET "channels" = CASE
+    WHEN "channel_layout" = 'mono' THEN 1
+    WHEN "channel_layout" IN ('stereo', 'downmix') THEN 2
+    WHEN "channel_layout" IN ('2.1', '3.0', '3.0(back)') THEN 3
+    WHEN "channel_layout" IN ('3.1', '4.0', 'quad', 'quad(side)') THEN 4
+    WHEN "channel_layout" IN ('4.1', '5.0', '5.0(side)') THEN 5
+    WHEN "channel_layout" IN ('5.1', '5.1(side)', '6.0', '6.0(front)', 'hexagonal') THEN 6
+    WHEN "channel_layout" IN ('6.1', '6.1(back)', '6.1(front)', '7.0', '7.0(front)') THEN 7
+    WHEN "channel_layout" IN ('7.1', '7.1(wide)', '7.1(wide-side)', 'octagonal', 'cube') THEN 8
+    WHEN "channel_layout" LIKE '% channels' THEN CAST("channel_layout" AS INTEGER)
+    ELSE NULL
+END
+WHERE "channel_layout" IS NOT NULL;
+
+-- CreateIndex
+CREATE INDEX "ffmpeg_data_duration_int_idx" ON "ffmpeg_data"("duration_int");
@@ -0,0 +1,4 @@
+-- AlterTable
+-- Left empty for existing directories, the indexer recalculates directories sizes and file counts
+-- on the next scan of their location when it finds a directory without a file count.
+ALTER TABLE "file_path" ADD COLUMN "file_count" BIGINT;
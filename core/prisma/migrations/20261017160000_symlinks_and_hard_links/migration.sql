@@ -0,0 +1,12 @@
+-- AlterTable
+ALTER TABLE "location" ADD COLUMN "follow_symlinks" BOOLEAN;
+
+-- AlterTable
+ALTER TABLE "file_path" ADD COLUMN "symlink_target" TEXT;
+
+-- DropIndex
+-- Hard links share their inode, so many file paths of a location can have the same one
+DROP INDEX "file_path_location_id_inode_key";
+
+-- CreateIndex
+CREATE INDEX "file_path_location_id_inode_idx" ON "file_path"("location_id", "inode");
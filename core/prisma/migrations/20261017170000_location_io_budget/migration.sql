@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "location" ADD COLUMN "io_max_files_per_second" INTEGER;
+ALTER TABLE "location" ADD COLUMN "io_max_hashed_bytes_per_second" INTEGER;
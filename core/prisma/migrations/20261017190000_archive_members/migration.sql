@@ -0,0 +1,5 @@
+-- AlterTable
+ALTER TABLE "location" ADD COLUMN "index_archive_contents" BOOLEAN;
+
+-- AlterTable
+ALTER TABLE "file_path" ADD COLUMN "archive_member_path" TEXT;
@@ -17,8 +17,8 @@ use crate::{
 use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
 use sd_core_heavy_lifting::media_processor::{exif_media_data, ffmpeg_media_data};
 use sd_core_prisma_helpers::{
-	file_path_to_isolate, file_path_to_isolate_with_id, object_with_file_paths,
-	object_with_media_data,
+	file_path_to_isolate, file_path_to_isolate_with_id, file_path_to_resolve_alias,
+	object_with_file_paths, object_with_media_data,
 };
 
 use sd_file_ext::kind::ObjectKind;
@@ -171,6 +171,102 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.map(|str| str.to_string()))
 				})
 		})
+		.procedure("resolveAlias", {
+			#[derive(Serialize, Type)]
+			pub struct ResolvedAlias {
+				/// Where the symbolic link points to, as it would be opened
+				pub target: PathBuf,
+				/// The target, if indexed on a location of this library
+				pub file_path_id: Option<file_path::id::Type>,
+				pub location_id: Option<location::id::Type>,
+			}
+
+			R.with2(library())
+				.query(|(_, library), id: file_path::id::Type| async move {
+					let file_path = library
+						.db
+						.file_path()
+						.find_unique(file_path::id::equals(id))
+						.select(file_path_to_resolve_alias::select())
+						.exec()
+						.await?
+						.ok_or(LocationError::FilePath(FilePathError::IdNotFound(id)))?;
+
+					let Some(symlink_target) = &file_path.symlink_target else {
+						return Err(rspc::Error::new(
+							ErrorCode::BadRequest,
+							"File path isn't an alias".to_string(),
+						));
+					};
+
+					let isolated_path = IsolatedFilePathData::try_from(&file_path)
+						.map_err(LocationError::MissingField)?;
+
+					let link_path = Path::new(
+						&get_location_path_from_location_id(
+							&library.db,
+							isolated_path.location_id(),
+						)
+						.await?,
+					)
+					.join(&isolated_path);
+
+					// Relative targets start from the directory holding the link
+					let target = link_path.parent().map_or_else(
+						|| PathBuf::from(symlink_target),
+						|parent| parent.join(symlink_target),
+					);
+
+					// Broken links can't be canonicalized, their target is given as stored
+					let Ok(target) = fs::canonicalize(&target).await else {
+						return Ok(ResolvedAlias {
+							target,
+							file_path_id: None,
+							location_id: None,
+						});
+					};
+
+					let is_dir = fs::metadata(&target)
+						.await
+						.map_err(|e| FileIOError::from((&target, e)))?
+						.is_dir();
+
+					let mut indexed = None;
+
+					// The innermost location holding the target is the one indexing it
+					if let Some((location_id, location_path)) = library
+						.db
+						.location()
+						.find_many(vec![])
+						.select(location::select!({ id path }))
+						.exec()
+						.await?
+						.into_iter()
+						.filter_map(|location| location.path.map(|path| (location.id, path)))
+						.filter(|(_, path)| target.starts_with(path))
+						.max_by_key(|(_, path)| path.len())
+					{
+						let iso_file_path =
+							IsolatedFilePathData::new(location_id, &location_path, &target, is_dir)
+								.map_err(LocationError::from)?;
+
+						indexed = library
+							.db
+							.file_path()
+							.find_unique((&iso_file_path).into())
+							.select(file_path::select!({ id }))
+							.exec()
+							.await?
+							.map(|file_path| (file_path.id, location_id));
+					}
+
+					Ok(ResolvedAlias {
+						target,
+						file_path_id: indexed.map(|(file_path_id, _)| file_path_id),
+						location_id: indexed.map(|(_, location_id)| location_id),
+					})
+				})
+		})
 		.procedure("setNote", {
 			#[derive(Type, Deserialize)]
 			pub struct SetNoteArgs {
@@ -443,6 +539,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("deleteFiles", {
 			R.with2(library())
 				.mutation(|(node, library), args: OldFileDeleterJobInit| async move {
+					reject_archive_members(&args.file_path_ids, &library).await?;
+
 					match args.file_path_ids.len() {
 						0 => Ok(()),
 						1 => {
@@ -543,6 +641,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						));
 					}
 
+					reject_archive_members(&args.file_path_ids, &library).await?;
+
 					match args.file_path_ids.len() {
 						0 => Ok(()),
 						1 => {
@@ -738,6 +838,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("eraseFiles", {
 			R.with2(library())
 				.mutation(|(node, library), args: OldFileEraserJobInit| async move {
+					reject_archive_members(&args.file_path_ids, &library).await?;
+
 					OldJob::new(args)
 						.spawn(&node, &library)
 						.await
@@ -747,6 +849,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("copyFiles", {
 			R.with2(library())
 				.mutation(|(node, library), args: OldFileCopierJobInit| async move {
+					reject_archive_members(&args.sources_file_path_ids, &library).await?;
+
 					OldJob::new(args)
 						.spawn(&node, &library)
 						.await
@@ -756,6 +860,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("cutFiles", {
 			R.with2(library())
 				.mutation(|(node, library), args: OldFileCutterJobInit| async move {
+					reject_archive_members(&args.sources_file_path_ids, &library).await?;
+
 					OldJob::new(args)
 						.spawn(&node, &library)
 						.await
@@ -947,6 +1053,15 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 
 			R.with2(library()).mutation(
 				|(_, library), RenameFileArgs { location_id, kind }: RenameFileArgs| async move {
+					match &kind {
+						RenameKind::One(RenameOne {
+							from_file_path_id, ..
+						}) => reject_archive_members(&[*from_file_path_id], &library).await?,
+						RenameKind::Many(RenameMany {
+							from_file_path_ids, ..
+						}) => reject_archive_members(from_file_path_ids, &library).await?,
+					}
+
 					let location_path =
 						get_location_path_from_location_id(&library.db, location_id).await?;
 
@@ -1046,6 +1161,33 @@ pub(super) async fn create_file(
 		.to_string())
 }
 
+/// Archives members only exist inside their archive, so they can't be changed on the file system
+async fn reject_archive_members(
+	file_path_ids: &[file_path::id::Type],
+	library: &Library,
+) -> Result<(), rspc::Error> {
+	let members_count = library
+		.db
+		.file_path()
+		.count(vec![
+			file_path::id::in_vec(file_path_ids.to_vec()),
+			file_path::archive_member_path::not(None),
+		])
+		.exec()
+		.await?;
+
+	if members_count > 0 {
+		return Err(rspc::Error::new(
+			ErrorCode::BadRequest,
+			"Files inside archives can't be deleted, renamed, copied or moved, \
+			extract them from their archive first"
+				.to_string(),
+		));
+	}
+
+	Ok(())
+}
+
 #[derive(Type, Deserialize)]
 pub struct FromPattern {
 	pub pattern: String,
@@ -8,7 +8,7 @@ use crate::{
 
 use sd_core_heavy_lifting::{
 	file_identifier::FileIdentifier, job_system::report, media_processor::job::MediaProcessor,
-	JobId, JobSystemError, Report,
+	IoBudget, JobEnqueuer, JobId, JobSystemError, Report,
 };
 
 use sd_prisma::prisma::{job, location, SortOrder};
@@ -298,6 +298,31 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					Ok(())
 				})
 		})
+		.procedure("setIoBudget", {
+			// Only jobs from the new job system have an I/O budget, queued jobs inherit the
+			// budget of the job running before them
+			#[derive(Type, Deserialize)]
+			pub struct SetIoBudgetArgs {
+				pub id: JobId,
+				pub io_budget: IoBudget,
+			}
+
+			R.with2(library()).mutation(
+				|(node, library), SetIoBudgetArgs { id, io_budget }: SetIoBudgetArgs| async move {
+					node.job_system
+						.set_io_budget(
+							NodeContext {
+								node: Arc::clone(&node),
+								library,
+							},
+							id,
+							io_budget,
+						)
+						.await
+						.map_err(Into::into)
+				},
+			)
+		})
 		.procedure("generateThumbsForLocation", {
 			#[derive(Type, Deserialize)]
 			pub struct GenerateThumbsForLocationArgs {
@@ -399,7 +424,42 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 
 					node.job_system
 						.dispatch(
-							FileIdentifier::new(location, Some(path))?,
+							FileIdentifier::new(location, Some(path), false)?,
+							id,
+							NodeContext {
+								node: Arc::clone(&node),
+								library,
+							},
+						)
+						.await
+						.map_err(Into::into)
+				},
+			)
+		})
+		.procedure("verifyIntegrity", {
+			// Identifies orphans too, then computes full checksums for files missing them,
+			// splitting objects wrongly merged by a `cas_id` collision
+			#[derive(Type, Deserialize)]
+			pub struct VerifyIntegrityArgs {
+				pub id: location::id::Type,
+				pub path: PathBuf,
+			}
+
+			R.with2(library()).mutation(
+				|(node, library), VerifyIntegrityArgs { id, path }: VerifyIntegrityArgs| async move {
+					let Some(location) = find_location(&library, id).exec().await? else {
+						return Err(LocationError::IdNotFound(id).into());
+					};
+
+					node.job_system
+						.dispatch(
+							JobEnqueuer::new(FileIdentifier::new(
+								location.clone(),
+								Some(path),
+								true,
+							)?)
+							.with_action("verify_integrity")
+							.with_metadata(report::ReportInputMetadata::Location(location)),
 							id,
 							NodeContext {
 								node: Arc::clone(&node),
@@ -428,13 +488,13 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		})
 		.procedure("newFilePathIdentified", {
 			R.with2(library())
-				.subscription(|(node, _), _: ()| async move {
-					// TODO: Only return event for the library that was subscribed to
+				.subscription(|(node, library), _: ()| async move {
 					let mut event_bus_rx = node.event_bus.0.subscribe();
 					async_stream::stream! {
 						while let Ok(event) = event_bus_rx.recv().await {
 							match event {
-								CoreEvent::NewIdentifiedObjects { file_path_ids } => yield file_path_ids,
+								CoreEvent::NewIdentifiedObjects { file_path_ids, library_id }
+									if library_id == library.id => yield file_path_ids,
 								_ => {}
 							}
 						}
@@ -376,7 +376,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 
 							let scan_state = ScanState::try_from(location.scan_state)?;
 
-							scan_location(&node, &library, location, scan_state)
+							scan_location(&node, &library, location, scan_state, false)
 								.await
 								.map_err(rspc::Error::from)
 						}))
@@ -1,7 +1,13 @@
 use crate::{
 	invalidate_query,
 	location::{
-		delete_location, find_location, light_scan_location, non_indexed::NonIndexedPathItem,
+		delete_location, find_location,
+		indexer_rules::{
+			explain_path, export_rules, import_rules, preview_rules, ExplainPathArgs,
+			ImportRulesArgs, PreviewRulesArgs,
+		},
+		light_scan_location,
+		non_indexed::NonIndexedPathItem,
 		relink_location, scan_location, scan_location_sub_path, LocationCreateArgs, LocationError,
 		LocationUpdateArgs, ScanState,
 	},
@@ -218,6 +224,10 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				pub generate_preview_media: Option<bool>,
 				pub sync_preview_media: Option<bool>,
 				pub hidden: Option<bool>,
+				pub follow_symlinks: Option<bool>,
+				pub index_archive_contents: Option<bool>,
+				pub io_max_files_per_second: Option<i32>,
+				pub io_max_hashed_bytes_per_second: Option<i32>,
 				pub date_created: Option<DateTime<FixedOffset>>,
 				pub instance_id: Option<i32>,
 				pub indexer_rules: Vec<indexer_rule::Data>,
@@ -237,6 +247,10 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						generate_preview_media: value.generate_preview_media,
 						sync_preview_media: value.sync_preview_media,
 						hidden: value.hidden,
+						follow_symlinks: value.follow_symlinks,
+						index_archive_contents: value.index_archive_contents,
+						io_max_files_per_second: value.io_max_files_per_second,
+						io_max_hashed_bytes_per_second: value.io_max_hashed_bytes_per_second,
 						date_created: value.date_created,
 						instance_id: value.instance_id,
 						indexer_rules: value
@@ -265,7 +279,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				.mutation(|(node, library), args: LocationCreateArgs| async move {
 					if let Some(location) = args.create(&node, &library).await? {
 						let id = Some(location.id);
-						scan_location(&node, &library, location, ScanState::Pending).await?;
+						scan_location(&node, &library, location, ScanState::Pending, false).await?;
 						invalidate_query!(library, "locations.list");
 						Ok(id)
 					} else {
@@ -304,7 +318,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					if let Some(location) = args.add_library(&node, &library).await? {
 						let id = location.id;
 						let location_scan_state = ScanState::try_from(location.scan_state)?;
-						scan_location(&node, &library, location, location_scan_state).await?;
+						scan_location(&node, &library, location, location_scan_state, false)
+							.await?;
 						invalidate_query!(library, "locations.list");
 						Ok(Some(id))
 					} else {
@@ -317,12 +332,16 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			pub struct FullRescanArgs {
 				pub location_id: location::id::Type,
 				pub reidentify_objects: bool,
+				/// Walk through every directory, even the ones unchanged since the last scan
+				#[serde(default)]
+				pub paranoid: bool,
 			}
 			R.with2(library()).mutation(
 				|(node, library),
 				 FullRescanArgs {
 				     location_id,
 				     reidentify_objects,
+				     paranoid,
 				 }| async move {
 					if reidentify_objects {
 						let count = library
@@ -356,7 +375,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					let location_scan_state = ScanState::try_from(location.scan_state)?;
 
 					// rescan location
-					scan_location(&node, &library, location, location_scan_state)
+					scan_location(&node, &library, location, location_scan_state, paranoid)
 						.await
 						.map_err(Into::into)
 				},
@@ -367,6 +386,9 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			pub struct RescanArgs {
 				pub location_id: location::id::Type,
 				pub sub_path: String,
+				/// Walk through every directory, even the ones unchanged since the last scan
+				#[serde(default)]
+				pub paranoid: bool,
 			}
 
 			R.with2(library()).mutation(
@@ -374,6 +396,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				 RescanArgs {
 				     location_id,
 				     sub_path,
+				     paranoid,
 				 }: RescanArgs| async move {
 					scan_location_sub_path(
 						&node,
@@ -384,6 +407,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 							.await?
 							.ok_or(LocationError::IdNotFound(location_id))?,
 						sub_path,
+						paranoid,
 					)
 					.await
 					.map_err(Into::into)
@@ -558,4 +582,41 @@ fn mount_indexer_rule_routes() -> AlphaRouter<Ctx> {
 						.await?)
 				})
 		})
+		// tells which rules accept or reject a path of a location
+		.procedure("explain", {
+			R.with2(library())
+				.query(|(_, library), args: ExplainPathArgs| async move {
+					Ok(explain_path(args, &library).await?)
+				})
+		})
+		// counts how many entries of a directory tree each rule would reject, before creating a
+		// location for it
+		.procedure("preview", {
+			R.with2(library())
+				.query(|(_, library), args: PreviewRulesArgs| async move {
+					Ok(preview_rules(args, &library).await?)
+				})
+		})
+		// an empty list of ids exports every rule of the library
+		.procedure("export", {
+			R.with2(library())
+				.query(|(_, library), indexer_rules_ids: Vec<i32>| async move {
+					Ok(export_rules(indexer_rules_ids, &library).await?)
+				})
+		})
+		.procedure("import", {
+			R.with2(library())
+				.mutation(|(_, library), args: ImportRulesArgs| async move {
+					let attached_to_locations = !args.location_ids.is_empty();
+
+					let imported = import_rules(args, &library).await?;
+
+					invalidate_query!(library, "locations.indexer_rules.list");
+					if attached_to_locations {
+						invalidate_query!(library, "locations.indexer_rules.listForLocation");
+					}
+
+					Ok(imported)
+				})
+		})
 }
@@ -61,6 +61,7 @@ pub enum CoreEvent {
 	},
 	NewIdentifiedObjects {
 		file_path_ids: Vec<file_path::id::Type>,
+		library_id: LibraryId,
 	},
 	UpdatedKindStatistic(KindStatistic, LibraryId),
 	JobProgress(JobProgressEvent),
@@ -0,0 +1,449 @@
+//! Searches fanned out to every library loaded in the node.
+//!
+//! Each library is queried with the same filters and the results are merged by the requested
+//! order, ties being broken by library id and then by the item id within its library. As rows of
+//! different databases can't be compared in a single query, the cursor keeps the last item
+//! returned from each library, so each library resumes right after it even if items were added or
+//! removed in the meantime.
+
+use crate::{api::locations::ExplorerItem, library::Library};
+
+use sd_core_prisma_helpers::{file_path_for_frontend, object_with_file_paths};
+
+use sd_prisma::prisma::{self, file_path, object};
+
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use chrono::{DateTime, FixedOffset};
+use futures::future::try_join_all;
+use prisma_client_rust::{and, not, or};
+use rspc::{alpha::AlphaRouter, ErrorCode};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+use super::{
+	andify, merge_filters, object_item, path_item, Ctx, SearchFilterArgs, SortOrder, MAX_TAKE, R,
+};
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", tag = "field", content = "value")]
+pub enum AllLibrariesPathOrder {
+	Name(SortOrder),
+	SizeInBytes(SortOrder),
+	DateCreated(SortOrder),
+	DateModified(SortOrder),
+	DateIndexed(SortOrder),
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", tag = "field", content = "value")]
+pub enum AllLibrariesObjectOrder {
+	DateCreated(SortOrder),
+	DateAccessed(SortOrder),
+	Kind(SortOrder),
+}
+
+/// Last item of a library already returned, the next page of that library starts right after it
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryCursor {
+	pub library_id: Uuid,
+	/// Id of the item within its library
+	pub id: i32,
+	/// Value of the ordered field of the item
+	pub key: AllLibrariesSortKey,
+}
+
+#[derive(Deserialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllLibrariesSearchArgs<TOrder> {
+	#[specta(optional)]
+	pub take: Option<u8>,
+	/// Without an order, items are grouped by library
+	#[specta(optional)]
+	pub order: Option<TOrder>,
+	/// Must be used with the same `order` and `filters` as the search which returned it
+	#[specta(optional)]
+	pub cursor: Option<Vec<LibraryCursor>>,
+	/// Ids used by filters, like locations or tags, are different on each library, so they only
+	/// match items in the library they belong to
+	#[serde(default)]
+	pub filters: Vec<SearchFilterArgs>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryItem {
+	pub library_id: Uuid,
+	pub item: ExplorerItem,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AllLibrariesSearchData {
+	pub items: Vec<LibraryItem>,
+	pub cursor: Option<Vec<LibraryCursor>>,
+}
+
+/// Value used to merge the results of different libraries, `None`s come first when ascending,
+/// the same as `SQLite` does with `NULL`s
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase", tag = "type", content = "value")]
+pub enum AllLibrariesSortKey {
+	Unordered,
+	Text(Option<String>),
+	Int(Option<i64>),
+	Date(Option<DateTime<FixedOffset>>),
+}
+
+/// Items of a library ordered after the one with `$value` in `$field` and `$id`, as ties are
+/// ordered by ascending ids and `SQLite` puts `NULL`s first when ascending and last when descending
+macro_rules! after {
+	($model:ident::$field:ident, $order:expr, $value:expr, $id:expr) => {{
+		let id = $id;
+
+		match ($order, $value) {
+			(SortOrder::Asc, Some(value)) => or![
+				$model::$field::gt(value.clone()),
+				and![$model::$field::equals(Some(value)), $model::id::gt(id)]
+			],
+			(SortOrder::Desc, Some(value)) => or![
+				$model::$field::lt(value.clone()),
+				and![$model::$field::equals(Some(value)), $model::id::gt(id)],
+				$model::$field::equals(None)
+			],
+			(SortOrder::Asc, None) => or![
+				not![$model::$field::equals(None)],
+				and![$model::$field::equals(None), $model::id::gt(id)]
+			],
+			(SortOrder::Desc, None) => and![$model::$field::equals(None), $model::id::gt(id)],
+		}
+	}};
+}
+
+fn mismatched_cursor() -> rspc::Error {
+	rspc::Error::new(
+		ErrorCode::BadRequest,
+		"cursor doesn't match the order of the search".to_string(),
+	)
+}
+
+impl AllLibrariesPathOrder {
+	fn sort_order(self) -> SortOrder {
+		match self {
+			Self::Name(v)
+			| Self::SizeInBytes(v)
+			| Self::DateCreated(v)
+			| Self::DateModified(v)
+			| Self::DateIndexed(v) => v,
+		}
+	}
+
+	fn into_param(self) -> file_path::OrderByWithRelationParam {
+		let dir = self.sort_order().into();
+
+		match self {
+			Self::Name(_) => file_path::name::order(dir),
+			Self::SizeInBytes(_) => file_path::size_in_bytes_int::order(dir),
+			Self::DateCreated(_) => file_path::date_created::order(dir),
+			Self::DateModified(_) => file_path::date_modified::order(dir),
+			Self::DateIndexed(_) => file_path::date_indexed::order(dir),
+		}
+	}
+
+	fn sort_key(self, file_path: &file_path_for_frontend::Data) -> AllLibrariesSortKey {
+		match self {
+			Self::Name(_) => AllLibrariesSortKey::Text(file_path.name.clone()),
+			Self::SizeInBytes(_) => AllLibrariesSortKey::Int(file_path.size_in_bytes_int),
+			Self::DateCreated(_) => AllLibrariesSortKey::Date(file_path.date_created),
+			Self::DateModified(_) => AllLibrariesSortKey::Date(file_path.date_modified),
+			Self::DateIndexed(_) => AllLibrariesSortKey::Date(file_path.date_indexed),
+		}
+	}
+
+	fn after(
+		order: Option<Self>,
+		id: file_path::id::Type,
+		key: AllLibrariesSortKey,
+	) -> Result<file_path::WhereParam, rspc::Error> {
+		Ok(match (order, key) {
+			(None, AllLibrariesSortKey::Unordered) => file_path::id::gt(id),
+			(Some(Self::Name(order)), AllLibrariesSortKey::Text(name)) => {
+				after!(file_path::name, order, name, id)
+			}
+			(Some(Self::SizeInBytes(order)), AllLibrariesSortKey::Int(size)) => {
+				after!(file_path::size_in_bytes_int, order, size, id)
+			}
+			(Some(Self::DateCreated(order)), AllLibrariesSortKey::Date(date)) => {
+				after!(file_path::date_created, order, date, id)
+			}
+			(Some(Self::DateModified(order)), AllLibrariesSortKey::Date(date)) => {
+				after!(file_path::date_modified, order, date, id)
+			}
+			(Some(Self::DateIndexed(order)), AllLibrariesSortKey::Date(date)) => {
+				after!(file_path::date_indexed, order, date, id)
+			}
+			_ => return Err(mismatched_cursor()),
+		})
+	}
+}
+
+impl AllLibrariesObjectOrder {
+	fn sort_order(self) -> SortOrder {
+		match self {
+			Self::DateCreated(v) | Self::DateAccessed(v) | Self::Kind(v) => v,
+		}
+	}
+
+	fn into_param(self) -> object::OrderByWithRelationParam {
+		let dir = self.sort_order().into();
+
+		match self {
+			Self::DateCreated(_) => object::date_created::order(dir),
+			Self::DateAccessed(_) => object::date_accessed::order(dir),
+			Self::Kind(_) => object::kind::order(dir),
+		}
+	}
+
+	fn sort_key(self, object: &object_with_file_paths::Data) -> AllLibrariesSortKey {
+		match self {
+			Self::DateCreated(_) => AllLibrariesSortKey::Date(object.date_created),
+			Self::DateAccessed(_) => AllLibrariesSortKey::Date(object.date_accessed),
+			Self::Kind(_) => AllLibrariesSortKey::Int(object.kind.map(i64::from)),
+		}
+	}
+
+	fn after(
+		order: Option<Self>,
+		id: object::id::Type,
+		key: AllLibrariesSortKey,
+	) -> Result<object::WhereParam, rspc::Error> {
+		Ok(match (order, key) {
+			(None, AllLibrariesSortKey::Unordered) => object::id::gt(id),
+			(Some(Self::DateCreated(order)), AllLibrariesSortKey::Date(date)) => {
+				after!(object::date_created, order, date, id)
+			}
+			(Some(Self::DateAccessed(order)), AllLibrariesSortKey::Date(date)) => {
+				after!(object::date_accessed, order, date, id)
+			}
+			(Some(Self::Kind(order)), AllLibrariesSortKey::Int(kind)) => {
+				let kind = kind
+					.map(i32::try_from)
+					.transpose()
+					.map_err(|_| mismatched_cursor())?;
+
+				after!(object::kind, order, kind, id)
+			}
+			_ => return Err(mismatched_cursor()),
+		})
+	}
+}
+
+pub(crate) fn mount() -> AlphaRouter<Ctx> {
+	R.router()
+		.procedure("paths", {
+			R.query(
+				|node, args: AllLibrariesSearchArgs<AllLibrariesPathOrder>| async move {
+					let order = args.order;
+
+					let (found, cursor) = search_all(
+						node.libraries.get_all().await,
+						args,
+						|library, filters, after, take| async move {
+							let db = &library.db;
+
+							let (mut fp, obj) = merge_filters(filters, db).await?;
+
+							if !obj.is_empty() {
+								fp.push(file_path::object::is(obj));
+							}
+
+							if let Some(LibraryCursor { id, key, .. }) = after {
+								fp.push(AllLibrariesPathOrder::after(order, id, key)?);
+							}
+
+							let mut query = db.file_path().find_many(andify(fp)).take(take);
+
+							if let Some(order) = order {
+								query = query.order_by(order.into_param());
+							}
+
+							Ok(query
+								.order_by(file_path::id::order(prisma::SortOrder::Asc))
+								.include(file_path_for_frontend::include())
+								.exec()
+								.await?
+								.into_iter()
+								.map(|file_path| {
+									(
+										order.map_or(AllLibrariesSortKey::Unordered, |order| {
+											order.sort_key(&file_path)
+										}),
+										file_path.id,
+										file_path,
+									)
+								})
+								.collect())
+						},
+						order.map(AllLibrariesPathOrder::sort_order),
+					)
+					.await?;
+
+					let mut items = Vec::with_capacity(found.len());
+
+					for (library, file_path) in found {
+						items.push(LibraryItem {
+							library_id: library.id,
+							item: path_item(&node, &library, file_path).await?,
+						});
+					}
+
+					Ok(AllLibrariesSearchData { items, cursor })
+				},
+			)
+		})
+		.procedure("objects", {
+			R.query(
+				|node, args: AllLibrariesSearchArgs<AllLibrariesObjectOrder>| async move {
+					let order = args.order;
+
+					let (found, cursor) = search_all(
+						node.libraries.get_all().await,
+						args,
+						|library, filters, after, take| async move {
+							let db = &library.db;
+
+							let (fp, mut obj) = merge_filters(filters, db).await?;
+
+							if !fp.is_empty() {
+								obj.push(object::file_paths::some(fp));
+							}
+
+							if let Some(LibraryCursor { id, key, .. }) = after {
+								obj.push(AllLibrariesObjectOrder::after(order, id, key)?);
+							}
+
+							let mut query = db.object().find_many(andify(obj)).take(take);
+
+							if let Some(order) = order {
+								query = query.order_by(order.into_param());
+							}
+
+							Ok(query
+								.order_by(object::id::order(prisma::SortOrder::Asc))
+								.include(object_with_file_paths::include())
+								.exec()
+								.await?
+								.into_iter()
+								.map(|object| {
+									(
+										order.map_or(AllLibrariesSortKey::Unordered, |order| {
+											order.sort_key(&object)
+										}),
+										object.id,
+										object,
+									)
+								})
+								.collect())
+						},
+						order.map(AllLibrariesObjectOrder::sort_order),
+					)
+					.await?;
+
+					let mut items = Vec::with_capacity(found.len());
+
+					for (library, object) in found {
+						items.push(LibraryItem {
+							library_id: library.id,
+							item: object_item(&node, &library, object).await?,
+						});
+					}
+
+					Ok(AllLibrariesSearchData { items, cursor })
+				},
+			)
+		})
+}
+
+/// Runs `fetch` on every library, starting after the last item previous pages returned from it,
+/// and merges the results. Each library is asked for one more item than needed to know if there
+/// are more.
+async fn search_all<TOrder, T, Fut>(
+	mut libraries: Vec<Arc<Library>>,
+	AllLibrariesSearchArgs {
+		take,
+		cursor,
+		filters,
+		..
+	}: AllLibrariesSearchArgs<TOrder>,
+	fetch: impl Fn(Arc<Library>, Vec<SearchFilterArgs>, Option<LibraryCursor>, i64) -> Fut,
+	sort_order: Option<SortOrder>,
+) -> Result<(Vec<(Arc<Library>, T)>, Option<Vec<LibraryCursor>>), rspc::Error>
+where
+	Fut: Future<Output = Result<Vec<(AllLibrariesSortKey, i32, T)>, rspc::Error>>,
+{
+	let take = take.unwrap_or(MAX_TAKE).min(MAX_TAKE);
+
+	let mut cursors = cursor
+		.unwrap_or_default()
+		.into_iter()
+		.map(|cursor| (cursor.library_id, cursor))
+		.collect::<HashMap<_, _>>();
+
+	libraries.sort_by_key(|library| library.id);
+
+	let results = try_join_all(libraries.into_iter().map(|library| {
+		let fut = fetch(
+			Arc::clone(&library),
+			filters.clone(),
+			cursors.get(&library.id).cloned(),
+			i64::from(take) + 1,
+		);
+
+		async move { fut.await.map(|found| (library, found)) }
+	}))
+	.await?;
+
+	// Libraries are sorted by id and each one is already sorted by the database, so a stable
+	// sort by the key alone keeps the tie breaking between and within libraries
+	let mut merged = results
+		.into_iter()
+		.flat_map(|(library, found)| {
+			found
+				.into_iter()
+				.map(move |(key, id, item)| (key, id, Arc::clone(&library), item))
+		})
+		.collect::<Vec<_>>();
+
+	merged.sort_by(|(one, ..), (two, ..)| match sort_order {
+		Some(SortOrder::Desc) => two.cmp(one),
+		Some(SortOrder::Asc) | None => one.cmp(two),
+	});
+
+	let has_more = merged.len() > usize::from(take);
+	merged.truncate(usize::from(take));
+
+	let items = merged
+		.into_iter()
+		.map(|(key, id, library, item)| {
+			cursors.insert(
+				library.id,
+				LibraryCursor {
+					library_id: library.id,
+					id,
+					key,
+				},
+			);
+			(library, item)
+		})
+		.collect();
+
+	let cursor = has_more.then(|| {
+		let mut cursors = cursors.into_values().collect::<Vec<_>>();
+		cursors.sort_by_key(|cursor| cursor.library_id);
+		cursors
+	});
+
+	Ok((items, cursor))
+}
@@ -0,0 +1,336 @@
+use sd_core_heavy_lifting::media_processor::text_content::TEXT_CONTENT_TABLE;
+
+use sd_prisma::prisma::{file_path, object, PrismaClient};
+
+use std::collections::{HashMap, HashSet};
+
+use prisma_client_rust::{raw, PrismaValue};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::{andify, utils::TextMatch};
+
+/// How many matches are checked against the other filters at a time. Prisma can't take the
+/// `MATCH` as a subquery, so matches go through an `IN` clause, well below the limit of bound
+/// variables of SQLite.
+const MATCHES_CHUNK_SIZE: usize = 1_000;
+
+/// Amount of tokens that FTS5 will put in each snippet
+const SNIPPET_TOKENS: u8 = 16;
+
+// Control characters never show up in indexed text, so they're safe to use as highlight markers
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_END: char = '\u{3}';
+
+#[derive(Serialize, Type, Debug)]
+pub struct ContentSnippetPart {
+	pub text: String,
+	pub highlighted: bool,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentMatch {
+	pub object_id: object::id::Type,
+	/// BM25 relevance, lower is better
+	pub rank: f64,
+	pub snippet: Vec<ContentSnippetPart>,
+}
+
+/// Converts a [`TextMatch`] into a FTS5 query string.
+///
+/// FTS5 works with whole tokens, so `Contains` and `StartsWith` become prefix queries and `EndsWith`
+/// degrades into a phrase match, as the index can't anchor a match to the end of a file.
+pub fn fts_query(text_match: &TextMatch) -> Option<String> {
+	if text_match.is_empty() {
+		return None;
+	}
+
+	let (TextMatch::Contains(text)
+	| TextMatch::StartsWith(text)
+	| TextMatch::EndsWith(text)
+	| TextMatch::Equals(text)) = text_match;
+
+	// Quoting the whole input makes FTS5 treat it as a single phrase, so users can't inject operators
+	let phrase = format!("\"{}\"", text.replace('"', "\"\""));
+
+	Some(match text_match {
+		TextMatch::Contains(_) => format!("{phrase}*"),
+		TextMatch::StartsWith(_) => format!("^{phrase}*"),
+		TextMatch::EndsWith(_) | TextMatch::Equals(_) => phrase,
+	})
+}
+
+/// Object ids with text content matching `text_match` and a file path matching every one of
+/// `params`, which are the other filters of the search.
+///
+/// Every match is checked against the other filters, one chunk at a time, so no match is left out
+/// before filtering and the `IN` clauses never grow past [`MATCHES_CHUNK_SIZE`].
+pub async fn matching_object_ids(
+	text_match: &TextMatch,
+	params: Vec<file_path::WhereParam>,
+	db: &PrismaClient,
+) -> Result<Vec<object::id::Type>, rspc::Error> {
+	#[derive(Deserialize)]
+	struct ObjectId {
+		object_id: object::id::Type,
+	}
+
+	let Some(query) = fts_query(text_match) else {
+		return Ok(vec![]);
+	};
+
+	let mut object_ids = HashSet::new();
+	let mut last_object_id = 0;
+
+	loop {
+		// Paginating by rowid, so each chunk is found straight from the index
+		let matches = db
+			._query_raw::<ObjectId>(raw!(
+				&format!(
+					"SELECT rowid AS object_id FROM {TEXT_CONTENT_TABLE}
+					WHERE {TEXT_CONTENT_TABLE} MATCH {{}} AND rowid > {{}}
+					ORDER BY rowid
+					LIMIT {MATCHES_CHUNK_SIZE}"
+				),
+				PrismaValue::String(query.clone()),
+				PrismaValue::Int(last_object_id)
+			))
+			.exec()
+			.await?
+			.into_iter()
+			.map(|ObjectId { object_id }| object_id)
+			.collect::<Vec<_>>();
+
+		let Some(&last_match) = matches.last() else {
+			break;
+		};
+
+		let is_last_chunk = matches.len() < MATCHES_CHUNK_SIZE;
+		last_object_id = last_match;
+
+		object_ids.extend(
+			db.file_path()
+				.find_many(andify(
+					params
+						.iter()
+						.cloned()
+						.chain([file_path::object_id::in_vec(matches)])
+						.collect(),
+				))
+				.select(file_path::select!({ object_id }))
+				.exec()
+				.await?
+				.into_iter()
+				.filter_map(|file_path| file_path.object_id),
+		);
+
+		if is_last_chunk {
+			break;
+		}
+	}
+
+	Ok(object_ids.into_iter().collect())
+}
+
+/// Paginates the file paths matching every one of `params` and with text content matching
+/// `text_match` by the BM25 relevance of their object's text content, best matches first, so
+/// ranking holds across pages and not only inside each of them.
+///
+/// Ranked matches are walked one chunk at a time, keeping the ones allowed by the other filters,
+/// until the requested page is filled.
+pub async fn ranked_file_path_ids(
+	text_match: &TextMatch,
+	params: Vec<file_path::WhereParam>,
+	take: Option<u8>,
+	offset: i32,
+	db: &PrismaClient,
+) -> Result<Vec<file_path::id::Type>, rspc::Error> {
+	#[derive(Deserialize)]
+	struct FilePathId {
+		id: file_path::id::Type,
+	}
+
+	let Some(query) = fts_query(text_match) else {
+		return Ok(vec![]);
+	};
+
+	let offset = usize::try_from(offset).unwrap_or_default();
+	let needed = take.map(|take| offset + usize::from(take));
+
+	let mut ranked_ids = vec![];
+
+	for chunk_offset in (0..).step_by(MATCHES_CHUNK_SIZE) {
+		// The limits are integers, so this is sql injection safe
+		let matches = db
+			._query_raw::<FilePathId>(raw!(
+				&format!(
+					"SELECT file_path.id AS id
+					FROM file_path
+					INNER JOIN {TEXT_CONTENT_TABLE}
+						ON {TEXT_CONTENT_TABLE}.rowid = file_path.object_id
+					WHERE {TEXT_CONTENT_TABLE} MATCH {{}}
+					ORDER BY bm25({TEXT_CONTENT_TABLE}), file_path.id
+					LIMIT {MATCHES_CHUNK_SIZE} OFFSET {chunk_offset}"
+				),
+				PrismaValue::String(query.clone())
+			))
+			.exec()
+			.await?
+			.into_iter()
+			.map(|FilePathId { id }| id)
+			.collect::<Vec<_>>();
+
+		let is_last_chunk = matches.len() < MATCHES_CHUNK_SIZE;
+
+		let allowed_ids = db
+			.file_path()
+			.find_many(andify(
+				params
+					.iter()
+					.cloned()
+					.chain([file_path::id::in_vec(matches.clone())])
+					.collect(),
+			))
+			.select(file_path::select!({ id }))
+			.exec()
+			.await?
+			.into_iter()
+			.map(|file_path| file_path.id)
+			.collect::<HashSet<_>>();
+
+		ranked_ids.extend(matches.into_iter().filter(|id| allowed_ids.contains(id)));
+
+		if is_last_chunk || needed.is_some_and(|needed| ranked_ids.len() >= needed) {
+			break;
+		}
+	}
+
+	Ok(ranked_ids
+		.into_iter()
+		.skip(offset)
+		.take(take.map_or(usize::MAX, usize::from))
+		.collect())
+}
+
+/// Ranks and builds highlighted snippets for the given objects
+pub async fn content_matches(
+	text_match: &TextMatch,
+	object_ids: impl IntoIterator<Item = object::id::Type>,
+	db: &PrismaClient,
+) -> Result<HashMap<object::id::Type, ContentMatch>, rspc::Error> {
+	#[derive(Deserialize)]
+	struct RawContentMatch {
+		object_id: object::id::Type,
+		rank: f64,
+		snippet: String,
+	}
+
+	let Some(query) = fts_query(text_match) else {
+		return Ok(HashMap::new());
+	};
+
+	let object_ids = object_ids
+		.into_iter()
+		.map(|id| id.to_string())
+		.collect::<Vec<_>>();
+
+	if object_ids.is_empty() {
+		return Ok(HashMap::new());
+	}
+
+	// FIXME: Had to use format! macro because PCR doesn't support IN with Vec for SQLite
+	// The ids come from the database, so this is sql injection safe
+	Ok(db
+		._query_raw::<RawContentMatch>(raw!(
+			&format!(
+				"SELECT
+					rowid AS object_id,
+					rank,
+					snippet({TEXT_CONTENT_TABLE}, 0, '{HIGHLIGHT_START}', '{HIGHLIGHT_END}', '…', \
+					{SNIPPET_TOKENS}) AS snippet
+				FROM {TEXT_CONTENT_TABLE}
+				WHERE {TEXT_CONTENT_TABLE} MATCH {{}} AND rowid IN ({})",
+				object_ids.join(",")
+			),
+			PrismaValue::String(query)
+		))
+		.exec()
+		.await?
+		.into_iter()
+		.map(
+			|RawContentMatch {
+			     object_id,
+			     rank,
+			     snippet,
+			 }| {
+				(
+					object_id,
+					ContentMatch {
+						object_id,
+						rank,
+						snippet: split_snippet(&snippet),
+					},
+				)
+			},
+		)
+		.collect())
+}
+
+fn split_snippet(snippet: &str) -> Vec<ContentSnippetPart> {
+	let mut parts = vec![];
+	let mut highlighted = false;
+
+	for part in snippet.split([HIGHLIGHT_START, HIGHLIGHT_END]) {
+		if !part.is_empty() {
+			parts.push(ContentSnippetPart {
+				text: part.to_string(),
+				highlighted,
+			});
+		}
+
+		highlighted = !highlighted;
+	}
+
+	parts
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fts_query_escapes_and_anchors() {
+		assert_eq!(
+			fts_query(&TextMatch::Contains("hello world".into())).as_deref(),
+			Some("\"hello world\"*")
+		);
+		assert_eq!(
+			fts_query(&TextMatch::StartsWith("TODO".into())).as_deref(),
+			Some("^\"TODO\"*")
+		);
+		assert_eq!(
+			fts_query(&TextMatch::Equals("say \"hi\" OR".into())).as_deref(),
+			Some("\"say \"\"hi\"\" OR\"")
+		);
+		assert_eq!(fts_query(&TextMatch::EndsWith(String::new())), None);
+	}
+
+	#[test]
+	fn snippet_highlights() {
+		let parts = split_snippet("…the \u{2}quick\u{3} brown \u{2}fox\u{3}");
+
+		assert_eq!(
+			parts
+				.iter()
+				.map(|part| (part.text.as_str(), part.highlighted))
+				.collect::<Vec<_>>(),
+			vec![
+				("…the ", false),
+				("quick", true),
+				(" brown ", false),
+				("fox", true)
+			]
+		);
+	}
+}
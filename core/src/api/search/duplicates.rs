@@ -0,0 +1,338 @@
+use crate::library::Library;
+
+use sd_core_heavy_lifting::indexer::REPEATED_HARD_LINK;
+use sd_core_prisma_helpers::file_path_for_frontend;
+
+use sd_prisma::prisma::{file_path, location, object};
+
+use std::collections::{HashMap, HashSet};
+
+use prisma_client_rust::{or, PrismaValue, Raw};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::MAX_TAKE;
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesCursor {
+	pub wasted_bytes: u64,
+	pub object_id: object::id::Type,
+}
+
+#[derive(Deserialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesArgs {
+	#[specta(optional)]
+	pub take: Option<u8>,
+	#[specta(optional)]
+	pub cursor: Option<DuplicatesCursor>,
+	/// Only consider copies inside these locations, all locations if empty
+	#[serde(default)]
+	pub locations: Vec<location::id::Type>,
+	/// [`ObjectKind`](sd_file_ext::kind::ObjectKind)s to look for, all kinds if empty
+	#[serde(default)]
+	pub kinds: Vec<i32>,
+	#[specta(optional)]
+	pub min_size_in_bytes: Option<u64>,
+	/// Confirms groups with the full file checksums stored by the `jobs.verifyIntegrity` job,
+	/// instead of trusting the sampled `cas_id`. Paths without a checksum are left out.
+	#[serde(default)]
+	pub verify_integrity: bool,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+	pub object_id: object::id::Type,
+	pub size_in_bytes: u64,
+	/// Bytes freed by keeping a single copy, hard links to the same file are a single copy
+	pub wasted_bytes: u64,
+	/// Only set for groups confirmed with `verifyIntegrity`
+	pub integrity_checksum: Option<String>,
+	/// Paths left out with `verifyIntegrity` because they weren't checksummed yet
+	pub unverified_paths: u32,
+	pub paths: Vec<file_path_for_frontend::Data>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesData {
+	pub groups: Vec<DuplicateGroup>,
+	pub cursor: Option<DuplicatesCursor>,
+	/// Totals for every page, based on `cas_id` alone as verifying everything would be too slow
+	pub total_groups: u32,
+	pub total_wasted_bytes: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawGroup {
+	object_id: object::id::Type,
+	size_in_bytes: i64,
+	wasted_bytes: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawTotals {
+	group_count: i64,
+	wasted_bytes: i64,
+}
+
+pub async fn find(
+	DuplicatesArgs {
+		take,
+		cursor,
+		locations,
+		kinds,
+		min_size_in_bytes,
+		verify_integrity,
+	}: DuplicatesArgs,
+	library: &Library,
+) -> Result<DuplicatesData, rspc::Error> {
+	let Library { db, .. } = library;
+
+	let take = take.unwrap_or(MAX_TAKE).min(MAX_TAKE);
+
+	// FIXME: Had to use format! macro because PCR doesn't support IN with Vec for SQLite
+	// Location ids and kinds are integers, so this is sql injection safe
+	// Hard links don't take any extra space, so only the first link of each file is a copy
+	let mut conditions = vec![
+		"COALESCE(file_path.is_dir, 0) = 0".to_string(),
+		format!("NOT {REPEATED_HARD_LINK}"),
+	];
+	let mut params = vec![];
+
+	if !locations.is_empty() {
+		conditions.push(format!(
+			"file_path.location_id IN ({})",
+			join_ints(&locations)
+		));
+	}
+
+	if !kinds.is_empty() {
+		conditions.push(format!("object.kind IN ({})", join_ints(&kinds)));
+	}
+
+	if let Some(min_size_in_bytes) = min_size_in_bytes {
+		conditions.push("file_path.size_in_bytes_int >= {}".to_string());
+		params.push(PrismaValue::BigInt(to_db(min_size_in_bytes)));
+	}
+
+	let groups_query = format!(
+		"SELECT object_id, paths, size_in_bytes, (paths - 1) * size_in_bytes AS wasted_bytes
+		FROM (
+			SELECT
+				file_path.object_id AS object_id,
+				COUNT(*) AS paths,
+				COALESCE(MAX(file_path.size_in_bytes_int), 0) AS size_in_bytes
+			FROM file_path
+			INNER JOIN object ON object.id = file_path.object_id
+			WHERE {}
+			GROUP BY file_path.object_id
+			HAVING COUNT(*) > 1
+		)",
+		conditions.join(" AND ")
+	);
+
+	let RawTotals {
+		group_count: total_groups,
+		wasted_bytes: total_wasted_bytes,
+	} = db._query_raw::<RawTotals>(Raw::new(
+		&format!(
+			"SELECT COUNT(*) AS group_count, COALESCE(SUM(wasted_bytes), 0) AS wasted_bytes
+				FROM ({groups_query})"
+		),
+		params.clone(),
+	))
+	.exec()
+	.await?
+	.pop()
+	.unwrap_or(RawTotals {
+		group_count: 0,
+		wasted_bytes: 0,
+	});
+
+	let mut page_params = params;
+	let cursor_condition = cursor
+		.map(
+			|DuplicatesCursor {
+			     wasted_bytes,
+			     object_id,
+			 }| {
+				page_params.extend([
+					PrismaValue::BigInt(to_db(wasted_bytes)),
+					PrismaValue::BigInt(to_db(wasted_bytes)),
+					PrismaValue::Int(object_id),
+				]);
+
+				"WHERE wasted_bytes < {} OR (wasted_bytes = {} AND object_id < {})"
+			},
+		)
+		.unwrap_or_default();
+
+	// Fetching one more than needed to know if there is a next page
+	let mut raw_groups = db
+		._query_raw::<RawGroup>(Raw::new(
+			&format!(
+				"SELECT * FROM ({groups_query})
+				{cursor_condition}
+				ORDER BY wasted_bytes DESC, object_id DESC
+				LIMIT {}",
+				u16::from(take) + 1
+			),
+			page_params,
+		))
+		.exec()
+		.await?;
+
+	let cursor = if raw_groups.len() > usize::from(take) {
+		raw_groups.truncate(usize::from(take));
+		raw_groups.last().map(|group| DuplicatesCursor {
+			wasted_bytes: from_db(group.wasted_bytes),
+			object_id: group.object_id,
+		})
+	} else {
+		None
+	};
+
+	let mut paths_by_object = db
+		.file_path()
+		.find_many(
+			[
+				file_path::object_id::in_vec(
+					raw_groups.iter().map(|group| group.object_id).collect(),
+				),
+				or![
+					file_path::is_dir::equals(Some(false)),
+					file_path::is_dir::equals(None)
+				],
+			]
+			.into_iter()
+			.chain((!locations.is_empty()).then(|| file_path::location_id::in_vec(locations)))
+			.chain(min_size_in_bytes.map(|size| file_path::size_in_bytes_int::gte(to_db(size))))
+			.collect(),
+		)
+		.include(file_path_for_frontend::include())
+		.exec()
+		.await?
+		.into_iter()
+		.fold(
+			HashMap::<_, Vec<_>>::new(),
+			|mut paths_by_object, file_path| {
+				if let Some(object_id) = file_path.object_id {
+					paths_by_object
+						.entry(object_id)
+						.or_default()
+						.push(file_path);
+				}
+				paths_by_object
+			},
+		);
+
+	let mut groups = Vec::with_capacity(raw_groups.len());
+
+	for RawGroup {
+		object_id,
+		size_in_bytes,
+		wasted_bytes,
+	} in raw_groups
+	{
+		let Some(paths) = paths_by_object.remove(&object_id) else {
+			continue;
+		};
+
+		let size_in_bytes = from_db(size_in_bytes);
+
+		if !verify_integrity {
+			groups.push(DuplicateGroup {
+				object_id,
+				size_in_bytes,
+				wasted_bytes: from_db(wasted_bytes),
+				integrity_checksum: None,
+				unverified_paths: 0,
+				paths,
+			});
+			continue;
+		}
+
+		let (paths_by_checksum, unverified_paths) = split_by_checksum(paths);
+
+		// Files sharing a `cas_id` but with different contents end up in different groups
+		for (integrity_checksum, paths) in paths_by_checksum {
+			let copies = distinct_files_count(&paths);
+			if copies > 1 {
+				groups.push(DuplicateGroup {
+					object_id,
+					size_in_bytes,
+					wasted_bytes: size_in_bytes * (copies - 1),
+					integrity_checksum: Some(integrity_checksum),
+					unverified_paths,
+					paths,
+				});
+			}
+		}
+	}
+
+	Ok(DuplicatesData {
+		groups,
+		cursor,
+		total_groups: u32::try_from(total_groups).unwrap_or(u32::MAX),
+		total_wasted_bytes: from_db(total_wasted_bytes),
+	})
+}
+
+/// Groups paths by their stored full file checksum, computing checksums is up to the
+/// `jobs.verifyIntegrity` job as a query must not read whole files.
+/// Also returns how many paths were left out for not having a checksum yet.
+fn split_by_checksum(
+	paths: Vec<file_path_for_frontend::Data>,
+) -> (HashMap<String, Vec<file_path_for_frontend::Data>>, u32) {
+	let mut unverified_paths = 0;
+
+	let paths_by_checksum = paths.into_iter().fold(
+		HashMap::<_, Vec<_>>::new(),
+		|mut paths_by_checksum, file_path| {
+			if let Some(checksum) = file_path.integrity_checksum.clone() {
+				paths_by_checksum
+					.entry(checksum)
+					.or_default()
+					.push(file_path);
+			} else {
+				unverified_paths += 1;
+			}
+			paths_by_checksum
+		},
+	);
+
+	(paths_by_checksum, unverified_paths)
+}
+
+/// Hard links to the same file share their inode, size and modification date, like on
+/// [`REPEATED_HARD_LINK`], so they're counted once
+fn distinct_files_count(paths: &[file_path_for_frontend::Data]) -> u64 {
+	paths
+		.iter()
+		.map(|file_path| {
+			file_path.inode.as_ref().map_or(Err(file_path.id), |inode| {
+				Ok((inode, file_path.size_in_bytes_int, file_path.date_modified))
+			})
+		})
+		.collect::<HashSet<_>>()
+		.len() as u64
+}
+
+fn join_ints(ints: &[i32]) -> String {
+	ints.iter()
+		.map(ToString::to_string)
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+/// Sizes are stored as i64 in `size_in_bytes_int`, no file is big enough to overflow it
+fn to_db(size: u64) -> i64 {
+	i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+fn from_db(size: i64) -> u64 {
+	u64::try_from(size).unwrap_or_default()
+}
@@ -1,5 +1,10 @@
+use sd_core_heavy_lifting::media_processor::exif_media_data::orientation_to_db;
+
+use sd_media_metadata::exif::Orientation;
 use sd_prisma::prisma::{self, exif_data};
 
+use chrono::{DateTime, Utc};
+use prisma_client_rust::or;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -27,3 +32,112 @@ impl ExifDataOrder {
 		}
 	}
 }
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+	pub min_latitude: f64,
+	pub max_latitude: f64,
+	/// Can be bigger than `max_longitude` for boxes crossing the antimeridian
+	pub min_longitude: f64,
+	pub max_longitude: f64,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum ExifDataFilterArgs {
+	CameraMake(TextMatch),
+	CameraModel(TextMatch),
+	Lens(TextMatch),
+	Iso(Range<i32>),
+	Aperture(Range<f64>),
+	/// In seconds
+	ExposureTime(Range<f64>),
+	MediaDate(Range<DateTime<Utc>>),
+	Orientation(InOrNotIn<Orientation>),
+	Location(BoundingBox),
+}
+
+impl ExifDataFilterArgs {
+	pub fn into_params(self) -> Vec<exif_data::WhereParam> {
+		use exif_data::*;
+
+		match self {
+			Self::CameraMake(v) => v
+				.into_param(
+					camera_make::contains,
+					camera_make::starts_with,
+					camera_make::ends_with,
+					|s| camera_make::equals(Some(s)),
+				)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::CameraModel(v) => v
+				.into_param(
+					camera_model::contains,
+					camera_model::starts_with,
+					camera_model::ends_with,
+					|s| camera_model::equals(Some(s)),
+				)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::Lens(v) => v
+				.into_param(
+					lens_model::contains,
+					lens_model::starts_with,
+					lens_model::ends_with,
+					|s| lens_model::equals(Some(s)),
+				)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::Iso(v) => vec![match v {
+				Range::From(v) => iso::gte(v),
+				Range::To(v) => iso::lte(v),
+			}],
+			Self::Aperture(v) => vec![match v {
+				Range::From(v) => aperture::gte(v),
+				Range::To(v) => aperture::lte(v),
+			}],
+			Self::ExposureTime(v) => vec![match v {
+				Range::From(v) => exposure_time::gte(v),
+				Range::To(v) => exposure_time::lte(v),
+			}],
+			// `epoch_time` comes from the same `MediaDate` we show on the frontend
+			Self::MediaDate(v) => vec![match v {
+				Range::From(v) => epoch_time::gte(v.timestamp()),
+				Range::To(v) => epoch_time::lte(v.timestamp()),
+			}],
+			Self::Orientation(v) => {
+				fn to_db(orientations: Vec<Orientation>) -> Vec<String> {
+					orientations.iter().filter_map(orientation_to_db).collect()
+				}
+
+				v.into_param(
+					|v| orientation::in_vec(to_db(v)),
+					|v| orientation::not_in_vec(to_db(v)),
+				)
+				.map(|v| vec![v])
+				.unwrap_or_default()
+			}
+			Self::Location(BoundingBox {
+				min_latitude,
+				max_latitude,
+				min_longitude,
+				max_longitude,
+			}) => {
+				let mut params = vec![latitude::gte(min_latitude), latitude::lte(max_latitude)];
+
+				if min_longitude <= max_longitude {
+					params.extend([longitude::gte(min_longitude), longitude::lte(max_longitude)]);
+				} else {
+					params.push(or![
+						longitude::gte(min_longitude),
+						longitude::lte(max_longitude)
+					]);
+				}
+
+				params
+			}
+		}
+	}
+}
@@ -0,0 +1,128 @@
+use sd_prisma::prisma::{
+	ffmpeg_data, ffmpeg_media_audio_props, ffmpeg_media_codec, ffmpeg_media_program,
+	ffmpeg_media_stream, ffmpeg_media_video_props,
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::utils::*;
+
+/// FFmpeg durations are stored in `AV_TIME_BASE` units
+const MICROSECONDS_PER_SECOND: f64 = 1_000_000.0;
+
+/// Codec kind FFmpeg gives to subtitle streams
+const SUBTITLE_KIND: &str = "subtitle";
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum FfmpegFilterArgs {
+	/// In seconds
+	Duration(Range<f64>),
+	/// In bits per second
+	BitRate(Range<u32>),
+	Width(Range<i32>),
+	Height(Range<i32>),
+	/// FFmpeg codec names, like `h264`, `hevc` or `aac`
+	Codec(InOrNotIn<String>),
+	AudioChannels(Range<i32>),
+	HasSubtitles(bool),
+	HasChapters(bool),
+}
+
+impl FfmpegFilterArgs {
+	pub fn into_params(self) -> Vec<ffmpeg_data::WhereParam> {
+		use ffmpeg_data::*;
+
+		match self {
+			Self::Duration(v) => {
+				// NaN, infinite or negative durations don't bound anything, so they don't filter
+				let to_db = |seconds: f64| {
+					(seconds.is_finite() && seconds >= 0.0)
+						.then(|| (seconds * MICROSECONDS_PER_SECOND) as i64)
+				};
+
+				match v {
+					Range::From(v) => to_db(v).map(duration_int::gte),
+					Range::To(v) => to_db(v).map(duration_int::lte),
+				}
+				.into_iter()
+				.collect()
+			}
+			Self::BitRate(v) => vec![match v {
+				Range::From(v) => bit_rate_int::gte(i64::from(v)),
+				Range::To(v) => bit_rate_int::lte(i64::from(v)),
+			}],
+			Self::Width(v) => vec![with_video_props(match v {
+				Range::From(v) => ffmpeg_media_video_props::width::gte(v),
+				Range::To(v) => ffmpeg_media_video_props::width::lte(v),
+			})],
+			Self::Height(v) => vec![with_video_props(match v {
+				Range::From(v) => ffmpeg_media_video_props::height::gte(v),
+				Range::To(v) => ffmpeg_media_video_props::height::lte(v),
+			})],
+			Self::Codec(v) => {
+				let (names, negated) = match v {
+					InOrNotIn::In(v) => (v, false),
+					InOrNotIn::NotIn(v) => (v, true),
+				};
+
+				if names.is_empty() {
+					return vec![];
+				}
+
+				let names = names.into_iter().map(|name| name.to_lowercase()).collect();
+
+				vec![with_stream(
+					vec![ffmpeg_media_stream::codec::is(vec![
+						ffmpeg_media_codec::name::in_vec(names),
+					])],
+					!negated,
+				)]
+			}
+			Self::AudioChannels(v) => vec![with_stream(
+				vec![ffmpeg_media_stream::codec::is(vec![
+					ffmpeg_media_codec::audio_props::is(vec![match v {
+						Range::From(v) => ffmpeg_media_audio_props::channels::gte(v),
+						Range::To(v) => ffmpeg_media_audio_props::channels::lte(v),
+					}]),
+				])],
+				true,
+			)],
+			Self::HasSubtitles(v) => vec![with_stream(
+				vec![ffmpeg_media_stream::codec::is(vec![
+					ffmpeg_media_codec::kind::equals(Some(SUBTITLE_KIND.to_string())),
+				])],
+				v,
+			)],
+			Self::HasChapters(v) => vec![if v {
+				chapters::some(vec![])
+			} else {
+				chapters::none(vec![])
+			}],
+		}
+	}
+}
+
+/// Matches media with at least one stream matching all `params` if `exists`, or with none otherwise
+fn with_stream(
+	params: Vec<ffmpeg_media_stream::WhereParam>,
+	exists: bool,
+) -> ffmpeg_data::WhereParam {
+	let program = vec![ffmpeg_media_program::streams::some(params)];
+
+	if exists {
+		ffmpeg_data::programs::some(program)
+	} else {
+		ffmpeg_data::programs::none(program)
+	}
+}
+
+fn with_video_props(param: ffmpeg_media_video_props::WhereParam) -> ffmpeg_data::WhereParam {
+	with_stream(
+		vec![ffmpeg_media_stream::codec::is(vec![
+			ffmpeg_media_codec::video_props::is(vec![param]),
+		])],
+		true,
+	)
+}
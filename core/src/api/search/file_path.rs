@@ -5,7 +5,7 @@ use sd_core_file_path_helper::{check_file_path_exists, IsolatedFilePathData};
 use sd_prisma::prisma::{self, file_path};
 
 use chrono::{DateTime, FixedOffset, Utc};
-use prisma_client_rust::{OrderByQuery, PaginatedQuery, WhereQuery};
+use prisma_client_rust::{not, OrderByQuery, PaginatedQuery, WhereQuery};
 use rspc::ErrorCode;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -44,7 +44,7 @@ impl FilePathOrder {
 		use file_path::*;
 		match self {
 			Self::Name(_) => name::order(dir),
-			Self::SizeInBytes(_) => size_in_bytes_bytes::order(dir),
+			Self::SizeInBytes(_) => size_in_bytes_int::order(dir),
 			Self::DateCreated(_) => date_created::order(dir),
 			Self::DateModified(_) => date_modified::order(dir),
 			Self::DateIndexed(_) => date_indexed::order(dir),
@@ -65,11 +65,17 @@ pub enum FilePathFilterArgs {
 	// #[deprecated]
 	// Search(String),
 	Name(TextMatch),
+	/// File paths whose name doesn't match, from negated free text in search queries
+	NotName(TextMatch),
 	Extension(InOrNotIn<String>),
 	CreatedAt(Range<DateTime<Utc>>),
 	ModifiedAt(Range<DateTime<Utc>>),
 	IndexedAt(Range<DateTime<Utc>>),
 	Hidden(bool),
+	SizeInBytes(Range<u64>),
+	IsDir(bool),
+	/// Full-text search over the content of indexed text, code and config files
+	Content(TextMatch),
 }
 
 impl FilePathFilterArgs {
@@ -127,6 +133,12 @@ impl FilePathFilterArgs {
 				})
 				.map(|v| vec![v])
 				.unwrap_or_default(),
+			Self::NotName(v) => v
+				.into_param(name::contains, name::starts_with, name::ends_with, |s| {
+					name::equals(Some(s))
+				})
+				.map(|v| vec![not![v]])
+				.unwrap_or_default(),
 			Self::Extension(v) => v
 				.into_param(extension::in_vec, extension::not_in_vec)
 				.map(|v| vec![v])
@@ -152,11 +164,26 @@ impl FilePathFilterArgs {
 			Self::Hidden(v) => {
 				vec![hidden::equals(Some(v))]
 			}
+			Self::SizeInBytes(v) => {
+				// Sizes are stored as i64 in the database, anything bigger than that saturates, which
+				// is fine as no one has a single file with more than 8 exabytes
+				let to_db = |size: u64| i64::try_from(size).unwrap_or(i64::MAX);
+
+				vec![match v {
+					Range::From(v) => size_in_bytes_int::gte(to_db(v)),
+					Range::To(v) => size_in_bytes_int::lte(to_db(v)),
+				}]
+			}
+			Self::IsDir(v) => {
+				vec![is_dir::equals(Some(v))]
+			}
+			// Matched once every other filter is known, as only their matches are worth checking
+			Self::Content(_) => vec![],
 		})
 	}
 }
 
-#[derive(Deserialize, Type, Debug)]
+#[derive(Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum FilePathObjectCursor {
 	DateAccessed(CursorOrderItem<DateTime<FixedOffset>>),
@@ -193,7 +220,7 @@ impl FilePathObjectCursor {
 	}
 }
 
-#[derive(Deserialize, Type, Debug)]
+#[derive(Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum FilePathCursorVariant {
 	None,
@@ -253,7 +280,7 @@ impl FilePathCursorVariant {
 	}
 }
 
-#[derive(Deserialize, Type, Debug)]
+#[derive(Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FilePathCursor {
 	pub is_dir: bool,
@@ -3,6 +3,7 @@ use crate::{
 	library::Library,
 	location::{non_indexed, LocationError},
 	util::{unsafe_streamed_query, BatchedStream},
+	Node,
 };
 
 use prisma_client_rust::Operator;
@@ -10,7 +11,7 @@ use sd_core_heavy_lifting::media_processor::ThumbKey;
 use sd_core_prisma_helpers::{file_path_for_frontend, object_with_file_paths, CasId};
 use sd_prisma::prisma::{self, PrismaClient};
 
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use async_stream::stream;
 use futures::StreamExt;
@@ -19,10 +20,16 @@ use rspc::{alpha::AlphaRouter, ErrorCode};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+pub mod all_libraries;
+pub mod content;
+pub mod duplicates;
 pub mod exif_data;
+pub mod ffmpeg_data;
 pub mod file_path;
 pub mod object;
+pub mod query;
 pub mod saved;
+pub mod similar_images;
 mod utils;
 
 pub use self::{file_path::*, object::*, utils::*};
@@ -35,6 +42,8 @@ const MAX_TAKE: u8 = 100;
 struct SearchData<T> {
 	cursor: Option<Vec<u8>>,
 	items: Vec<T>,
+	/// Only filled when searching with a [`FilePathFilterArgs::Content`] filter, one per object in `items` order
+	content_matches: Vec<content::ContentMatch>,
 }
 
 #[derive(Serialize, Deserialize, Type, Debug, Clone)]
@@ -42,6 +51,8 @@ struct SearchData<T> {
 pub enum SearchFilterArgs {
 	FilePath(FilePathFilterArgs),
 	Object(ObjectFilterArgs),
+	ExifData(exif_data::ExifDataFilterArgs),
+	FfmpegData(ffmpeg_data::FfmpegFilterArgs),
 }
 
 impl SearchFilterArgs {
@@ -54,6 +65,18 @@ impl SearchFilterArgs {
 		match self {
 			Self::FilePath(v) => file_path.extend(v.into_params(db).await?),
 			Self::Object(v) => object.extend(v.into_params()),
+			Self::ExifData(v) => {
+				let params = v.into_params();
+				if !params.is_empty() {
+					object.push(prisma::object::exif_data::is(params));
+				}
+			}
+			Self::FfmpegData(v) => {
+				let params = v.into_params();
+				if !params.is_empty() {
+					object.push(prisma::object::ffmpeg_data::is(params));
+				}
+			}
 		};
 		Ok(())
 	}
@@ -78,6 +101,10 @@ pub fn mount() -> AlphaRouter<Ctx> {
 				with_hidden_files: bool,
 				#[specta(optional)]
 				order: Option<EphemeralPathOrder>,
+				/// Searches recursively below `path` for entries matching these filters,
+				/// instead of only listing its direct children
+				#[specta(optional)]
+				search: Option<non_indexed::EphemeralSearchFilters>,
 			}
 			#[derive(Serialize, Type, Debug)]
 			struct EphemeralPathsResultItem {
@@ -91,42 +118,58 @@ pub fn mount() -> AlphaRouter<Ctx> {
 				     path,
 				     with_hidden_files,
 				     order,
+				     search,
 				 }| async move {
-					let paths =
-						non_indexed::walk(path, with_hidden_files, node, library, |entries| {
-							macro_rules! order_match {
-								($order:ident, [$(($variant:ident, |$i:ident| $func:expr)),+]) => {{
-									match $order {
-										$(EphemeralPathOrder::$variant(order) => {
-											entries.sort_unstable_by(|path1, path2| {
-												let func = |$i: &non_indexed::Entry| $func;
-
-												let one = func(path1);
-												let two = func(path2);
-
-												match order {
-													SortOrder::Desc => two.cmp(&one),
-													SortOrder::Asc => one.cmp(&two),
-												}
-											});
-										})+
-									}
-								}};
-							}
+					let sort_fn = move |entries: &mut Vec<non_indexed::Entry>| {
+						macro_rules! order_match {
+							($order:ident, [$(($variant:ident, |$i:ident| $func:expr)),+]) => {{
+								match $order {
+									$(EphemeralPathOrder::$variant(order) => {
+										entries.sort_unstable_by(|path1, path2| {
+											let func = |$i: &non_indexed::Entry| $func;
+
+											let one = func(path1);
+											let two = func(path2);
+
+											match order {
+												SortOrder::Desc => two.cmp(&one),
+												SortOrder::Asc => one.cmp(&two),
+											}
+										});
+									})+
+								}
+							}};
+						}
 
-							if let Some(order) = order {
-								order_match!(
-									order,
-									[
-										(Name, |p| p.name().to_lowercase()),
-										(SizeInBytes, |p| p.size_in_bytes()),
-										(DateCreated, |p| p.date_created()),
-										(DateModified, |p| p.date_modified())
-									]
-								)
-							}
-						})
-						.await?;
+						if let Some(order) = &order {
+							order_match!(
+								order,
+								[
+									(Name, |p| p.name().to_lowercase()),
+									(SizeInBytes, |p| p.size_in_bytes()),
+									(DateCreated, |p| p.date_created()),
+									(DateModified, |p| p.date_modified())
+								]
+							)
+						}
+					};
+
+					let paths = if let Some(filters) = search {
+						non_indexed::search(
+							path,
+							with_hidden_files,
+							filters,
+							node,
+							library,
+							sort_fn,
+						)
+						.await?
+						.boxed()
+					} else {
+						non_indexed::walk(path, with_hidden_files, node, library, sort_fn)
+							.await?
+							.boxed()
+					};
 
 					let mut stream = BatchedStream::new(paths);
 					Ok(unsafe_streamed_query(stream! {
@@ -182,7 +225,36 @@ pub fn mount() -> AlphaRouter<Ctx> {
 				 }| async move {
 					let Library { db, .. } = library.as_ref();
 
+					let content_text_match = content_filter(&filters);
+
+					// Without an explicit order, content searches show the most relevant files first
+					let relevance_offset = match &order_and_pagination {
+						None => Some(0),
+						Some(file_path::OrderAndPagination::Offset {
+							offset,
+							order: None,
+						}) => Some(*offset),
+						Some(_) => None,
+					};
+
+					let relevance = content_text_match.as_ref().zip(relevance_offset);
+
 					let params = {
+						// Ranking by relevance already matches the content
+						let filters = if relevance.is_some() {
+							filters
+								.into_iter()
+								.filter(|filter| {
+									!matches!(
+										filter,
+										SearchFilterArgs::FilePath(FilePathFilterArgs::Content(_))
+									)
+								})
+								.collect()
+						} else {
+							filters
+						};
+
 						let (mut fp, obj) = merge_filters(filters, db).await?;
 
 						if !obj.is_empty() {
@@ -192,56 +264,81 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						fp
 					};
 
-					let mut query = db.file_path().find_many(andify(params));
+					let file_paths = if let Some((text_match, offset)) = relevance {
+						// Directories never have text content, so there is nothing to group here
+						let ranked_ids =
+							content::ranked_file_path_ids(text_match, params, take, offset, db)
+								.await?;
 
-					if let Some(take) = take {
-						query = query.take(take as i64);
-					}
+						let mut file_paths = db
+							.file_path()
+							.find_many(vec![prisma::file_path::id::in_vec(ranked_ids.clone())])
+							.include(file_path_for_frontend::include())
+							.exec()
+							.await?;
 
-					// WARN: this order_by for grouping directories MUST always come before the other order_by
-					if group_directories {
-						query = query
-							.order_by(prisma::file_path::is_dir::order(prisma::SortOrder::Desc));
-					}
+						file_paths.sort_by_key(|file_path| {
+							ranked_ids.iter().position(|id| *id == file_path.id)
+						});
 
-					// WARN: this order_by for sorting data MUST always come after the other order_by
-					if let Some(order_and_pagination) = order_and_pagination {
-						order_and_pagination.apply(&mut query, group_directories)
-					}
+						file_paths
+					} else {
+						let mut query = db.file_path().find_many(andify(params));
 
-					let file_paths = query
-						.include(file_path_for_frontend::include())
-						.exec()
-						.await?;
+						if let Some(take) = take {
+							query = query.take(take as i64);
+						}
 
-					let mut items = Vec::with_capacity(file_paths.len());
+						// WARN: this order_by for grouping directories MUST always come before the other order_by
+						if group_directories {
+							query = query.order_by(prisma::file_path::is_dir::order(
+								prisma::SortOrder::Desc,
+							));
+						}
 
-					for file_path in file_paths {
-						let has_created_thumbnail =
-							if let Some(cas_id) = file_path.cas_id.as_ref().map(CasId::from) {
-								library
-									.thumbnail_exists(&node, &cas_id)
-									.await
-									.map_err(LocationError::from)?
-							} else {
-								false
-							};
+						// WARN: this order_by for sorting data MUST always come after the other order_by
+						if let Some(order_and_pagination) = order_and_pagination {
+							order_and_pagination.apply(&mut query, group_directories)
+						}
+
+						query
+							.include(file_path_for_frontend::include())
+							.exec()
+							.await?
+					};
+
+					let mut content_matches = if let Some(text_match) = &content_text_match {
+						content::content_matches(
+							text_match,
+							file_paths
+								.iter()
+								.filter_map(|file_path| file_path.object_id),
+							db,
+						)
+						.await?
+					} else {
+						HashMap::new()
+					};
 
-						items.push(ExplorerItem::Path {
-							thumbnail: file_path
-								.cas_id
-								.as_ref()
-								.map(CasId::from)
-								.map(CasId::into_owned)
-								.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
-							has_created_thumbnail,
-							item: Box::new(file_path),
+					let content_matches = file_paths
+						.iter()
+						.filter_map(|file_path| {
+							file_path
+								.object_id
+								.and_then(|object_id| content_matches.remove(&object_id))
 						})
+						.collect();
+
+					let mut items = Vec::with_capacity(file_paths.len());
+
+					for file_path in file_paths {
+						items.push(path_item(&node, &library, file_path).await?);
 					}
 
 					Ok(SearchData {
 						items,
 						cursor: None,
+						content_matches,
 					})
 				},
 			)
@@ -296,6 +393,8 @@ pub fn mount() -> AlphaRouter<Ctx> {
 
 					let take = take.max(MAX_TAKE);
 
+					let content_text_match = content_filter(&filters);
+
 					let mut query = db
 						.object()
 						.find_many({
@@ -327,38 +426,33 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						(objects, cursor)
 					};
 
-					let mut items = Vec::with_capacity(objects.len());
+					let content_matches = if let Some(text_match) = &content_text_match {
+						let mut content_matches = content::content_matches(
+							text_match,
+							objects.iter().map(|object| object.id),
+							db,
+						)
+						.await?;
 
-					for object in objects {
-						let cas_id = object
-							.file_paths
+						objects
 							.iter()
-							.map(|fp| fp.cas_id.as_ref())
-							.find_map(|c| c)
-							.map(CasId::from)
-							.map(|cas_id| cas_id.to_owned());
-
-						let has_created_thumbnail = if let Some(cas_id) = &cas_id {
-							library.thumbnail_exists(&node, cas_id).await.map_err(|e| {
-								rspc::Error::with_cause(
-									ErrorCode::InternalServerError,
-									"Failed to check that thumbnail exists".to_string(),
-									e,
-								)
-							})?
-						} else {
-							false
-						};
+							.filter_map(|object| content_matches.remove(&object.id))
+							.collect()
+					} else {
+						vec![]
+					};
 
-						items.push(ExplorerItem::Object {
-							thumbnail: cas_id
-								.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
-							item: object,
-							has_created_thumbnail,
-						});
+					let mut items = Vec::with_capacity(objects.len());
+
+					for object in objects {
+						items.push(object_item(&node, &library, object).await?);
 					}
 
-					Ok(SearchData { items, cursor })
+					Ok(SearchData {
+						items,
+						cursor,
+						content_matches,
+					})
 				},
 			)
 		})
@@ -390,7 +484,86 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						.await? as u32)
 				})
 		})
+		.procedure("duplicates", {
+			R.with2(library()).query(
+				|(_, library), args: duplicates::DuplicatesArgs| async move {
+					duplicates::find(args, &library).await
+				},
+			)
+		})
+		.procedure("similarImages", {
+			R.with2(library()).query(
+				|(_, library), args: similar_images::SimilarImagesArgs| async move {
+					similar_images::find(args, &library).await
+				},
+			)
+		})
+		.procedure("parse", {
+			R.with2(library())
+				.query(|(_, library), query: String| async move {
+					query::parse(&query, &library.db).await
+				})
+		})
 		.merge("saved.", saved::mount())
+		.merge("allLibraries.", all_libraries::mount())
+}
+
+async fn path_item(
+	node: &Node,
+	library: &Library,
+	file_path: file_path_for_frontend::Data,
+) -> Result<ExplorerItem, rspc::Error> {
+	let has_created_thumbnail = if let Some(cas_id) = file_path.cas_id.as_ref().map(CasId::from) {
+		library
+			.thumbnail_exists(node, &cas_id)
+			.await
+			.map_err(LocationError::from)?
+	} else {
+		false
+	};
+
+	Ok(ExplorerItem::Path {
+		thumbnail: file_path
+			.cas_id
+			.as_ref()
+			.map(CasId::from)
+			.map(CasId::into_owned)
+			.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
+		has_created_thumbnail,
+		item: Box::new(file_path),
+	})
+}
+
+async fn object_item(
+	node: &Node,
+	library: &Library,
+	object: object_with_file_paths::Data,
+) -> Result<ExplorerItem, rspc::Error> {
+	let cas_id = object
+		.file_paths
+		.iter()
+		.map(|fp| fp.cas_id.as_ref())
+		.find_map(|c| c)
+		.map(CasId::from)
+		.map(|cas_id| cas_id.to_owned());
+
+	let has_created_thumbnail = if let Some(cas_id) = &cas_id {
+		library.thumbnail_exists(node, cas_id).await.map_err(|e| {
+			rspc::Error::with_cause(
+				ErrorCode::InternalServerError,
+				"Failed to check that thumbnail exists".to_string(),
+				e,
+			)
+		})?
+	} else {
+		false
+	};
+
+	Ok(ExplorerItem::Object {
+		thumbnail: cas_id.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
+		item: object,
+		has_created_thumbnail,
+	})
 }
 
 async fn merge_filters(
@@ -406,13 +579,35 @@ async fn merge_filters(
 	let mut obj = vec![];
 	let mut fp = vec![];
 
+	let content_text_match = content_filter(&filters);
+
 	for filter in filters {
 		filter.into_params(db, &mut fp, &mut obj).await?;
 	}
 
+	if let Some(text_match) = content_text_match.filter(|text_match| !text_match.is_empty()) {
+		let mut params = fp.clone();
+		if !obj.is_empty() {
+			params.push(prisma::file_path::object::is(obj.clone()));
+		}
+
+		fp.push(prisma::file_path::object_id::in_vec(
+			content::matching_object_ids(&text_match, params, db).await?,
+		));
+	}
+
 	Ok((fp, obj))
 }
 
+fn content_filter(filters: &[SearchFilterArgs]) -> Option<TextMatch> {
+	filters.iter().find_map(|filter| match filter {
+		SearchFilterArgs::FilePath(FilePathFilterArgs::Content(text_match)) => {
+			Some(text_match.clone())
+		}
+		_ => None,
+	})
+}
+
 /// PCR 0.6.x's AND does { AND: [{ ...}] } instead of { AND: [{ ... }, { ... }, { ... }] },
 /// this works around it.
 fn andify<T: From<Operator<T>>>(params: Vec<T>) -> Vec<T> {
@@ -0,0 +1,1070 @@
+//! A small textual query language that compiles down to [`SearchFilterArgs`].
+//!
+//! A query is a whitespace separated list of terms, each being either free text (matched against
+//! file names) or a `key:value` filter, like:
+//!
+//! ```text
+//! kind:image tag:"Trip 2024" ext:jpg,png modified:>2024-01-01 size:>5MB in:/Photos -ext:gif
+//! camera:X100V iso:>3200 codec:hevc duration:>10m
+//! ```
+//!
+//! Values can be quoted to include spaces, lists are comma separated and a leading `-` negates
+//! the filter where that makes sense, or quoted free text like `-"draft"`.
+
+use sd_file_ext::kind::ObjectKind;
+use sd_prisma::prisma::{label, location, tag, PrismaClient};
+
+use std::path::Path;
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, Utc};
+use serde::Serialize;
+use specta::Type;
+use strum::IntoEnumIterator;
+
+use super::{
+	exif_data::ExifDataFilterArgs, ffmpeg_data::FfmpegFilterArgs, FilePathFilterArgs, InOrNotIn,
+	ObjectFilterArgs, Range, SearchFilterArgs, TextMatch,
+};
+
+location::select!(location_for_query { id name path });
+
+#[derive(Serialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryErrorKind {
+	UnterminatedQuote,
+	EmptyValue,
+	UnknownKey,
+	InvalidValue,
+	NotFound,
+}
+
+/// An error on a specific part of a query, `start` and `end` are character offsets
+#[derive(Serialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryError {
+	pub kind: QueryErrorKind,
+	pub message: String,
+	pub start: u32,
+	pub end: u32,
+}
+
+impl QueryError {
+	fn new(kind: QueryErrorKind, span: Span, message: impl Into<String>) -> Self {
+		#[allow(clippy::cast_possible_truncation)]
+		// SAFETY: No one will ever type a search query with more than 4 billion characters
+		Self {
+			kind,
+			message: message.into(),
+			start: span.start as u32,
+			end: span.end as u32,
+		}
+	}
+}
+
+impl std::fmt::Display for QueryError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} (at {}..{})", self.message, self.start, self.end)
+	}
+}
+
+#[derive(Serialize, Type, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedQuery {
+	pub filters: Vec<SearchFilterArgs>,
+	pub errors: Vec<QueryError>,
+}
+
+impl ParsedQuery {
+	/// Filters of a query, used where we can't show errors inline like saved searches.
+	/// Queries with errors, like free text saved before this language existed, fall back to
+	/// searching file names for the whole `query`.
+	pub fn into_filters_or_name(self, query: &str) -> Vec<SearchFilterArgs> {
+		if self.errors.is_empty() {
+			self.filters
+		} else {
+			vec![SearchFilterArgs::FilePath(FilePathFilterArgs::Name(
+				TextMatch::Contains(query.trim().to_string()),
+			))]
+		}
+	}
+}
+
+/// Parses a textual query and resolves the names it references (tags, labels and locations)
+/// against the library database.
+pub async fn parse(query: &str, db: &PrismaClient) -> Result<ParsedQuery, rspc::Error> {
+	let (terms, mut errors) = tokenize(query);
+
+	let mut filters = vec![];
+	let mut unresolved = vec![];
+
+	for term in terms {
+		match interpret(term) {
+			Ok(interpreted) => {
+				for filter in interpreted {
+					match filter {
+						Interpreted::Ready(filter) => filters.push(filter),
+						other => unresolved.push(other),
+					}
+				}
+			}
+			Err(e) => errors.push(e),
+		}
+	}
+
+	for filter in unresolved {
+		match resolve(filter, db).await? {
+			Ok(filter) => filters.push(filter),
+			Err(e) => errors.extend(e),
+		}
+	}
+
+	errors.sort_by_key(|e| e.start);
+
+	Ok(ParsedQuery { filters, errors })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+	start: usize,
+	end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Value {
+	text: String,
+	span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+	Text(Value),
+	/// Only quoted text can be negated, as names often start with a dash
+	NegatedText(Value),
+	Filter {
+		negated: bool,
+		key: Value,
+		values: Vec<Value>,
+		span: Span,
+	},
+}
+
+fn tokenize(query: &str) -> (Vec<Term>, Vec<QueryError>) {
+	let chars = query.chars().collect::<Vec<_>>();
+	let mut terms = vec![];
+	let mut errors = vec![];
+	let mut i = 0;
+
+	while i < chars.len() {
+		if chars[i].is_whitespace() {
+			i += 1;
+			continue;
+		}
+
+		let start = i;
+		let negated = chars[i] == '-' && chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+		if negated {
+			i += 1;
+		}
+
+		if chars[i] == '"' {
+			match read_quoted(&chars, i) {
+				Ok((value, next)) => {
+					terms.push(if negated {
+						Term::NegatedText(value)
+					} else {
+						Term::Text(value)
+					});
+					i = next;
+				}
+				Err(e) => {
+					errors.push(e);
+					break;
+				}
+			}
+			continue;
+		}
+
+		let key_start = i;
+		while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ':' {
+			i += 1;
+		}
+		let word = chars[key_start..i].iter().collect::<String>();
+
+		if i >= chars.len() || chars[i] != ':' {
+			// A free text term, we keep the leading dash as it's probably part of a file name
+			terms.push(Term::Text(Value {
+				text: chars[start..i].iter().collect(),
+				span: Span { start, end: i },
+			}));
+			continue;
+		}
+
+		let key = Value {
+			text: word.to_lowercase(),
+			span: Span {
+				start: key_start,
+				end: i,
+			},
+		};
+		i += 1; // skipping ':'
+
+		let mut values = vec![];
+		loop {
+			if i < chars.len() && chars[i] == '"' {
+				match read_quoted(&chars, i) {
+					Ok((value, next)) => {
+						values.push(value);
+						i = next;
+					}
+					Err(e) => {
+						errors.push(e);
+						return (terms, errors);
+					}
+				}
+			} else {
+				let value_start = i;
+				while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ',' {
+					i += 1;
+				}
+				values.push(Value {
+					text: chars[value_start..i].iter().collect(),
+					span: Span {
+						start: value_start,
+						end: i,
+					},
+				});
+			}
+
+			if i < chars.len() && chars[i] == ',' {
+				i += 1;
+			} else {
+				break;
+			}
+		}
+
+		terms.push(Term::Filter {
+			negated,
+			key,
+			values,
+			span: Span { start, end: i },
+		});
+	}
+
+	(terms, errors)
+}
+
+fn read_quoted(chars: &[char], start: usize) -> Result<(Value, usize), QueryError> {
+	chars[start + 1..]
+		.iter()
+		.position(|&c| c == '"')
+		.map(|len| {
+			let end = start + 1 + len;
+			(
+				Value {
+					text: chars[start + 1..end].iter().collect(),
+					span: Span {
+						start,
+						end: end + 1,
+					},
+				},
+				end + 1,
+			)
+		})
+		.ok_or_else(|| {
+			QueryError::new(
+				QueryErrorKind::UnterminatedQuote,
+				Span {
+					start,
+					end: chars.len(),
+				},
+				"missing closing quote",
+			)
+		})
+}
+
+/// Filters that only need the query text are built right away, while the ones referencing
+/// things by name have to be resolved against the database later
+#[derive(Debug)]
+enum Interpreted {
+	Ready(SearchFilterArgs),
+	Tags { negated: bool, names: Vec<Value> },
+	Labels { negated: bool, names: Vec<Value> },
+	Locations { negated: bool, names: Vec<Value> },
+	InPath(Value),
+}
+
+fn interpret(term: Term) -> Result<Vec<Interpreted>, QueryError> {
+	use Interpreted::Ready;
+
+	let (negated, key, mut values, span) = match term {
+		Term::Text(Value { text, span }) => {
+			if text.is_empty() {
+				return Err(QueryError::new(
+					QueryErrorKind::EmptyValue,
+					span,
+					"empty search term",
+				));
+			}
+
+			return Ok(vec![Ready(SearchFilterArgs::FilePath(
+				FilePathFilterArgs::Name(TextMatch::Contains(text)),
+			))]);
+		}
+		Term::NegatedText(Value { text, span }) => {
+			if text.is_empty() {
+				return Err(QueryError::new(
+					QueryErrorKind::EmptyValue,
+					span,
+					"empty search term",
+				));
+			}
+
+			return Ok(vec![Ready(SearchFilterArgs::FilePath(
+				FilePathFilterArgs::NotName(TextMatch::Contains(text)),
+			))]);
+		}
+		Term::Filter {
+			negated,
+			key,
+			values,
+			span,
+		} => (negated, key, values, span),
+	};
+
+	if let Some(empty) = values.iter().find(|value| value.text.is_empty()) {
+		return Err(QueryError::new(
+			QueryErrorKind::EmptyValue,
+			empty.span,
+			format!("missing value for `{}`", key.text),
+		));
+	}
+
+	let single = |values: &mut Vec<Value>| {
+		if values.len() == 1 {
+			Ok(values.remove(0))
+		} else {
+			Err(QueryError::new(
+				QueryErrorKind::InvalidValue,
+				span,
+				format!("`{}` only accepts a single value", key.text),
+			))
+		}
+	};
+
+	let not_negated = || {
+		if negated {
+			Err(QueryError::new(
+				QueryErrorKind::InvalidValue,
+				span,
+				format!("`{}` can't be negated", key.text),
+			))
+		} else {
+			Ok(())
+		}
+	};
+
+	let in_or_not_in = |values: Vec<String>| {
+		if negated {
+			InOrNotIn::NotIn(values)
+		} else {
+			InOrNotIn::In(values)
+		}
+	};
+
+	Ok(match key.text.as_str() {
+		"name" => {
+			not_negated()?;
+			vec![Ready(SearchFilterArgs::FilePath(FilePathFilterArgs::Name(
+				TextMatch::Contains(single(&mut values)?.text),
+			)))]
+		}
+
+		"content" => {
+			not_negated()?;
+			vec![Ready(SearchFilterArgs::FilePath(
+				FilePathFilterArgs::Content(TextMatch::Contains(single(&mut values)?.text)),
+			))]
+		}
+
+		"ext" | "extension" => vec![Ready(SearchFilterArgs::FilePath(
+			FilePathFilterArgs::Extension(in_or_not_in(
+				values
+					.into_iter()
+					.map(|value| value.text.trim_start_matches('.').to_lowercase())
+					.collect(),
+			)),
+		))],
+
+		"kind" => {
+			let kinds = values
+				.iter()
+				.map(|value| parse_kind(value).map(|kind| kind as i32))
+				.collect::<Result<Vec<_>, _>>()?;
+
+			vec![Ready(SearchFilterArgs::Object(ObjectFilterArgs::Kind(
+				if negated {
+					InOrNotIn::NotIn(kinds)
+				} else {
+					InOrNotIn::In(kinds)
+				},
+			)))]
+		}
+
+		"tag" | "tags" => vec![Interpreted::Tags {
+			negated,
+			names: values,
+		}],
+
+		"label" | "labels" => vec![Interpreted::Labels {
+			negated,
+			names: values,
+		}],
+
+		"location" => vec![Interpreted::Locations {
+			negated,
+			names: values,
+		}],
+
+		"in" => {
+			not_negated()?;
+			vec![Interpreted::InPath(single(&mut values)?)]
+		}
+
+		"hidden" => vec![Ready(SearchFilterArgs::FilePath(
+			FilePathFilterArgs::Hidden(parse_bool(&single(&mut values)?)? != negated),
+		))],
+
+		"favorite" | "favourite" => vec![Ready(SearchFilterArgs::Object(
+			ObjectFilterArgs::Favorite(parse_bool(&single(&mut values)?)? != negated),
+		))],
+
+		"created" | "modified" | "indexed" | "accessed" => {
+			not_negated()?;
+			let ranges = parse_date_ranges(&single(&mut values)?)?;
+
+			ranges
+				.into_iter()
+				.map(|range| {
+					Ready(match key.text.as_str() {
+						"created" => {
+							SearchFilterArgs::FilePath(FilePathFilterArgs::CreatedAt(range))
+						}
+						"modified" => {
+							SearchFilterArgs::FilePath(FilePathFilterArgs::ModifiedAt(range))
+						}
+						"indexed" => {
+							SearchFilterArgs::FilePath(FilePathFilterArgs::IndexedAt(range))
+						}
+						_ => {
+							SearchFilterArgs::Object(ObjectFilterArgs::DateAccessed(match range {
+								Range::From(date) => Range::From(date.fixed_offset()),
+								Range::To(date) => Range::To(date.fixed_offset()),
+							}))
+						}
+					})
+				})
+				.collect()
+		}
+
+		"size" => {
+			not_negated()?;
+			let (comparison, size) = parse_size_comparison(&single(&mut values)?)?;
+
+			comparison_ranges(
+				comparison,
+				size,
+				|size| size.saturating_add(1),
+				|size| size.saturating_sub(1),
+			)
+			.into_iter()
+			.map(|range| {
+				Ready(SearchFilterArgs::FilePath(FilePathFilterArgs::SizeInBytes(
+					range,
+				)))
+			})
+			.collect()
+		}
+
+		"camera" | "make" | "lens" => {
+			not_negated()?;
+			let text_match = TextMatch::Contains(single(&mut values)?.text);
+
+			vec![Ready(SearchFilterArgs::ExifData(match key.text.as_str() {
+				"camera" => ExifDataFilterArgs::CameraModel(text_match),
+				"make" => ExifDataFilterArgs::CameraMake(text_match),
+				_ => ExifDataFilterArgs::Lens(text_match),
+			}))]
+		}
+
+		"iso" => {
+			not_negated()?;
+			let value = single(&mut values)?;
+			let (comparison, iso) = split_comparison(&value.text);
+			let iso = iso.parse::<i32>().map_err(|_| {
+				QueryError::new(
+					QueryErrorKind::InvalidValue,
+					value.span,
+					format!("invalid ISO `{iso}`, expected a number"),
+				)
+			})?;
+
+			comparison_ranges(
+				comparison,
+				iso,
+				|iso| iso.saturating_add(1),
+				|iso| iso.saturating_sub(1),
+			)
+			.into_iter()
+			.map(|range| Ready(SearchFilterArgs::ExifData(ExifDataFilterArgs::Iso(range))))
+			.collect()
+		}
+
+		"codec" => vec![Ready(SearchFilterArgs::FfmpegData(
+			FfmpegFilterArgs::Codec(in_or_not_in(
+				values.into_iter().map(|value| value.text).collect(),
+			)),
+		))],
+
+		"duration" => {
+			not_negated()?;
+			let value = single(&mut values)?;
+			let (comparison, duration) = split_comparison(&value.text);
+			let duration =
+				parse_duration(duration).ok_or_else(|| {
+					QueryError::new(
+					QueryErrorKind::InvalidValue,
+					value.span,
+					format!("invalid duration `{duration}`, expected something like `90s` or `1.5h`"),
+				)
+				})?;
+
+			// Durations are continuous, so strict and non strict comparisons are the same
+			comparison_ranges(comparison, duration, |d| d, |d| d)
+				.into_iter()
+				.map(|range| {
+					Ready(SearchFilterArgs::FfmpegData(FfmpegFilterArgs::Duration(
+						range,
+					)))
+				})
+				.collect()
+		}
+
+		"dir" | "folder" => vec![Ready(SearchFilterArgs::FilePath(
+			FilePathFilterArgs::IsDir(parse_bool(&single(&mut values)?)? != negated),
+		))],
+
+		_ => {
+			return Err(QueryError::new(
+				QueryErrorKind::UnknownKey,
+				key.span,
+				format!("unknown filter `{}`", key.text),
+			))
+		}
+	})
+}
+
+fn parse_kind(value: &Value) -> Result<ObjectKind, QueryError> {
+	ObjectKind::iter()
+		.find(|kind| kind.to_string().eq_ignore_ascii_case(&value.text))
+		.ok_or_else(|| {
+			QueryError::new(
+				QueryErrorKind::InvalidValue,
+				value.span,
+				format!("unknown kind `{}`", value.text),
+			)
+		})
+}
+
+fn parse_bool(value: &Value) -> Result<bool, QueryError> {
+	match value.text.to_lowercase().as_str() {
+		"true" | "yes" | "1" => Ok(true),
+		"false" | "no" | "0" => Ok(false),
+		_ => Err(QueryError::new(
+			QueryErrorKind::InvalidValue,
+			value.span,
+			format!("expected `true` or `false`, found `{}`", value.text),
+		)),
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+	Greater,
+	GreaterOrEqual,
+	Less,
+	LessOrEqual,
+	Equal,
+}
+
+fn split_comparison(text: &str) -> (Comparison, &str) {
+	[
+		(">=", Comparison::GreaterOrEqual),
+		("<=", Comparison::LessOrEqual),
+		(">", Comparison::Greater),
+		("<", Comparison::Less),
+		("=", Comparison::Equal),
+	]
+	.into_iter()
+	.find_map(|(prefix, comparison)| text.strip_prefix(prefix).map(|rest| (comparison, rest)))
+	.unwrap_or((Comparison::Equal, text))
+}
+
+/// Ranges are inclusive, so strict comparisons have to step over the compared value
+fn comparison_ranges<T: Copy>(
+	comparison: Comparison,
+	value: T,
+	next: impl Fn(T) -> T,
+	previous: impl Fn(T) -> T,
+) -> Vec<Range<T>> {
+	match comparison {
+		Comparison::Greater => vec![Range::From(next(value))],
+		Comparison::GreaterOrEqual => vec![Range::From(value)],
+		Comparison::Less => vec![Range::To(previous(value))],
+		Comparison::LessOrEqual => vec![Range::To(value)],
+		Comparison::Equal => vec![Range::From(value), Range::To(value)],
+	}
+}
+
+/// Returns the first instant of the given date and the first instant after it, so a date without
+/// time covers the whole day
+fn parse_date(text: &str, span: Span) -> Result<(DateTime<Utc>, DateTime<Utc>), QueryError> {
+	if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+		let start = date.and_time(chrono::NaiveTime::MIN).and_utc();
+		return Ok((start, start + Days::new(1)));
+	}
+
+	DateTime::<FixedOffset>::parse_from_rfc3339(text)
+		.map(|date| (date.to_utc(), date.to_utc()))
+		.map_err(|_| {
+			QueryError::new(
+				QueryErrorKind::InvalidValue,
+				span,
+				format!("invalid date `{text}`, expected `YYYY-MM-DD` or a RFC 3339 timestamp"),
+			)
+		})
+}
+
+fn parse_date_ranges(value: &Value) -> Result<Vec<Range<DateTime<Utc>>>, QueryError> {
+	if let Some((from, to)) = value.text.split_once("..") {
+		let mut ranges = vec![];
+		if !from.is_empty() {
+			ranges.push(Range::From(parse_date(from, value.span)?.0));
+		}
+		if !to.is_empty() {
+			ranges.push(Range::To(parse_date(to, value.span)?.1));
+		}
+		return Ok(ranges);
+	}
+
+	let (comparison, date) = split_comparison(&value.text);
+	let (start, next) = parse_date(date, value.span)?;
+
+	Ok(match comparison {
+		Comparison::Greater => vec![Range::From(next)],
+		Comparison::GreaterOrEqual => vec![Range::From(start)],
+		Comparison::Less => vec![Range::To(start)],
+		Comparison::LessOrEqual => vec![Range::To(next)],
+		Comparison::Equal => vec![Range::From(start), Range::To(next)],
+	})
+}
+
+/// Parses human readable sizes like `5MB`, `1.5GiB` or `1024`, decimal units are powers of 1000
+/// and binary ones are powers of 1024
+fn parse_size(text: &str) -> Option<u64> {
+	let number_end = text
+		.find(|c: char| !c.is_ascii_digit() && c != '.')
+		.unwrap_or(text.len());
+	let (number, unit) = text.split_at(number_end);
+
+	let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+		"" | "b" => 1,
+		"k" | "kb" => 1000,
+		"kib" => 1 << 10,
+		"m" | "mb" => 1000u64.pow(2),
+		"mib" => 1 << 20,
+		"g" | "gb" => 1000u64.pow(3),
+		"gib" => 1 << 30,
+		"t" | "tb" => 1000u64.pow(4),
+		"tib" => 1 << 40,
+		_ => return None,
+	};
+
+	if let Ok(number) = number.parse::<u64>() {
+		return number.checked_mul(multiplier);
+	}
+
+	let number = number.parse::<f64>().ok()?;
+
+	#[allow(
+		clippy::cast_precision_loss,
+		clippy::cast_possible_truncation,
+		clippy::cast_sign_loss
+	)]
+	// SAFETY: We checked that the result is a finite non negative number that fits in a u64
+	{
+		let bytes = (number * multiplier as f64).round();
+		(bytes.is_finite() && bytes >= 0.0 && bytes < u64::MAX as f64).then_some(bytes as u64)
+	}
+}
+
+/// Parses durations in seconds, with an optional `s`, `m` or `h` suffix
+fn parse_duration(text: &str) -> Option<f64> {
+	let (number, multiplier) = [("h", 3600.0), ("m", 60.0), ("s", 1.0)]
+		.into_iter()
+		.find_map(|(suffix, multiplier)| {
+			text.strip_suffix(suffix).map(|number| (number, multiplier))
+		})
+		.unwrap_or((text, 1.0));
+
+	number
+		.parse::<f64>()
+		.ok()
+		.filter(|number| number.is_finite() && *number >= 0.0)
+		.map(|number| number * multiplier)
+}
+
+fn parse_size_comparison(value: &Value) -> Result<(Comparison, u64), QueryError> {
+	let (comparison, size) = split_comparison(&value.text);
+
+	parse_size(size)
+		.map(|size| (comparison, size))
+		.ok_or_else(|| {
+			QueryError::new(
+				QueryErrorKind::InvalidValue,
+				value.span,
+				format!("invalid size `{size}`, expected something like `500KB` or `1.5GiB`"),
+			)
+		})
+}
+
+fn not_found(value: &Value, what: &str) -> QueryError {
+	QueryError::new(
+		QueryErrorKind::NotFound,
+		value.span,
+		format!("{what} `{}` not found", value.text),
+	)
+}
+
+/// Resolves names against the database, the outer error is for database failures while the inner
+/// one holds errors on the query itself
+async fn resolve(
+	interpreted: Interpreted,
+	db: &PrismaClient,
+) -> Result<Result<SearchFilterArgs, Vec<QueryError>>, rspc::Error> {
+	fn match_names<T>(
+		names: Vec<Value>,
+		candidates: &[T],
+		name_of: impl Fn(&T) -> Option<&str>,
+		id_of: impl Fn(&T) -> i32,
+		what: &str,
+	) -> Result<Vec<i32>, Vec<QueryError>> {
+		let mut ids = vec![];
+		let mut errors = vec![];
+
+		for name in names {
+			let found = candidates
+				.iter()
+				.filter(|candidate| {
+					name_of(candidate)
+						.is_some_and(|candidate| candidate.eq_ignore_ascii_case(&name.text))
+				})
+				.map(&id_of)
+				.collect::<Vec<_>>();
+
+			if found.is_empty() {
+				errors.push(not_found(&name, what));
+			} else {
+				ids.extend(found);
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(ids)
+		} else {
+			Err(errors)
+		}
+	}
+
+	let in_or_not_in = |negated: bool, ids: Vec<i32>| {
+		if negated {
+			InOrNotIn::NotIn(ids)
+		} else {
+			InOrNotIn::In(ids)
+		}
+	};
+
+	Ok(match interpreted {
+		Interpreted::Ready(filter) => Ok(filter),
+
+		Interpreted::Tags { negated, names } => {
+			let tags = db
+				.tag()
+				.find_many(vec![])
+				.select(tag::select!({ id name }))
+				.exec()
+				.await?;
+
+			match_names(names, &tags, |tag| tag.name.as_deref(), |tag| tag.id, "tag").map(|ids| {
+				SearchFilterArgs::Object(ObjectFilterArgs::Tags(in_or_not_in(negated, ids)))
+			})
+		}
+
+		Interpreted::Labels { negated, names } => {
+			let labels = db
+				.label()
+				.find_many(vec![])
+				.select(label::select!({ id name }))
+				.exec()
+				.await?;
+
+			match_names(
+				names,
+				&labels,
+				|label| Some(label.name.as_str()),
+				|label| label.id,
+				"label",
+			)
+			.map(|ids| {
+				SearchFilterArgs::Object(ObjectFilterArgs::Labels(in_or_not_in(negated, ids)))
+			})
+		}
+
+		Interpreted::Locations { negated, names } => {
+			let locations = db
+				.location()
+				.find_many(vec![])
+				.select(location_for_query::select())
+				.exec()
+				.await?;
+
+			match_names(
+				names,
+				&locations,
+				|location| location.name.as_deref(),
+				|location| location.id,
+				"location",
+			)
+			.map(|ids| {
+				SearchFilterArgs::FilePath(FilePathFilterArgs::Locations(in_or_not_in(
+					negated, ids,
+				)))
+			})
+		}
+
+		Interpreted::InPath(value) => {
+			let locations = db
+				.location()
+				.find_many(vec![])
+				.select(location_for_query::select())
+				.exec()
+				.await?;
+
+			resolve_in_path(&value, &locations).map_err(|e| vec![e])
+		}
+	})
+}
+
+/// `in:` accepts either an absolute path inside a location, or a path starting with a location
+/// name, like `in:Photos/2024`. A leading slash is optional in the later form.
+fn resolve_in_path(
+	value: &Value,
+	locations: &[location_for_query::Data],
+) -> Result<SearchFilterArgs, QueryError> {
+	let in_location = |location_id, path: &str| {
+		SearchFilterArgs::FilePath(FilePathFilterArgs::Path {
+			location_id,
+			path: format!("/{}", path.trim_matches('/')),
+			include_descendants: true,
+		})
+	};
+
+	let path = Path::new(&value.text);
+
+	// Prefer the deepest location containing the path, as locations can be nested
+	if let Some((location_id, sub_path)) = locations
+		.iter()
+		.filter_map(|location| {
+			let location_path = Path::new(location.path.as_deref()?);
+			path.strip_prefix(location_path)
+				.ok()
+				.map(|sub_path| (location.id, location_path.as_os_str().len(), sub_path))
+		})
+		.max_by_key(|(_, location_path_len, _)| *location_path_len)
+		.map(|(location_id, _, sub_path)| (location_id, sub_path.to_string_lossy()))
+	{
+		return Ok(in_location(location_id, &sub_path));
+	}
+
+	let trimmed = value.text.trim_start_matches('/');
+	let (location_name, sub_path) = trimmed.split_once('/').unwrap_or((trimmed, ""));
+
+	locations
+		.iter()
+		.find(|location| {
+			location
+				.name
+				.as_deref()
+				.is_some_and(|name| name.eq_ignore_ascii_case(location_name))
+		})
+		.map(|location| in_location(location.id, sub_path))
+		.ok_or_else(|| not_found(value, "location for path"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn value(text: &str, start: usize, end: usize) -> Value {
+		Value {
+			text: text.into(),
+			span: Span { start, end },
+		}
+	}
+
+	#[test]
+	fn tokenize_mixed_query() {
+		let (terms, errors) = tokenize(r#"holiday -ext:gif,PNG tag:"Trip 2024" in:/Photos"#);
+
+		assert!(errors.is_empty());
+		assert_eq!(
+			terms,
+			vec![
+				Term::Text(value("holiday", 0, 7)),
+				Term::Filter {
+					negated: true,
+					key: value("ext", 9, 12),
+					values: vec![value("gif", 13, 16), value("PNG", 17, 20)],
+					span: Span { start: 8, end: 20 },
+				},
+				Term::Filter {
+					negated: false,
+					key: value("tag", 21, 24),
+					values: vec![value("Trip 2024", 25, 36)],
+					span: Span { start: 21, end: 36 },
+				},
+				Term::Filter {
+					negated: false,
+					key: value("in", 37, 39),
+					values: vec![value("/Photos", 40, 47)],
+					span: Span { start: 37, end: 47 },
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn tokenize_negated_quoted_text() {
+		let (terms, errors) = tokenize(r#"-"draft copy" -notes"#);
+
+		assert!(errors.is_empty());
+		assert_eq!(
+			terms,
+			vec![
+				Term::NegatedText(value("draft copy", 1, 13)),
+				Term::Text(value("-notes", 14, 20)),
+			]
+		);
+	}
+
+	#[test]
+	fn offsets_are_in_characters() {
+		let (_, errors) = tokenize(r#"café tag:"unterminated"#);
+
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].kind, QueryErrorKind::UnterminatedQuote);
+		assert_eq!((errors[0].start, errors[0].end), (9, 22));
+	}
+
+	#[test]
+	fn unknown_keys_and_bad_values() {
+		let (terms, _) = tokenize("colour:red kind:imag hidden:maybe ext:");
+		let errors = terms
+			.into_iter()
+			.map(|term| interpret(term).expect_err("all terms are invalid"))
+			.map(|e| (e.kind, e.start, e.end))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			errors,
+			vec![
+				(QueryErrorKind::UnknownKey, 0, 6),
+				(QueryErrorKind::InvalidValue, 16, 20),
+				(QueryErrorKind::InvalidValue, 28, 33),
+				(QueryErrorKind::EmptyValue, 38, 38),
+			]
+		);
+	}
+
+	#[test]
+	fn date_comparisons() {
+		let day = |text| NaiveDate::parse_from_str(text, "%Y-%m-%d").expect("valid date");
+		let start = |text| day(text).and_time(chrono::NaiveTime::MIN).and_utc();
+
+		let ranges = |text: &str| {
+			parse_date_ranges(&value(text, 0, text.len()))
+				.expect("valid range")
+				.into_iter()
+				.map(|range| match range {
+					Range::From(date) => (true, date),
+					Range::To(date) => (false, date),
+				})
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(ranges(">2024-01-01"), vec![(true, start("2024-01-02"))]);
+		assert_eq!(ranges(">=2024-01-01"), vec![(true, start("2024-01-01"))]);
+		assert_eq!(ranges("<2024-01-01"), vec![(false, start("2024-01-01"))]);
+		assert_eq!(
+			ranges("2024-01-01"),
+			vec![(true, start("2024-01-01")), (false, start("2024-01-02"))]
+		);
+		assert_eq!(
+			ranges("2024-01-01..2024-01-31"),
+			vec![(true, start("2024-01-01")), (false, start("2024-02-01"))]
+		);
+	}
+
+	#[test]
+	fn size_comparisons() {
+		let ranges = |query: &str| {
+			let (mut terms, _) = tokenize(query);
+			interpret(terms.remove(0))
+				.expect("valid size")
+				.into_iter()
+				.map(|interpreted| match interpreted {
+					Interpreted::Ready(SearchFilterArgs::FilePath(
+						FilePathFilterArgs::SizeInBytes(Range::From(size)),
+					)) => (true, size),
+					Interpreted::Ready(SearchFilterArgs::FilePath(
+						FilePathFilterArgs::SizeInBytes(Range::To(size)),
+					)) => (false, size),
+					other => panic!("unexpected filter: {other:?}"),
+				})
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(ranges("size:>5MB"), vec![(true, 5_000_001)]);
+		assert_eq!(ranges("size:<=1KiB"), vec![(false, 1024)]);
+		assert_eq!(ranges("size:100"), vec![(true, 100), (false, 100)]);
+	}
+
+	#[test]
+	fn sizes() {
+		assert_eq!(parse_size("1024"), Some(1024));
+		assert_eq!(parse_size("5MB"), Some(5_000_000));
+		assert_eq!(parse_size("1.5GiB"), Some(1_610_612_736));
+		assert_eq!(parse_size("2 kib"), Some(2048));
+		assert_eq!(parse_size("5 parsecs"), None);
+		assert_eq!(parse_size("MB"), None);
+	}
+
+	#[test]
+	fn durations() {
+		assert_eq!(parse_duration("90"), Some(90.0));
+		assert_eq!(parse_duration("90s"), Some(90.0));
+		assert_eq!(parse_duration("10m"), Some(600.0));
+		assert_eq!(parse_duration("1.5h"), Some(5400.0));
+		assert_eq!(parse_duration("-1m"), None);
+		assert_eq!(parse_duration("soon"), None);
+	}
+}
@@ -1,19 +1,40 @@
-use std::str::FromStr;
+use crate::{
+	api::{
+		utils::{library, InvalidateOperationEvent},
+		CoreEvent,
+	},
+	invalidate_query,
+	library::{Library, LibraryId},
+};
 
-use crate::{api::utils::library, invalidate_query, library::Library};
-
-use sd_prisma::{prisma::saved_search, prisma_sync};
+use sd_prisma::{
+	prisma::{file_path, saved_search, PrismaClient, SortOrder},
+	prisma_sync,
+};
 use sd_sync::{option_sync_db_entry, sync_db_entry, OperationFactory};
 use sd_utils::chain_optional_iter;
 
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use async_stream::stream;
 use chrono::{DateTime, FixedOffset, Utc};
 use rspc::alpha::AlphaRouter;
-use serde::{de::IgnoredAny, Deserialize};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
 use specta::Type;
-use tracing::error;
+use tokio::{sync::broadcast, time::sleep};
+use tracing::{debug, error};
 use uuid::Uuid;
 
-use super::{Ctx, R};
+use super::{
+	andify, file_path::OrderAndPagination, merge_filters, query, Ctx, SearchFilterArgs, MAX_TAKE, R,
+};
+
+/// How long we wait for the database to settle before evaluating a subscribed saved search again,
+/// as the indexer and identifier touch lots of rows in quick succession
+const SUBSCRIPTION_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Invalidated queries which may change the results of a saved search
+const SUBSCRIPTION_TRIGGERS: [&str; 4] = ["search.", "locations.", "tags.", "labels."];
 
 #[derive(Type, Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
@@ -64,8 +85,14 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					pub icon: Option<String>,
 				}
 
-				|(_, library), args: Args| async move {
+				|(_, library), mut args: Args| async move {
 					let Library { db, sync, .. } = library.as_ref();
+
+					if let Some(search) = &args.search {
+						let filters = filters_from_query(search, db).await?;
+						args.filters = args.filters.or(filters);
+					}
+
 					let pub_id = Uuid::now_v7().as_bytes().to_vec();
 					let date_created: DateTime<FixedOffset> = Utc::now().into();
 
@@ -140,6 +167,88 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					.await?)
 			})
 		})
+		.procedure("subscribe", {
+			#[derive(Type, Deserialize, Debug)]
+			#[serde(rename_all = "camelCase")]
+			#[specta(inline)]
+			struct SubscribeArgs {
+				search_id: saved_search::id::Type,
+				/// Same as in `search.paths`, so only the page the client is showing is watched
+				#[specta(optional)]
+				take: Option<u8>,
+				#[specta(optional)]
+				order_and_pagination: Option<OrderAndPagination>,
+				#[serde(default = "default_group_directories")]
+				group_directories: bool,
+			}
+
+			fn default_group_directories() -> bool {
+				true
+			}
+
+			#[derive(Serialize, Type, Debug)]
+			#[specta(inline)]
+			struct SavedSearchChanges {
+				added: Vec<file_path::id::Type>,
+				removed: Vec<file_path::id::Type>,
+			}
+
+			R.with2(library()).subscription(
+				|(node, library),
+				 SubscribeArgs {
+				     search_id,
+				     take,
+				     order_and_pagination,
+				     group_directories,
+				 }| async move {
+					let mut event_bus_rx = node.event_bus.0.subscribe();
+					let take = take.map_or(MAX_TAKE, |take| take.min(MAX_TAKE));
+
+					stream! {
+						// The first message has every path of the page currently matching the saved search
+						let mut matching = HashSet::new();
+						let mut first = true;
+
+						loop {
+							let paths = match matching_paths(
+								search_id,
+								take,
+								order_and_pagination.clone(),
+								group_directories,
+								&library.db,
+							)
+							.await
+							{
+								Ok(Some(paths)) => paths,
+								Ok(None) => {
+									debug!(%search_id, "Saved search was deleted, ending subscription");
+									break;
+								}
+								Err(e) => {
+									error!(%search_id, ?e, "Failed to evaluate saved search;");
+									yield Err(e);
+									break;
+								}
+							};
+
+							let added = paths.difference(&matching).copied().collect::<Vec<_>>();
+							let removed = matching.difference(&paths).copied().collect::<Vec<_>>();
+
+							if first || !added.is_empty() || !removed.is_empty() {
+								first = false;
+								yield Ok(SavedSearchChanges { added, removed });
+							}
+
+							matching = paths;
+
+							if !wait_for_changes(&mut event_bus_rx, library.id).await {
+								break;
+							}
+						}
+					}
+				},
+			)
+		})
 		.procedure("update", {
 			R.with2(library()).mutation({
 				saved_search::partial_unchecked!(Args {
@@ -150,10 +259,17 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					filters
 				});
 
-				|(_, library), (id, args): (saved_search::id::Type, Args)| async move {
+				|(_, library), (id, mut args): (saved_search::id::Type, Args)| async move {
 					let Library { db, sync, .. } = library.as_ref();
 					let updated_at = Utc::now();
 
+					if let Some(Some(search)) = &args.search {
+						let filters = filters_from_query(search, db).await?;
+						if args.filters.is_none() {
+							args.filters = filters.map(Some);
+						}
+					}
+
 					let search = db
 						.saved_search()
 						.find_unique(saved_search::id::equals(id))
@@ -230,3 +346,129 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				})
 		})
 }
+
+/// Compiles the raw query of a saved search down to filters, so clients that only know how to
+/// deal with filters can still use the saved search. Queries that don't parse search file names.
+async fn filters_from_query(
+	search: &str,
+	db: &PrismaClient,
+) -> Result<Option<String>, rspc::Error> {
+	if search.trim().is_empty() {
+		return Ok(None);
+	}
+
+	let filters = query::parse(search, db).await?.into_filters_or_name(search);
+
+	serde_json::to_string(&filters).map(Some).map_err(|e| {
+		rspc::Error::with_cause(
+			rspc::ErrorCode::InternalServerError,
+			"Failed to serialize search filters".into(),
+			e,
+		)
+	})
+}
+
+/// Evaluates the stored filters of a saved search, or its raw query if it has no filters, over
+/// the same page as `search.paths` would return, returning `None` if the saved search doesn't
+/// exist anymore
+async fn matching_paths(
+	search_id: saved_search::id::Type,
+	take: u8,
+	order_and_pagination: Option<OrderAndPagination>,
+	group_directories: bool,
+	db: &PrismaClient,
+) -> Result<Option<HashSet<file_path::id::Type>>, rspc::Error> {
+	let Some(search) = db
+		.saved_search()
+		.find_unique(saved_search::id::equals(search_id))
+		.select(saved_search::select!({ search filters }))
+		.exec()
+		.await?
+	else {
+		return Ok(None);
+	};
+
+	let filters = match (search.filters, search.search) {
+		(Some(filters), _) => {
+			serde_json::from_str::<Vec<SearchFilterArgs>>(&filters).map_err(|e| {
+				rspc::Error::with_cause(
+					rspc::ErrorCode::InternalServerError,
+					"Failed to deserialize saved search filters".into(),
+					e,
+				)
+			})?
+		}
+		(None, Some(search)) => query::parse(&search, db)
+			.await?
+			.into_filters_or_name(&search),
+		(None, None) => vec![],
+	};
+
+	let (mut fp, obj) = merge_filters(filters, db).await?;
+
+	if !obj.is_empty() {
+		fp.push(file_path::object::is(obj));
+	}
+
+	let mut query = db.file_path().find_many(andify(fp)).take(i64::from(take));
+
+	// WARN: same ordering rules as `search.paths`, grouping directories MUST come first
+	if group_directories {
+		query = query.order_by(file_path::is_dir::order(SortOrder::Desc));
+	}
+
+	if let Some(order_and_pagination) = order_and_pagination {
+		order_and_pagination.apply(&mut query, group_directories);
+	}
+
+	Ok(Some(
+		query
+			.select(file_path::select!({ id }))
+			.exec()
+			.await?
+			.into_iter()
+			.map(|file_path| file_path.id)
+			.collect(),
+	))
+}
+
+/// Waits until something which may affect saved searches of the library happens, then for things
+/// to settle down. Returns `false` if the event bus was closed.
+async fn wait_for_changes(
+	event_bus_rx: &mut broadcast::Receiver<CoreEvent>,
+	library_id: LibraryId,
+) -> bool {
+	loop {
+		match event_bus_rx.recv().await {
+			Ok(CoreEvent::NewIdentifiedObjects {
+				library_id: event_library_id,
+				..
+			}) if event_library_id == library_id => break,
+
+			Ok(CoreEvent::InvalidateOperation(InvalidateOperationEvent::All))
+			// If we missed events, we can't know what changed
+			| Err(broadcast::error::RecvError::Lagged(_)) => break,
+
+			Ok(CoreEvent::InvalidateOperation(InvalidateOperationEvent::Single(event)))
+				// Invalidations without a library come from the node and may affect every library
+				if event.library_id.map_or(true, |id| id == library_id)
+					&& SUBSCRIPTION_TRIGGERS
+						.iter()
+						.any(|prefix| event.key.starts_with(prefix)) =>
+			{
+				break
+			}
+
+			Ok(_) => {}
+
+			Err(broadcast::error::RecvError::Closed) => return false,
+		}
+	}
+
+	sleep(SUBSCRIPTION_DEBOUNCE).await;
+
+	// Everything which happened while we were waiting is covered by the next evaluation
+	*event_bus_rx = event_bus_rx.resubscribe();
+
+	true
+}
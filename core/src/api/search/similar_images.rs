@@ -0,0 +1,114 @@
+use crate::library::Library;
+
+use sd_core_heavy_lifting::media_processor::perceptual_hash;
+use sd_core_prisma_helpers::object_with_file_paths;
+
+use sd_prisma::prisma::object;
+
+use std::collections::HashMap;
+
+use rspc::ErrorCode;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::MAX_TAKE;
+
+#[derive(Deserialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImagesArgs {
+	pub object_id: object::id::Type,
+	/// How many of the 64 perceptual hash bits may differ, re-encoded or resized copies of a
+	/// picture are usually within 5 bits and unrelated pictures are around 32 bits apart
+	pub max_distance: u32,
+	#[specta(optional)]
+	pub take: Option<u8>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarImage {
+	pub distance: u32,
+	pub object: object_with_file_paths::Data,
+}
+
+/// Finds objects whose perceptual hash is within `max_distance` of the given object's one,
+/// closest first. The queried object itself isn't included.
+pub async fn find(
+	SimilarImagesArgs {
+		object_id,
+		max_distance,
+		take,
+	}: SimilarImagesArgs,
+	library: &Library,
+) -> Result<Vec<SimilarImage>, rspc::Error> {
+	let Library { db, .. } = library;
+
+	let take = usize::from(take.unwrap_or(MAX_TAKE).min(MAX_TAKE));
+
+	let hash = db
+		.object()
+		.find_unique(object::id::equals(object_id))
+		.select(object::select!({ perceptual_hash }))
+		.exec()
+		.await?
+		.ok_or_else(|| {
+			rspc::Error::new(
+				ErrorCode::NotFound,
+				format!("object <id='{object_id}'> not found"),
+			)
+		})?
+		.perceptual_hash
+		.map(perceptual_hash::from_db)
+		.ok_or_else(|| {
+			rspc::Error::new(
+				ErrorCode::BadRequest,
+				format!("object <id='{object_id}'> doesn't have a perceptual hash yet"),
+			)
+		})?;
+
+	// Hamming distance can't be computed by SQLite, but the hashes are just 8 bytes per object
+	// so comparing all of them here is fast enough
+	let mut candidates = db
+		.object()
+		.find_many(vec![
+			object::perceptual_hash::not(None),
+			object::id::not(object_id),
+		])
+		.select(object::select!({ id perceptual_hash }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|candidate| {
+			let distance = perceptual_hash::distance(
+				hash,
+				perceptual_hash::from_db(candidate.perceptual_hash?),
+			);
+
+			(distance <= max_distance).then_some((candidate.id, distance))
+		})
+		.collect::<Vec<_>>();
+
+	candidates.sort_unstable_by_key(|&(id, distance)| (distance, id));
+	candidates.truncate(take);
+
+	let mut objects = db
+		.object()
+		.find_many(vec![object::id::in_vec(
+			candidates.iter().map(|&(id, _)| id).collect(),
+		)])
+		.include(object_with_file_paths::include())
+		.exec()
+		.await?
+		.into_iter()
+		.map(|object| (object.id, object))
+		.collect::<HashMap<_, _>>();
+
+	Ok(candidates
+		.into_iter()
+		.filter_map(|(id, distance)| {
+			objects
+				.remove(&id)
+				.map(|object| SimilarImage { distance, object })
+		})
+		.collect())
+}
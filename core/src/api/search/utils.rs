@@ -42,14 +42,14 @@ impl From<SortOrder> for prisma::SortOrder {
 // 	}
 // }
 
-#[derive(Deserialize, Type, Debug)]
+#[derive(Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CursorOrderItem<T> {
 	pub order: SortOrder,
 	pub data: T,
 }
 
-#[derive(Deserialize, Type, Debug)]
+#[derive(Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum OrderAndPagination<TId, TOrder, TCursor> {
 	OrderOnly(TOrder),
@@ -86,6 +86,16 @@ impl<T> InOrNotIn<T> {
 	}
 }
 
+impl<T: PartialEq> InOrNotIn<T> {
+	/// Empty lists match everything, the same as an empty [`InOrNotIn::into_param`]
+	pub fn matches(&self, value: &T) -> bool {
+		match self {
+			Self::In(v) => v.is_empty() || v.contains(value),
+			Self::NotIn(v) => !v.contains(value),
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum TextMatch {
@@ -122,4 +132,16 @@ impl TextMatch {
 				Self::Equals(v) => Some(equals_fn(v)),
 			})
 	}
+
+	/// Case insensitive, like the `LIKE` based prisma params
+	pub fn matches(&self, value: &str) -> bool {
+		let value = value.to_lowercase();
+
+		match self {
+			Self::Contains(v) => value.contains(&v.to_lowercase()),
+			Self::StartsWith(v) => value.starts_with(&v.to_lowercase()),
+			Self::EndsWith(v) => value.ends_with(&v.to_lowercase()),
+			Self::Equals(v) => value == v.to_lowercase(),
+		}
+	}
 }
@@ -1,4 +1,7 @@
-use crate::api::{CoreEvent, Ctx, Router, R};
+use crate::{
+	api::{CoreEvent, Ctx, Router, R},
+	library::LibraryId,
+};
 
 use async_stream::stream;
 use rspc::alpha::AlphaRouter;
@@ -32,6 +35,9 @@ pub struct SingleInvalidateOperationEvent {
 	pub key: &'static str,
 	arg: Value,
 	result: Option<Value>,
+	/// The library the invalidated query belongs to, set when emitted through a library
+	#[serde(skip)]
+	pub(crate) library_id: Option<LibraryId>,
 }
 
 #[derive(Debug, Clone, Serialize, Type)]
@@ -45,7 +51,12 @@ pub enum InvalidateOperationEvent {
 impl InvalidateOperationEvent {
 	/// If you are using this function, your doing it wrong.
 	pub fn dangerously_create(key: &'static str, arg: Value, result: Option<Value>) -> Self {
-		Self::Single(SingleInvalidateOperationEvent { key, arg, result })
+		Self::Single(SingleInvalidateOperationEvent {
+			key,
+			arg,
+			result,
+			library_id: None,
+		})
 	}
 
 	pub fn all() -> Self {
@@ -2,7 +2,7 @@ use crate::{api::CoreEvent, invalidate_query, library::Library, old_job::JobProg
 
 use sd_core_heavy_lifting::{
 	job_system::report::{Report, Status},
-	OuterContext, ProgressUpdate, UpdateEvent,
+	IoThrottle, OuterContext, ProgressUpdate, UpdateEvent,
 };
 use sd_core_sync::SyncManager;
 
@@ -69,7 +69,10 @@ impl OuterContext for NodeContext {
 		let event = match update {
 			UpdateEvent::NewThumbnail { thumb_key } => CoreEvent::NewThumbnail { thumb_key },
 			UpdateEvent::NewIdentifiedObjects { file_path_ids } => {
-				CoreEvent::NewIdentifiedObjects { file_path_ids }
+				CoreEvent::NewIdentifiedObjects {
+					file_path_ids,
+					library_id: self.library.id,
+				}
 			}
 		};
 		self.node.emit(event);
@@ -86,6 +89,7 @@ pub struct JobContext<OuterCtx: OuterContext + NodeContextExt> {
 	report: Arc<RwLock<Report>>,
 	start_time: DateTime<Utc>,
 	report_update_counter: Arc<AtomicU8>,
+	io_throttle: IoThrottle,
 }
 
 impl<OuterCtx: OuterContext + NodeContextExt> OuterContext for JobContext<OuterCtx> {
@@ -121,12 +125,13 @@ impl<OuterCtx: OuterContext + NodeContextExt> OuterContext for JobContext<OuterC
 impl<OuterCtx: OuterContext + NodeContextExt> sd_core_heavy_lifting::JobContext<OuterCtx>
 	for JobContext<OuterCtx>
 {
-	fn new(report: Report, outer_ctx: OuterCtx) -> Self {
+	fn new(report: Report, io_throttle: IoThrottle, outer_ctx: OuterCtx) -> Self {
 		Self {
 			report: Arc::new(RwLock::new(report)),
 			outer_ctx,
 			start_time: Utc::now(),
 			report_update_counter: Arc::new(AtomicU8::new(0)),
+			io_throttle,
 		}
 	}
 
@@ -228,4 +233,8 @@ impl<OuterCtx: OuterContext + NodeContextExt> sd_core_heavy_lifting::JobContext<
 	fn get_outer_ctx(&self) -> OuterCtx {
 		self.outer_ctx.clone()
 	}
+
+	fn io_throttle(&self) -> &IoThrottle {
+		&self.io_throttle
+	}
 }
@@ -7,7 +7,10 @@ use crate::{
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
-use sd_core_heavy_lifting::media_processor::WEBP_EXTENSION;
+use sd_core_heavy_lifting::{
+	indexer::{read_member, ArchiveFormat},
+	media_processor::WEBP_EXTENSION,
+};
 use sd_core_prisma_helpers::file_path_to_handle_custom_uri;
 
 use sd_file_ext::text::is_text;
@@ -44,6 +47,7 @@ use mini_moka::sync::Cache;
 use tokio::{
 	fs::{self, File},
 	io::{self, copy_bidirectional, AsyncReadExt, AsyncSeekExt, SeekFrom},
+	task::spawn_blocking,
 };
 use tracing::{error, warn};
 use uuid::Uuid;
@@ -51,10 +55,12 @@ use uuid::Uuid;
 use self::{serve_file::serve_file, utils::*};
 
 mod mpsc_to_async_write;
+mod mpsc_to_write;
 mod serve_file;
 mod utils;
 
 use mpsc_to_async_write::MpscToAsyncWrite;
+use mpsc_to_write::MpscToWrite;
 
 type CacheKey = (Uuid, file_path::id::Type);
 
@@ -63,6 +69,8 @@ struct CacheValue {
 	name: PathBuf,
 	ext: String,
 	file_path_pub_id: Uuid,
+	/// Path inside the archive at `name`, for files that only exist inside archives
+	archive_member: Option<String>,
 	serve_from: ServeFrom,
 }
 
@@ -185,9 +193,16 @@ async fn get_or_init_lru_entry(
 		let instance = maybe_missing(&location.instance, "file_path.location.instance")
 			.map_err(internal_server_error)?;
 
-		let path = Path::new(path)
+		let mut path = Path::new(path)
 			.join(IsolatedFilePathData::try_from((location_id, &file_path)).map_err(not_found)?);
 
+		// Archives members are beneath their archive, as if it was a directory
+		if let Some(member_path) = &file_path.archive_member_path {
+			for _ in member_path.split('/') {
+				path.pop();
+			}
+		}
+
 		let library_identity =
 			RemoteIdentity::from_bytes(&instance.remote_identity).map_err(internal_server_error)?;
 
@@ -203,6 +218,7 @@ async fn get_or_init_lru_entry(
 			name: path,
 			ext: maybe_missing(file_path.extension, "extension").map_err(not_found)?,
 			file_path_pub_id: Uuid::from_slice(&file_path.pub_id).map_err(internal_server_error)?,
+			archive_member: file_path.archive_member_path,
 			serve_from: if library_identity == library.identity.to_remote_identity() {
 				ServeFrom::Local
 			} else {
@@ -234,7 +250,8 @@ pub fn base_router() -> Router<LocalState> {
 					let path = thumbnail_path.join(path);
 
 					// Prevent directory traversal attacks (Eg. requesting `../../../etc/passwd`)
-					// For now we only support `webp` thumbnails.
+					// For now we only support `webp` thumbnails, video thumbstrips and previews
+					// are `webp` files living next to their thumbnail, so they're served here too.
 					(path.starts_with(&thumbnail_path)
 						&& path.extension() == Some(WEBP_EXTENSION.as_ref()))
 					.then_some(())
@@ -270,12 +287,17 @@ pub fn base_router() -> Router<LocalState> {
 							name: file_path_full_path,
 							ext: extension,
 							file_path_pub_id,
+							archive_member,
 							serve_from,
-							..
 						},
 						_library,
 					) = get_or_init_lru_entry(&state, path).await?;
 
+					if let (ServeFrom::Local, Some(member_path)) = (&serve_from, archive_member) {
+						return serve_archive_member(file_path_full_path, member_path, &extension)
+							.await;
+					}
+
 					match serve_from {
 						ServeFrom::Local => {
 							let metadata = fs::metadata(&file_path_full_path)
@@ -450,6 +472,76 @@ pub fn router(node: Arc<Node>) -> Router<()> {
 		.with_state(with_state(node))
 }
 
+/// Streams a file from inside an archive, which can't be seeked, so range requests aren't supported
+/// and the mime type of text files is told from the first chunk read
+async fn serve_archive_member(
+	archive_path: PathBuf,
+	member_path: String,
+	extension: &str,
+) -> Result<Response<Body>, Response<Body>> {
+	let format = ArchiveFormat::from_path(&archive_path).ok_or_else(|| not_found(()))?;
+
+	let (tx, mut rx) = tokio::sync::mpsc::channel::<io::Result<Bytes>>(150);
+
+	spawn_blocking(move || {
+		match read_member(
+			&archive_path,
+			format,
+			&member_path,
+			&mut MpscToWrite::new(tx.clone()),
+		) {
+			Ok(true) => {}
+			Ok(false) => {
+				tx.blocking_send(Err(io::ErrorKind::NotFound.into())).ok();
+			}
+			Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+			Err(e) => {
+				error!(
+					archive_path = %archive_path.display(),
+					%member_path,
+					?e,
+					"Error reading file from archive;",
+				);
+				tx.blocking_send(Err(e)).ok();
+			}
+		}
+	});
+
+	let first_chunk = match rx.recv().await {
+		Some(Ok(chunk)) => chunk,
+		Some(Err(e)) if e.kind() == io::ErrorKind::NotFound => return Err(not_found(e)),
+		Some(Err(e)) => return Err(internal_server_error(e)),
+		// Empty files
+		None => Bytes::new(),
+	};
+
+	let ext = extension.to_lowercase();
+	let mime_type = match binary_mime_type(&ext) {
+		Some(mime_type) => mime_type.to_string(),
+		None => {
+			let text_buf = &first_chunk[..min(first_chunk.len(), MAX_TEXT_READ_LENGTH)];
+			// We can't know if the chunk holds the whole file without reading the next one
+			text_mime_type(&ext, text_buf, true)?
+		}
+	};
+
+	Ok(InfallibleResponse::builder()
+		.header(
+			"Content-Type",
+			HeaderValue::from_str(&mime_type).map_err(|e| {
+				error!(?e, "Error converting mime-type into header value;");
+				internal_server_error(())
+			})?,
+		)
+		.status(StatusCode::OK)
+		.body(Body::from_stream(stream! {
+			yield Ok::<_, io::Error>(first_chunk);
+			while let Some(item) = rx.recv().await {
+				yield item;
+			}
+		})))
+}
+
 // TODO: This should possibly be determined from magic bytes when the file is indexed and stored it in the DB on the file path
 async fn infer_the_mime_type(
 	ext: &str,
@@ -457,136 +549,144 @@ async fn infer_the_mime_type(
 	metadata: &Metadata,
 ) -> Result<String, Response<Body>> {
 	let ext = ext.to_lowercase();
-	let mime_type = match ext.as_str() {
+	if let Some(mime_type) = binary_mime_type(&ext) {
+		return Ok(mime_type.to_string());
+	}
+
+	let mut text_buf = vec![
+		0;
+		min(
+			metadata.len().try_into().unwrap_or(usize::MAX),
+			MAX_TEXT_READ_LENGTH
+		)
+	];
+	if !text_buf.is_empty() {
+		file.read_exact(&mut text_buf)
+			.await
+			.map_err(internal_server_error)?;
+		file.seek(SeekFrom::Start(0))
+			.await
+			.map_err(internal_server_error)?;
+	}
+
+	text_mime_type(&ext, &text_buf, text_buf.len() == (metadata.len() as usize))
+}
+
+/// Mime type of the lowercase extension, `None` for files that may be text
+fn binary_mime_type(ext: &str) -> Option<&'static str> {
+	match ext {
 		// AAC audio
-		"aac" => "audio/aac",
+		"aac" => Some("audio/aac"),
 		// Musical Instrument Digital Interface (MIDI)
-		"mid" | "midi" => "audio/midi, audio/x-midi",
+		"mid" | "midi" => Some("audio/midi, audio/x-midi"),
 		// MP3 audio
-		"mp3" => "audio/mpeg",
+		"mp3" => Some("audio/mpeg"),
 		// MP4 audio
-		"m4a" => "audio/mp4",
+		"m4a" => Some("audio/mp4"),
 		// OGG audio
-		"oga" => "audio/ogg",
+		"oga" => Some("audio/ogg"),
 		// Opus audio
-		"opus" => "audio/opus",
+		"opus" => Some("audio/opus"),
 		// Waveform Audio Format
-		"wav" => "audio/wav",
+		"wav" => Some("audio/wav"),
 		// WEBM audio
-		"weba" => "audio/webm",
+		"weba" => Some("audio/webm"),
 		// AVI: Audio Video Interleave
-		"avi" => "video/x-msvideo",
+		"avi" => Some("video/x-msvideo"),
 		// MP4 video
-		"mp4" | "m4v" => "video/mp4",
+		"mp4" | "m4v" => Some("video/mp4"),
 		// TODO: Bruh
 		#[cfg(not(target_os = "macos"))]
 		// TODO: Bruh
 		// FIX-ME: This media types break macOS video rendering
 		// MPEG transport stream
-		"ts" => "video/mp2t",
+		"ts" => Some("video/mp2t"),
 		// TODO: Bruh
 		#[cfg(not(target_os = "macos"))]
 		// FIX-ME: This media types break macOS video rendering
 		// MPEG Video
-		"mpeg" => "video/mpeg",
+		"mpeg" => Some("video/mpeg"),
 		// OGG video
-		"ogv" => "video/ogg",
+		"ogv" => Some("video/ogg"),
 		// WEBM video
-		"webm" => "video/webm",
+		"webm" => Some("video/webm"),
 		// 3GPP audio/video container (TODO: audio/3gpp if it doesn't contain video)
-		"3gp" => "video/3gpp",
+		"3gp" => Some("video/3gpp"),
 		// 3GPP2 audio/video container (TODO: audio/3gpp2 if it doesn't contain video)
-		"3g2" => "video/3gpp2",
+		"3g2" => Some("video/3gpp2"),
 		// Quicktime movies
-		"mov" => "video/quicktime",
+		"mov" => Some("video/quicktime"),
 		// Windows OS/2 Bitmap Graphics
-		"bmp" => "image/bmp",
+		"bmp" => Some("image/bmp"),
 		// Graphics Interchange Format (GIF)
-		"gif" => "image/gif",
+		"gif" => Some("image/gif"),
 		// Icon format
-		"ico" => "image/vnd.microsoft.icon",
+		"ico" => Some("image/vnd.microsoft.icon"),
 		// JPEG images
-		"jpeg" | "jpg" => "image/jpeg",
+		"jpeg" | "jpg" => Some("image/jpeg"),
 		// Portable Network Graphics
-		"png" => "image/png",
+		"png" => Some("image/png"),
 		// Scalable Vector Graphics (SVG)
-		"svg" => "image/svg+xml",
+		"svg" => Some("image/svg+xml"),
 		// Tagged Image File Format (TIFF)
-		"tif" | "tiff" => "image/tiff",
+		"tif" | "tiff" => Some("image/tiff"),
 		// WEBP image
-		"webp" => "image/webp",
+		"webp" => Some("image/webp"),
 		// PDF document
-		"pdf" => "application/pdf",
+		"pdf" => Some("application/pdf"),
 		// HEIF images
-		"heif" => "image/heif",
+		"heif" => Some("image/heif"),
 		// HEIF images sequence (animated)
-		"heifs" => "image/heif-sequence",
+		"heifs" => Some("image/heif-sequence"),
 		// HEIC images
-		"heic" | "hif" => "image/heic",
+		"heic" | "hif" => Some("image/heic"),
 		// HEIC images sequence (animated)
-		"heics" => "image/heic-sequence",
+		"heics" => Some("image/heic-sequence"),
 		// AV1 in HEIF images
-		"avif" => "image/avif",
+		"avif" => Some("image/avif"),
 		// AV1 in HEIF images sequence (DEPRECATED: https://github.com/AOMediaCodec/av1-avif/pull/86/files)
-		"avifs" => "image/avif-sequence",
+		"avifs" => Some("image/avif-sequence"),
 		// AVC in HEIF images
-		"avci" => "image/avci",
+		"avci" => Some("image/avci"),
 		// AVC in HEIF images sequence (animated)
-		"avcs" => "image/avcs",
-		_ => "text/plain",
-	};
+		"avcs" => Some("image/avcs"),
+		_ => None,
+	}
+}
 
-	Ok(if mime_type == "text/plain" {
-		let mut text_buf = vec![
-			0;
-			min(
-				metadata.len().try_into().unwrap_or(usize::MAX),
-				MAX_TEXT_READ_LENGTH
-			)
-		];
-		if !text_buf.is_empty() {
-			file.read_exact(&mut text_buf)
-				.await
-				.map_err(internal_server_error)?;
-			file.seek(SeekFrom::Start(0))
-				.await
-				.map_err(internal_server_error)?;
+/// Mime type of text files, telling their charset from the beginning of their contents
+fn text_mime_type(ext: &str, text_buf: &[u8], partial: bool) -> Result<String, Response<Body>> {
+	let charset = is_text(text_buf, partial).unwrap_or("");
+
+	// Only browser recognized types, everything else should be text/plain
+	// https://www.iana.org/assignments/media-types/media-types.xhtml#table-text
+	let mime_type = match ext {
+		// HyperText Markup Language
+		"html" | "htm" => "text/html",
+		// Cascading Style Sheets
+		"css" => "text/css",
+		// Javascript
+		"js" | "mjs" => "text/javascript",
+		// Comma-separated values
+		"csv" => "text/csv",
+		// Markdown
+		"md" | "markdown" => "text/markdown",
+		// Rich text format
+		"rtf" => "text/rtf",
+		// Web Video Text Tracks
+		"vtt" => "text/vtt",
+		// Extensible Markup Language
+		"xml" => "text/xml",
+		// Text
+		"txt" => "text/plain",
+		_ => {
+			if charset.is_empty() {
+				// "TODO: This filetype is not supported because of the missing mime type!",
+				return Err(not_implemented(()));
+			};
+			"text/plain"
 		}
+	};
 
-		let charset = is_text(&text_buf, text_buf.len() == (metadata.len() as usize)).unwrap_or("");
-
-		// Only browser recognized types, everything else should be text/plain
-		// https://www.iana.org/assignments/media-types/media-types.xhtml#table-text
-		let mime_type = match ext.as_str() {
-			// HyperText Markup Language
-			"html" | "htm" => "text/html",
-			// Cascading Style Sheets
-			"css" => "text/css",
-			// Javascript
-			"js" | "mjs" => "text/javascript",
-			// Comma-separated values
-			"csv" => "text/csv",
-			// Markdown
-			"md" | "markdown" => "text/markdown",
-			// Rich text format
-			"rtf" => "text/rtf",
-			// Web Video Text Tracks
-			"vtt" => "text/vtt",
-			// Extensible Markup Language
-			"xml" => "text/xml",
-			// Text
-			"txt" => "text/plain",
-			_ => {
-				if charset.is_empty() {
-					// "TODO: This filetype is not supported because of the missing mime type!",
-					return Err(not_implemented(()));
-				};
-				mime_type
-			}
-		};
-
-		format!("{mime_type}; charset={charset}")
-	} else {
-		mime_type.to_string()
-	})
+	Ok(format!("{mime_type}; charset={charset}"))
 }
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+/// Allowing wrapping an `mpsc::Sender` into a blocking `Write`, for synchronous readers
+pub struct MpscToWrite(mpsc::Sender<io::Result<Bytes>>);
+
+impl MpscToWrite {
+	pub fn new(sender: mpsc::Sender<io::Result<Bytes>>) -> Self {
+		Self(sender)
+	}
+}
+
+impl Write for MpscToWrite {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0
+			.blocking_send(Ok(Bytes::copy_from_slice(buf)))
+			// The receiver is only dropped when the client stops reading the response
+			.map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
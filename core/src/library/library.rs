@@ -1,4 +1,7 @@
-use crate::{api::CoreEvent, Node};
+use crate::{
+	api::{utils::InvalidateOperationEvent, CoreEvent},
+	Node,
+};
 
 use sd_core_cloud_services::{declare_cloud_sync, CloudSyncActors, CloudSyncActorsState};
 use sd_core_file_path_helper::IsolatedFilePathData;
@@ -150,7 +153,12 @@ impl Library {
 	}
 
 	// TODO: Remove this once we replace the old invalidation system
-	pub(crate) fn emit(&self, event: CoreEvent) {
+	pub(crate) fn emit(&self, mut event: CoreEvent) {
+		if let CoreEvent::InvalidateOperation(InvalidateOperationEvent::Single(event)) = &mut event
+		{
+			event.library_id = Some(self.id);
+		}
+
 		if let Err(e) = self.event_bus_tx.send(event) {
 			warn!(?e, "Error sending event to event bus;");
 		}
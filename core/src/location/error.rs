@@ -80,6 +80,10 @@ pub enum LocationError {
 	InvalidScanStateValue(i32),
 	#[error(transparent)]
 	Sync(#[from] sd_core_sync::Error),
+	#[error(transparent)]
+	IndexerRules(#[from] sd_core_indexer_rules::Error),
+	#[error("failed to rescan location: {0}")]
+	Rescan(#[from] sd_core_heavy_lifting::Error),
 }
 
 impl From<LocationError> for rspc::Error {
@@ -109,6 +113,8 @@ impl From<LocationError> for rspc::Error {
 
 			// Internal errors
 			MissingField(missing_error) => missing_error.into(),
+			IndexerRules(indexer_rules_error) => indexer_rules_error.into(),
+			Rescan(rescan_error) => rescan_error.into(),
 			_ => Self::with_cause(ErrorCode::InternalServerError, e.to_string(), e),
 		}
 	}
@@ -0,0 +1,506 @@
+//! Helpers to understand what the indexer rules of a location do, without indexing anything, and
+//! to share rules between libraries.
+
+use crate::library::Library;
+
+use sd_core_file_path_helper::ensure_sub_path_is_in_location;
+use sd_core_heavy_lifting::indexer::fetch_metadata;
+use sd_core_indexer_rules::{
+	rules_per_kind_to_db,
+	seed::{GitIgnoreRules, SpacedriveIgnoreRules, GITIGNORE},
+	ClassificationMethod, IndexerRule, IndexerRuleCreateArgs, IndexerRuler,
+	ObjectKindClassification, PathExplanation, PortableIndexerRule, PortableIndexerRules,
+	RuleExplanation, RulerDecision,
+};
+use sd_core_prisma_helpers::location_with_indexer_rules;
+
+use sd_prisma::prisma::{indexer_rule, indexer_rules_in_location, location, SortOrder};
+use sd_utils::{db::maybe_missing, error::FileIOError};
+
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::fs;
+use tracing::{debug, instrument, trace};
+
+use super::{find_location, link_location_and_indexer_rules, LocationError};
+
+/// Walking a directory tree for a preview stops after this many entries
+const MAX_PREVIEW_ENTRIES: u32 = 100_000;
+
+#[derive(Deserialize, Type, Debug)]
+pub struct ExplainPathArgs {
+	pub location_id: location::id::Type,
+	/// Absolute or relative to the location root
+	pub path: PathBuf,
+}
+
+#[derive(Serialize, Type, Debug)]
+pub struct IndexerRulesExplanation {
+	pub path: PathBuf,
+	/// Rules applied to the path itself, including the ones from `.gitignore` and
+	/// `.spacedriveignore` files
+	pub rules: Vec<RuleExplanation>,
+	pub decision: RulerDecision,
+	/// The closest directory to the location root which the rules reject, the indexer never walks
+	/// into it so nothing inside of it gets indexed no matter what the rules say about `path`
+	pub rejected_ancestor: Option<PathBuf>,
+	/// The kind object kind rules gave to `path` and how it was told
+	pub object_kind: Option<ObjectKindClassification>,
+}
+
+/// What to do with an imported rule whose name is already taken by a rule of the library
+#[derive(Deserialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportConflictStrategy {
+	Skip,
+	/// Appends a number to the name of the imported rule, like `Rule (2)`
+	Rename,
+	/// Overwrites the existing rule, keeping its locations. Default rules are never replaced.
+	Replace,
+}
+
+#[derive(Deserialize, Type, Debug)]
+pub struct ImportRulesArgs {
+	/// A file created by exporting indexer rules
+	pub content: String,
+	pub on_conflict: ImportConflictStrategy,
+	/// Locations which will use the imported rules from now on, they take effect on their next scan
+	#[serde(default)]
+	pub location_ids: Vec<location::id::Type>,
+}
+
+#[derive(Serialize, Type, Debug)]
+pub struct ImportedRules {
+	/// Rules created or replaced by the import
+	pub imported: Vec<indexer_rule::id::Type>,
+	/// Names of the rules left untouched due to a name conflict
+	pub skipped: Vec<String>,
+}
+
+#[derive(Deserialize, Type, Debug)]
+pub struct PreviewRulesArgs {
+	pub path: PathBuf,
+	pub indexer_rules_ids: Vec<indexer_rule::id::Type>,
+}
+
+#[derive(Serialize, Type, Debug)]
+pub struct RuleRejections {
+	pub id: Option<indexer_rule::id::Type>,
+	pub name: String,
+	pub rejected: u32,
+}
+
+#[derive(Serialize, Type, Debug)]
+pub struct IndexerRulesPreview {
+	pub accepted: u32,
+	pub rejected: u32,
+	/// An entry can be rejected by more than one rule at once, so these don't add up to `rejected`
+	pub rejections_per_rule: Vec<RuleRejections>,
+	/// If the walk stopped early because the tree has too many entries
+	pub truncated: bool,
+	/// How many files object kind rules classified by their extension alone, because their format
+	/// doesn't have magic bytes or reading them failed or took too long
+	pub classified_by_extension: u32,
+	/// How many entries couldn't be read, so they're neither accepted nor rejected. A directory
+	/// which couldn't be listed counts as a single entry
+	pub unreadable: u32,
+}
+
+/// Tells why the indexer accepts or rejects a path of a location
+#[instrument(skip(library), err)]
+pub async fn explain_path(
+	ExplainPathArgs { location_id, path }: ExplainPathArgs,
+	library: &Library,
+) -> Result<IndexerRulesExplanation, LocationError> {
+	let location = find_location(library, location_id)
+		.include(location_with_indexer_rules::include())
+		.exec()
+		.await?
+		.ok_or(LocationError::IdNotFound(location_id))?;
+
+	let location_path = PathBuf::from(maybe_missing(&location.path, "location.path")?);
+	let follow_symlinks = location.follow_symlinks.unwrap_or(true);
+	let ruler = location_ruler(&location)?;
+
+	let path = if path.starts_with(&location_path) {
+		path
+	} else {
+		ensure_sub_path_is_in_location(&location_path, &path).await?
+	};
+
+	let mut rejected_ancestor = None;
+
+	// Ancestors are checked from the location root onwards, just like the indexer walks them
+	let mut ancestors = path
+		.ancestors()
+		.skip(1)
+		.take_while(|ancestor| *ancestor != location_path && ancestor.starts_with(&location_path))
+		.collect::<Vec<_>>();
+	ancestors.reverse();
+
+	for ancestor in ancestors {
+		if explain_entry(&ruler, &location_path, ancestor, follow_symlinks)
+			.await?
+			.decision == RulerDecision::Reject
+		{
+			trace!(ancestor = %ancestor.display(), "Found rejected ancestor;");
+			rejected_ancestor = Some(ancestor.to_path_buf());
+			break;
+		}
+	}
+
+	let PathExplanation {
+		rules,
+		decision,
+		object_kind,
+	} = explain_entry(&ruler, &location_path, &path, follow_symlinks).await?;
+
+	Ok(IndexerRulesExplanation {
+		path,
+		rules,
+		decision,
+		rejected_ancestor,
+		object_kind,
+	})
+}
+
+/// Walks a directory tree, which doesn't need to be a location yet, counting how many entries
+/// the given rules would reject. Rejected directories aren't walked into, as the indexer does.
+#[instrument(skip(library), err)]
+pub async fn preview_rules(
+	PreviewRulesArgs {
+		path,
+		indexer_rules_ids,
+	}: PreviewRulesArgs,
+	library: &Library,
+) -> Result<IndexerRulesPreview, LocationError> {
+	let ruler = library
+		.db
+		.indexer_rule()
+		.find_many(vec![indexer_rule::id::in_vec(indexer_rules_ids)])
+		.exec()
+		.await?
+		.iter()
+		.map(IndexerRule::try_from)
+		.collect::<Result<Vec<_>, _>>()
+		.map(IndexerRuler::new)?;
+
+	match fs::metadata(&path).await {
+		Ok(metadata) if metadata.is_dir() => {}
+		Ok(_) => return Err(LocationError::NotDirectory(path.into_boxed_path())),
+		Err(e) => return Err(FileIOError::from((&path, e)).into()),
+	}
+
+	let mut accepted = 0;
+	let mut rejected = 0;
+	let mut rejections_per_rule = HashMap::<_, u32>::new();
+	let mut truncated = false;
+	let mut classified_by_extension = 0;
+	let mut unreadable = 0;
+
+	let mut to_walk = VecDeque::from([path.clone()]);
+
+	'walk: while let Some(dir) = to_walk.pop_front() {
+		let dir_ruler = ruler_for_directory(&ruler, &path, &dir).await;
+
+		let mut read_dir = match fs::read_dir(&dir).await {
+			Ok(read_dir) => read_dir,
+			Err(e) => {
+				debug!(dir = %dir.display(), ?e, "Skipping unreadable directory;");
+				unreadable += 1;
+				continue;
+			}
+		};
+
+		loop {
+			let entry = match read_dir.next_entry().await {
+				Ok(Some(entry)) => entry,
+				Ok(None) => break,
+				Err(e) => {
+					// The rest of this directory can't be listed anymore
+					debug!(dir = %dir.display(), ?e, "Failed to read directory entry;");
+					unreadable += 1;
+					break;
+				}
+			};
+
+			if accepted + rejected >= MAX_PREVIEW_ENTRIES {
+				truncated = true;
+				break 'walk;
+			}
+
+			let entry_path = entry.path();
+			// Symbolic links aren't followed here, they're previewed as the aliases the indexer
+			// records by default
+			let metadata = match entry.metadata().await {
+				Ok(metadata) => metadata,
+				Err(e) => {
+					debug!(path = %entry_path.display(), ?e, "Skipping unreadable entry;");
+					unreadable += 1;
+					continue;
+				}
+			};
+
+			let PathExplanation {
+				rules,
+				decision,
+				object_kind,
+			} = dir_ruler.explain_path(&entry_path, &metadata).await?;
+
+			if object_kind.map_or(false, |classification| {
+				classification.method == ClassificationMethod::Extension
+			}) {
+				classified_by_extension += 1;
+			}
+
+			match decision {
+				RulerDecision::Accept => {
+					accepted += 1;
+
+					if metadata.is_dir() {
+						to_walk.push_back(entry_path);
+					}
+				}
+				RulerDecision::Reject => {
+					rejected += 1;
+
+					for rule in rules.into_iter().filter(RuleExplanation::fired) {
+						*rejections_per_rule.entry((rule.id, rule.name)).or_default() += 1;
+					}
+				}
+			}
+		}
+	}
+
+	let mut rejections_per_rule = rejections_per_rule
+		.into_iter()
+		.map(|((id, name), rejected)| RuleRejections { id, name, rejected })
+		.collect::<Vec<_>>();
+	rejections_per_rule.sort_by(|a, b| b.rejected.cmp(&a.rejected).then(a.name.cmp(&b.name)));
+
+	Ok(IndexerRulesPreview {
+		accepted,
+		rejected,
+		rejections_per_rule,
+		truncated,
+		classified_by_extension,
+		unreadable,
+	})
+}
+
+/// Writes the given rules, or every rule of the library if none is given, in a human readable
+/// format which can be imported into other libraries
+#[instrument(skip(library), err)]
+pub async fn export_rules(
+	indexer_rules_ids: Vec<indexer_rule::id::Type>,
+	library: &Library,
+) -> Result<String, LocationError> {
+	let filters = if indexer_rules_ids.is_empty() {
+		vec![]
+	} else {
+		vec![indexer_rule::id::in_vec(indexer_rules_ids)]
+	};
+
+	let rules = library
+		.db
+		.indexer_rule()
+		.find_many(filters)
+		.order_by(indexer_rule::id::order(SortOrder::Asc))
+		.exec()
+		.await?
+		.iter()
+		.map(IndexerRule::try_from)
+		.collect::<Result<Vec<_>, _>>()?;
+
+	PortableIndexerRules::new(rules.iter().map(PortableIndexerRule::from))
+		.to_json()
+		.map_err(Into::into)
+}
+
+/// Creates the rules of an exported file. Every rule is validated before anything is written, so
+/// an invalid file doesn't leave a partial import behind.
+#[instrument(skip(library, content), err)]
+pub async fn import_rules(
+	ImportRulesArgs {
+		content,
+		on_conflict,
+		location_ids,
+	}: ImportRulesArgs,
+	library: &Library,
+) -> Result<ImportedRules, LocationError> {
+	let db = &library.db;
+
+	let to_import = PortableIndexerRules::from_json(&content)?
+		.rules
+		.into_iter()
+		.map(|rule| {
+			let rules = rule.kinds_and_parameters();
+			rules_per_kind_to_db(rules.clone()).map(|rules_data| (rule.name, rules, rules_data))
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let found_locations = db
+		.location()
+		.find_many(vec![location::id::in_vec(location_ids.clone())])
+		.select(location::select!({ id }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|location| location.id)
+		.collect::<HashSet<_>>();
+
+	if let Some(missing) = location_ids
+		.iter()
+		.find(|location_id| !found_locations.contains(location_id))
+	{
+		return Err(LocationError::IdNotFound(*missing));
+	}
+
+	let mut existing = db
+		.indexer_rule()
+		.find_many(vec![])
+		.select(indexer_rule::select!({ id name default }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|rule| {
+			rule.name
+				.map(|name| (name, (rule.id, rule.default.unwrap_or_default())))
+		})
+		.collect::<HashMap<_, _>>();
+
+	let mut imported = Vec::with_capacity(to_import.len());
+	let mut skipped = vec![];
+
+	for (name, rules, rules_data) in to_import {
+		let name = match (existing.get(&name), on_conflict) {
+			(None, _) => name,
+
+			(Some(_), ImportConflictStrategy::Skip) | (Some((_, true)), _) => {
+				debug!(%name, "Skipping imported indexer rule due to a name conflict;");
+				skipped.push(name);
+				continue;
+			}
+
+			(Some(&(id, false)), ImportConflictStrategy::Replace) => {
+				db.indexer_rule()
+					.update(
+						indexer_rule::id::equals(id),
+						vec![
+							indexer_rule::rules_per_kind::set(Some(rules_data)),
+							indexer_rule::date_modified::set(Some(Utc::now().into())),
+						],
+					)
+					.exec()
+					.await?;
+
+				imported.push(id);
+				continue;
+			}
+
+			(Some(_), ImportConflictStrategy::Rename) => (2..)
+				.map(|n| format!("{name} ({n})"))
+				.find(|candidate| !existing.contains_key(candidate))
+				.expect("unbounded range always has a free name"),
+		};
+
+		if let Some(created) = (IndexerRuleCreateArgs {
+			name: name.clone(),
+			dry_run: false,
+			rules,
+		})
+		.create(db)
+		.await?
+		{
+			existing.insert(name, (created.id, false));
+			imported.push(created.id);
+		}
+	}
+
+	for location_id in location_ids {
+		let already_linked = db
+			.indexer_rules_in_location()
+			.find_many(vec![indexer_rules_in_location::location_id::equals(
+				location_id,
+			)])
+			.exec()
+			.await?
+			.into_iter()
+			.map(|link| link.indexer_rule_id)
+			.collect::<HashSet<_>>();
+
+		let to_link = imported
+			.iter()
+			.copied()
+			.filter(|id| !already_linked.contains(id))
+			.collect::<Vec<_>>();
+
+		if !to_link.is_empty() {
+			link_location_and_indexer_rules(library, location_id, &to_link).await?;
+		}
+	}
+
+	Ok(ImportedRules { imported, skipped })
+}
+
+fn location_ruler(
+	location: &location_with_indexer_rules::Data,
+) -> Result<IndexerRuler, LocationError> {
+	location
+		.indexer_rules
+		.iter()
+		.map(|rule| IndexerRule::try_from(&rule.indexer_rule))
+		.collect::<Result<Vec<_>, _>>()
+		.map(IndexerRuler::new)
+		.map_err(Into::into)
+}
+
+/// Adds the rules from `.gitignore` and `.spacedriveignore` files which apply to entries of `dir`,
+/// the same way the indexer walker does
+async fn ruler_for_directory(ruler: &IndexerRuler, root: &Path, dir: &Path) -> IndexerRuler {
+	// Cloning a ruler drops its extra rules, so rules from other directories don't leak here
+	let mut dir_ruler = ruler.clone();
+
+	if dir_ruler.has_system(&GITIGNORE) {
+		if let Some(rules) = GitIgnoreRules::get_rules_if_in_git_repo(root, dir).await {
+			dir_ruler.extend(rules.map(Into::into));
+		}
+	}
+
+	let (rules, errors) =
+		SpacedriveIgnoreRules::get_rules(root, dir, ruler.spacedrive_ignore_files()).await;
+
+	for e in errors {
+		debug!(?e, "Failed to read ignore file;");
+	}
+
+	if let Some(rules) = rules {
+		dir_ruler.extend([rules.into()]);
+	}
+
+	dir_ruler
+}
+
+/// Explains an entry with the same metadata the walker evaluates it with, so symbolic links are
+/// only followed on locations following them
+async fn explain_entry(
+	ruler: &IndexerRuler,
+	root: &Path,
+	path: &Path,
+	follow_symlinks: bool,
+) -> Result<PathExplanation, LocationError> {
+	let (metadata, _) = fetch_metadata(path, root, follow_symlinks)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	ruler_for_directory(ruler, root, path.parent().unwrap_or(root))
+		.await
+		.explain_path(path, &metadata)
+		.await
+		.map_err(Into::into)
+}
@@ -7,7 +7,7 @@ use sd_prisma::prisma::{location, PrismaClient};
 use sd_utils::{db::maybe_missing, uuid_to_bytes};
 
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	future::Future,
 	path::{Path, PathBuf},
 	pin::pin,
@@ -47,7 +47,7 @@ mod android;
 
 mod utils;
 
-use utils::reject_event;
+use utils::{collect_changed_spacedrive_ignores, reject_event, rescan_changed_spacedrive_ignores};
 
 #[cfg(target_os = "linux")]
 type Handler = linux::EventHandler;
@@ -219,6 +219,8 @@ impl LocationWatcher {
 
 		let mut paths_to_ignore = HashSet::new();
 
+		let mut changed_spacedrive_ignores = HashMap::new();
+
 		let mut handler_tick_interval =
 			interval_at(Instant::now() + HUNDRED_MILLIS, HUNDRED_MILLIS);
 		// In case of doubt check: https://docs.rs/tokio/latest/tokio/time/enum.MissedTickBehavior.html
@@ -249,6 +251,8 @@ impl LocationWatcher {
 
 					last_event_at = Instant::now();
 
+					collect_changed_spacedrive_ignores(&event, &mut changed_spacedrive_ignores);
+
 					if let Err(e) = Self::handle_single_event(
 						location_pub_id,
 						cached_location_path.as_deref(),
@@ -274,7 +278,25 @@ impl LocationWatcher {
 					}
 				}
 
-				StreamMessage::Tick => event_handler.tick().await,
+				StreamMessage::Tick => {
+					event_handler.tick().await;
+
+					if !changed_spacedrive_ignores.is_empty() {
+						if let Err(e) = rescan_changed_spacedrive_ignores(
+							&mut changed_spacedrive_ignores,
+							location_id,
+							&node,
+							&library,
+						)
+						.await
+						{
+							error!(
+								?e,
+								"Failed to rescan directories with changed ignore rules;"
+							);
+						}
+					}
+				}
 
 				StreamMessage::Stop => {
 					debug!("Stopping Location Manager event handler for location");
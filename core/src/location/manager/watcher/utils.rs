@@ -11,7 +11,7 @@ use crate::{
 };
 
 use sd_core_file_path_helper::{
-	check_file_path_exists, filter_existing_file_path_params,
+	check_file_path_exists, filter_existing_file_path_params, hard_links_count,
 	isolated_file_path_data::extract_normalized_materialized_path_str,
 	loose_find_existing_file_path_params, path_is_hidden, FilePathError, FilePathMetadata,
 	IsolatedFilePathData, MetadataExt,
@@ -20,11 +20,14 @@ use sd_core_heavy_lifting::{
 	file_identifier::FileMetadata,
 	media_processor::{
 		exif_media_data, ffmpeg_media_data, generate_single_thumbnail, get_thumbnails_directory,
-		ThumbnailKind,
+		perceptual_hash, ThumbnailKind,
 	},
 };
 use sd_core_indexer_rules::{
-	seed::{GitIgnoreRules, GITIGNORE},
+	seed::{
+		GitIgnoreRules, SpacedriveIgnoreFiles, SpacedriveIgnoreRules, GITIGNORE,
+		SPACEDRIVE_IGNORE_FILE_NAME,
+	},
 	IndexerRuler, RulerDecision,
 };
 use sd_core_prisma_helpers::{
@@ -39,7 +42,9 @@ use sd_prisma::{
 	prisma::{device, file_path, location, object},
 	prisma_sync,
 };
-use sd_sync::{option_sync_db_entry, sync_db_entry, sync_entry, OperationFactory};
+use sd_sync::{
+	option_sync_db_entry, sync_db_entry, sync_db_nullable_entry, sync_entry, OperationFactory,
+};
 use sd_utils::{
 	chain_optional_iter,
 	db::{inode_from_db, inode_to_db, maybe_missing, size_in_bytes_to_db},
@@ -63,7 +68,7 @@ use std::{
 
 use chrono::{DateTime, FixedOffset, Local, Utc};
 use futures_concurrency::future::Join;
-use notify::Event;
+use notify::{Event, EventKind};
 use tokio::{
 	fs,
 	io::{self, ErrorKind},
@@ -133,6 +138,24 @@ pub(super) async fn reject_event(
 								independent_ruler.extend(rules.map(Into::into));
 							}
 						}
+
+						// The watcher's ruler outlives many events, so ignore files are read anew
+						// for each one to pick up their changes
+						let (rules, errors) = SpacedriveIgnoreRules::get_rules(
+							location_path,
+							path_to_check_gitignore,
+							&SpacedriveIgnoreFiles::default(),
+						)
+						.await;
+
+						for e in errors {
+							warn!(?e, "Failed to read ignore file;");
+						}
+
+						if let Some(rules) = rules {
+							trace!("Found spacedriveignore rules to follow");
+							independent_ruler.extend([rules.into()]);
+						}
 					}
 
 					independent_ruler.evaluate_path(path, &metadata).await
@@ -159,6 +182,84 @@ pub(super) async fn reject_event(
 	false
 }
 
+/// Marks the directories whose `.spacedriveignore` file was created, changed or removed, so they
+/// can be scanned again with the new rules
+pub(super) fn collect_changed_spacedrive_ignores(
+	event: &Event,
+	changed_ignores: &mut HashMap<PathBuf, Instant>,
+) {
+	if matches!(event.kind, EventKind::Access(_)) {
+		return;
+	}
+
+	for path in &event.paths {
+		if path
+			.file_name()
+			.map_or(false, |name| name == SPACEDRIVE_IGNORE_FILE_NAME)
+		{
+			if let Some(parent) = path.parent() {
+				trace!(directory = %parent.display(), "Spacedrive ignore file changed;");
+				changed_ignores.insert(parent.to_path_buf(), Instant::now());
+			}
+		}
+	}
+}
+
+/// Scans again the directories that had their `.spacedriveignore` file changed, after a second
+/// without new changes, so the newly ignored entries are removed and the no longer ignored ones
+/// are indexed
+#[instrument(skip_all, err)]
+pub(super) async fn rescan_changed_spacedrive_ignores(
+	changed_ignores: &mut HashMap<PathBuf, Instant>,
+	location_id: location::id::Type,
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+) -> Result<(), LocationManagerError> {
+	let to_rescan = changed_ignores
+		.iter()
+		.filter(|(_, instant)| instant.elapsed() > ONE_SECOND)
+		.map(|(path, _)| path.clone())
+		.collect::<Vec<_>>();
+
+	if to_rescan.is_empty() {
+		return Ok(());
+	}
+
+	for path in &to_rescan {
+		changed_ignores.remove(path);
+	}
+
+	let location = find_location(library, location_id)
+		.include(location_with_indexer_rules::include())
+		.exec()
+		.await?
+		.ok_or(LocationManagerError::LocationNotFound(location_id))?;
+
+	let location_path = PathBuf::from(maybe_missing(&location.path, "location.path")?);
+
+	for path in to_rescan {
+		let Ok(sub_path) = path.strip_prefix(&location_path) else {
+			continue;
+		};
+
+		// The directory itself might have been removed along with its ignore file
+		if !matches!(fs::try_exists(&path).await, Ok(true)) {
+			continue;
+		}
+
+		trace!(directory = %path.display(), "Scanning directory with changed ignore rules;");
+
+		// Newly ignored or unignored paths don't change any directory modification time
+		if let Err(e) =
+			scan_location_sub_path(node, library, location.clone(), sub_path, true).await
+		{
+			error!(?e, "Failed to scan directory with changed ignore rules;");
+		}
+	}
+
+	Ok(())
+}
+
 #[instrument(skip_all, fields(path = %path.as_ref().display()), err)]
 pub(super) async fn create_dir(
 	location_id: location::id::Type,
@@ -213,8 +314,14 @@ pub(super) async fn create_dir(
 			trace!(%iso_file_path, "Scanning new directory;");
 
 			// scan the new directory
-			if let Err(e) =
-				scan_location_sub_path(&node, &library, location, &children_materialized_path).await
+			if let Err(e) = scan_location_sub_path(
+				&node,
+				&library,
+				location,
+				&children_materialized_path,
+				false,
+			)
+			.await
 			{
 				error!(?e, "Failed to scan new directory;");
 			}
@@ -268,20 +375,26 @@ async fn inner_create_file(
 	let iso_file_path_parts = iso_file_path.to_parts();
 	let extension = iso_file_path_parts.extension.to_string();
 
+	let is_hard_linked = hard_links_count(metadata) > 1;
 	let metadata = FilePathMetadata::from_path(path, metadata)?;
 
 	// First we check if already exist a file with this same inode number
-	// if it does, we just update it
-	if let Some(file_path) = db
-		.file_path()
-		.find_unique(file_path::location_id_inode(
-			location_id,
-			inode_to_db(metadata.inode),
-		))
-		.include(file_path_with_object::include())
-		.exec()
-		.await?
-	{
+	// if it does, we just update it. Hard linked files share their inode with their other links,
+	// so they can't be told apart this way
+	let file_path_with_same_inode = if is_hard_linked {
+		None
+	} else {
+		db.file_path()
+			.find_first(vec![
+				file_path::location_id::equals(Some(location_id)),
+				file_path::inode::equals(Some(inode_to_db(metadata.inode))),
+			])
+			.include(file_path_with_object::include())
+			.exec()
+			.await?
+	};
+
+	if let Some(file_path) = file_path_with_same_inode {
 		trace!(%iso_file_path, "File already exists with that inode;");
 
 		return inner_update_file(location_path, &file_path, path, node, library, None).await;
@@ -325,8 +438,14 @@ async fn inner_create_file(
 	let FileMetadata {
 		cas_id,
 		kind,
+		detected_extension,
 		fs_metadata,
-	} = FileMetadata::new(&location_path, &iso_file_path).await?;
+	} = FileMetadata::new(&location_path, &iso_file_path, None).await?;
+
+	// Extensionless or misnamed files are handled by what their contents tell
+	let extension = detected_extension
+		.as_ref()
+		.map_or(extension, ToString::to_string);
 
 	let created_file =
 		create_file_path(library, iso_file_path_parts, cas_id.clone(), metadata).await?;
@@ -387,25 +506,36 @@ async fn inner_create_file(
 		.await?
 	};
 
+	let (sync_params, db_params) = chain_optional_iter(
+		[(
+			sync_entry!(
+				prisma_sync::object::SyncId {
+					pub_id: object_pub_id.clone()
+				},
+				file_path::object
+			),
+			file_path::object::connect(object::pub_id::equals(object_pub_id.clone())),
+		)],
+		[option_sync_db_entry!(
+			detected_extension.map(|extension| extension.to_string()),
+			file_path::detected_extension
+		)],
+	)
+	.into_iter()
+	.unzip::<_, _, Vec<_>, Vec<_>>();
+
 	sync.write_op(
 		db,
 		sync.shared_update(
 			prisma_sync::location::SyncId {
 				pub_id: created_file.pub_id.clone(),
 			},
-			[sync_entry!(
-				prisma_sync::object::SyncId {
-					pub_id: object_pub_id.clone()
-				},
-				file_path::object
-			)],
+			sync_params,
 		),
 		db.file_path()
 			.update(
 				file_path::pub_id::equals(created_file.pub_id.clone()),
-				vec![file_path::object::connect(object::pub_id::equals(
-					object_pub_id.clone(),
-				))],
+				db_params,
 			)
 			.select(file_path::select!({ id })),
 	)
@@ -419,30 +549,36 @@ async fn inner_create_file(
 			ObjectKind::Image | ObjectKind::Video | ObjectKind::Audio
 		) {
 		// Running in a detached task as thumbnail generation can take a while and we don't want to block the watcher
-		if matches!(kind, ObjectKind::Image | ObjectKind::Video) {
-			if let Some(cas_id) = cas_id {
-				spawn({
-					let extension = extension.clone();
-					let path = path.to_path_buf();
-					let thumbnails_directory =
-						get_thumbnails_directory(node.config.data_directory());
-					let library_id = *library_id;
-
-					async move {
-						if let Err(e) = generate_single_thumbnail(
-							&thumbnails_directory,
-							extension,
-							cas_id,
-							path,
-							ThumbnailKind::Indexed(library_id),
-						)
-						.await
-						{
-							error!(?e, "Failed to generate thumbnail in the watcher;");
+		if let Some(cas_id) = cas_id {
+			spawn({
+				let extension = extension.clone();
+				let path = path.to_path_buf();
+				let thumbnails_directory = get_thumbnails_directory(node.config.data_directory());
+				let library_id = *library_id;
+				let file_path_id = created_file.id;
+				let db = Arc::clone(db);
+
+				async move {
+					match generate_single_thumbnail(
+						&thumbnails_directory,
+						extension,
+						cas_id,
+						path,
+						ThumbnailKind::Indexed(library_id),
+					)
+					.await
+					{
+						Ok(Some(hash)) => {
+							if let Err(e) = perceptual_hash::save([(file_path_id, hash)], &db).await
+							{
+								error!(?e, "Failed to save perceptual hash in the watcher;");
+							}
 						}
+						Ok(None) => {}
+						Err(e) => error!(?e, "Failed to generate thumbnail in the watcher;"),
 					}
-				});
-			}
+				}
+			});
 		}
 
 		match kind {
@@ -577,7 +713,8 @@ async fn inner_update_file(
 		cas_id,
 		fs_metadata,
 		kind,
-	} = FileMetadata::new(&location_path, &iso_file_path).await?;
+		detected_extension,
+	} = FileMetadata::new(&location_path, &iso_file_path, None).await?;
 
 	let inode = if let Some(inode) = maybe_new_inode {
 		inode
@@ -606,6 +743,11 @@ async fn inner_update_file(
 					DateTime::<Utc>::from(fs_metadata.modified_or_now()),
 					file_path::date_modified
 				),
+				// Cleared when the new contents match the extension again
+				sync_db_nullable_entry!(
+					detected_extension.map(|extension| extension.to_string()),
+					file_path::detected_extension
+				),
 			],
 			[
 				option_sync_db_entry!(file_path.cas_id.clone(), file_path::cas_id),
@@ -749,13 +891,15 @@ async fn inner_update_file(
 							let path = full_path.to_path_buf();
 							let library_id = library.id;
 							let old_cas_id = old_cas_id.to_owned();
+							let file_path_id = file_path.id;
+							let db = Arc::clone(db);
 
 							spawn(async move {
 								let thumbnails_directory =
 									get_thumbnails_directory(node.config.data_directory());
 
 								let was_overwritten = old_cas_id == cas_id;
-								if let Err(e) = generate_single_thumbnail(
+								match generate_single_thumbnail(
 									&thumbnails_directory,
 									ext.clone(),
 									cas_id,
@@ -764,7 +908,20 @@ async fn inner_update_file(
 								)
 								.await
 								{
-									error!(?e, "Failed to generate thumbnail in the watcher;");
+									Ok(Some(hash)) => {
+										if let Err(e) =
+											perceptual_hash::save([(file_path_id, hash)], &db).await
+										{
+											error!(
+												?e,
+												"Failed to save perceptual hash in the watcher;"
+											);
+										}
+									}
+									Ok(None) => {}
+									Err(e) => {
+										error!(?e, "Failed to generate thumbnail in the watcher;");
+									}
 								}
 
 								// If only a few bytes changed, cas_id will probably remains intact
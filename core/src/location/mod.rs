@@ -8,7 +8,7 @@ use sd_core_heavy_lifting::{
 	indexer::{self, job::Indexer},
 	job_system::report::ReportInputMetadata,
 	media_processor::{self, job::MediaProcessor},
-	JobEnqueuer, JobId,
+	IoBudget, JobEnqueuer, JobId,
 };
 use sd_core_prisma_helpers::{location_with_indexer_rules, CasId};
 
@@ -18,7 +18,7 @@ use sd_prisma::{
 };
 use sd_sync::*;
 use sd_utils::{
-	db::{maybe_missing, size_in_bytes_from_db, size_in_bytes_to_db},
+	db::{maybe_missing, size_in_bytes_to_db},
 	error::{FileIOError, NonUtf8PathError},
 	uuid_to_bytes,
 };
@@ -40,6 +40,7 @@ use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
 mod error;
+pub mod indexer_rules;
 mod manager;
 pub mod metadata;
 pub mod non_indexed;
@@ -288,12 +289,22 @@ pub struct LocationUpdateArgs {
 	generate_preview_media: Option<bool>,
 	sync_preview_media: Option<bool>,
 	hidden: Option<bool>,
+	/// Changing it rescans the location, as its symbolic links must be walked or recorded again
+	follow_symlinks: Option<bool>,
+	/// Changing it rescans the location, to list its archives contents or to remove them
+	index_archive_contents: Option<bool>,
+	/// Replaces the I/O budget used by the next scans of the location
+	io_budget: Option<IoBudget>,
 	indexer_rules_ids: Vec<i32>,
 	path: Option<String>,
 }
 
 impl LocationUpdateArgs {
-	pub async fn update(self, node: &Node, library: &Arc<Library>) -> Result<(), LocationError> {
+	pub async fn update(
+		self,
+		node: &Arc<Node>,
+		library: &Arc<Library>,
+	) -> Result<(), LocationError> {
 		let Library { sync, db, .. } = &**library;
 
 		let location = find_location(library, self.id)
@@ -304,6 +315,15 @@ impl LocationUpdateArgs {
 
 		let name = self.name.clone();
 
+		// Locations created before this setting existed have it unset, and always followed symlinks
+		let follow_symlinks_changed = self.follow_symlinks.is_some_and(|follow_symlinks| {
+			location.follow_symlinks.unwrap_or(true) != follow_symlinks
+		});
+
+		let index_archive_contents_changed = self
+			.index_archive_contents
+			.is_some_and(|index| location.index_archive_contents.unwrap_or(false) != index);
+
 		let (sync_params, db_params) = [
 			option_sync_db_entry!(
 				self.name
@@ -316,6 +336,21 @@ impl LocationUpdateArgs {
 			),
 			option_sync_db_entry!(self.sync_preview_media, location::sync_preview_media),
 			option_sync_db_entry!(self.hidden, location::hidden),
+			option_sync_db_entry!(self.follow_symlinks, location::follow_symlinks),
+			option_sync_db_entry!(
+				self.index_archive_contents,
+				location::index_archive_contents
+			),
+			option_sync_db_entry!(
+				self.io_budget
+					.map(|budget| budget.max_files_per_second.map(budget_to_db)),
+				location::io_max_files_per_second
+			),
+			option_sync_db_entry!(
+				self.io_budget
+					.map(|budget| budget.max_hashed_bytes_per_second.map(budget_to_db)),
+				location::io_max_hashed_bytes_per_second
+			),
 			option_sync_db_entry!(self.path.clone(), location::path),
 		]
 		.into_iter()
@@ -391,10 +426,48 @@ impl LocationUpdateArgs {
 			}
 		}
 
+		if follow_symlinks_changed || index_archive_contents_changed {
+			let location = find_location(library, self.id)
+				.include(location_with_indexer_rules::include())
+				.exec()
+				.await?
+				.ok_or(LocationError::IdNotFound(self.id))?;
+
+			let location_scan_state = ScanState::try_from(location.scan_state)?;
+
+			// Directories holding symbolic links may be unchanged since the last scan, while
+			// archives are always checked by the indexer, so they don't need a paranoid scan
+			scan_location(
+				node,
+				library,
+				location,
+				location_scan_state,
+				follow_symlinks_changed,
+			)
+			.await?;
+		}
+
 		Ok(())
 	}
 }
 
+/// Limits are stored as signed integers, so bigger ones are clamped
+fn budget_to_db(limit: u32) -> i32 {
+	i32::try_from(limit).unwrap_or(i32::MAX)
+}
+
+/// The I/O budget of jobs scanning a location, negative limits are treated as unlimited
+fn location_io_budget(location: &location_with_indexer_rules::Data) -> IoBudget {
+	IoBudget {
+		max_files_per_second: location
+			.io_max_files_per_second
+			.and_then(|limit| u32::try_from(limit).ok()),
+		max_hashed_bytes_per_second: location
+			.io_max_hashed_bytes_per_second
+			.and_then(|limit| u32::try_from(limit).ok()),
+	}
+}
+
 pub fn find_location(
 	library: &Library,
 	location_id: location::id::Type,
@@ -425,6 +498,8 @@ async fn link_location_and_indexer_rules(
 	Ok(())
 }
 
+/// Paranoid scans walk through every directory, even the ones unchanged since the last scan,
+/// see [`Indexer::new`] for when they're needed
 #[instrument(
 	skip(node, library, location),
 	fields(library_id = %library.id, location_id = %location.id),
@@ -435,6 +510,7 @@ pub async fn scan_location(
 	library: &Arc<Library>,
 	location: location_with_indexer_rules::Data,
 	location_scan_state: ScanState,
+	is_paranoid: bool,
 ) -> Result<Option<JobId>, sd_core_heavy_lifting::Error> {
 	// TODO(N): This isn't gonna work with removable media and this will likely permanently break if the DB is restored from a backup.
 	if location.instance_id != Some(library.config().await.instance_id) {
@@ -449,6 +525,7 @@ pub async fn scan_location(
 	};
 
 	let location_base_data = location::Data::from(&location);
+	let io_budget = location_io_budget(&location);
 
 	debug!("Scanning location");
 
@@ -456,10 +533,15 @@ pub async fn scan_location(
 		ScanState::Pending | ScanState::Completed => {
 			node.job_system
 				.dispatch(
-					JobEnqueuer::new(Indexer::new(location, None)?)
+					JobEnqueuer::new(Indexer::new(location, None, is_paranoid)?)
 						.with_action("scan_location")
+						.with_io_budget(io_budget)
 						.with_metadata(ReportInputMetadata::Location(location_base_data.clone()))
-						.enqueue_next(FileIdentifier::new(location_base_data.clone(), None)?)
+						.enqueue_next(FileIdentifier::new(
+							location_base_data.clone(),
+							None,
+							false,
+						)?)
 						.enqueue_next(MediaProcessor::new(location_base_data, None, false)?),
 					location_id,
 					ctx.clone(),
@@ -470,10 +552,15 @@ pub async fn scan_location(
 		ScanState::Indexed => {
 			node.job_system
 				.dispatch(
-					JobEnqueuer::new(FileIdentifier::new(location_base_data.clone(), None)?)
-						.with_action("scan_location_already_indexed")
-						.with_metadata(ReportInputMetadata::Location(location_base_data.clone()))
-						.enqueue_next(MediaProcessor::new(location_base_data, None, false)?),
+					JobEnqueuer::new(FileIdentifier::new(
+						location_base_data.clone(),
+						None,
+						false,
+					)?)
+					.with_action("scan_location_already_indexed")
+					.with_io_budget(io_budget)
+					.with_metadata(ReportInputMetadata::Location(location_base_data.clone()))
+					.enqueue_next(MediaProcessor::new(location_base_data, None, false)?),
 					location_id,
 					ctx.clone(),
 				)
@@ -489,6 +576,7 @@ pub async fn scan_location(
 						false,
 					)?)
 					.with_action("scan_location_files_already_identified")
+					.with_io_budget(io_budget)
 					.with_metadata(ReportInputMetadata::Location(location_base_data)),
 					location_id,
 					ctx.clone(),
@@ -506,6 +594,7 @@ pub async fn scan_location(
 		library_id = %library.id,
 		location_id = %location.id,
 		sub_path = %sub_path.as_ref().display(),
+		%is_paranoid,
 	),
 	err,
 )]
@@ -514,6 +603,7 @@ pub async fn scan_location_sub_path(
 	library: &Arc<Library>,
 	location: location_with_indexer_rules::Data,
 	sub_path: impl AsRef<Path> + Send,
+	is_paranoid: bool,
 ) -> Result<Option<JobId>, sd_core_heavy_lifting::Error> {
 	let sub_path = sub_path.as_ref().to_path_buf();
 
@@ -530,18 +620,21 @@ pub async fn scan_location_sub_path(
 	};
 
 	let location_base_data = location::Data::from(&location);
+	let io_budget = location_io_budget(&location);
 
 	debug!("Scanning location on a sub path");
 
 	node.job_system
 		.dispatch(
-			JobEnqueuer::new(Indexer::new(location, Some(sub_path.clone()))?)
+			JobEnqueuer::new(Indexer::new(location, Some(sub_path.clone()), is_paranoid)?)
 				.with_action("scan_location")
+				.with_io_budget(io_budget)
 				.with_metadata(ReportInputMetadata::Location(location_base_data.clone()))
 				.with_metadata(ReportInputMetadata::SubPath(sub_path.clone()))
 				.enqueue_next(FileIdentifier::new(
 					location_base_data.clone(),
 					Some(sub_path.clone()),
+					false,
 				)?)
 				.enqueue_next(MediaProcessor::new(
 					location_base_data,
@@ -1004,23 +1097,7 @@ pub async fn update_location_size(
 ) -> Result<(), sd_core_sync::Error> {
 	let Library { db, sync, .. } = library;
 
-	let total_size = size_in_bytes_to_db(
-		db.file_path()
-			.find_many(vec![
-				file_path::location_id::equals(Some(location_id)),
-				file_path::materialized_path::equals(Some("/".to_string())),
-			])
-			.select(file_path::select!({ size_in_bytes_bytes }))
-			.exec()
-			.await?
-			.into_iter()
-			.filter_map(|file_path| {
-				file_path
-					.size_in_bytes_bytes
-					.map(|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes))
-			})
-			.sum::<u64>(),
-	);
+	let total_size = size_in_bytes_to_db(indexer::location_size(location_id, db).await?);
 
 	let (sync_param, db_param) = sync_db_entry!(total_size, location::size_in_bytes);
 
@@ -1,4 +1,12 @@
-use crate::{api::locations::ExplorerItem, context::NodeContext, library::Library, Node};
+use crate::{
+	api::{
+		locations::ExplorerItem,
+		search::{InOrNotIn, TextMatch},
+	},
+	context::NodeContext,
+	library::Library,
+	Node,
+};
 
 use sd_core_file_path_helper::{path_is_hidden, MetadataExt};
 use sd_core_heavy_lifting::{
@@ -18,7 +26,7 @@ use sd_prisma::prisma::location;
 use sd_utils::{chain_optional_iter, error::FileIOError};
 
 use std::{
-	collections::HashMap,
+	collections::{HashMap, VecDeque},
 	io::ErrorKind,
 	ops::Deref,
 	path::{Path, PathBuf},
@@ -29,7 +37,7 @@ use chrono::{DateTime, Utc};
 use futures::Stream;
 use itertools::{Either, Itertools};
 use rspc::ErrorCode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use specta::Type;
 use thiserror::Error;
 use tokio::{io, spawn, sync::mpsc, task::JoinError};
@@ -96,6 +104,8 @@ pub struct NonIndexedPathItem {
 	pub hidden: bool,
 }
 
+type EntrySender = mpsc::Sender<Result<ExplorerItem, Either<rspc::Error, NonIndexedLocationError>>>;
+
 // #[instrument(name = "non_indexed::walk", skip(sort_fn))]
 pub async fn walk(
 	path: PathBuf,
@@ -121,212 +131,149 @@ pub async fn walk(
 
 	// We wanna process and let the caller use the stream.
 	let task = spawn(async move {
-		let path = &path;
-		let indexer_ruler = IndexerRuler::new(chain_optional_iter(
-			[IndexerRule::from(NO_SYSTEM_FILES.deref())],
-			[(!with_hidden_files).then(|| IndexerRule::from(NO_HIDDEN.deref()))],
-		));
-
-		let mut thumbnails_to_generate = vec![];
-		// Generating thumbnails for PDFs is kinda slow, so we're leaving them for last in the batch
-		let mut document_thumbnails_to_generate = vec![];
-		let mut directories = vec![];
-
-		for entry in entries.into_iter() {
-			let (entry_path, name) = match normalize_path(entry.path) {
-				Ok(v) => v,
-				Err(e) => {
-					tx.send(Err(Either::Left(
-						NonIndexedLocationError::from((path, e)).into(),
-					)))
-					.await?;
-					continue;
-				}
-			};
-
-			match indexer_ruler
-				.evaluate_path(&entry_path, &entry.metadata)
-				.await
-			{
-				Ok(RulerDecision::Accept) => { /* Everything is awesome! */ }
+		let indexer_ruler = ephemeral_indexer_ruler(with_hidden_files);
+		let entries = accepted_entries(entries, &indexer_ruler, &tx).await?;
 
-				Ok(RulerDecision::Reject) => {
-					continue;
-				}
+		process_entries(&path, entries, &node, &library, &tx).await
+	});
 
-				Err(e) => {
-					tx.send(Err(Either::Left(e.into()))).await?;
-					continue;
-				}
+	spawn(async move {
+		match task.await {
+			Ok(Ok(())) => {}
+			Ok(Err(e)) => {
+				let _ = tx2.send(Err(Either::Left(e.into()))).await;
 			}
+			Err(e) => error!(?e, "error joining tokio task"),
+		}
+	});
 
-			if entry.metadata.is_dir() {
-				directories.push((entry_path, name, entry.metadata));
-			} else {
-				let path = Path::new(&entry_path);
+	Ok(ReceiverStream::new(rx))
+}
+
+/// Filters for a recursive [`search`], entries must match all of them
+#[derive(Deserialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EphemeralSearchFilters {
+	/// How many levels below the searched path to look into, unlimited if not set
+	#[specta(optional)]
+	pub max_depth: Option<u32>,
+	#[specta(optional)]
+	pub name: Option<TextMatch>,
+	#[specta(optional)]
+	pub extension: Option<InOrNotIn<String>>,
+	#[specta(optional)]
+	pub kind: Option<InOrNotIn<i32>>,
+}
 
-				let Some(name) = path
-					.file_stem()
-					.and_then(|s| s.to_str().map(str::to_string))
-				else {
-					warn!(%entry_path, "Failed to extract name from path;");
-					continue;
-				};
+impl EphemeralSearchFilters {
+	async fn matches(&self, entry: &Entry) -> bool {
+		if let Some(name) = &self.name {
+			if !name.matches(&entry.name) {
+				return false;
+			}
+		}
 
-				let extension = path
+		let is_dir = entry.metadata.is_dir();
+
+		if let Some(extension) = &self.extension {
+			let entry_extension = if is_dir {
+				String::new()
+			} else {
+				entry
+					.path
 					.extension()
-					.and_then(|s| s.to_str().map(str::to_string))
-					.unwrap_or_default();
+					.and_then(|extension| extension.to_str())
+					.map(str::to_lowercase)
+					.unwrap_or_default()
+			};
+
+			if !extension.matches(&entry_extension) {
+				return false;
+			}
+		}
 
-				let kind = Extension::resolve_conflicting(&path, false)
+		if let Some(kind) = &self.kind {
+			let entry_kind = if is_dir {
+				ObjectKind::Folder
+			} else {
+				Extension::resolve_conflicting(&entry.path, false)
 					.await
 					.map(Into::into)
-					.unwrap_or(ObjectKind::Unknown);
-
-				let should_generate_thumbnail = {
-					#[cfg(feature = "ffmpeg")]
-					{
-						matches!(
-							kind,
-							ObjectKind::Image | ObjectKind::Video | ObjectKind::Document
-						)
-					}
+					.unwrap_or(ObjectKind::Unknown)
+			};
 
-					#[cfg(not(feature = "ffmpeg"))]
-					{
-						matches!(kind, ObjectKind::Image | ObjectKind::Document)
-					}
-				};
-
-				let (thumbnail_key, has_created_thumbnail) = if should_generate_thumbnail {
-					if let Ok(cas_id) =
-						generate_cas_id(&path, entry.metadata.len())
-							.await
-							.map_err(|e| {
-								tx.send(Err(Either::Left(
-									NonIndexedLocationError::from((path, e)).into(),
-								)))
-							}) {
-						if kind == ObjectKind::Document {
-							document_thumbnails_to_generate.push(GenerateThumbnailArgs::new(
-								extension.clone(),
-								cas_id.clone(),
-								path.to_path_buf(),
-							));
-						} else {
-							thumbnails_to_generate.push(GenerateThumbnailArgs::new(
-								extension.clone(),
-								cas_id.clone(),
-								path.to_path_buf(),
-							));
-						}
-
-						let thumb_exists = node
-							.ephemeral_thumbnail_exists(&cas_id)
-							.await
-							.map_err(NonIndexedLocationError::from)?;
-
-						(Some(ThumbKey::new_ephemeral(cas_id)), thumb_exists)
-					} else {
-						(None, false)
-					}
-				} else {
-					(None, false)
-				};
-
-				tx.send(Ok(ExplorerItem::NonIndexedPath {
-					thumbnail: thumbnail_key,
-					item: NonIndexedPathItem {
-						hidden: path_is_hidden(Path::new(&entry_path), &entry.metadata),
-						path: entry_path,
-						name,
-						extension,
-						kind: kind as i32,
-						is_dir: false,
-						date_created: entry.metadata.created_or_now().into(),
-						date_modified: entry.metadata.modified_or_now().into(),
-						size_in_bytes_bytes: entry.metadata.len().to_be_bytes().to_vec(),
-					},
-					has_created_thumbnail,
-				}))
-				.await?;
+			if !kind.matches(&(entry_kind as i32)) {
+				return false;
 			}
 		}
 
-		thumbnails_to_generate.extend(document_thumbnails_to_generate);
-
-		let thumbnails_directory = Arc::new(get_thumbnails_directory(node.config.data_directory()));
-		let reporter: Arc<dyn NewThumbnailReporter> = Arc::new(NewThumbnailsReporter {
-			ctx: NodeContext {
-				node: Arc::clone(&node),
-				library: Arc::clone(&library),
-			},
-		});
-
-		if node
-			.task_system
-			.dispatch_many(
-				thumbnails_to_generate
-					.into_iter()
-					.chunks(10)
-					.into_iter()
-					.map(|chunk| {
-						media_processor::Thumbnailer::new_ephemeral(
-							Arc::clone(&thumbnails_directory),
-							chunk.collect(),
-							Arc::clone(&reporter),
-						)
-					})
-					.collect::<Vec<_>>(),
-			)
-			.await
-			.is_err()
-		{
-			debug!("Task system shutting down");
-		}
+		true
+	}
+}
 
-		let mut locations = library
-			.db
-			.location()
-			.find_many(vec![location::path::in_vec(
-				directories
-					.iter()
-					.map(|(path, _, _)| path.clone())
-					.collect(),
-			)])
-			.exec()
-			.await?
-			.into_iter()
-			.flat_map(|location| {
-				location
-					.path
-					.clone()
-					.map(|location_path| (location_path, location))
-			})
-			.collect::<HashMap<_, _>>();
-
-		for (directory, name, metadata) in directories {
-			if let Some(location) = locations.remove(&directory) {
-				tx.send(Ok(ExplorerItem::Location { item: location }))
-					.await?;
+/// Recursively searches a non indexed path, streaming matching entries as each directory is read.
+///
+/// The same indexer rules of [`walk`] are applied, and directories rejected by them aren't entered.
+/// Entries of each directory are sorted with `sort_fn` before being sent.
+pub async fn search(
+	path: PathBuf,
+	with_hidden_files: bool,
+	filters: EphemeralSearchFilters,
+	node: Arc<Node>,
+	library: Arc<Library>,
+	sort_fn: impl Fn(&mut Vec<Entry>) + Send + 'static,
+) -> Result<
+	impl Stream<Item = Result<ExplorerItem, Either<rspc::Error, NonIndexedLocationError>>> + Send,
+	NonIndexedLocationError,
+> {
+	// Reading the root directory right away, so we can error out early if it doesn't exist
+	let root_entries = get_all_entries(path.clone()).await?;
+
+	let (tx, rx) = mpsc::channel(128);
+	let tx2 = tx.clone();
+
+	let task = spawn(async move {
+		let indexer_ruler = ephemeral_indexer_ruler(with_hidden_files);
+
+		let mut to_walk = VecDeque::from([(path, 0, Some(root_entries))]);
+
+		while let Some((directory, depth, maybe_entries)) = to_walk.pop_front() {
+			let entries = if let Some(entries) = maybe_entries {
+				entries
 			} else {
-				tx.send(Ok(ExplorerItem::NonIndexedPath {
-					thumbnail: None,
-					item: NonIndexedPathItem {
-						hidden: path_is_hidden(Path::new(&directory), &metadata),
-						path: directory,
-						name,
-						extension: String::new(),
-						kind: ObjectKind::Folder as i32,
-						is_dir: true,
-						date_created: metadata.created_or_now().into(),
-						date_modified: metadata.modified_or_now().into(),
-						size_in_bytes_bytes: metadata.len().to_be_bytes().to_vec(),
-					},
-					has_created_thumbnail: false,
-				}))
-				.await?;
+				match get_all_entries(directory.clone()).await {
+					Ok(entries) => entries,
+					Err(e) => {
+						// A single unreadable directory shouldn't stop the whole search
+						tx.send(Err(Either::Right(e))).await?;
+						continue;
+					}
+				}
+			};
+
+			let mut matching_entries = Vec::new();
+
+			for entry in accepted_entries(entries, &indexer_ruler, &tx).await? {
+				if entry.metadata.is_dir()
+					&& filters
+						.max_depth
+						.map_or(true, |max_depth| depth < max_depth)
+				{
+					to_walk.push_back((entry.path.clone(), depth + 1, None));
+				}
+
+				if filters.matches(&entry).await {
+					matching_entries.push(entry);
+				}
+			}
+
+			if matching_entries.is_empty() {
+				continue;
 			}
+
+			sort_fn(&mut matching_entries);
+
+			process_entries(&directory, matching_entries, &node, &library, &tx).await?;
 		}
 
 		Ok::<_, NonIndexedLocationError>(())
@@ -345,6 +292,239 @@ pub async fn walk(
 	Ok(ReceiverStream::new(rx))
 }
 
+fn ephemeral_indexer_ruler(with_hidden_files: bool) -> IndexerRuler {
+	IndexerRuler::new(chain_optional_iter(
+		[IndexerRule::from(NO_SYSTEM_FILES.deref())],
+		[(!with_hidden_files).then(|| IndexerRule::from(NO_HIDDEN.deref()))],
+	))
+}
+
+/// Entries accepted by the indexer rules, sending through `tx` the errors of the ones that couldn't
+/// be evaluated
+async fn accepted_entries(
+	entries: Vec<Entry>,
+	indexer_ruler: &IndexerRuler,
+	tx: &EntrySender,
+) -> Result<Vec<Entry>, NonIndexedLocationError> {
+	let mut accepted_entries = Vec::with_capacity(entries.len());
+
+	for entry in entries {
+		match indexer_ruler
+			.evaluate_path(&entry.path, &entry.metadata)
+			.await
+		{
+			Ok(RulerDecision::Accept) => accepted_entries.push(entry),
+			Ok(RulerDecision::Reject) => {}
+			Err(e) => tx.send(Err(Either::Left(e.into()))).await?,
+		}
+	}
+
+	Ok(accepted_entries)
+}
+
+/// Turns entries of the `path` directory, already [accepted](accepted_entries) by the indexer
+/// rules, into [`ExplorerItem`]s, sending them through `tx` and dispatching thumbnail generation
+/// for them
+async fn process_entries(
+	path: &Path,
+	entries: Vec<Entry>,
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+	tx: &EntrySender,
+) -> Result<(), NonIndexedLocationError> {
+	let mut thumbnails_to_generate = vec![];
+	// Generating thumbnails for PDFs is kinda slow, so we're leaving them for last in the batch
+	let mut document_thumbnails_to_generate = vec![];
+	let mut directories = vec![];
+
+	for entry in entries.into_iter() {
+		let (entry_path, name) = match normalize_path(entry.path) {
+			Ok(v) => v,
+			Err(e) => {
+				tx.send(Err(Either::Left(
+					NonIndexedLocationError::from((path, e)).into(),
+				)))
+				.await?;
+				continue;
+			}
+		};
+
+		if entry.metadata.is_dir() {
+			directories.push((entry_path, name, entry.metadata));
+		} else {
+			let path = Path::new(&entry_path);
+
+			let Some(name) = path
+				.file_stem()
+				.and_then(|s| s.to_str().map(str::to_string))
+			else {
+				warn!(%entry_path, "Failed to extract name from path;");
+				continue;
+			};
+
+			let extension = path
+				.extension()
+				.and_then(|s| s.to_str().map(str::to_string))
+				.unwrap_or_default();
+
+			let kind = Extension::resolve_conflicting(&path, false)
+				.await
+				.map(Into::into)
+				.unwrap_or(ObjectKind::Unknown);
+
+			let should_generate_thumbnail = {
+				#[cfg(feature = "ffmpeg")]
+				{
+					matches!(
+						kind,
+						ObjectKind::Image
+							| ObjectKind::Video | ObjectKind::Audio
+							| ObjectKind::Document
+					)
+				}
+
+				#[cfg(not(feature = "ffmpeg"))]
+				{
+					matches!(kind, ObjectKind::Image | ObjectKind::Document)
+				}
+			};
+
+			let (thumbnail_key, has_created_thumbnail) = if should_generate_thumbnail {
+				if let Ok(cas_id) =
+					generate_cas_id(&path, entry.metadata.len())
+						.await
+						.map_err(|e| {
+							tx.send(Err(Either::Left(
+								NonIndexedLocationError::from((path, e)).into(),
+							)))
+						}) {
+					if kind == ObjectKind::Document {
+						document_thumbnails_to_generate.push(GenerateThumbnailArgs::new(
+							extension.clone(),
+							cas_id.clone(),
+							path.to_path_buf(),
+							false,
+						));
+					} else {
+						thumbnails_to_generate.push(GenerateThumbnailArgs::new(
+							extension.clone(),
+							cas_id.clone(),
+							path.to_path_buf(),
+							false,
+						));
+					}
+
+					let thumb_exists = node
+						.ephemeral_thumbnail_exists(&cas_id)
+						.await
+						.map_err(NonIndexedLocationError::from)?;
+
+					(Some(ThumbKey::new_ephemeral(cas_id)), thumb_exists)
+				} else {
+					(None, false)
+				}
+			} else {
+				(None, false)
+			};
+
+			tx.send(Ok(ExplorerItem::NonIndexedPath {
+				thumbnail: thumbnail_key,
+				item: NonIndexedPathItem {
+					hidden: path_is_hidden(Path::new(&entry_path), &entry.metadata),
+					path: entry_path,
+					name,
+					extension,
+					kind: kind as i32,
+					is_dir: false,
+					date_created: entry.metadata.created_or_now().into(),
+					date_modified: entry.metadata.modified_or_now().into(),
+					size_in_bytes_bytes: entry.metadata.len().to_be_bytes().to_vec(),
+				},
+				has_created_thumbnail,
+			}))
+			.await?;
+		}
+	}
+
+	thumbnails_to_generate.extend(document_thumbnails_to_generate);
+
+	let thumbnails_directory = Arc::new(get_thumbnails_directory(node.config.data_directory()));
+	let reporter: Arc<dyn NewThumbnailReporter> = Arc::new(NewThumbnailsReporter {
+		ctx: NodeContext {
+			node: Arc::clone(node),
+			library: Arc::clone(library),
+		},
+	});
+
+	if node
+		.task_system
+		.dispatch_many(
+			thumbnails_to_generate
+				.into_iter()
+				.chunks(10)
+				.into_iter()
+				.map(|chunk| {
+					media_processor::Thumbnailer::new_ephemeral(
+						Arc::clone(&thumbnails_directory),
+						chunk.collect(),
+						Arc::clone(&reporter),
+					)
+				})
+				.collect::<Vec<_>>(),
+		)
+		.await
+		.is_err()
+	{
+		debug!("Task system shutting down");
+	}
+
+	let mut locations = library
+		.db
+		.location()
+		.find_many(vec![location::path::in_vec(
+			directories
+				.iter()
+				.map(|(path, _, _)| path.clone())
+				.collect(),
+		)])
+		.exec()
+		.await?
+		.into_iter()
+		.flat_map(|location| {
+			location
+				.path
+				.clone()
+				.map(|location_path| (location_path, location))
+		})
+		.collect::<HashMap<_, _>>();
+
+	for (directory, name, metadata) in directories {
+		if let Some(location) = locations.remove(&directory) {
+			tx.send(Ok(ExplorerItem::Location { item: location }))
+				.await?;
+		} else {
+			tx.send(Ok(ExplorerItem::NonIndexedPath {
+				thumbnail: None,
+				item: NonIndexedPathItem {
+					hidden: path_is_hidden(Path::new(&directory), &metadata),
+					path: directory,
+					name,
+					extension: String::new(),
+					kind: ObjectKind::Folder as i32,
+					is_dir: true,
+					date_created: metadata.created_or_now().into(),
+					date_modified: metadata.modified_or_now().into(),
+					size_in_bytes_bytes: metadata.len().to_be_bytes().to_vec(),
+				},
+				has_created_thumbnail: false,
+			}))
+			.await?;
+		}
+	}
+
+	Ok(())
+}
+
 #[derive(Debug)]
 pub struct Entry {
 	path: PathBuf,
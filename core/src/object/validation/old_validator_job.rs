@@ -113,6 +113,7 @@ impl StatefulJob for OldObjectValidatorJobInit {
 					file_path::location_id::equals(Some(init.location.id)),
 					file_path::is_dir::equals(Some(false)),
 					file_path::integrity_checksum::equals(None),
+					file_path::archive_member_path::equals(None),
 				],
 				[maybe_sub_iso_file_path.and_then(|iso_sub_path| {
 					iso_sub_path
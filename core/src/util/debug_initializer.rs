@@ -181,7 +181,7 @@ impl InitConfig {
 				.create(node, &library)
 				.await?
 				{
-					scan_location(node, &library, location, ScanState::Pending).await?;
+					scan_location(node, &library, location, ScanState::Pending, false).await?;
 				} else {
 					warn!(
 						location_path = ?loc.path,
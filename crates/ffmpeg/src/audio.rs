@@ -0,0 +1,412 @@
+use crate::{
+	codec_ctx::FFmpegCodecContext,
+	error::{Error, FFmpegError},
+	format_ctx::FFmpegFormatContext,
+	thumbnailer::write_webp,
+	utils::{check_error, from_path},
+	video_frame::FFmpegFrame,
+};
+
+use std::{ops::Deref, path::Path, ptr, slice};
+
+use ffmpeg_sys_next::{
+	av_find_best_stream, av_packet_alloc, av_packet_free, av_packet_unref, av_seek_frame, AVFrame,
+	AVMediaType, AVPacket, AVSampleFormat, AVSEEK_FLAG_BACKWARD, AV_TIME_BASE,
+};
+use image::{imageops, DynamicImage, GenericImageView, Rgba, RgbaImage};
+use tokio::task::spawn_blocking;
+use tracing::debug;
+use webp::Encoder;
+
+const WAVEFORM_BAR_WIDTH: u32 = 6;
+const WAVEFORM_BAR_GAP: u32 = 2;
+const WAVEFORM_COLOR: Rgba<u8> = Rgba([0x36, 0x9A, 0xFF, 0xFF]);
+
+/// How many levels we keep per second of audio, before fitting them to the waveform bars
+const LEVELS_PER_SECOND: i32 = 100;
+
+/// How much audio we decode at most to render a waveform
+const MAX_DECODED_SECONDS: i32 = 60;
+/// Longer streams are decoded in this many windows, evenly spread through them
+const DECODED_WINDOWS_COUNT: i32 = 20;
+
+/// Generates a thumbnail for an audio file and writes it to the file system with webp format.
+/// The embedded cover art is used when the file has one, otherwise we render its waveform.
+pub(crate) async fn generate_audio_thumbnail(
+	audio_file_path: impl AsRef<Path> + Send,
+	output_thumbnail_path: impl AsRef<Path> + Send,
+	size: u32,
+	quality: f32,
+) -> Result<(), Error> {
+	if !(0.0..=100.0).contains(&quality) {
+		return Err(Error::InvalidQuality(quality));
+	}
+
+	let webp = spawn_blocking({
+		let audio_file_path = audio_file_path.as_ref().to_path_buf();
+		move || -> Result<Vec<u8>, Error> {
+			let mut format_ctx =
+				FFmpegFormatContext::open_file(from_path(&audio_file_path)?.as_c_str())?;
+			format_ctx.find_stream_info()?;
+
+			let image = match format_ctx.attached_picture().map(image::load_from_memory) {
+				Some(Ok(cover_art)) => fit_cover_art(&cover_art, size),
+				Some(Err(e)) => {
+					debug!(
+						?e,
+						"Failed to decode embedded cover art, rendering waveform instead;"
+					);
+					render_waveform(&decode_levels(&mut format_ctx)?, size)?
+				}
+				None => render_waveform(&decode_levels(&mut format_ctx)?, size)?,
+			};
+
+			// Type WebPMemory is !Send, which makes the Future in this function !Send,
+			// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
+			// which implies on a unwanted clone...
+			Ok(Encoder::from_image(&image)
+				.expect("Should not fail as the underlining DynamicImage is an Rgb or Rgba image")
+				.encode(quality)
+				.deref()
+				.to_vec())
+		}
+	})
+	.await??;
+
+	write_webp(output_thumbnail_path.as_ref(), &webp).await
+}
+
+/// Downscales the cover art to fit in `size`, converting it to a pixel format webp can encode
+fn fit_cover_art(cover_art: &DynamicImage, size: u32) -> DynamicImage {
+	let (width, height) = cover_art.dimensions();
+
+	DynamicImage::ImageRgb8(if width > size || height > size {
+		cover_art
+			.resize(size, size, imageops::FilterType::Triangle)
+			.to_rgb8()
+	} else {
+		cover_art.to_rgb8()
+	})
+}
+
+struct Packet(*mut AVPacket);
+
+impl Drop for Packet {
+	fn drop(&mut self) {
+		unsafe { av_packet_free(&mut self.0) };
+	}
+}
+
+/// Decodes the audio stream to PCM, keeping the loudness (RMS) of each slice of
+/// `1 / LEVELS_PER_SECOND` seconds. Streams longer than [`MAX_DECODED_SECONDS`] are only decoded
+/// in [`DECODED_WINDOWS_COUNT`] windows spread through them, so hours long files stay cheap.
+fn decode_levels(format_ctx: &mut FFmpegFormatContext) -> Result<Vec<f32>, Error> {
+	let mut decoder = ptr::null();
+	let stream_index = unsafe {
+		av_find_best_stream(
+			format_ctx.as_mut(),
+			AVMediaType::AVMEDIA_TYPE_AUDIO,
+			-1,
+			-1,
+			&mut decoder,
+			0,
+		)
+	};
+	check_error(stream_index, "Failed to find an audio stream")?;
+
+	let decoder = unsafe { decoder.as_ref() }.ok_or(FFmpegError::DecoderNotFound)?;
+
+	let mut codec_ctx = FFmpegCodecContext::new()?;
+	codec_ctx.parameters_to_context(
+		format_ctx
+			.stream(u32::try_from(stream_index)?)
+			.and_then(|stream| unsafe { stream.codecpar.as_ref() })
+			.ok_or(FFmpegError::NullError)?,
+	)?;
+	codec_ctx.open2(decoder)?;
+
+	let slice_len = (codec_ctx.as_ref().sample_rate / LEVELS_PER_SECOND).max(1);
+
+	let packet = Packet(unsafe { av_packet_alloc() });
+	if packet.0.is_null() {
+		Err(FFmpegError::PacketAllocation)?;
+	}
+
+	let max_levels = usize::try_from(MAX_DECODED_SECONDS * LEVELS_PER_SECOND)?;
+
+	let mut levels_decoder = LevelsDecoder {
+		codec_ctx,
+		frame: FFmpegFrame::new()?,
+		packet,
+		stream_index,
+		slice_len: usize::try_from(slice_len)?,
+		squares_sum: 0.0,
+		samples_count: 0,
+		levels: Vec::with_capacity(max_levels),
+	};
+
+	match format_ctx.duration() {
+		Some(duration) if duration > i64::from(MAX_DECODED_SECONDS) * i64::from(AV_TIME_BASE) => {
+			let window_levels = max_levels / usize::try_from(DECODED_WINDOWS_COUNT)?;
+
+			for window in 0..DECODED_WINDOWS_COUNT {
+				if window > 0 {
+					let seeked = check_error(
+						unsafe {
+							av_seek_frame(
+								format_ctx.as_mut(),
+								-1,
+								duration / i64::from(DECODED_WINDOWS_COUNT) * i64::from(window),
+								AVSEEK_FLAG_BACKWARD,
+							)
+						},
+						"Seeking audio failed",
+					);
+
+					if let Err(e) = seeked {
+						// The windows we already have still make a decent waveform
+						debug!(?e, "Failed to seek audio, rendering a partial waveform;");
+						break;
+					}
+
+					levels_decoder.reset();
+				}
+
+				let target_levels = levels_decoder.levels.len() + window_levels;
+				levels_decoder.decode_until(format_ctx, target_levels);
+			}
+		}
+
+		// Short streams, or ones that don't know their duration, are decoded from the start
+		_ => levels_decoder.decode_until(format_ctx, max_levels),
+	}
+
+	if levels_decoder.levels.is_empty() {
+		return Err(Error::FrameDecodeError);
+	}
+
+	Ok(levels_decoder.levels)
+}
+
+struct LevelsDecoder {
+	codec_ctx: FFmpegCodecContext,
+	frame: FFmpegFrame,
+	packet: Packet,
+	stream_index: i32,
+	slice_len: usize,
+	squares_sum: f64,
+	samples_count: usize,
+	levels: Vec<f32>,
+}
+
+impl LevelsDecoder {
+	/// Reads packets until we have `target_levels` levels or the stream ends
+	fn decode_until(&mut self, format_ctx: &mut FFmpegFormatContext, target_levels: usize) {
+		while self.levels.len() < target_levels {
+			if format_ctx.read_frame(self.packet.0).is_err() {
+				// End of the stream, sending a null packet drains the frames still in the decoder
+				let _ = self.codec_ctx.send_packet(ptr::null_mut());
+				self.receive_frames(target_levels);
+				return;
+			}
+
+			if unsafe { (*self.packet.0).stream_index } == self.stream_index {
+				loop {
+					// Corrupt packets are common enough on audio files, we just skip them
+					let sent = self.codec_ctx.send_packet(self.packet.0);
+
+					self.receive_frames(target_levels);
+
+					// The decoder was full, now that we drained its frames we can send the packet again
+					if !matches!(sent, Err(FFmpegError::Again)) {
+						break;
+					}
+				}
+			}
+
+			unsafe { av_packet_unref(self.packet.0) };
+		}
+	}
+
+	fn receive_frames(&mut self, target_levels: usize) {
+		let Self {
+			codec_ctx,
+			frame,
+			slice_len,
+			squares_sum,
+			samples_count,
+			levels,
+			..
+		} = self;
+
+		while let Ok(true) = codec_ctx.receive_frame(frame.as_mut()) {
+			for_each_sample_peak(frame.as_ref(), |peak| {
+				if levels.len() >= target_levels {
+					return;
+				}
+
+				*squares_sum += f64::from(peak) * f64::from(peak);
+				*samples_count += 1;
+
+				if *samples_count == *slice_len {
+					#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+					{
+						// SAFETY: slices are way smaller than 2^52 samples and a loudness
+						// level is between 0.0 and 1.0, so the casts don't lose anything
+						levels.push((*squares_sum / *samples_count as f64).sqrt() as f32);
+					}
+					(*squares_sum, *samples_count) = (0.0, 0);
+				}
+			});
+		}
+	}
+
+	/// After seeking, the decoder and the slice being accumulated belong to the previous window
+	fn reset(&mut self) {
+		self.codec_ctx.flush();
+		(self.squares_sum, self.samples_count) = (0.0, 0);
+	}
+}
+
+/// Calls `f` with the loudest absolute amplitude among all channels, for each sample of the frame.
+/// Sample formats we don't know about are ignored.
+fn for_each_sample_peak(frame: &AVFrame, mut f: impl FnMut(f32)) {
+	use AVSampleFormat::{
+		AV_SAMPLE_FMT_DBL, AV_SAMPLE_FMT_DBLP, AV_SAMPLE_FMT_FLT, AV_SAMPLE_FMT_FLTP,
+		AV_SAMPLE_FMT_S16, AV_SAMPLE_FMT_S16P, AV_SAMPLE_FMT_S32, AV_SAMPLE_FMT_S32P,
+		AV_SAMPLE_FMT_U8, AV_SAMPLE_FMT_U8P,
+	};
+
+	let (Ok(channels), Ok(samples)) = (
+		usize::try_from(frame.ch_layout.nb_channels),
+		usize::try_from(frame.nb_samples),
+	) else {
+		return;
+	};
+
+	if channels == 0 || frame.extended_data.is_null() {
+		return;
+	}
+
+	let (planar, sample_size, read_sample): (bool, usize, fn(&[u8], usize) -> f32) =
+		match frame.format {
+			format if format == AV_SAMPLE_FMT_U8 as i32 => (false, 1, read_u8),
+			format if format == AV_SAMPLE_FMT_U8P as i32 => (true, 1, read_u8),
+			format if format == AV_SAMPLE_FMT_S16 as i32 => (false, 2, read_s16),
+			format if format == AV_SAMPLE_FMT_S16P as i32 => (true, 2, read_s16),
+			format if format == AV_SAMPLE_FMT_S32 as i32 => (false, 4, read_s32),
+			format if format == AV_SAMPLE_FMT_S32P as i32 => (true, 4, read_s32),
+			format if format == AV_SAMPLE_FMT_FLT as i32 => (false, 4, read_f32),
+			format if format == AV_SAMPLE_FMT_FLTP as i32 => (true, 4, read_f32),
+			format if format == AV_SAMPLE_FMT_DBL as i32 => (false, 8, read_f64),
+			format if format == AV_SAMPLE_FMT_DBLP as i32 => (true, 8, read_f64),
+			_ => return,
+		};
+
+	// Planar formats have a buffer per channel, packed ones interleave all channels in a single one
+	let (buffers_count, buffer_len) = if planar {
+		(channels, samples * sample_size)
+	} else {
+		(1, samples * channels * sample_size)
+	};
+
+	// SAFETY: `extended_data` holds a pointer for each channel on planar formats and a single one
+	// on packed formats, each pointing to a buffer with at least `nb_samples` samples of the frame
+	// format for the channels it holds. The frame isn't touched while the slices are alive, as
+	// they're all dropped before returning.
+	let buffers = unsafe {
+		let pointers = slice::from_raw_parts(frame.extended_data, buffers_count);
+
+		if pointers.iter().any(|pointer| pointer.is_null()) {
+			return;
+		}
+
+		pointers
+			.iter()
+			.map(|&pointer| slice::from_raw_parts(pointer.cast_const(), buffer_len))
+			.collect::<Vec<_>>()
+	};
+
+	for sample in 0..samples {
+		f((0..channels)
+			.map(|channel| {
+				if planar {
+					read_sample(buffers[channel], sample)
+				} else {
+					read_sample(buffers[0], sample * channels + channel)
+				}
+				.abs()
+			})
+			.fold(0.0, f32::max));
+	}
+}
+
+/// The bytes of the `idx`th sample with `N` bytes in the buffer
+fn sample_bytes<const N: usize>(buffer: &[u8], idx: usize) -> [u8; N] {
+	buffer[idx * N..(idx + 1) * N]
+		.try_into()
+		.expect("the range has exactly N bytes")
+}
+
+fn read_u8(buffer: &[u8], idx: usize) -> f32 {
+	(f32::from(buffer[idx]) - 128.0) / 128.0
+}
+
+fn read_s16(buffer: &[u8], idx: usize) -> f32 {
+	f32::from(i16::from_ne_bytes(sample_bytes(buffer, idx))) / 32_768.0
+}
+
+fn read_s32(buffer: &[u8], idx: usize) -> f32 {
+	#[allow(clippy::cast_possible_truncation)]
+	{
+		// SAFETY: it's only a loudness level, we don't care about the precision lost here
+		(f64::from(i32::from_ne_bytes(sample_bytes(buffer, idx))) / 2_147_483_648.0) as f32
+	}
+}
+
+fn read_f32(buffer: &[u8], idx: usize) -> f32 {
+	f32::from_ne_bytes(sample_bytes(buffer, idx))
+}
+
+fn read_f64(buffer: &[u8], idx: usize) -> f32 {
+	#[allow(clippy::cast_possible_truncation)]
+	{
+		// SAFETY: it's only a loudness level, we don't care about the precision lost here
+		f64::from_ne_bytes(sample_bytes(buffer, idx)) as f32
+	}
+}
+
+/// Renders the loudness levels as vertical bars, mirrored around the horizontal center,
+/// over a transparent background with half the height of its width
+fn render_waveform(levels: &[f32], size: u32) -> Result<DynamicImage, Error> {
+	let (width, height) = (size, size / 2);
+	let center = height / 2;
+
+	let bars_count = usize::try_from(width / (WAVEFORM_BAR_WIDTH + WAVEFORM_BAR_GAP))?.max(1);
+
+	// Each bar shows the loudest slice among the ones it covers
+	let bars = levels
+		.chunks(levels.len().div_ceil(bars_count).max(1))
+		.map(|chunk| chunk.iter().copied().fold(0.0, f32::max))
+		.collect::<Vec<_>>();
+
+	// Normalizing by the loudest bar, so quiet recordings still have a visible waveform
+	let loudest = bars.iter().copied().fold(f32::EPSILON, f32::max);
+
+	let mut image = RgbaImage::new(width, height);
+
+	for (bar_idx, level) in (0..).zip(bars) {
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		// SAFETY: the level is between 0.0 and 1.0, so the result fits between 0 and `center`
+		let half_height = ((f64::from(level / loudest) * f64::from(center)) as u32).max(1);
+
+		let x_start = bar_idx * (WAVEFORM_BAR_WIDTH + WAVEFORM_BAR_GAP);
+		for x in x_start..(x_start + WAVEFORM_BAR_WIDTH).min(width) {
+			for y in center.saturating_sub(half_height)..(center + half_height).min(height) {
+				image.put_pixel(x, y, WAVEFORM_COLOR);
+			}
+		}
+	}
+
+	Ok(DynamicImage::ImageRgba8(image))
+}
@@ -262,6 +262,12 @@ impl FFmpegCodecContext {
 				Some(String::from_utf8_lossy(cstr.to_bytes()).to_string())
 			};
 
+		let channels = if ctx.ch_layout.nb_channels > 0 {
+			Some(ctx.ch_layout.nb_channels)
+		} else {
+			None
+		};
+
 		let sample_format = if ctx.sample_fmt == AVSampleFormat::AV_SAMPLE_FMT_NONE {
 			None
 		} else {
@@ -286,6 +292,7 @@ impl FFmpegCodecContext {
 			sample_format,
 			bit_per_sample,
 			channel_layout,
+			channels,
 		})
 	}
 
@@ -79,6 +79,21 @@ impl FFmpegFormatContext {
 		unsafe { (*(streams.offset(index))).as_mut() }
 	}
 
+	/// Encoded bytes of a picture attached to the file, like the cover art of an audio file
+	pub(crate) fn attached_picture(&self) -> Option<&[u8]> {
+		(0..self.as_ref().nb_streams)
+			.filter_map(|stream_idx| self.stream(stream_idx))
+			.find(|stream| {
+				stream.disposition & AV_DISPOSITION_ATTACHED_PIC != 0
+					&& !stream.attached_pic.data.is_null()
+					&& stream.attached_pic.size > 0
+			})
+			.and_then(|stream| {
+				let size = usize::try_from(stream.attached_pic.size).ok()?;
+				Some(unsafe { std::slice::from_raw_parts(stream.attached_pic.data, size) })
+			})
+	}
+
 	pub(crate) fn get_stream_rotation_angle(&self, index: u32) -> f64 {
 		let Some(stream) = self.stream(index) else {
 			return 0.0;
@@ -110,12 +110,14 @@ impl FrameDecoder {
 		Ok(())
 	}
 
-	pub(crate) fn seek(&mut self, seconds: i64) -> Result<(), Error> {
+	pub(crate) fn seek(&mut self, seconds: f64) -> Result<(), Error> {
 		if !self.allow_seek {
 			return Ok(());
 		}
 
-		let timestamp = i64::from(AV_TIME_BASE).checked_mul(seconds).unwrap_or(0);
+		#[allow(clippy::cast_possible_truncation)]
+		// SAFETY: float to int casts saturate and turn NaN into 0, which is fine for a timestamp
+		let timestamp = (seconds.max(0.0) * f64::from(AV_TIME_BASE)) as i64;
 
 		check_error(
 			unsafe { av_seek_frame(self.format_ctx.as_mut(), -1, timestamp, 0) },
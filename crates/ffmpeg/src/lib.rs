@@ -29,10 +29,11 @@
 
 use crate::{format_ctx::FFmpegFormatContext, frame_decoder::FrameDecoder, utils::from_path};
 
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
 use ffmpeg_sys_next::{av_log_set_level, AV_LOG_FATAL};
 
+mod audio;
 mod codec_ctx;
 mod dict;
 mod error;
@@ -43,6 +44,7 @@ pub mod model;
 mod thumbnailer;
 mod utils;
 mod video_frame;
+mod video_preview;
 
 pub use error::Error;
 pub use frame_decoder::ThumbnailSize;
@@ -97,6 +99,48 @@ pub async fn to_thumbnail(
 		.await
 }
 
+/// Helper function to generate a thumbnail file from an audio file, with its embedded cover art
+/// when available or a rendering of its waveform otherwise, fitting in `size` pixels
+pub async fn to_audio_thumbnail(
+	audio_file_path: impl AsRef<Path> + Send,
+	output_thumbnail_path: impl AsRef<Path> + Send,
+	size: u32,
+	quality: f32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	audio::generate_audio_thumbnail(audio_file_path, output_thumbnail_path, size, quality).await
+}
+
+/// Helper function to generate the previews used to scrub through a video: a thumbstrip file, a
+/// sprite sheet with `frames_count` frames evenly spread through the video laid side by side from
+/// left to right, and a short looping animated webp file showing those frames for
+/// `frame_duration` each
+pub async fn to_video_previews(
+	video_file_path: impl AsRef<Path> + Send,
+	output_thumbstrip_path: impl AsRef<Path> + Send,
+	output_preview_path: impl AsRef<Path> + Send,
+	frames_count: u32,
+	frame_size: ThumbnailSize,
+	frame_duration: Duration,
+	quality: f32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	video_preview::generate_video_previews(
+		video_file_path,
+		output_thumbstrip_path,
+		output_preview_path,
+		frames_count,
+		frame_size,
+		frame_duration,
+		quality,
+	)
+	.await
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1,4 +1,7 @@
-use crate::{frame_decoder::ThumbnailSize, Error, FrameDecoder};
+use crate::{
+	frame_decoder::{ThumbnailSize, VideoFrame},
+	Error, FrameDecoder,
+};
 
 use std::{io, ops::Deref, path::Path};
 
@@ -22,33 +25,9 @@ impl Thumbnailer {
 		video_file_path: impl AsRef<Path> + Send,
 		output_thumbnail_path: impl AsRef<Path> + Send,
 	) -> Result<(), Error> {
-		let output_thumbnail_path = output_thumbnail_path.as_ref();
-		let path = output_thumbnail_path.parent().ok_or_else(|| {
-			FileIOError::from((
-				output_thumbnail_path,
-				io::Error::new(
-					io::ErrorKind::InvalidInput,
-					"Cannot determine parent directory",
-				),
-			))
-		})?;
-
-		fs::create_dir_all(path)
-			.await
-			.map_err(|e| FileIOError::from((path, e)))?;
-
 		let webp = self.process_to_webp_bytes(video_file_path).await?;
-		let mut file = fs::File::create(output_thumbnail_path)
-			.await
-			.map_err(|e: io::Error| FileIOError::from((output_thumbnail_path, e)))?;
-
-		file.write_all(&webp)
-			.await
-			.map_err(|e| FileIOError::from((output_thumbnail_path, e)))?;
 
-		file.sync_all()
-			.await
-			.map_err(|e| FileIOError::from((output_thumbnail_path, e)).into())
+		write_webp(output_thumbnail_path.as_ref(), &webp).await
 	}
 
 	/// Processes an video input file and returns a webp encoded thumbnail as bytes
@@ -80,13 +59,7 @@ impl Thumbnailer {
 						.get_duration_secs()
 						.ok_or(Error::NoVideoDuration)
 						.and_then(|duration| {
-							decoder.seek(
-								#[allow(clippy::cast_possible_truncation)]
-								{
-									// This conversion is ok because we don't worry much about precision here
-									(duration * f64::from(seek_percentage)).round() as i64
-								},
-							)
+							decoder.seek((duration * f64::from(seek_percentage)).round())
 						});
 
 					if let Err(err) = result {
@@ -103,24 +76,10 @@ impl Thumbnailer {
 					}
 				}
 
-				let video_frame =
-					decoder.get_scaled_video_frame(Some(size), maintain_aspect_ratio)?;
-
-				let mut image = DynamicImage::ImageRgb8(
-					RgbImage::from_raw(video_frame.width, video_frame.height, video_frame.data)
-						.ok_or(Error::CorruptVideo(video_file_path.into_boxed_path()))?,
-				);
-
-				let image = if video_frame.rotation < -135.0 {
-					imageops::rotate180_in_place(&mut image);
-					image
-				} else if video_frame.rotation > 45.0 && video_frame.rotation < 135.0 {
-					image.rotate270()
-				} else if video_frame.rotation < -45.0 && video_frame.rotation > -135.0 {
-					image.rotate90()
-				} else {
-					image
-				};
+				let image = frame_to_image(
+					decoder.get_scaled_video_frame(Some(size), maintain_aspect_ratio)?,
+					&video_file_path,
+				)?;
 
 				// Type WebPMemory is !Send, which makes the Future in this function !Send,
 				// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
@@ -136,6 +95,62 @@ impl Thumbnailer {
 	}
 }
 
+/// Converts a decoded frame to an image, applying the rotation from the video stream metadata
+pub(crate) fn frame_to_image(
+	VideoFrame {
+		data,
+		width,
+		height,
+		rotation,
+	}: VideoFrame,
+	video_file_path: &Path,
+) -> Result<DynamicImage, Error> {
+	let mut image = DynamicImage::ImageRgb8(
+		RgbImage::from_raw(width, height, data)
+			.ok_or_else(|| Error::CorruptVideo(video_file_path.into()))?,
+	);
+
+	Ok(if rotation < -135.0 {
+		imageops::rotate180_in_place(&mut image);
+		image
+	} else if rotation > 45.0 && rotation < 135.0 {
+		image.rotate270()
+	} else if rotation < -45.0 && rotation > -135.0 {
+		image.rotate90()
+	} else {
+		image
+	})
+}
+
+/// Writes webp encoded bytes to the output path, creating its parent directory if needed
+pub(crate) async fn write_webp(output_path: &Path, webp: &[u8]) -> Result<(), Error> {
+	let path = output_path.parent().ok_or_else(|| {
+		FileIOError::from((
+			output_path,
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"Cannot determine parent directory",
+			),
+		))
+	})?;
+
+	fs::create_dir_all(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	let mut file = fs::File::create(output_path)
+		.await
+		.map_err(|e: io::Error| FileIOError::from((output_path, e)))?;
+
+	file.write_all(webp)
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)))?;
+
+	file.sync_all()
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)).into())
+}
+
 /// `ThumbnailerBuilder` struct holds data to build a `Thumbnailer` struct, exposing many methods
 /// to configure how a thumbnail must be generated.
 #[derive(Debug, Clone)]
@@ -0,0 +1,150 @@
+use crate::{
+	frame_decoder::ThumbnailSize,
+	thumbnailer::{frame_to_image, write_webp},
+	Error, FrameDecoder,
+};
+
+use std::{ops::Deref, path::Path, time::Duration};
+
+use image::{imageops, DynamicImage, GenericImageView, RgbImage};
+use tokio::task::spawn_blocking;
+use webp::{AnimEncoder, AnimFrame, Encoder, WebPConfig};
+
+/// Decodes `frames_count` frames evenly spread through the video. Each frame is taken from the
+/// middle of its slice of the video, so we skip the usual black frames at the very start and end.
+fn sample_frames(
+	video_file_path: &Path,
+	frames_count: u32,
+	size: ThumbnailSize,
+) -> Result<Vec<DynamicImage>, Error> {
+	// Embedded cover art is a single picture, useless to preview the video contents
+	let mut decoder = FrameDecoder::new(video_file_path, true, false)?;
+
+	// We actually have to decode a frame to get some metadata before we can start decoding for real
+	decoder.decode_video_frame()?;
+
+	let duration = decoder.get_duration_secs().ok_or(Error::NoVideoDuration)?;
+
+	let mut frames = Vec::new();
+
+	for idx in 0..frames_count {
+		// Not rounding to whole seconds, otherwise short videos would repeat the same frames
+		decoder.seek(duration * (f64::from(idx) + 0.5) / f64::from(frames_count))?;
+
+		let mut frame = frame_to_image(
+			decoder.get_scaled_video_frame(Some(size), true)?,
+			video_file_path,
+		)?;
+
+		// Streams can change resolution midway, but all frames must share the first one's size
+		if let Some(first) = frames.first().map(DynamicImage::dimensions) {
+			if frame.dimensions() != first {
+				frame = frame.resize_exact(first.0, first.1, imageops::FilterType::Triangle);
+			}
+		}
+
+		frames.push(frame);
+	}
+
+	Ok(frames)
+}
+
+/// Lays the frames side by side, from left to right, in a single image
+fn into_sprite_sheet(frames: &[DynamicImage]) -> Result<DynamicImage, Error> {
+	let (width, height) = frames.first().map_or((0, 0), DynamicImage::dimensions);
+
+	let mut sheet = RgbImage::new(width * u32::try_from(frames.len())?, height);
+	let mut x = 0;
+	for frame in frames {
+		imageops::replace(&mut sheet, &frame.to_rgb8(), x, 0);
+		x += i64::from(width);
+	}
+
+	Ok(DynamicImage::ImageRgb8(sheet))
+}
+
+fn validate(frames_count: u32, quality: f32) -> Result<(), Error> {
+	if frames_count == 0 {
+		return Err(Error::InvalidFramesCount(frames_count));
+	}
+
+	if !(0.0..=100.0).contains(&quality) {
+		return Err(Error::InvalidQuality(quality));
+	}
+
+	Ok(())
+}
+
+/// Generates both a thumbstrip, a sprite sheet with the frames side by side, and a short looping
+/// animated webp, showing each frame for `frame_duration`. They're built from the same
+/// `frames_count` frames evenly spread through the video, so it's only decoded once.
+pub(crate) async fn generate_video_previews(
+	video_file_path: impl AsRef<Path> + Send,
+	thumbstrip_path: impl AsRef<Path> + Send,
+	animated_preview_path: impl AsRef<Path> + Send,
+	frames_count: u32,
+	frame_size: ThumbnailSize,
+	frame_duration: Duration,
+	quality: f32,
+) -> Result<(), Error> {
+	validate(frames_count, quality)?;
+
+	let frame_duration_ms = i32::try_from(frame_duration.as_millis())?;
+
+	let (thumbstrip, animated_preview) = spawn_blocking({
+		let video_file_path = video_file_path.as_ref().to_path_buf();
+		move || -> Result<(Vec<u8>, Vec<u8>), Error> {
+			let frames = sample_frames(&video_file_path, frames_count, frame_size)?;
+
+			// Type WebPMemory is !Send, which makes the Future in this function !Send,
+			// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
+			// which implies on a unwanted clone...
+			let thumbstrip = Encoder::from_image(&into_sprite_sheet(&frames)?)
+				.expect("Should not fail as the underlining DynamicImage is an RgbImage")
+				.encode(quality)
+				.deref()
+				.to_vec();
+
+			Ok((
+				thumbstrip,
+				encode_animation(&frames, frame_duration_ms, quality)?,
+			))
+		}
+	})
+	.await??;
+
+	write_webp(thumbstrip_path.as_ref(), &thumbstrip).await?;
+	write_webp(animated_preview_path.as_ref(), &animated_preview).await
+}
+
+fn encode_animation(
+	frames: &[DynamicImage],
+	frame_duration_ms: i32,
+	quality: f32,
+) -> Result<Vec<u8>, Error> {
+	let (width, height) = frames.first().map_or((0, 0), DynamicImage::dimensions);
+
+	let mut config = WebPConfig::new().map_err(|()| {
+		Error::AnimatedWebPEncoding("failed to instantiate webp config".to_string())
+	})?;
+	config.lossless = 0;
+	config.quality = quality;
+
+	let mut encoder = AnimEncoder::new(width, height, &config);
+	// Looping forever
+	encoder.set_loop_count(0);
+
+	for (idx, frame) in frames.iter().enumerate() {
+		encoder.add_frame(
+			AnimFrame::from_image(frame, i32::try_from(idx)? * frame_duration_ms)
+				.map_err(|e| Error::AnimatedWebPEncoding(e.to_string()))?,
+		);
+	}
+
+	// Same unwanted clone as above, as `WebPMemory` is !Send
+	Ok(encoder
+		.try_encode()
+		.map_err(|e| Error::AnimatedWebPEncoding(format!("{e:?}")))?
+		.deref()
+		.to_vec())
+}
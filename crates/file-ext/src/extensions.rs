@@ -179,7 +179,7 @@ extension_category_enum! {
 
 // text file extensions
 extension_category_enum! {
-	TextExtension _ALL_TEXT_EXTENSIONS {
+	TextExtension ALL_TEXT_EXTENSIONS {
 		Txt,
 		Rtf,
 		Md,
@@ -188,7 +188,7 @@ extension_category_enum! {
 }
 // config file extensions
 extension_category_enum! {
-	ConfigExtension _ALL_CONFIG_EXTENSIONS {
+	ConfigExtension ALL_CONFIG_EXTENSIONS {
 		Ini,
 		Json,
 		Yaml,
@@ -248,7 +248,7 @@ extension_category_enum! {
 
 // code extensions
 extension_category_enum! {
-	CodeExtension _ALL_CODE_EXTENSIONS {
+	CodeExtension ALL_CODE_EXTENSIONS {
 		// AppleScript
 		Scpt,
 		Scptd,
@@ -563,4 +563,19 @@ mod test {
 			Some(Extension::Audio(AudioExtension::Wv))
 		);
 	}
+
+	#[test]
+	fn extension_from_magic_bytes() {
+		assert_eq!(
+			Extension::from_magic_bytes(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00]),
+			Some(Extension::Image(ImageExtension::Png))
+		);
+		assert_eq!(
+			Extension::from_magic_bytes(&[0x50, 0x4B, 0x03, 0x04, 0x14, 0x00]),
+			Some(Extension::Archive(ArchiveExtension::Zip))
+		);
+		// Single byte magic bytes, like the ones from MPEG transport streams, are ignored
+		assert_eq!(Extension::from_magic_bytes(b"Good morning"), None);
+		assert_eq!(Extension::from_magic_bytes(&[]), None);
+	}
 }
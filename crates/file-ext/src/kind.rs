@@ -3,7 +3,7 @@ use specta::Type;
 use strum_macros::{Display, EnumIter};
 // Note: The order of this enum should never change, and always be kept in sync with `packages/client/src/utils/objectKind.ts`
 #[repr(i32)]
-#[derive(Debug, Clone, Display, Copy, EnumIter, Type, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Display, Copy, EnumIter, Type, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub enum ObjectKind {
 	/// A file that can not be identified by the indexer
 	Unknown = 0,
@@ -1,6 +1,11 @@
 #![allow(dead_code)]
 
-use crate::extensions::{CodeExtension, Extension, VideoExtension};
+use crate::extensions::{
+	CodeExtension, Extension, VideoExtension, ALL_AUDIO_EXTENSIONS, ALL_DOCUMENT_EXTENSIONS,
+	ALL_IMAGE_EXTENSIONS, ALL_VIDEO_EXTENSIONS, _ALL_ARCHIVE_EXTENSIONS, _ALL_BOOK_EXTENSIONS,
+	_ALL_DATABASE_EXTENSIONS, _ALL_ENCRYPTED_EXTENSIONS, _ALL_EXECUTABLE_EXTENSIONS,
+	_ALL_FONT_EXTENSIONS, _ALL_MESH_EXTENSIONS,
+};
 use std::{ffi::OsStr, io::SeekFrom, path::Path};
 
 use tokio::{
@@ -172,7 +177,114 @@ pub async fn verify_magic_bytes<T: MagicBytes>(ext: T, file: &mut File) -> Optio
 	None
 }
 
+/// How many bytes from the start of a file are needed to check any of the known magic bytes
+pub const MAGIC_BYTES_HEADER_LEN: usize = 64;
+
+fn has_magic_bytes_meta(ext: &impl MagicBytes) -> bool {
+	ext.magic_bytes_meta().iter().any(|meta| meta.length > 0)
+}
+
+async fn check_magic_bytes<T: MagicBytes + Copy>(ext: T, file: &mut File) -> Option<bool> {
+	if has_magic_bytes_meta(&ext) {
+		Some(verify_magic_bytes(ext, file).await.is_some())
+	} else {
+		None
+	}
+}
+
+fn check_header_magic_bytes<T: MagicBytes + Copy>(ext: T, header: &[u8]) -> Option<bool> {
+	has_magic_bytes_meta(&ext).then(|| {
+		ext.magic_bytes_meta().iter().any(|meta| {
+			header
+				.get(meta.offset..meta.offset + meta.length)
+				.map_or(false, |buf| ext.has_magic_bytes(buf))
+		})
+	})
+}
+
+fn find_by_magic_bytes<T: MagicBytes + Copy>(candidates: &[T], header: &[u8]) -> Option<T> {
+	candidates.iter().copied().find(|ext| {
+		ext.magic_bytes_meta()
+			.iter()
+			// a single byte matches way too many unrelated files
+			.filter(|meta| meta.length > 1)
+			.any(|meta| {
+				header
+					.get(meta.offset..meta.offset + meta.length)
+					.map_or(false, |buf| ext.has_magic_bytes(buf))
+			})
+	})
+}
+
 impl Extension {
+	/// Checks if the file starts with the magic bytes expected for this extension, returning
+	/// `None` if this extension doesn't have any known magic bytes
+	pub async fn check_magic_bytes(&self, file: &mut File) -> Option<bool> {
+		match *self {
+			Self::Document(x) => check_magic_bytes(x, file).await,
+			Self::Video(x) => check_magic_bytes(x, file).await,
+			Self::Image(x) => check_magic_bytes(x, file).await,
+			Self::Audio(x) => check_magic_bytes(x, file).await,
+			Self::Archive(x) => check_magic_bytes(x, file).await,
+			Self::Executable(x) => check_magic_bytes(x, file).await,
+			Self::Encrypted(x) => check_magic_bytes(x, file).await,
+			Self::Font(x) => check_magic_bytes(x, file).await,
+			Self::Mesh(x) => check_magic_bytes(x, file).await,
+			Self::Database(x) => check_magic_bytes(x, file).await,
+			Self::Book(x) => check_magic_bytes(x, file).await,
+			Self::Text(_) | Self::Key(_) | Self::Code(_) | Self::Config(_) => None,
+		}
+	}
+
+	/// Same as [`Extension::check_magic_bytes`], but over the first [`MAGIC_BYTES_HEADER_LEN`]
+	/// bytes of a file already read
+	#[must_use]
+	pub fn check_header_magic_bytes(&self, header: &[u8]) -> Option<bool> {
+		match *self {
+			Self::Document(x) => check_header_magic_bytes(x, header),
+			Self::Video(x) => check_header_magic_bytes(x, header),
+			Self::Image(x) => check_header_magic_bytes(x, header),
+			Self::Audio(x) => check_header_magic_bytes(x, header),
+			Self::Archive(x) => check_header_magic_bytes(x, header),
+			Self::Executable(x) => check_header_magic_bytes(x, header),
+			Self::Encrypted(x) => check_header_magic_bytes(x, header),
+			Self::Font(x) => check_header_magic_bytes(x, header),
+			Self::Mesh(x) => check_header_magic_bytes(x, header),
+			Self::Database(x) => check_header_magic_bytes(x, header),
+			Self::Book(x) => check_header_magic_bytes(x, header),
+			Self::Text(_) | Self::Key(_) | Self::Code(_) | Self::Config(_) => None,
+		}
+	}
+
+	/// If files with this extension can be checked by their magic bytes
+	#[must_use]
+	pub fn has_known_magic_bytes(&self) -> bool {
+		self.check_header_magic_bytes(&[]).is_some()
+	}
+
+	/// Guesses the extension of a file only from its first [`MAGIC_BYTES_HEADER_LEN`] bytes, for
+	/// files without an extension or with a wrong one.
+	///
+	/// Many formats share the same magic bytes, like zip based documents and archives, so this
+	/// is only reliable to tell the kind of the file and not its exact extension.
+	#[must_use]
+	pub fn from_magic_bytes(header: &[u8]) -> Option<Self> {
+		find_by_magic_bytes(ALL_IMAGE_EXTENSIONS, header)
+			.map(Self::Image)
+			.or_else(|| find_by_magic_bytes(ALL_VIDEO_EXTENSIONS, header).map(Self::Video))
+			.or_else(|| find_by_magic_bytes(ALL_AUDIO_EXTENSIONS, header).map(Self::Audio))
+			.or_else(|| find_by_magic_bytes(_ALL_ARCHIVE_EXTENSIONS, header).map(Self::Archive))
+			.or_else(|| {
+				find_by_magic_bytes(_ALL_EXECUTABLE_EXTENSIONS, header).map(Self::Executable)
+			})
+			.or_else(|| find_by_magic_bytes(ALL_DOCUMENT_EXTENSIONS, header).map(Self::Document))
+			.or_else(|| find_by_magic_bytes(_ALL_DATABASE_EXTENSIONS, header).map(Self::Database))
+			.or_else(|| find_by_magic_bytes(_ALL_FONT_EXTENSIONS, header).map(Self::Font))
+			.or_else(|| find_by_magic_bytes(_ALL_MESH_EXTENSIONS, header).map(Self::Mesh))
+			.or_else(|| find_by_magic_bytes(_ALL_BOOK_EXTENSIONS, header).map(Self::Book))
+			.or_else(|| find_by_magic_bytes(_ALL_ENCRYPTED_EXTENSIONS, header).map(Self::Encrypted))
+	}
+
 	pub async fn resolve_conflicting(
 		path: impl AsRef<Path>,
 		always_check_magic_bytes: bool,
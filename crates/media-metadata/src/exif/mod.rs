@@ -87,6 +87,8 @@ impl ExifMetadata {
 				color_profile: ColorProfile::from_reader(reader),
 				focal_length: reader.get_tag(Tag::FocalLength),
 				shutter_speed: reader.get_tag(Tag::ShutterSpeedValue),
+				aperture: reader.get_tag_float(Tag::FNumber),
+				exposure_time: reader.get_tag_float(Tag::ExposureTime),
 				flash: Flash::from_reader(reader),
 				orientation: Orientation::from_reader(reader).unwrap_or_default(),
 				lens_make: reader.get_tag(Tag::LensMake),
@@ -130,6 +132,10 @@ pub struct CameraData {
 	pub color_profile: Option<ColorProfile>,
 	pub focal_length: Option<f64>,
 	pub shutter_speed: Option<f64>,
+	/// The f-number, like `2.8` for f/2.8
+	pub aperture: Option<f64>,
+	/// In seconds, like `0.004` for 1/250
+	pub exposure_time: Option<f64>,
 	pub flash: Option<Flash>,
 	pub orientation: Orientation,
 	pub lens_make: Option<String>,
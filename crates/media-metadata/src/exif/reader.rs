@@ -7,7 +7,7 @@ use std::{
 	str::FromStr,
 };
 
-use exif::{Exif, In, Tag};
+use exif::{Exif, In, Rational, SRational, Tag, Value};
 use sd_utils::error::FileIOError;
 
 /// An [`ExifReader`]. This can get exif tags from images (either files or slices).
@@ -53,4 +53,16 @@ impl ExifReader {
 			.map(|x| x.value.get_uint(0))
 			.unwrap_or_default()
 	}
+
+	/// Gets rational tags as floats, as their display values come with units and formatting (e.g. `1/250`)
+	pub(crate) fn get_tag_float(&self, tag: Tag) -> Option<f64> {
+		self.0
+			.get_field(tag, In::PRIMARY)
+			.and_then(|x| match &x.value {
+				Value::Rational(v) => v.first().map(Rational::to_f64),
+				Value::SRational(v) => v.first().map(SRational::to_f64),
+				_ => None,
+			})
+			.filter(|v| v.is_finite())
+	}
 }
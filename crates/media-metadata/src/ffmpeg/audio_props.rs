@@ -9,4 +9,5 @@ pub struct AudioProps {
 	pub sample_format: Option<String>,
 	pub bit_per_sample: Option<i32>,
 	pub channel_layout: Option<String>,
+	pub channels: Option<i32>,
 }
@@ -202,6 +202,7 @@ mod extract_data {
 				sample_format,
 				bit_per_sample,
 				channel_layout,
+				channels,
 			}: FFmpegAudioProps,
 		) -> Self {
 			Self {
@@ -211,6 +212,7 @@ mod extract_data {
 				sample_format,
 				bit_per_sample,
 				channel_layout,
+				channels,
 			}
 		}
 	}
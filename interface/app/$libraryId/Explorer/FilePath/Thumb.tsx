@@ -25,7 +25,12 @@ import { Image } from './Image';
 import LayeredFileIcon from './LayeredFileIcon';
 import { Original } from './Original';
 import { useFrame } from './useFrame';
-import { useBlackBars, useSize } from './utils';
+import {
+	getVideoPreviewUrl,
+	THUMBSTRIP_FRAMES_COUNT,
+	useBlackBars,
+	useSize
+} from './utils';
 
 export type ThumbType = 'original' | 'thumbnail' | 'icon';
 
@@ -40,6 +45,7 @@ interface ThumbnailProps extends ComponentProps<'img'> {
 	blackBars?: boolean;
 	blackBarsSize?: number;
 	videoExtension?: string;
+	thumbstripSrc?: string;
 }
 
 const Thumbnail = memo(
@@ -49,6 +55,7 @@ const Thumbnail = memo(
 				blackBars,
 				blackBarsSize,
 				videoExtension: extension,
+				thumbstripSrc,
 				cover,
 				className,
 				style,
@@ -63,21 +70,57 @@ const Thumbnail = memo(
 			);
 
 			const size = useSize(ref);
+			const [scrubFrame, setScrubFrame] = useState<number | null>(null);
 
 			const { style: blackBarsStyle } = useBlackBars(ref, size, {
 				size: blackBarsSize,
 				disabled: !blackBars
 			});
 
+			// Cropped thumbnails don't share the thumbstrip frames aspect ratio
+			const scrubbable = thumbstripSrc != null && !cover;
+
 			return (
 				<>
 					<Image
 						{...props}
 						className={clsx(className, blackBars && size.width === 0 && 'invisible')}
 						style={{ ...style, ...blackBarsStyle }}
+						onMouseMove={(e) => {
+							props.onMouseMove?.(e);
+							if (!scrubbable || size.width === 0) return;
+							const { left } = e.currentTarget.getBoundingClientRect();
+							const progress = Math.max(0, e.clientX - left) / size.width;
+							setScrubFrame(
+								Math.min(
+									THUMBSTRIP_FRAMES_COUNT - 1,
+									Math.floor(progress * THUMBSTRIP_FRAMES_COUNT)
+								)
+							);
+						}}
+						onMouseLeave={(e) => {
+							props.onMouseLeave?.(e);
+							setScrubFrame(null);
+						}}
 						ref={ref}
 					/>
 
+					{/* A missing thumbstrip just leaves this transparent, showing the thumbnail below */}
+					{scrubbable && scrubFrame != null && (
+						<div
+							style={{
+								width: size.width,
+								height: size.height,
+								backgroundImage: `url("${thumbstripSrc}")`,
+								backgroundSize: `${THUMBSTRIP_FRAMES_COUNT * 100}% 100%`,
+								backgroundPosition: `${
+									(scrubFrame / (THUMBSTRIP_FRAMES_COUNT - 1)) * 100
+								}% 0`
+							}}
+							className="pointer-events-none absolute bg-no-repeat"
+						/>
+					)}
+
 					{(cover || size.width > 80) && extension && (
 						<div
 							style={{
@@ -144,6 +187,7 @@ const Thumb = memo(
 				mediaControls,
 				blackBarsSize,
 				videoExtension,
+				thumbstripSrc,
 				frameClassName,
 				isSidebarPreview,
 				onLoad,
@@ -212,6 +256,7 @@ const Thumb = memo(
 							crossOrigin="anonymous" // Here it is ok, because it is not a react attr
 							blackBarsSize={blackBarsSize}
 							videoExtension={videoExtension}
+							thumbstripSrc={thumbstripSrc}
 						/>
 					);
 					break;
@@ -430,6 +475,11 @@ export const FileThumb = memo(
 								? itemData.extension
 								: undefined
 						}
+						thumbstripSrc={
+							thumbType === 'thumbnail' && src && itemData.kind === 'Video'
+								? getVideoPreviewUrl(src, 'thumbstrip')
+								: undefined
+						}
 						isSidebarPreview={props.isSidebarPreview ?? false}
 					/>
 				</ErrorBarrier>
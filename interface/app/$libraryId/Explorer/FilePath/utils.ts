@@ -1,6 +1,18 @@
 import { useMemo, useRef, useState, type CSSProperties, type RefObject } from 'react';
 import { useCallbackToWatchResize } from '~/hooks';
 
+/**
+ * How many frames a video thumbstrip has, must match `THUMBSTRIP_FRAMES_COUNT` in the media processor
+ */
+export const THUMBSTRIP_FRAMES_COUNT = 10;
+
+/**
+ * Video thumbstrips and animated previews are stored next to the video thumbnail, as `<cas_id>_<suffix>.webp`
+ */
+export function getVideoPreviewUrl(thumbnailUrl: string, suffix: 'thumbstrip' | 'preview') {
+	return thumbnailUrl.replace(/\.webp(?=\?|$)/, `_${suffix}.webp`);
+}
+
 export function useSize(ref: RefObject<Element>) {
 	const [size, setSize] = useState({ width: 0, height: 0 });
 
@@ -249,10 +249,12 @@ export const SingleItemMetadata = ({ item }: { item: ExplorerItem }) => {
 
 	const pubId = objectData != null ? uniqueId(objectData) : null;
 
-	let extension, integrityChecksum;
+	let extension, detectedExtension, integrityChecksum;
 
 	if (filePathData != null) {
 		extension = filePathData.extension;
+		detectedExtension =
+			'detected_extension' in filePathData ? filePathData.detected_extension : null;
 		integrityChecksum =
 			'integrity_checksum' in filePathData ? filePathData.integrity_checksum : null;
 	}
@@ -371,6 +373,12 @@ export const SingleItemMetadata = ({ item }: { item: ExplorerItem }) => {
 
 				{extension && <InfoPill>{extension}</InfoPill>}
 
+				{detectedExtension && (
+					<Tooltip label={t('detected_extension_tooltip')}>
+						<InfoPill>{detectedExtension}</InfoPill>
+					</Tooltip>
+				)}
+
 				{/* {labels.data?.map((label) => (
 					<InfoPill key={label.id} className="truncate !text-white">
 						{label.name}
@@ -34,7 +34,7 @@ export default (props: PropsWithChildren) => {
 
 	const generateThumbsForLocation = useLibraryMutation('jobs.generateThumbsForLocation');
 	// const generateLabelsForLocation = useLibraryMutation('jobs.generateLabelsForLocation');
-	const objectValidator = useLibraryMutation('jobs.objectValidator');
+	const verifyIntegrity = useLibraryMutation('jobs.verifyIntegrity');
 	const rescanLocation = useLibraryMutation('locations.subPathRescan');
 	const createFolder = useLibraryMutation(['files.createFolder'], {
 		onError: (e) => {
@@ -275,7 +275,7 @@ export default (props: PropsWithChildren) => {
 						<CM.Item
 							onClick={async () => {
 								try {
-									objectValidator.mutateAsync({
+									verifyIntegrity.mutateAsync({
 										id: parent.location.id,
 										path: currentPath ?? '/'
 									});
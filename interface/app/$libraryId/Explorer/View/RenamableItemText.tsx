@@ -93,6 +93,8 @@ export const RenamableItemText = ({
 							generate_preview_media: null,
 							sync_preview_media: null,
 							hidden: null,
+							follow_symlinks: null,
+							io_budget: null,
 							indexer_rules_ids: []
 						});
 
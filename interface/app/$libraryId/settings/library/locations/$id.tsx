@@ -37,9 +37,18 @@ const schema = z.object({
 	indexerRulesIds: z.array(z.number()),
 	locationType: z.string(),
 	syncPreviewMedia: z.boolean().nullable(),
-	generatePreviewMedia: z.boolean().nullable()
+	generatePreviewMedia: z.boolean().nullable(),
+	followSymlinks: z.boolean().nullable(),
+	indexArchiveContents: z.boolean().nullable(),
+	ioMaxFilesPerSecond: z.number().int().min(1).nullable(),
+	ioMaxHashedMegabytesPerSecond: z.number().int().min(1).max(4095).nullable()
 });
 
+const MEGABYTE = 1024 * 1024;
+
+// Empty inputs mean unlimited
+const optionalNumber = (value: string) => (value === '' ? null : Number(value));
+
 export const Component = () => {
 	return (
 		<Suspense fallback={<div></div>}>
@@ -68,7 +77,14 @@ const EditLocationForm = () => {
 			path: locationData?.path ?? '',
 			hidden: locationData?.hidden ?? false,
 			syncPreviewMedia: locationData?.sync_preview_media ?? false,
-			generatePreviewMedia: locationData?.generate_preview_media ?? false
+			generatePreviewMedia: locationData?.generate_preview_media ?? false,
+			followSymlinks: locationData?.follow_symlinks ?? true,
+			indexArchiveContents: locationData?.index_archive_contents ?? false,
+			ioMaxFilesPerSecond: locationData?.io_max_files_per_second ?? null,
+			ioMaxHashedMegabytesPerSecond:
+				locationData?.io_max_hashed_bytes_per_second != null
+					? Math.floor(locationData.io_max_hashed_bytes_per_second / MEGABYTE)
+					: null
 		}
 	});
 
@@ -90,7 +106,16 @@ const EditLocationForm = () => {
 			hidden: data.hidden,
 			indexer_rules_ids: data.indexerRulesIds,
 			sync_preview_media: data.syncPreviewMedia,
-			generate_preview_media: data.generatePreviewMedia
+			generate_preview_media: data.generatePreviewMedia,
+			follow_symlinks: data.followSymlinks,
+			index_archive_contents: data.indexArchiveContents,
+			io_budget: {
+				max_files_per_second: data.ioMaxFilesPerSecond,
+				max_hashed_bytes_per_second:
+					data.ioMaxHashedMegabytesPerSecond != null
+						? data.ioMaxHashedMegabytesPerSecond * MEGABYTE
+						: null
+			}
 		})
 	);
 
@@ -170,7 +195,47 @@ const EditLocationForm = () => {
 						</Label>
 						<SwitchField {...form.register('hidden')} size="sm" />
 					</ToggleSection>
+					<ToggleSection>
+						<Label className="grow">
+							{t('follow_symlinks')}{' '}
+							<Tooltip label={t('follow_symlinks_label')}>
+								<Info className="inline" />
+							</Tooltip>
+						</Label>
+						<SwitchField {...form.register('followSymlinks')} size="sm" />
+					</ToggleSection>
+					<ToggleSection>
+						<Label className="grow">
+							{t('index_archive_contents')}{' '}
+							<Tooltip label={t('index_archive_contents_label')}>
+								<Info className="inline" />
+							</Tooltip>
+						</Label>
+						<SwitchField {...form.register('indexArchiveContents')} size="sm" />
+					</ToggleSection>
+				</div>
+				<Divider />
+				<div className="flex space-x-4">
+					<FlexCol>
+						<InputField
+							type="number"
+							label={t('io_max_files_per_second')}
+							placeholder={t('unlimited')}
+							{...form.register('ioMaxFilesPerSecond', { setValueAs: optionalNumber })}
+						/>
+					</FlexCol>
+					<FlexCol>
+						<InputField
+							type="number"
+							label={t('io_max_hashed_megabytes_per_second')}
+							placeholder={t('unlimited')}
+							{...form.register('ioMaxHashedMegabytesPerSecond', {
+								setValueAs: optionalNumber
+							})}
+						/>
+					</FlexCol>
 				</div>
+				<InfoText>{t('location_io_budget_info')}</InfoText>
 				<Divider />
 				<Controller
 					name="indexerRulesIds"
@@ -193,7 +258,8 @@ const EditLocationForm = () => {
 								onClick={() =>
 									fullRescan.mutate({
 										location_id: locationId,
-										reidentify_objects: true
+										reidentify_objects: true,
+										paranoid: true
 									})
 								}
 								size="sm"
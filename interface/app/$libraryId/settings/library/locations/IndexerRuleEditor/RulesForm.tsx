@@ -22,7 +22,16 @@ const ruleKinds: UnionToTuple<RuleKind> = [
 	'RejectFilesByGlob',
 	'AcceptIfChildrenDirectoriesArePresent',
 	'RejectIfChildrenDirectoriesArePresent',
-	'IgnoredByGit'
+	'IgnoredByGit',
+	'RejectFilesLargerThan',
+	'AcceptFilesSmallerThan',
+	'AcceptIfExtendedAttributesArePresent',
+	'RejectIfExtendedAttributesArePresent',
+	'RejectFilesModifiedWithin',
+	'RejectFilesNotModifiedWithin',
+	'IgnoredBySpacedrive',
+	'AcceptByObjectKind',
+	'RejectByObjectKind'
 ];
 const ruleKindEnum = z.enum(ruleKinds);
 
@@ -21,6 +21,7 @@ export type Procedures = {
         { key: "files.getConvertibleImageExtensions", input: never, result: string[] } | 
         { key: "files.getMediaData", input: LibraryArgs<number>, result: MediaData } | 
         { key: "files.getPath", input: LibraryArgs<number>, result: string | null } | 
+        { key: "files.resolveAlias", input: LibraryArgs<number>, result: ResolvedAlias } | 
         { key: "invalidation.test-invalidate", input: never, result: number } | 
         { key: "jobs.isActive", input: LibraryArgs<null>, result: boolean } | 
         { key: "jobs.reports", input: LibraryArgs<null>, result: JobGroup[] } | 
@@ -35,9 +36,12 @@ export type Procedures = {
         { key: "library.statistics", input: LibraryArgs<null>, result: StatisticsResponse } | 
         { key: "locations.get", input: LibraryArgs<number>, result: Location | null } | 
         { key: "locations.getWithRules", input: LibraryArgs<number>, result: LocationWithIndexerRule | null } | 
+        { key: "locations.indexer_rules.explain", input: LibraryArgs<ExplainPathArgs>, result: IndexerRulesExplanation } | 
+        { key: "locations.indexer_rules.export", input: LibraryArgs<number[]>, result: string } | 
         { key: "locations.indexer_rules.get", input: LibraryArgs<number>, result: IndexerRule } | 
         { key: "locations.indexer_rules.list", input: LibraryArgs<null>, result: IndexerRule[] } | 
         { key: "locations.indexer_rules.listForLocation", input: LibraryArgs<number>, result: IndexerRule[] } | 
+        { key: "locations.indexer_rules.preview", input: LibraryArgs<PreviewRulesArgs>, result: IndexerRulesPreview } | 
         { key: "locations.list", input: LibraryArgs<null>, result: Location[] } | 
         { key: "locations.systemLocations", input: never, result: SystemLocations } | 
         { key: "models.image_detection.list", input: never, result: string[] } | 
@@ -49,12 +53,17 @@ export type Procedures = {
         { key: "p2p.listeners", input: never, result: Listeners } | 
         { key: "p2p.state", input: never, result: JsonValue } | 
         { key: "preferences.get", input: LibraryArgs<null>, result: LibraryPreferences } | 
+        { key: "search.allLibraries.objects", input: AllLibrariesSearchArgs<AllLibrariesObjectOrder>, result: AllLibrariesSearchData } | 
+        { key: "search.allLibraries.paths", input: AllLibrariesSearchArgs<AllLibrariesPathOrder>, result: AllLibrariesSearchData } | 
+        { key: "search.duplicates", input: LibraryArgs<DuplicatesArgs>, result: DuplicatesData } | 
         { key: "search.objects", input: LibraryArgs<ObjectSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.objectsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
+        { key: "search.parse", input: LibraryArgs<string>, result: ParsedQuery } | 
         { key: "search.paths", input: LibraryArgs<FilePathSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.pathsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
         { key: "search.saved.get", input: LibraryArgs<number>, result: SavedSearch | null } | 
         { key: "search.saved.list", input: LibraryArgs<null>, result: SavedSearch[] } | 
+        { key: "search.similarImages", input: LibraryArgs<SimilarImagesArgs>, result: SimilarImage[] } | 
         { key: "sync.enabled", input: LibraryArgs<null>, result: boolean } | 
         { key: "tags.get", input: LibraryArgs<number>, result: Tag | null } | 
         { key: "tags.getForObject", input: LibraryArgs<number>, result: Tag[] } | 
@@ -107,6 +116,8 @@ export type Procedures = {
         { key: "jobs.objectValidator", input: LibraryArgs<ObjectValidatorArgs>, result: null } | 
         { key: "jobs.pause", input: LibraryArgs<string>, result: null } | 
         { key: "jobs.resume", input: LibraryArgs<string>, result: null } | 
+        { key: "jobs.setIoBudget", input: LibraryArgs<SetIoBudgetArgs>, result: null } | 
+        { key: "jobs.verifyIntegrity", input: LibraryArgs<VerifyIntegrityArgs>, result: string } | 
         { key: "labels.delete", input: LibraryArgs<number>, result: null } | 
         { key: "library.create", input: CreateLibraryArgs, result: LibraryConfigWrapped } | 
         { key: "library.delete", input: string, result: null } | 
@@ -118,6 +129,7 @@ export type Procedures = {
         { key: "locations.fullRescan", input: LibraryArgs<FullRescanArgs>, result: string | null } | 
         { key: "locations.indexer_rules.create", input: LibraryArgs<IndexerRuleCreateArgs>, result: null } | 
         { key: "locations.indexer_rules.delete", input: LibraryArgs<number>, result: null } | 
+        { key: "locations.indexer_rules.import", input: LibraryArgs<ImportRulesArgs>, result: ImportedRules } | 
         { key: "locations.relink", input: LibraryArgs<string>, result: number } | 
         { key: "locations.subPathRescan", input: LibraryArgs<RescanArgs>, result: string | null } | 
         { key: "locations.update", input: LibraryArgs<LocationUpdateArgs>, result: null } | 
@@ -150,6 +162,11 @@ export type Procedures = {
         { key: "notifications.listen", input: never, result: Notification } | 
         { key: "p2p.events", input: never, result: P2PEvent } | 
         { key: "search.ephemeralPaths", input: LibraryArgs<EphemeralPathSearchArgs>, result: { entries: ExplorerItem[]; errors: Error[] } } | 
+        { key: "search.saved.subscribe", input: LibraryArgs<{ searchId: number; 
+/**
+ * Same as in `search.paths`, so only the page the client is showing is watched
+ */
+take?: number | null; orderAndPagination?: OrderAndPagination<number, FilePathOrder, FilePathCursor> | null; groupDirectories?: boolean }>, result: { added: number[]; removed: number[] } } | 
         { key: "sync.active", input: LibraryArgs<null>, result: SyncStatus }
 };
 
@@ -158,9 +175,36 @@ export type Procedures = {
  */
 export type AccessToken = string
 
+export type AllLibrariesObjectOrder = { field: "dateCreated"; value: SortOrder } | { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder }
+
+export type AllLibrariesPathOrder = { field: "name"; value: SortOrder } | { field: "sizeInBytes"; value: SortOrder } | { field: "dateCreated"; value: SortOrder } | { field: "dateModified"; value: SortOrder } | { field: "dateIndexed"; value: SortOrder }
+
+export type AllLibrariesSearchArgs<TOrder> = { take?: number | null; 
+/**
+ * Without an order, items are grouped by library
+ */
+order?: TOrder | null; 
+/**
+ * Must be used with the same `order` and `filters` as the search which returned it
+ */
+cursor?: LibraryCursor[] | null; 
+/**
+ * Ids used by filters, like locations or tags, are different on each library, so they only
+ * match items in the library they belong to
+ */
+filters?: SearchFilterArgs[] }
+
+export type AllLibrariesSearchData = { items: LibraryItem[]; cursor: LibraryCursor[] | null }
+
+/**
+ * Value used to merge the results of different libraries, `None`s come first when ascending,
+ * the same as `SQLite` does with `NULL`s
+ */
+export type AllLibrariesSortKey = { type: "unordered" } | { type: "text"; value: string | null } | { type: "int"; value: bigint | null } | { type: "date"; value: string | null }
+
 export type Args = { search?: string | null; filters?: string | null; name?: string | null; icon?: string | null; description?: string | null }
 
-export type AudioProps = { delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null }
+export type AudioProps = { delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; channels: number | null }
 
 /**
  * All of the feature flags provided by the core itself. The frontend has it's own set of feature flags!
@@ -173,9 +217,23 @@ export type Backup = ({ id: string; timestamp: string; library_id: string; libra
 
 export type BasicLibraryCreationArgs = { id: CloudLibraryPubId; name: string; description: string | null }
 
+export type BoundingBox = { minLatitude: number; maxLatitude: number; 
+/**
+ * Can be bigger than `maxLongitude` for boxes crossing the antimeridian
+ */
+minLongitude: number; maxLongitude: number }
+
 export type BuildInfo = { version: string; commit: string }
 
-export type CameraData = { device_make: string | null; device_model: string | null; color_space: string | null; color_profile: ColorProfile | null; focal_length: number | null; shutter_speed: number | null; flash: Flash | null; orientation: Orientation; lens_make: string | null; lens_model: string | null; bit_depth: number | null; zoom: number | null; iso: number | null; software: string | null; serial_number: string | null; lens_serial_number: string | null; contrast: number | null; saturation: number | null; sharpness: number | null; composite: Composite | null }
+export type CameraData = { device_make: string | null; device_model: string | null; color_space: string | null; color_profile: ColorProfile | null; focal_length: number | null; shutter_speed: number | null; 
+/**
+ * The f-number, like `2.8` for f/2.8
+ */
+aperture: number | null; 
+/**
+ * In seconds, like `0.004` for 1/250
+ */
+exposure_time: number | null; flash: Flash | null; orientation: Orientation; lens_make: string | null; lens_model: string | null; bit_depth: number | null; zoom: number | null; iso: number | null; software: string | null; serial_number: string | null; lens_serial_number: string | null; contrast: number | null; saturation: number | null; sharpness: number | null; composite: Composite | null }
 
 export type CasId = string
 
@@ -183,6 +241,16 @@ export type ChangeNodeNameArgs = { name: string | null; p2p_port: Port | null; p
 
 export type Chapter = { id: number; start: [number, number]; end: [number, number]; time_base_den: number; time_base_num: number; metadata: Metadata }
 
+export type ClassificationMethod = 
+/**
+ * The contents of the file were checked
+ */
+"MagicBytes" | 
+/**
+ * Only the extension of the file was used
+ */
+"Extension"
+
 export type CloudCreateLocationArgs = { pub_id: CloudLocationPubId; name: string; library_pub_id: CloudLibraryPubId; device_pub_id: CloudDevicePubId }
 
 export type CloudDevice = { pub_id: CloudDevicePubId; name: string; os: DeviceOS; hardware_model: HardwareModel; connection_id: string; created_at: string; updated_at: string }
@@ -257,6 +325,14 @@ export type Composite =
  */
 export type ConnectionMethod = "Relay" | "Local" | "Disconnected"
 
+export type ContentMatch = { objectId: number; 
+/**
+ * BM25 relevance, lower is better
+ */
+rank: number; snippet: ContentSnippetPart[] }
+
+export type ContentSnippetPart = { text: string; highlighted: boolean }
+
 export type ConvertImageArgs = { location_id: number; file_path_id: number; delete_src: boolean; desired_extension: ConvertibleExtension; quality_percentage: number | null }
 
 export type ConvertibleExtension = "bmp" | "dib" | "ff" | "gif" | "ico" | "jpg" | "jpeg" | "png" | "pnm" | "qoi" | "tga" | "icb" | "vda" | "vst" | "tiff" | "tif" | "hif" | "heif" | "heifs" | "heic" | "heics" | "avif" | "avci" | "avcs" | "svg" | "svgz" | "pdf" | "webp"
@@ -293,6 +369,43 @@ export type DiskType = "SSD" | "HDD" | "Removable"
 
 export type DoubleClickAction = "openFile" | "quickPreview"
 
+export type DuplicateGroup = { objectId: number; sizeInBytes: bigint; 
+/**
+ * Bytes freed by keeping a single copy, hard links to the same file are a single copy
+ */
+wastedBytes: bigint; 
+/**
+ * Only set for groups confirmed with `verifyIntegrity`
+ */
+integrityChecksum: string | null; 
+/**
+ * Paths left out with `verifyIntegrity` because they weren't checksummed yet
+ */
+unverifiedPaths: number; paths: FilePathForFrontend[] }
+
+export type DuplicatesArgs = { take?: number | null; cursor?: DuplicatesCursor | null; 
+/**
+ * Only consider copies inside these locations, all locations if empty
+ */
+locations?: number[]; 
+/**
+ * [`ObjectKind`](sd_file_ext::kind::ObjectKind)s to look for, all kinds if empty
+ */
+kinds?: number[]; minSizeInBytes?: bigint | null; 
+/**
+ * Confirms groups with the full file checksums stored by the `jobs.verifyIntegrity` job,
+ * instead of trusting the sampled `cas_id`. Paths without a checksum are left out.
+ */
+verifyIntegrity?: boolean }
+
+export type DuplicatesCursor = { wastedBytes: bigint; objectId: number }
+
+export type DuplicatesData = { groups: DuplicateGroup[]; cursor: DuplicatesCursor | null; 
+/**
+ * Totals for every page, based on `cas_id` alone as verifying everything would be too slow
+ */
+totalGroups: number; totalWastedBytes: bigint }
+
 export type EditLibraryArgs = { id: string; name: LibraryName | null; description: MaybeUndefined<string> }
 
 export type EphemeralFileCreateContextTypes = "empty" | "text"
@@ -301,7 +414,12 @@ export type EphemeralFileSystemOps = { sources: string[]; target_dir: string }
 
 export type EphemeralPathOrder = { field: "name"; value: SortOrder } | { field: "sizeInBytes"; value: SortOrder } | { field: "dateCreated"; value: SortOrder } | { field: "dateModified"; value: SortOrder }
 
-export type EphemeralPathSearchArgs = { path: string; withHiddenFiles: boolean; order?: EphemeralPathOrder | null }
+export type EphemeralPathSearchArgs = { path: string; withHiddenFiles: boolean; order?: EphemeralPathOrder | null; 
+/**
+ * Searches recursively below `path` for entries matching these filters,
+ * instead of only listing its direct children
+ */
+search?: EphemeralSearchFilters | null }
 
 export type EphemeralRenameFileArgs = { kind: EphemeralRenameKind }
 
@@ -311,6 +429,15 @@ export type EphemeralRenameMany = { from_pattern: FromPattern; to_pattern: strin
 
 export type EphemeralRenameOne = { from_path: string; to: string }
 
+/**
+ * Filters for a recursive [`search`], entries must match all of them
+ */
+export type EphemeralSearchFilters = { 
+/**
+ * How many levels below the searched path to look into, unlimited if not set
+ */
+maxDepth?: number | null; name?: TextMatch | null; extension?: InOrNotIn<string> | null; kind?: InOrNotIn<number> | null }
+
 export type Error = { code: ErrorCode; message: string }
 
 /**
@@ -318,10 +445,22 @@ export type Error = { code: ErrorCode; message: string }
  */
 export type ErrorCode = "BadRequest" | "Unauthorized" | "Forbidden" | "NotFound" | "Timeout" | "Conflict" | "PreconditionFailed" | "PayloadTooLarge" | "MethodNotSupported" | "ClientClosedRequest" | "InternalServerError"
 
+export type ExifDataFilterArgs = { cameraMake: TextMatch } | { cameraModel: TextMatch } | { lens: TextMatch } | { iso: Range<number> } | { aperture: Range<number> } | 
+/**
+ * In seconds
+ */
+{ exposureTime: Range<number> } | { mediaDate: Range<string> } | { orientation: InOrNotIn<Orientation> } | { location: BoundingBox }
+
 export type ExifDataOrder = { field: "epochTime"; value: SortOrder }
 
 export type ExifMetadata = { resolution: Resolution; date_taken: MediaDate | null; location: MediaLocation | null; camera_data: CameraData; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null }
 
+export type ExplainPathArgs = { location_id: number; 
+/**
+ * Absolute or relative to the location root
+ */
+path: string }
+
 export type ExplorerItem = { type: "Path"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: FilePathForFrontend } | { type: "Object"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: ObjectWithFilePaths } | { type: "NonIndexedPath"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: NonIndexedPathItem } | { type: "Location"; item: Location } | { type: "SpacedropPeer"; item: PeerMetadata } | { type: "Label"; thumbnails: ThumbKey[]; item: LabelWithObjects }
 
 export type ExplorerLayout = "grid" | "list" | "media"
@@ -332,7 +471,21 @@ export type FFmpegMetadata = { formats: string[]; duration: [number, number] | n
 
 export type Feedback = { message: string; emoji: number }
 
-export type FfmpegMediaAudioProps = { id: number; delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; codec_id: number }
+export type FfmpegFilterArgs = 
+/**
+ * In seconds
+ */
+{ duration: Range<number> } | 
+/**
+ * In bits per second
+ */
+{ bitRate: Range<number> } | { width: Range<number> } | { height: Range<number> } | 
+/**
+ * FFmpeg codec names, like `h264`, `hevc` or `aac`
+ */
+{ codec: InOrNotIn<string> } | { audioChannels: Range<number> } | { hasSubtitles: boolean } | { hasChapters: boolean }
+
+export type FfmpegMediaAudioProps = { id: number; delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; channels: number | null; codec_id: number }
 
 export type FfmpegMediaChapter = { chapter_id: number; start: number[]; end: number[]; time_base_den: number; time_base_num: number; title: string | null; metadata: number[] | null; ffmpeg_data_id: number }
 
@@ -340,15 +493,23 @@ export type FfmpegMediaVideoProps = { id: number; pixel_format: string | null; c
 
 export type FileCreateContextTypes = "empty" | "text"
 
-export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null }
+export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; detected_extension: string | null; archive_member_path: string | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; size_in_bytes_int: bigint | null; file_count: bigint | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null }
 
 export type FilePathCursor = { isDir: boolean; variant: FilePathCursorVariant }
 
 export type FilePathCursorVariant = "none" | { name: CursorOrderItem<string> } | { sizeInBytes: SortOrder } | { dateCreated: CursorOrderItem<string> } | { dateModified: CursorOrderItem<string> } | { dateIndexed: CursorOrderItem<string> } | { object: FilePathObjectCursor }
 
-export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean }
+export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | 
+/**
+ * File paths whose name doesn't match, from negated free text in search queries
+ */
+{ notName: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean } | { sizeInBytes: Range<bigint> } | { isDir: boolean } | 
+/**
+ * Full-text search over the content of indexed text, code and config files
+ */
+{ content: TextMatch }
 
-export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null; device_id: number | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null }
+export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; detected_extension: string | null; archive_member_path: string | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; size_in_bytes_int: bigint | null; file_count: bigint | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null; device_id: number | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null }
 
 export type FilePathObjectCursor = { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
@@ -403,7 +564,11 @@ export type FlashMode =
 
 export type FromPattern = { pattern: string; replace_all: boolean }
 
-export type FullRescanArgs = { location_id: number; reidentify_objects: boolean }
+export type FullRescanArgs = { location_id: number; reidentify_objects: boolean; 
+/**
+ * Walk through every directory, even the ones unchanged since the last scan
+ */
+paranoid?: boolean }
 
 export type GenerateThumbsForLocationArgs = { id: number; path: string; regenerate?: boolean }
 
@@ -413,6 +578,39 @@ export type HardwareModel = "Other" | "MacStudio" | "MacBookAir" | "MacBookPro"
 
 export type IdentifyUniqueFilesArgs = { id: number; path: string }
 
+/**
+ * What to do with an imported rule whose name is already taken by a rule of the library
+ */
+export type ImportConflictStrategy = "Skip" | 
+/**
+ * Appends a number to the name of the imported rule, like `Rule (2)`
+ */
+"Rename" | 
+/**
+ * Overwrites the existing rule, keeping its locations. Default rules are never replaced.
+ */
+"Replace"
+
+export type ImportRulesArgs = { 
+/**
+ * A file created by exporting indexer rules
+ */
+content: string; on_conflict: ImportConflictStrategy; 
+/**
+ * Locations which will use the imported rules from now on, they take effect on their next scan
+ */
+location_ids?: number[] }
+
+export type ImportedRules = { 
+/**
+ * Rules created or replaced by the import
+ */
+imported: number[]; 
+/**
+ * Names of the rules left untouched due to a name conflict
+ */
+skipped: string[] }
+
 export type InOrNotIn<T> = { in: T[] } | { notIn: T[] }
 
 export type IndexerRule = { id: number; pub_id: number[]; name: string | null; default: boolean | null; rules_per_kind: number[] | null; date_created: string | null; date_modified: string | null }
@@ -426,11 +624,63 @@ export type IndexerRule = { id: number; pub_id: number[]; name: string | null; d
  * 
  * In case of `RuleKind::AcceptIfChildrenDirectoriesArePresent` or `RuleKind::RejectIfChildrenDirectoriesArePresent` the
  * `parameters` field must be a vector of strings containing the names of the directories.
+ * 
+ * In case of `RuleKind::RejectFilesLargerThan` or `RuleKind::AcceptFilesSmallerThan` the
+ * `parameters` field must be a vector with a single string containing the size in bytes.
+ * 
+ * In case of `RuleKind::AcceptIfExtendedAttributesArePresent` or
+ * `RuleKind::RejectIfExtendedAttributesArePresent` the `parameters` field must be a vector of
+ * strings containing the names of the extended attributes, a name ending with `*` matches every
+ * attribute starting with it.
+ * 
+ * In case of `RuleKind::RejectFilesModifiedWithin` or `RuleKind::RejectFilesNotModifiedWithin`
+ * the `parameters` field must be a vector with a single string containing the amount of seconds.
  */
 export type IndexerRuleCreateArgs = { name: string; dry_run: boolean; rules: ([RuleKind, string[]])[] }
 
+export type IndexerRulesExplanation = { path: string; 
+/**
+ * Rules applied to the path itself, including the ones from `.gitignore` and
+ * `.spacedriveignore` files
+ */
+rules: RuleExplanation[]; decision: RulerDecision; 
+/**
+ * The closest directory to the location root which the rules reject, the indexer never walks
+ * into it so nothing inside of it gets indexed no matter what the rules say about `path`
+ */
+rejected_ancestor: string | null; 
+/**
+ * The kind object kind rules gave to `path` and how it was told
+ */
+object_kind: ObjectKindClassification | null }
+
+export type IndexerRulesPreview = { accepted: number; rejected: number; 
+/**
+ * An entry can be rejected by more than one rule at once, so these don't add up to `rejected`
+ */
+rejections_per_rule: RuleRejections[]; 
+/**
+ * If the walk stopped early because the tree has too many entries
+ */
+truncated: boolean; 
+/**
+ * How many files object kind rules classified by their extension alone, because their format
+ * doesn't have magic bytes or reading them failed or took too long
+ */
+classified_by_extension: number; 
+/**
+ * How many entries couldn't be read, so they're neither accepted nor rejected. A directory
+ * which couldn't be listed counts as a single entry
+ */
+unreadable: number }
+
 export type InvalidateOperationEvent = { type: "single"; data: SingleInvalidateOperationEvent } | { type: "all" }
 
+/**
+ * Limits are per second and unlimited when `None`, a zero limit is the same as one
+ */
+export type IoBudget = { max_files_per_second: number | null; max_hashed_bytes_per_second: number | null }
+
 export type JobGroup = { id: string; running_job_id: string | null; action: string | null; status: Status; created_at: string; jobs: Report[] }
 
 export type JobName = "Indexer" | "FileIdentifier" | "MediaProcessor" | "Copy" | "Move" | "Delete" | "Erase" | "FileValidator"
@@ -482,6 +732,21 @@ export type LibraryConfigVersion = "V0" | "V1" | "V2" | "V3" | "V4" | "V5" | "V6
 
 export type LibraryConfigWrapped = { uuid: string; instance_id: string; instance_public_key: RemoteIdentity; config: LibraryConfig }
 
+/**
+ * Last item of a library already returned, the next page of that library starts right after it
+ */
+export type LibraryCursor = { libraryId: string; 
+/**
+ * Id of the item within its library
+ */
+id: number; 
+/**
+ * Value of the ordered field of the item
+ */
+key: AllLibrariesSortKey }
+
+export type LibraryItem = { libraryId: string; item: ExplorerItem }
+
 export type LibraryName = string
 
 export type LibraryPreferences = { location?: { [key in string]: LocationSettings }; tag?: { [key in string]: TagSettings } }
@@ -492,7 +757,7 @@ export type ListenerState = { type: "Listening" } | { type: "Error"; error: stri
 
 export type Listeners = { ipv4: ListenerState; ipv6: ListenerState; relay: ListenerState }
 
-export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; scan_state: number; device_id: number | null; instance_id: number | null }
+export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; follow_symlinks: boolean | null; index_archive_contents: boolean | null; date_created: string | null; scan_state: number; io_max_files_per_second: number | null; io_max_hashed_bytes_per_second: number | null; device_id: number | null; instance_id: number | null }
 
 /**
  * `LocationCreateArgs` is the argument received from the client using `rspc` to create a new location.
@@ -511,9 +776,21 @@ export type LocationSettings = { explorer: ExplorerSettings<FilePathOrder> }
  * It is important to note that only the indexer rule ids in this vector will be used from now on.
  * Old rules that aren't in this vector will be purged.
  */
-export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; indexer_rules_ids: number[]; path: string | null }
+export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; 
+/**
+ * Changing it rescans the location, as its symbolic links must be walked or recorded again
+ */
+follow_symlinks: boolean | null; 
+/**
+ * Changing it rescans the location, to list its archives contents or to remove them
+ */
+index_archive_contents: boolean | null; 
+/**
+ * Replaces the I/O budget used by the next scans of the location
+ */
+io_budget: IoBudget | null; indexer_rules_ids: number[]; path: string | null }
 
-export type LocationWithIndexerRule = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; instance_id: number | null; indexer_rules: IndexerRule[] }
+export type LocationWithIndexerRule = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; follow_symlinks: boolean | null; index_archive_contents: boolean | null; io_max_files_per_second: number | null; io_max_hashed_bytes_per_second: number | null; date_created: string | null; instance_id: number | null; indexer_rules: IndexerRule[] }
 
 export type MaybeUndefined<T> = null | T
 
@@ -557,15 +834,15 @@ name: string; identity: RemoteIdentity; p2p: NodeConfigP2P; features: BackendFea
 
 export type NonCriticalError = { indexer: NonCriticalIndexerError } | { file_identifier: NonCriticalFileIdentifierError } | { media_processor: NonCriticalMediaProcessorError }
 
-export type NonCriticalFileIdentifierError = { failed_to_extract_file_metadata: string } | { failed_to_extract_isolated_file_path_data: { file_path_pub_id: string; error: string } } | { file_path_without_is_dir_field: number }
+export type NonCriticalFileIdentifierError = { failed_to_extract_file_metadata: string } | { failed_to_extract_isolated_file_path_data: { file_path_pub_id: string; error: string } } | { file_path_without_is_dir_field: number } | { failed_to_compute_integrity_checksum: string } | { cas_id_collision: { cas_id: string; object_pub_id: string; moved_file_path_ids: number[] } }
 
-export type NonCriticalIndexerError = { failed_directory_entry: string } | { metadata: string } | { indexer_rule: string } | { file_path_metadata: string } | { fetch_already_existing_file_path_ids: string } | { fetch_file_paths_to_remove: string } | { iso_file_path: string } | { dispatch_keep_walking: string } | { missing_file_path_data: string }
+export type NonCriticalIndexerError = { failed_directory_entry: string } | { metadata: string } | { indexer_rule: string } | { file_path_metadata: string } | { fetch_already_existing_file_path_ids: string } | { fetch_file_paths_to_remove: string } | { iso_file_path: string } | { dispatch_keep_walking: string } | { missing_file_path_data: string } | { archive_contents: string } | { ignore_file: string }
 
-export type NonCriticalMediaDataExtractorError = { FailedToExtractImageMediaData: [string, string] } | { FilePathMissingObjectId: number } | { FailedToConstructIsolatedFilePathData: [number, string] }
+export type NonCriticalMediaDataExtractorError = { FailedToExtractImageMediaData: [string, string] } | { FailedToExtractTextContent: [string, string] } | { FilePathMissingObjectId: number } | { FailedToConstructIsolatedFilePathData: [number, string] }
 
 export type NonCriticalMediaProcessorError = { media_data_extractor: NonCriticalMediaDataExtractorError } | { thumbnailer: NonCriticalThumbnailerError }
 
-export type NonCriticalThumbnailerError = { MissingCasId: number } | { FailedToExtractIsolatedFilePathData: [number, string] } | { VideoThumbnailGenerationFailed: [string, string] } | { FormatImage: [string, string] } | { WebPEncoding: [string, string] } | { PanicWhileGeneratingThumbnail: [string, string] } | { CreateShardDirectory: string } | { SaveThumbnail: [string, string] } | { TaskTimeout: string }
+export type NonCriticalThumbnailerError = { MissingCasId: number } | { FailedToExtractIsolatedFilePathData: [number, string] } | { VideoThumbnailGenerationFailed: [string, string] } | { VideoPreviewGenerationFailed: [string, string] } | { AudioThumbnailGenerationFailed: [string, string] } | { FormatImage: [string, string] } | { WebPEncoding: [string, string] } | { PanicWhileGeneratingThumbnail: [string, string] } | { CreateShardDirectory: string } | { SaveThumbnail: [string, string] } | { SavePerceptualHashes: string } | { TaskTimeout: string }
 
 export type NonIndexedPathItem = { path: string; name: string; extension: string; kind: number; is_dir: boolean; date_created: string; date_modified: string; size_in_bytes_bytes: number[]; hidden: boolean }
 
@@ -584,7 +861,7 @@ export type NotificationId = { type: "library"; id: [string, number] } | { type:
 
 export type NotificationKind = "info" | "success" | "error" | "warning"
 
-export type Object = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; device_id: number | null }
+export type Object = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; device_id: number | null }
 
 export type ObjectCursor = "none" | { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
@@ -592,15 +869,127 @@ export type ObjectFilterArgs = { favorite: boolean } | { hidden: ObjectHiddenFil
 
 export type ObjectHiddenFilter = "exclude" | "include"
 
+export type ObjectKind = 
+/**
+ * A file that can not be identified by the indexer
+ */
+"Unknown" | 
+/**
+ * A known filetype, but without specific support
+ */
+"Document" | 
+/**
+ * A virtual filesystem directory
+ */
+"Folder" | 
+/**
+ * A file that contains human-readable text
+ */
+"Text" | 
+/**
+ * A virtual directory int
+ */
+"Package" | 
+/**
+ * An image file
+ */
+"Image" | 
+/**
+ * An audio file
+ */
+"Audio" | 
+/**
+ * A video file
+ */
+"Video" | 
+/**
+ * A compressed archive of data
+ */
+"Archive" | 
+/**
+ * An executable, program or application
+ */
+"Executable" | 
+/**
+ * A link to another object
+ */
+"Alias" | 
+/**
+ * Raw bytes encrypted by Spacedrive with self contained metadata
+ */
+"Encrypted" | 
+/**
+ * A key or certificate file
+ */
+"Key" | 
+/**
+ * A link can open web pages, apps or Spaces
+ */
+"Link" | 
+/**
+ * A special filetype that represents a preserved webpage
+ */
+"WebPageArchive" | 
+/**
+ * A widget is a mini app that can be placed in a Space at various sizes, associated Widget struct required
+ */
+"Widget" | 
+/**
+ * Albums can only have one level of children, and are associated with the Album struct
+ */
+"Album" | 
+/**
+ * Its like a folder, but appears like a stack of files, designed for burst photos / associated groups of files
+ */
+"Collection" | 
+/**
+ * You know, text init
+ */
+"Font" | 
+/**
+ * 3D Object
+ */
+"Mesh" | 
+/**
+ * Editable source code file
+ */
+"Code" | 
+/**
+ * Database file
+ */
+"Database" | 
+/**
+ * E-book file
+ */
+"Book" | 
+/**
+ * Config file
+ */
+"Config" | 
+/**
+ * Dotfile
+ */
+"Dotfile" | 
+/**
+ * Screenshot
+ */
+"Screenshot" | 
+/**
+ * Label
+ */
+"Label"
+
+export type ObjectKindClassification = { kind: ObjectKind; method: ClassificationMethod }
+
 export type ObjectOrder = { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder } | { field: "mediaData"; value: ExifDataOrder }
 
 export type ObjectSearchArgs = { take: number; orderAndPagination?: OrderAndPagination<number, ObjectOrder, ObjectCursor> | null; filters?: SearchFilterArgs[] }
 
 export type ObjectValidatorArgs = { id: number; path: string }
 
-export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null })[]; device_id: number | null }
+export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; detected_extension: string | null; archive_member_path: string | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; size_in_bytes_int: bigint | null; file_count: bigint | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; bit_rate_int: bigint | null; duration_int: bigint | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null })[]; device_id: number | null }
 
-export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null })[] }
+export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; detected_extension: string | null; archive_member_path: string | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; size_in_bytes_int: bigint | null; file_count: bigint | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; perceptual_hash: bigint | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; bit_rate_int: bigint | null; duration_int: bigint | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null; device_id: number | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null; device_id: number | null })[] }
 
 export type OldFileCopierJobInit = { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string }
 
@@ -624,16 +1013,27 @@ export type P2PDiscoveryState = "Everyone" | "ContactsOnly" | "Disabled"
 
 export type P2PEvent = { type: "PeerChange"; identity: RemoteIdentity; connection: ConnectionMethod; discovery: DiscoveryMethod; metadata: PeerMetadata; addrs: string[] } | { type: "PeerDelete"; identity: RemoteIdentity } | { type: "SpacedropRequest"; id: string; identity: RemoteIdentity; peer_name: string; files: string[] } | { type: "SpacedropProgress"; id: string; percent: number } | { type: "SpacedropTimedOut"; id: string } | { type: "SpacedropRejected"; id: string }
 
+export type ParsedQuery = { filters: SearchFilterArgs[]; errors: QueryError[] }
+
 export type PeerMetadata = { name: string; operating_system: OperatingSystem | null; device_model: CoreHardwareModel | null; version: string | null }
 
 export type PlusCode = string
 
 export type Port = { type: "random" } | { type: "discrete"; value: number }
 
+export type PreviewRulesArgs = { path: string; indexer_rules_ids: number[] }
+
 export type Program = { id: number; name: string | null; streams: Stream[]; metadata: Metadata }
 
 export type Props = { Video: VideoProps } | { Audio: AudioProps } | { Subtitle: SubtitleProps }
 
+/**
+ * An error on a specific part of a query, `start` and `end` are character offsets
+ */
+export type QueryError = { kind: QueryErrorKind; message: string; start: number; end: number }
+
+export type QueryErrorKind = "unterminatedQuote" | "emptyValue" | "unknownKey" | "invalidValue" | "notFound"
+
 export type Range<T> = { from: T } | { to: T }
 
 /**
@@ -659,24 +1059,71 @@ export type ReportMetadata = { type: "input"; metadata: ReportInputMetadata } |
 
 export type ReportOutputMetadata = { type: "metrics"; data: { [key in string]: JsonValue } } | { type: "indexer"; data: { total_paths: [number, number] } } | { type: "file_identifier"; data: { total_orphan_paths: [number, number]; total_objects_created: [number, number]; total_objects_linked: [number, number] } } | { type: "media_processor"; data: { media_data_extracted: [number, number]; media_data_skipped: [number, number]; thumbnails_generated: [number, number]; thumbnails_skipped: [number, number] } } | { type: "copier"; data: { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string } } | { type: "mover"; data: { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string } } | { type: "deleter"; data: { location_id: number; file_path_ids: number[] } } | { type: "eraser"; data: { location_id: number; file_path_ids: number[]; passes: number } } | { type: "file_validator"; data: { location_id: number; sub_path: string | null } }
 
-export type RescanArgs = { location_id: number; sub_path: string }
+export type RescanArgs = { location_id: number; sub_path: string; 
+/**
+ * Walk through every directory, even the ones unchanged since the last scan
+ */
+paranoid?: boolean }
 
 export type Resolution = { width: number; height: number }
 
-export type RuleKind = "AcceptFilesByGlob" | "RejectFilesByGlob" | "AcceptIfChildrenDirectoriesArePresent" | "RejectIfChildrenDirectoriesArePresent" | "IgnoredByGit"
+export type ResolvedAlias = { 
+/**
+ * Where the symbolic link points to, as it would be opened
+ */
+target: string; 
+/**
+ * The target, if indexed on a location of this library
+ */
+file_path_id: number | null; location_id: number | null }
+
+/**
+ * What a single indexer rule thinks about a path, see [`IndexerRuler::explain_path`]
+ */
+export type RuleExplanation = { 
+/**
+ * Rules built from `.gitignore` or `.spacedriveignore` files aren't stored, so they don't
+ * have an id
+ */
+id: number | null; name: string; 
+/**
+ * Each kind of this rule and if it accepted the path
+ */
+results: ([RuleKind, boolean])[] }
+
+export type RuleKind = "AcceptFilesByGlob" | "RejectFilesByGlob" | "AcceptIfChildrenDirectoriesArePresent" | "RejectIfChildrenDirectoriesArePresent" | "IgnoredByGit" | "RejectFilesLargerThan" | "AcceptFilesSmallerThan" | "AcceptIfExtendedAttributesArePresent" | "RejectIfExtendedAttributesArePresent" | "RejectFilesModifiedWithin" | "RejectFilesNotModifiedWithin" | "IgnoredBySpacedrive" | "AcceptByObjectKind" | "RejectByObjectKind"
+
+export type RuleRejections = { id: number | null; name: string; rejected: number }
+
+export type RulerDecision = "Accept" | "Reject"
 
 export type SavedSearch = { id: number; pub_id: number[]; target: string | null; search: string | null; filters: string | null; name: string | null; icon: string | null; description: string | null; date_created: string | null; date_modified: string | null }
 
-export type SearchData<T> = { cursor: number[] | null; items: T[] }
+export type SearchData<T> = { cursor: number[] | null; items: T[]; 
+/**
+ * Only filled when searching with a [`FilePathFilterArgs::Content`] filter, one per object in `items` order
+ */
+content_matches: ContentMatch[] }
 
-export type SearchFilterArgs = { filePath: FilePathFilterArgs } | { object: ObjectFilterArgs }
+export type SearchFilterArgs = { filePath: FilePathFilterArgs } | { object: ObjectFilterArgs } | { exifData: ExifDataFilterArgs } | { ffmpegData: FfmpegFilterArgs }
 
 export type SearchTarget = "paths" | "objects"
 
 export type SetFavoriteArgs = { id: number; favorite: boolean }
 
+export type SetIoBudgetArgs = { id: string; io_budget: IoBudget }
+
 export type SetNoteArgs = { id: number; note: string | null }
 
+export type SimilarImage = { distance: number; object: ObjectWithFilePaths }
+
+export type SimilarImagesArgs = { objectId: number; 
+/**
+ * How many of the 64 perceptual hash bits may differ, re-encoded or resized copies of a
+ * picture are usually within 5 bits and unrelated pictures are around 32 bits apart
+ */
+maxDistance: number; take?: number | null }
+
 export type SingleInvalidateOperationEvent = { 
 /**
  * This fields are intentionally private.
@@ -723,6 +1170,8 @@ export type ThumbKey = { shard_hex: string; cas_id: CasId; base_directory_str: s
 
 export type UpdateThumbnailerPreferences = Record<string, never>
 
+export type VerifyIntegrityArgs = { id: number; path: string }
+
 export type VideoProps = { pixel_format: string | null; color_range: string | null; bits_per_channel: number | null; color_space: string | null; color_primaries: string | null; color_transfer: string | null; field_order: string | null; chroma_location: string | null; width: number; height: number; aspect_ratio_num: number | null; aspect_ratio_den: number | null; properties: string[] }
 
 export type Volume = { name: string; mount_points: string[]; total_capacity: string; available_capacity: string; disk_type: DiskType; file_system: string | null; is_root_filesystem: boolean }
@@ -13,6 +13,7 @@ export interface ItemData {
 	isDir: boolean;
 	casId: string | null;
 	extension: string | null;
+	detectedExtension: string | null; // told by the file contents, for extensionless or misnamed files
 	locationId: number | null;
 	dateIndexed: string | null;
 	dateCreated: string | null;
@@ -69,6 +70,8 @@ export function getExplorerItemData(data?: ExplorerItem | null): ItemData {
 				itemData.size = humanizeSize(filePath.size_in_bytes_bytes);
 				itemData.isDir = filePath.is_dir ?? false;
 				itemData.extension = filePath.extension?.toLocaleLowerCase() ?? null;
+				if ('detected_extension' in filePath)
+					itemData.detectedExtension = filePath.detected_extension ?? null;
 				//
 				if ('cas_id' in filePath) itemData.casId = filePath.cas_id;
 				if ('location_id' in filePath) itemData.locationId = filePath.location_id;
@@ -129,6 +132,7 @@ function getDefaultItemData(kind: ObjectKindKey = 'Unknown'): ItemData {
 		isDir: false,
 		casId: null,
 		extension: null,
+		detectedExtension: null,
 		locationId: null,
 		dateIndexed: null,
 		dateCreated: null,
@@ -49,6 +49,10 @@ export function getJobNiceActionName(action: string, completed: boolean, job?: R
 			return completed ? `Added location "${name}"` : `Adding location "${name}"`;
 		case 'scan_location_sub_path':
 			return completed ? `Indexed new files "${name}"` : `Adding location "${name}"`;
+		case 'verify_integrity':
+			return completed
+				? `Verified integrity of "${name}"`
+				: `Verifying integrity of "${name}"`;
 	}
 	return action;
 }